@@ -18,6 +18,19 @@ pub const USER_DIR: &str = "data/user";
 pub const USER_DIR: &str = "data/test/user";
 pub const TABLE_TABLE: &str = "meta_tables";
 pub const COLUMN_TABLE: &str = "meta_columns";
+pub const VIEW_TABLE: &str = "meta_views";
+pub const DEFAULT_TABLE: &str = "meta_defaults";
+pub const TRIGGER_TABLE: &str = "meta_triggers";
+/// The row IDs kept by `TRUNCATE ... CONTINUE IDENTITY`, see [`crate::table::Table::last_row_id`].
+pub const ROW_ID_TABLE: &str = "meta_row_ids";
+
+/// Values of `table_type` in [`TABLE_TABLE`].
+pub const SYSTEM_TABLE_TYPE: i8 = 0;
+pub const USER_TABLE_TYPE: i8 = 1;
+pub const VIEW_TABLE_TYPE: i8 = 2;
+
+/// Longest text value that fits the one byte type code of a record header.
+pub const MAX_TEXT_LENGTH: usize = 0xFF - 0x0C;
 
 pub const PROMPT: &str = "db > ";
 
@@ -68,9 +81,12 @@ pub enum ParseDataTypeError {
 
     #[error("{0}")]
     ParseFloatError(#[from] ParseFloatError),
+
+    #[error("Value is out of the range of {0}")]
+    OutOfRange(&'static str),
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum DataType {
     Null,
     TinyInt(i8),
@@ -115,6 +131,25 @@ impl DataType {
         }
     }
 
+    /// Returns the SQL name of the type of the value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DataType::Null => "NULL",
+            DataType::TinyInt(_) => "TINYINT",
+            DataType::SmallInt(_) => "SMALLINT",
+            DataType::Int(_) => "INT",
+            DataType::BigInt(_) => "BIGINT",
+            DataType::Float(_) => "FLOAT",
+            DataType::Double(_) => "DOUBLE",
+            DataType::Unused => "UNUSED",
+            DataType::Year(_) => "YEAR",
+            DataType::Time(_) => "TIME",
+            DataType::DateTime(_) => "DATETIME",
+            DataType::Date(_) => "DATE",
+            DataType::Text(_) => "TEXT",
+        }
+    }
+
     pub fn parse_str(data_type: DataType, value: &str) -> Result<DataType, ParseDataTypeError> {
         let value = value.trim();
         let pared = match data_type {
@@ -122,7 +157,14 @@ impl DataType {
             DataType::SmallInt(_) => DataType::SmallInt(value.parse()?),
             DataType::Int(_) => DataType::Int(value.parse()?),
             DataType::BigInt(_) => DataType::BigInt(value.parse()?),
-            DataType::Float(_) => DataType::Float(value.parse()?),
+            DataType::Float(_) => {
+                // Finite numbers beyond the range of FLOAT would be stored as infinity.
+                let float: f64 = value.parse()?;
+                if float.is_finite() && float.abs() > f32::MAX as f64 {
+                    return Err(ParseDataTypeError::OutOfRange(data_type.type_name()));
+                }
+                DataType::Float(float as f32)
+            }
             DataType::Double(_) => DataType::Double(value.parse()?),
             DataType::Year(_) => {
                 let year: i32 = value.parse()?;
@@ -279,17 +321,17 @@ impl FromStr for DataType {
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
-            "tinyint" => Ok(DataType::TinyInt(0)),
+            "tinyint" | "boolean" | "bool" => Ok(DataType::TinyInt(0)),
             "smallint" => Ok(DataType::SmallInt(0)),
-            "int" => Ok(DataType::Int(0)),
+            "int" | "integer" => Ok(DataType::Int(0)),
             "bigint" => Ok(DataType::BigInt(0)),
-            "float" => Ok(DataType::Float(0.0)),
+            "float" | "real" => Ok(DataType::Float(0.0)),
             "double" => Ok(DataType::Double(0.0)),
             "year" => Ok(DataType::Year(0)),
             "time" => Ok(DataType::Time(0)),
-            "datetime" => Ok(DataType::DateTime(0)),
+            "datetime" | "timestamp" => Ok(DataType::DateTime(0)),
             "date" => Ok(DataType::Date(0)),
-            "text" => Ok(DataType::Text("".to_string())),
+            "text" | "varchar" | "char" => Ok(DataType::Text("".to_string())),
             _ => Err("Invalid DataType".to_string()),
         }
     }
@@ -1,23 +1,199 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Write as FmtWrite;
+use std::io::Write;
+use std::ops::Bound;
 use std::path::Path;
-use std::{collections::VecDeque, io::Write};
+use std::rc::Rc;
 
 use crate::{
     constants::*,
+    diagnostic::{column_not_found, locate_column},
+    error::{DbError, Result},
+    executor::{rows, shared_rows, Filter, IndexScan, Join, Limit, Project, Rows, Sort},
+    expression::{
+        coerce, display_literal, index_order, parse_literal, resolve_compared_value, text_content,
+        text_value, unify, Expression, Predicate,
+    },
+    index_file::operator_bounds,
+    lexer::{tokenize, tokenize_script, tokenize_statement},
+    parser::{
+        self, CommonTable, ConflictAction, Delete, Expr, Insert, Returning, Select, SetOperator,
+        Statement, TriggerEvent, TriggerTiming, Update, Value,
+    },
+    plan::Plan,
+    prepared_statement::PreparedStatement,
     record::Record,
-    table::{Column, Table},
+    table::{column_index, AccessPath, Column, Table},
+    window::{evaluate_windows, Aggregate, Window},
 };
 
+/// A search condition: the column to compare, the operator and the value to compare against.
+/// Without a column every record matches.
+type Condition = (Option<String>, String, DataType);
+
+/// The most steps a recursive common table expression may take before it is assumed to never
+/// finish.
+const MAX_RECURSIVE_STEPS: usize = 10_000;
+
+/// How deeply triggers may fire statements that fire triggers before they are assumed to never
+/// finish.
+const MAX_TRIGGER_DEPTH: usize = 16;
+
+/// A trigger as stored in the catalog. Triggers are fired by the [`Database`] statements that
+/// write rows, not by [`Table`], so that the statements they run go through the database too.
+struct Trigger {
+    name: String,
+    timing: TriggerTiming,
+    /// The statement run for each row, as written. When it fires, each `old.<column>` and
+    /// `new.<column>` in it is replaced by a parameter `$1`, `$2`, … numbered in the order they
+    /// appear, and the value of the column in the old or new row is bound to it.
+    body: String,
+}
+
+/// A record a statement wrote to a user table, kept until the statement finishes so that its
+/// writes, and those of the triggers it fired, can be undone if it fails.
+enum Change {
+    Inserted {
+        table: String,
+        record: Record,
+    },
+    Updated {
+        table: String,
+        old: Record,
+        new: Record,
+        column_index: usize,
+    },
+    Deleted {
+        table: String,
+        record: Record,
+    },
+}
+
+/// The records of a common table expression, computed once per statement.
+#[derive(Clone)]
+struct CommonTableRecords {
+    name: String,
+    columns: Vec<Column>,
+    records: Rc<Vec<Record>>,
+    /// How the records are computed, as shown by `EXPLAIN`.
+    plan: Plan,
+}
+
+/// A SELECT statement resolved against the catalog.
+struct SelectQuery {
+    /// The table, view or common table selected from.
+    source: String,
+    source_columns: Vec<Column>,
+    /// The records of the source if it is a common table.
+    common_table: Option<CommonTableRecords>,
+    /// The sources joined to `source`. With joins, `source_columns` are the columns of every
+    /// source named `<source>.<column>`, and the WHERE clause is always a `filter`.
+    joins: Vec<JoinQuery>,
+    /// The selected expressions and the columns they produce.
+    projections: Vec<(Column, Expression)>,
+    /// The selected window functions. Their results follow the source columns in the records
+    /// the projections are evaluated on.
+    windows: Vec<Window>,
+    /// The selected MIN and MAX aggregates. When there are any, every projection is one of them,
+    /// evaluated on the single record holding their results.
+    aggregates: Vec<Aggregate>,
+    condition: Condition,
+    /// The WHERE clause if it cannot be answered by `condition`.
+    filter: Option<Predicate>,
+    /// The ORDER BY expressions, each with whether it sorts in descending order. They are
+    /// evaluated on the source records, or on the combined records if `compound` is not empty.
+    order_by: Vec<(Expression, bool)>,
+    /// The queries combined with this one, see [`Select::compound`].
+    compound: Vec<(SetOperator, bool, SelectQuery)>,
+    limit: Option<u64>,
+}
+
+/// A source joined to the source of a SELECT statement.
+struct JoinQuery {
+    source: String,
+    /// The records of the source if it is a common table.
+    common_table: Option<CommonTableRecords>,
+    /// The columns of the source, named `<source>.<column>`.
+    columns: Vec<Column>,
+    /// The search condition of the WHERE clause if it compares a column of this source, which is
+    /// then answered while reading the source.
+    search: Condition,
+    /// Evaluated on the values of the records joined so far followed by those of this source.
+    condition: Predicate,
+    /// The positions of a column of the records joined so far and of a column of this source
+    /// that `condition` requires to be equal, if it does. The records of this source matching
+    /// a record joined so far may then be looked up by its value of the former.
+    key: Option<(usize, usize)>,
+}
+
+/// Where the records of a joined source are read from, each time the join needs them again.
+enum JoinSource {
+    Records(Rc<Vec<Record>>),
+    Table(Table),
+    /// The records of the table with the column named holding the key of the left record, read
+    /// from the index on the column.
+    Lookup(Table, String),
+}
+
+impl SelectQuery {
+    fn columns(&self) -> Vec<Column> {
+        self.projections.iter().map(|(c, _)| c.clone()).collect()
+    }
+
+    /// Returns the columns of the source, without those of the sources joined to it.
+    fn own_source_columns(&self) -> &[Column] {
+        let joined = self.joins.iter().map(|j| j.columns.len()).sum::<usize>();
+        &self.source_columns[..self.source_columns.len() - joined]
+    }
+
+    /// Returns the most records read from the source: the LIMIT, if every record read from the
+    /// source is returned until it is reached.
+    fn source_limit(&self) -> Option<u64> {
+        let kept = self.filter.is_none()
+            && self.joins.is_empty()
+            && self.windows.is_empty()
+            && self.aggregates.is_empty()
+            && self.compound.is_empty();
+        self.limit.filter(|_| kept)
+    }
+}
+
+/// An UPDATE statement resolved against the catalog.
+struct UpdateQuery {
+    table: Table,
+    column: String,
+    value: Expression,
+    condition: Condition,
+    filter: Option<Predicate>,
+}
+
+/// A DELETE statement resolved against the catalog.
+struct DeleteQuery {
+    table: Table,
+    condition: Condition,
+    filter: Option<Predicate>,
+}
+
 pub struct Database {
     pub table_table: Table,
     pub column_table: Table,
+    pub view_table: Table,
+    pub default_table: Table,
+    pub trigger_table: Table,
+    pub row_id_table: Table,
+    /// How many triggers are currently running statements inside one another.
+    trigger_depth: usize,
+    /// The writes of the statement being run, oldest first.
+    changes: Vec<Change>,
 }
 
 impl Database {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         let system_dir = std::path::Path::new(SYSTEM_DIR);
         if !system_dir.exists() {
-            Database::create_system_dir();
+            Database::create_system_dir()?;
         }
         let text_type = DataType::Text(Default::default());
         let tiny_int_type = DataType::TinyInt(Default::default());
@@ -25,34 +201,61 @@ impl Database {
             Column::new("table_name", text_type.clone(), false, true),
             Column::new("table_type", tiny_int_type.clone(), false, false),
         ];
-        let mut table_table = Table::new(TABLE_TABLE, table_table_columns, SYSTEM_DIR);
+        let mut table_table = Table::new(TABLE_TABLE, table_table_columns, SYSTEM_DIR)?;
         let column_table_columns = vec![
             Column::new("table_name", text_type.clone(), false, false),
             Column::new("column_name", text_type.clone(), false, false),
             Column::new("data_type", tiny_int_type.clone(), false, false),
             Column::new("ordinal_position", tiny_int_type.clone(), false, false),
             Column::new("is_nullable", tiny_int_type, false, false),
-            Column::new("column_key", text_type, true, false),
+            Column::new("column_key", text_type.clone(), true, false),
+        ];
+        let mut column_table = Table::new(COLUMN_TABLE, column_table_columns, SYSTEM_DIR)?;
+        let view_table_columns = vec![
+            Column::new("view_name", text_type.clone(), false, true),
+            Column::new("definition", text_type.clone(), false, false),
+        ];
+        let view_table = Table::new(VIEW_TABLE, view_table_columns, SYSTEM_DIR)?;
+        let default_table_columns = vec![
+            Column::new("table_name", text_type.clone(), false, false),
+            Column::new("column_name", text_type.clone(), false, false),
+            Column::new("default_value", text_type.clone(), false, false),
+        ];
+        let default_table = Table::new(DEFAULT_TABLE, default_table_columns, SYSTEM_DIR)?;
+        let trigger_table_columns = vec![
+            Column::new("trigger_name", text_type.clone(), false, true),
+            Column::new("table_name", text_type.clone(), false, false),
+            Column::new("timing", text_type.clone(), false, false),
+            Column::new("event", text_type.clone(), false, false),
+            Column::new("body", text_type.clone(), false, false),
         ];
-        let mut column_table = Table::new(COLUMN_TABLE, column_table_columns, SYSTEM_DIR);
-        Database::initialize_meta_tables(&mut table_table, &mut column_table)
-            .expect("Failed initializing meta tables");
-        Self {
+        let trigger_table = Table::new(TRIGGER_TABLE, trigger_table_columns, SYSTEM_DIR)?;
+        let row_id_table_columns = vec![
+            Column::new("table_name", text_type, false, true),
+            Column::new("row_id", DataType::BigInt(0), false, false),
+        ];
+        let row_id_table = Table::new(ROW_ID_TABLE, row_id_table_columns, SYSTEM_DIR)?;
+        Database::initialize_meta_tables(&mut table_table, &mut column_table)?;
+        Ok(Self {
             table_table,
             column_table,
-        }
+            view_table,
+            default_table,
+            trigger_table,
+            row_id_table,
+            trigger_depth: 0,
+            changes: vec![],
+        })
     }
 
-    fn create_system_dir() {
-        std::fs::create_dir_all(SYSTEM_DIR).expect("Failed creating system directory");
-        std::fs::create_dir_all(USER_DIR).expect("Failed creating user directory");
+    fn create_system_dir() -> Result<()> {
+        std::fs::create_dir_all(SYSTEM_DIR)?;
+        std::fs::create_dir_all(USER_DIR)?;
+        Ok(())
     }
 
-    fn initialize_meta_tables(
-        table_table: &mut Table,
-        column_table: &mut Table,
-    ) -> Result<(), String> {
-        if table_table.is_empty() {
+    fn initialize_meta_tables(table_table: &mut Table, column_table: &mut Table) -> Result<()> {
+        if table_table.is_empty()? {
             table_table.insert(vec![
                 DataType::Text(TABLE_TABLE.to_string()),
                 DataType::TinyInt(0),
@@ -63,7 +266,7 @@ impl Database {
             ])?;
         }
 
-        if column_table.is_empty() {
+        if column_table.is_empty()? {
             column_table.insert(cols_vec(TABLE_TABLE, "table_name", 0x0C, 0, 0, "PRI"))?;
             column_table.insert(cols_vec(TABLE_TABLE, "table_type", 0x01, 1, 0, ""))?;
             column_table.insert(cols_vec(COLUMN_TABLE, "table_name", 0x0C, 0, 0, ""))?;
@@ -73,84 +276,203 @@ impl Database {
             column_table.insert(cols_vec(COLUMN_TABLE, "is_nullable", 0x01, 4, 0, ""))?;
             column_table.insert(cols_vec(COLUMN_TABLE, "column_key", 0x0C, 5, 0, ""))?;
         }
+
+        // Catalogs created before views, defaults, triggers or kept row IDs existed have no
+        // entries for their tables yet.
+        let system_tables = [
+            (
+                VIEW_TABLE,
+                vec![("view_name", 0x0C, "PRI"), ("definition", 0x0C, "")],
+            ),
+            (
+                DEFAULT_TABLE,
+                vec![
+                    ("table_name", 0x0C, ""),
+                    ("column_name", 0x0C, ""),
+                    ("default_value", 0x0C, ""),
+                ],
+            ),
+            (
+                TRIGGER_TABLE,
+                vec![
+                    ("trigger_name", 0x0C, "PRI"),
+                    ("table_name", 0x0C, ""),
+                    ("timing", 0x0C, ""),
+                    ("event", 0x0C, ""),
+                    ("body", 0x0C, ""),
+                ],
+            ),
+            (
+                ROW_ID_TABLE,
+                vec![("table_name", 0x0C, "PRI"), ("row_id", 0x04, "")],
+            ),
+        ];
+        for (name, columns) in system_tables {
+            let name_dt = DataType::Text(name.to_string());
+            if table_table
+                .search(Some("table_name"), name_dt.clone(), "=")?
+                .is_empty()
+            {
+                table_table.insert(vec![name_dt.clone(), DataType::TinyInt(SYSTEM_TABLE_TYPE)])?;
+            }
+            if column_table
+                .search(Some("table_name"), name_dt, "=")?
+                .is_empty()
+            {
+                for (i, (column, data_type, key)) in columns.into_iter().enumerate() {
+                    column_table.insert(cols_vec(name, column, data_type, i as i8, 0, key))?;
+                }
+            }
+        }
         Ok(())
     }
 
-    pub fn load_table(&mut self, table_name: &str) -> Option<Table> {
+    /// Returns the `table_type` of `table_name` in the catalog, or [`None`] if no table or view
+    /// with that name exists.
+    pub fn table_type(&mut self, table_name: &str) -> Result<Option<i8>> {
+        let tables = self.table_table.search(
+            Some("table_name"),
+            DataType::Text(table_name.to_string()),
+            "=",
+        )?;
+        match tables.first().map(|t| &t.values[1]) {
+            None => Ok(None),
+            Some(DataType::TinyInt(v)) => Ok(Some(*v)),
+            Some(v) => Err(DbError::Corruption(format!(
+                "Table type of {} should be tiny int, found {}",
+                table_name, v
+            ))),
+        }
+    }
+
+    /// Returns the stored SELECT statement of the view `view_name`.
+    pub fn load_view(&mut self, view_name: &str) -> Result<Option<String>> {
+        let views = self.view_table.search(
+            Some("view_name"),
+            DataType::Text(view_name.to_string()),
+            "=",
+        )?;
+        match views.first().map(|v| &v.values[1]) {
+            None => Ok(None),
+            Some(DataType::Text(v)) => Ok(Some(v.clone())),
+            Some(v) => Err(DbError::Corruption(format!(
+                "Definition of view {} should be text, found {}",
+                view_name, v
+            ))),
+        }
+    }
+
+    pub fn load_table(&mut self, table_name: &str) -> Result<Option<Table>> {
         let table_name_dt = DataType::Text(table_name.to_string());
         let tables = self
             .table_table
-            .search(Some("table_name"), table_name_dt.clone(), "=")
-            .unwrap();
+            .search(Some("table_name"), table_name_dt.clone(), "=")?;
         if tables.len() > 1 {
-            panic!("Multiple tables with the same name found: {}", table_name);
+            return Err(DbError::Corruption(format!(
+                "Multiple tables with the same name found: {}",
+                table_name
+            )));
         }
         if tables.is_empty() {
-            return None;
+            return Ok(None);
         }
-        let mut columns = self
+        let columns = self
             .column_table
-            .search(Some("table_name"), table_name_dt.clone(), "=")
-            .unwrap();
-        columns.sort_by(|a, b| {
-            let a = match &a.values[3] {
-                DataType::TinyInt(v) => *v,
-                _ => unreachable!("Ordinal position should be tiny int"),
-            };
-            let b = match &b.values[3] {
-                DataType::TinyInt(v) => *v,
-                _ => unreachable!("Ordinal position should be tiny int"),
-            };
-            a.cmp(&b)
-        });
-        let columns = columns
+            .search(Some("table_name"), table_name_dt.clone(), "=")?;
+        let mut columns = columns
             .iter()
             .map(|c| {
-                let column_name = match &c.values[1] {
-                    DataType::Text(v) => v,
-                    _ => unreachable!("Column name should be text"),
-                };
-                let data_type: DataType = match &c.values[2] {
-                    DataType::TinyInt(v) => (*v as u8).into(),
-                    _ => unreachable!("Data type should be tiny int"),
-                };
-                let nullable = match &c.values[4] {
-                    DataType::TinyInt(v) => *v,
-                    _ => unreachable!("Nullable should be tiny int"),
+                let (
+                    DataType::Text(column_name),
+                    DataType::TinyInt(data_type),
+                    DataType::TinyInt(position),
+                    DataType::TinyInt(nullable),
+                ) = (&c.values[1], &c.values[2], &c.values[3], &c.values[4])
+                else {
+                    return Err(DbError::Corruption(format!(
+                        "Invalid column of table {}: {}",
+                        table_name, c
+                    )));
                 };
-                let column_key = match &c.values[5] {
-                    DataType::Text(v) => v,
-                    DataType::Null => "",
-                    _ => unreachable!("Column key should be text"),
+                let unique = match &c.values[5] {
+                    DataType::Text(v) => v == "UNI" || v == "PRI",
+                    DataType::Null => false,
+                    v => {
+                        return Err(DbError::Corruption(format!(
+                            "Invalid key of column {}: {}",
+                            column_name, v
+                        )))
+                    }
                 };
-                let unique = column_key == "UNI";
-                Column::new(column_name, data_type, nullable == 1, unique)
+                let data_type = *data_type as u8;
+                let max_length = (data_type > 0x0C).then(|| (data_type - 0x0C) as usize);
+                let column = Column::new(column_name, data_type.into(), *nullable == 1, unique)
+                    .with_max_length(max_length);
+                Ok((*position, column))
             })
+            .collect::<Result<Vec<(i8, Column)>>>()?;
+        columns.sort_by_key(|(position, _)| *position);
+        let mut columns = columns
+            .into_iter()
+            .map(|(_, column)| column)
             .collect::<Vec<Column>>();
-        let table_type = match &tables[0].values[1] {
-            DataType::TinyInt(v) => *v,
-            _ => unreachable!("Table type should be tiny int"),
+        let defaults = self
+            .default_table
+            .search(Some("table_name"), table_name_dt.clone(), "=")?;
+        for default in defaults {
+            let (DataType::Text(column_name), DataType::Text(value)) =
+                (&default.values[1], &default.values[2])
+            else {
+                return Err(DbError::Corruption(format!(
+                    "Invalid default of table {}: {}",
+                    table_name, default
+                )));
+            };
+            if let Some(column) = columns.iter_mut().find(|c| c.name == *column_name) {
+                let value = DataType::parse_str(column.data_type.clone(), value).map_err(|e| {
+                    DbError::Corruption(format!("Invalid default {}: {}", value, e))
+                })?;
+                column.default = Some(value);
+            }
+        }
+        let DataType::TinyInt(table_type) = tables[0].values[1] else {
+            return Err(DbError::Corruption(format!(
+                "Invalid type of table {}: {}",
+                table_name, tables[0].values[1]
+            )));
         };
-        if table_type == 0 {
-            Some(Table::new(table_name, columns, SYSTEM_DIR))
-        } else if table_type == 1 {
-            Some(Table::new(table_name, columns, USER_DIR))
-        } else {
-            panic!("Invalid table type: {}", table_type);
+        match table_type {
+            SYSTEM_TABLE_TYPE => Ok(Some(Table::new(table_name, columns, SYSTEM_DIR)?)),
+            USER_TABLE_TYPE => {
+                let row_ids = self
+                    .row_id_table
+                    .search(Some("table_name"), table_name_dt, "=")?;
+                let row_id_floor = match row_ids.first().map(|r| &r.values[1]) {
+                    None => 0,
+                    Some(DataType::BigInt(row_id)) => *row_id as u32,
+                    Some(v) => {
+                        return Err(DbError::Corruption(format!(
+                            "Kept row ID of {} should be big int, found {}",
+                            table_name, v
+                        )))
+                    }
+                };
+                let table = Table::new(table_name, columns, USER_DIR)?;
+                Ok(Some(table.with_row_id_floor(row_id_floor)))
+            }
+            VIEW_TABLE_TYPE => Ok(None),
+            _ => Err(DbError::Corruption(format!(
+                "Invalid table type: {}",
+                table_type
+            ))),
         }
     }
 
-    pub fn new_table(
-        &mut self,
-        table: &mut Table,
-        flags: Vec<(bool, bool, bool)>,
-    ) -> Result<(), String> {
-        self.table_table
-            .insert(vec![
-                DataType::Text(table.name.clone()),
-                DataType::TinyInt(1),
-            ])
-            .expect("Failed inserting");
+    pub fn new_table(&mut self, table: &mut Table, flags: Vec<(bool, bool, bool)>) -> Result<()> {
+        self.table_table.insert(vec![
+            DataType::Text(table.name.clone()),
+            DataType::TinyInt(USER_TABLE_TYPE),
+        ])?;
 
         let mut primary_key: Option<Column> = None;
         for (i, (column, flag)) in table.columns.iter().zip(flags.iter()).enumerate() {
@@ -162,23 +484,32 @@ impl Database {
             } else {
                 ""
             };
+            // Like the type codes of records, a text column stores the length of VARCHAR(n)
+            // above 0x0C.
             let column_type = match column.data_type.clone() {
-                DataType::Text(_) => 0x0C,
+                DataType::Text(_) => 0x0C + column.max_length.unwrap_or(0) as u8,
                 v => Into::<u8>::into(&v),
             } as i8;
-            self.column_table
-                .insert(cols_vec(
-                    table.name.as_str(),
-                    column.name.as_str(),
-                    column_type,
-                    i as i8 + 1,
-                    nullable,
-                    column_key,
-                ))
-                .expect("Failed inserting");
+            self.column_table.insert(cols_vec(
+                table.name.as_str(),
+                column.name.as_str(),
+                column_type,
+                i as i8 + 1,
+                nullable,
+                column_key,
+            ))?;
+            if let Some(default) = &column.default {
+                self.default_table.insert(vec![
+                    DataType::Text(table.name.clone()),
+                    DataType::Text(column.name.clone()),
+                    DataType::Text(default.to_string()),
+                ])?;
+            }
             if column_key == "PRI" {
                 if primary_key.is_some() {
-                    return Err("Multiple primary keys not allowed".to_string());
+                    return Err(DbError::Catalog(
+                        "Multiple primary keys specified.".to_string(),
+                    ));
                 }
                 primary_key = Some(column.clone());
             }
@@ -190,19 +521,236 @@ impl Database {
         Ok(())
     }
 
-    pub fn drop_table(&mut self, table_name: &str) -> Result<(), String> {
+    /// Stores `definition`, a SELECT statement without the leading `SELECT`, as the view
+    /// `view_name`.
+    pub fn new_view(&mut self, view_name: &str, definition: &str) -> Result<()> {
+        if definition.len() > MAX_TEXT_LENGTH {
+            return Err(DbError::Constraint(format!(
+                "View definition is too long ({} > {} characters).",
+                definition.len(),
+                MAX_TEXT_LENGTH
+            )));
+        }
+        self.table_table.insert(vec![
+            DataType::Text(view_name.to_string()),
+            DataType::TinyInt(VIEW_TABLE_TYPE),
+        ])?;
+        self.view_table.insert(vec![
+            DataType::Text(view_name.to_string()),
+            DataType::Text(definition.to_string()),
+        ])?;
+        Ok(())
+    }
+
+    pub fn drop_view(&mut self, view_name: &str) -> Result<()> {
+        if self.table_type(view_name)? != Some(VIEW_TABLE_TYPE) {
+            return Err(DbError::Catalog(format!(
+                "View {} does not exist.",
+                view_name
+            )));
+        }
+        let view_name_dt = DataType::Text(view_name.to_string());
+        self.view_table
+            .delete(Some("view_name"), &view_name_dt, "=")?;
+        self.table_table
+            .delete(Some("table_name"), &view_name_dt, "=")?;
+        Ok(())
+    }
+
+    /// Stores `body`, an INSERT, UPDATE or DELETE statement, as the trigger `trigger_name` that
+    /// runs it for each row of `table_name` that an `event` statement changes.
+    pub fn new_trigger(
+        &mut self,
+        trigger_name: &str,
+        table_name: &str,
+        timing: TriggerTiming,
+        event: TriggerEvent,
+        body: &str,
+    ) -> Result<()> {
+        if body.len() > MAX_TEXT_LENGTH {
+            return Err(DbError::Constraint(format!(
+                "Trigger statement is too long ({} > {} characters).",
+                body.len(),
+                MAX_TEXT_LENGTH
+            )));
+        }
+        self.trigger_table.insert(vec![
+            DataType::Text(trigger_name.to_string()),
+            DataType::Text(table_name.to_string()),
+            DataType::Text(timing.to_string()),
+            DataType::Text(event.to_string()),
+            DataType::Text(body.to_string()),
+        ])?;
+        Ok(())
+    }
+
+    pub fn drop_trigger(&mut self, trigger_name: &str) -> Result<()> {
+        let deleted = self.trigger_table.delete(
+            Some("trigger_name"),
+            &DataType::Text(trigger_name.to_string()),
+            "=",
+        )?;
+        if deleted.is_empty() {
+            return Err(DbError::Catalog(format!(
+                "Trigger {} does not exist",
+                trigger_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the triggers of `table_name` that fire for `event` statements, in the order they
+    /// were created.
+    fn load_triggers(&mut self, table_name: &str, event: TriggerEvent) -> Result<Vec<Trigger>> {
+        let records = self.trigger_table.search(
+            Some("table_name"),
+            DataType::Text(table_name.to_string()),
+            "=",
+        )?;
+        let mut triggers = vec![];
+        for record in records {
+            let texts = record
+                .values
+                .iter()
+                .map(|value| match value {
+                    DataType::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let Some(&[name, _, timing, trigger_event, body]) = texts.as_deref() else {
+                return Err(DbError::Corruption(format!(
+                    "Invalid trigger of table {}",
+                    table_name
+                )));
+            };
+            let timing = TriggerTiming::from_keyword(timing).ok_or_else(|| {
+                DbError::Corruption(format!("Invalid timing of trigger {}: {}", name, timing))
+            })?;
+            if TriggerEvent::from_keyword(trigger_event) == Some(event) {
+                triggers.push(Trigger {
+                    name: name.to_string(),
+                    timing,
+                    body: body.to_string(),
+                });
+            }
+        }
+        Ok(triggers)
+    }
+
+    /// Runs the `triggers` with `timing` for a row of a table with `columns`. `old` is the row
+    /// before an UPDATE or DELETE and `new` the row an INSERT or UPDATE writes; their values are
+    /// bound to `old.<column>` and `new.<column>` in the trigger statements, which are run as
+    /// prepared statements.
+    ///
+    /// Triggers fire here rather than in [`Table`] because they run whole statements, which
+    /// only the database can resolve.
+    fn fire_triggers(
+        &mut self,
+        triggers: &[Trigger],
+        timing: TriggerTiming,
+        columns: &[Column],
+        old: Option<&[DataType]>,
+        new: Option<&[DataType]>,
+    ) -> Result<()> {
+        for trigger in triggers.iter().filter(|t| t.timing == timing) {
+            if self.trigger_depth >= MAX_TRIGGER_DEPTH {
+                return Err(DbError::Constraint(format!(
+                    "Triggers nested more than {} levels deep.",
+                    MAX_TRIGGER_DEPTH
+                )));
+            }
+            // Only the trigger the user's statement fired names itself, instead of every
+            // trigger the error passes through.
+            let context = match self.trigger_depth {
+                0 => format!("Trigger {}: ", trigger.name),
+                _ => String::new(),
+            };
+            let mut tokens = tokenize(&trigger.body)?;
+            let mut params = vec![];
+            for token in tokens.iter_mut() {
+                let (row, name) = match token.split_once('.') {
+                    Some(("old", name)) => (old, name),
+                    Some(("new", name)) => (new, name),
+                    _ => continue,
+                };
+                let Some(row) = row else {
+                    return Err(DbError::Catalog(format!(
+                        "{}{} is not set.",
+                        context, token
+                    )));
+                };
+                let index = column_position(columns, name).map_err(|e| e.context(&context))?;
+                params.push(trigger_param(&row[index]));
+                *token = format!("${}", params.len());
+            }
+            let statement = parser::parse(&mut tokens).map_err(|e| e.context(&context))?;
+            let mut statement = PreparedStatement::new(statement);
+            for (i, value) in params.into_iter().enumerate() {
+                statement
+                    .bind(i + 1, value)
+                    .map_err(|e| e.context(&context))?;
+            }
+            self.trigger_depth += 1;
+            let result = statement.execute(self);
+            self.trigger_depth -= 1;
+            result.map_err(|e| e.context(&context))?;
+        }
+        Ok(())
+    }
+
+    pub fn drop_table(&mut self, table_name: &str) -> Result<()> {
+        if self.table_type(table_name)? == Some(VIEW_TABLE_TYPE) {
+            return Err(DbError::Catalog(format!(
+                "{} is a view. Use DROP VIEW.",
+                table_name
+            )));
+        }
         let table_name_dt = DataType::Text(table_name.to_string());
         let table = self
-            .load_table(table_name)
-            .ok_or(format!("Table {} does not exist", table_name))?;
-        let index_column = table.has_index();
-        if let Some(index_column) = index_column {
-            table.drop_index(index_column)?;
-        }
+            .load_table(table_name)?
+            .ok_or_else(|| DbError::Catalog(format!("Table {} does not exist.", table_name)))?;
         self.column_table
             .delete(Some("table_name"), &table_name_dt.clone(), "=")?;
+        self.default_table
+            .delete(Some("table_name"), &table_name_dt.clone(), "=")?;
+        self.trigger_table
+            .delete(Some("table_name"), &table_name_dt.clone(), "=")?;
+        self.row_id_table
+            .delete(Some("table_name"), &table_name_dt.clone(), "=")?;
         self.table_table
             .delete(Some("table_name"), &table_name_dt, "=")?;
+        table.drop_files()
+    }
+
+    /// Removes every record of `table_name`, resetting its table and index files to a single
+    /// empty page. Row IDs of new records start again at 1, or continue after those of the
+    /// removed records if `continue_identity`.
+    pub fn truncate_table(&mut self, table_name: &str, continue_identity: bool) -> Result<()> {
+        match self.table_type(table_name)? {
+            Some(VIEW_TABLE_TYPE) => {
+                return Err(DbError::Catalog(format!("{} is a view.", table_name)))
+            }
+            Some(SYSTEM_TABLE_TYPE) => {
+                return Err(DbError::Catalog(format!(
+                    "Cannot truncate system table {}.",
+                    table_name
+                )))
+            }
+            _ => {}
+        }
+        let mut table = self
+            .load_table(table_name)?
+            .ok_or_else(|| DbError::Catalog(format!("Table {} does not exist.", table_name)))?;
+        let last_row_id = table.last_row_id()?;
+        table.truncate()?;
+        // The row ID is written back after the pages are reset, as no record holds it anymore.
+        let table_name_dt = DataType::Text(table_name.to_string());
+        self.row_id_table
+            .delete(Some("table_name"), &table_name_dt, "=")?;
+        if continue_identity && last_row_id > 0 {
+            self.row_id_table
+                .insert(vec![table_name_dt, DataType::BigInt(last_row_id as i64)])?;
+        }
         Ok(())
     }
 
@@ -225,22 +773,30 @@ impl Database {
         input
     }
 
-    pub fn read_file_input(&mut self, file_path: &Path) -> Result<Vec<String>, String> {
+    /// Runs every statement of the script at `file_path` in order, stopping at the first error.
+    /// Errors name the failing statement and the line it starts on.
+    pub fn read_file_input(&mut self, file_path: &Path) -> Result<Vec<String>> {
         let content = std::fs::read_to_string(file_path)
-            .map_err(|e| format!("Failed reading file: {}", e))?;
-        let output = content
-            .split(';')
-            .map(|s| {
-                s.trim()
-                    .lines()
-                    .map(|line| line.trim())
-                    .collect::<Vec<_>>()
-                    .join(" ")
+            .map_err(|e| DbError::from(e).context("Failed reading file: "))?;
+        self.run_script(&content)
+    }
+
+    /// Runs every statement of `script`, see [`Database::read_file_input`].
+    pub fn run_script(&mut self, script: &str) -> Result<Vec<String>> {
+        tokenize_script(script)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut statement)| {
+                parser::parse_located(&mut statement, script)
+                    .and_then(|parsed| {
+                        self.execute(&parsed)
+                            .map_err(|e| locate_column(e, script, &statement.spans))
+                    })
+                    .map_err(|e| {
+                        e.context(&format!("Statement {} (line {}): ", i + 1, statement.line))
+                    })
             })
-            .filter(|s| !s.is_empty())
-            .map(|s| self.parse_user_input(&s))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(output)
+            .collect()
     }
 
     pub fn run(&mut self) {
@@ -248,46 +804,207 @@ impl Database {
         loop {
             let input = self.read_input();
             println!("Command: {}", input);
-            let res = self.parse_user_input(&input);
+            let res = tokenize_statement(&input).and_then(|mut tokens| {
+                let statement = parser::parse_located(&mut tokens, &input)?;
+                self.execute_to(&statement, &mut std::io::stdout().lock())
+                    .map_err(|e| locate_column(e, &input, &tokens.spans))
+            });
             match res {
-                Ok(m) => println!("{}", m),
+                Ok(()) => println!(),
                 Err(e) => println!("{}", e),
             }
         }
     }
 
-    pub fn parse_user_input(&mut self, input: &str) -> Result<String, String> {
-        let mut tokens: VecDeque<String> = input
-            .replace('(', " ( ")
-            .replace(')', " ) ")
-            .replace(',', " , ")
-            .replace(';', "")
-            .split_whitespace()
-            .map(|t| t.to_string().to_lowercase())
-            .collect();
-        match tokens.pop_front().as_deref().unwrap_or("") {
-            "show" => self.show(&mut tokens),
-            "select" => self.parse_select(&mut tokens),
-            "create" => self.parse_create(&mut tokens),
-            "insert" => self.parse_insert(&mut tokens),
-            "update" => self.parse_update(&mut tokens),
-            "delete" => self.parse_delete(&mut tokens),
-            "drop" => self.parse_drop(&mut tokens),
-            "help" => self.parse_help(&mut tokens),
-            "exit" => std::process::exit(0),
-            _ => Err(format!("Invalid command: {}", input)),
+    /// Parses and runs a single statement. The kind of a returned [`DbError`] tells whether the
+    /// statement was malformed, referred to missing objects, broke a constraint, or failed in
+    /// storage.
+    pub fn parse_user_input(&mut self, input: &str) -> Result<String> {
+        let mut tokens = tokenize_statement(input)?;
+        let statement = parser::parse_located(&mut tokens, input)?;
+        self.execute(&statement)
+            .map_err(|e| locate_column(e, input, &tokens.spans))
+    }
+
+    /// Parses `sql` once so it can be run repeatedly. Values are written as `?` or `$n`
+    /// placeholders and bound with [`PreparedStatement::bind`].
+    pub fn prepare(&self, sql: &str) -> Result<PreparedStatement> {
+        let statement = parser::parse_located(&mut tokenize_statement(sql)?, sql)?;
+        Ok(PreparedStatement::new(statement))
+    }
+
+    /// Runs a parsed statement like [`Database::execute`], writing its output to `out`. The
+    /// records of a SELECT statement are written as they are read.
+    pub fn execute_to(&mut self, statement: &Statement, out: &mut dyn Write) -> Result<()> {
+        match statement {
+            Statement::Select(select) => self.write_select(select, out),
+            statement => Ok(write!(out, "{}", self.execute(statement)?)?),
+        }
+    }
+
+    /// Runs a parsed statement and returns its output. If it fails, the records it and the
+    /// triggers it fired wrote are restored.
+    pub fn execute(&mut self, statement: &Statement) -> Result<String> {
+        if self.trigger_depth > 0 {
+            return self.execute_statement(statement);
+        }
+        let result = self.execute_statement(statement);
+        let changes = std::mem::take(&mut self.changes);
+        match result {
+            Err(e) => match self.undo(changes) {
+                Ok(()) => Err(e),
+                Err(undo) => Err(undo.context(&format!("{} Undoing the statement failed: ", e))),
+            },
+            result => result,
+        }
+    }
+
+    /// Reverts `changes`, newest first, so that each record is back where it was when the
+    /// change was made. Records deleted one after another from the same table are restored
+    /// together, so that the table is written again once.
+    fn undo(&mut self, changes: Vec<Change>) -> Result<()> {
+        let mut tables: HashMap<String, Table> = HashMap::new();
+        let mut changes = changes.into_iter().rev().peekable();
+        while let Some(change) = changes.next() {
+            let name = match &change {
+                Change::Inserted { table, .. }
+                | Change::Updated { table, .. }
+                | Change::Deleted { table, .. } => table.clone(),
+            };
+            let table = match tables.entry(name.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let table = self.load_user_table(entry.key())?;
+                    entry.insert(table)
+                }
+            };
+            match change {
+                Change::Inserted { record, .. } => table.delete_record(&record)?,
+                Change::Updated {
+                    old,
+                    new,
+                    column_index,
+                    ..
+                } => {
+                    table.update_record(&new, column_index, old.values[column_index].clone())?;
+                }
+                Change::Deleted { record, .. } => {
+                    let mut records = vec![record];
+                    while let Some(Change::Deleted { record, .. }) = changes.next_if(
+                        |change| matches!(change, Change::Deleted { table, .. } if *table == name),
+                    ) {
+                        records.push(record);
+                    }
+                    table.restore_records(records)?;
+                }
+            }
         }
+        Ok(())
     }
 
-    fn show(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        if tokens.pop_front().as_deref() != Some("tables") {
-            return Err("Invalid SHOW command. Expected TABLES.".to_string());
+    fn execute_statement(&mut self, statement: &Statement) -> Result<String> {
+        match statement {
+            Statement::ShowTables => self.show(),
+            Statement::Select(select) => self.select_statement(select),
+            Statement::CreateTable {
+                name,
+                columns,
+                flags,
+                keys,
+                if_not_exists,
+            } => {
+                if *if_not_exists && self.table_type(name)?.is_some() {
+                    return Ok(format!("{} already exists, skipped.", name));
+                }
+                self.create_table(name, columns, flags, keys)
+            }
+            Statement::CreateIndex {
+                table,
+                column,
+                if_not_exists,
+            } => self.create_index(table, column, *if_not_exists),
+            Statement::CreateView {
+                name,
+                definition,
+                query,
+                if_not_exists,
+            } => {
+                if *if_not_exists && self.table_type(name)?.is_some() {
+                    return Ok(format!("{} already exists, skipped.", name));
+                }
+                self.create_view(name, definition, query)
+            }
+            Statement::CreateTrigger {
+                name,
+                timing,
+                event,
+                table,
+                body,
+                if_not_exists,
+            } => {
+                if *if_not_exists && self.trigger_exists(name)? {
+                    return Ok(format!("Trigger {} already exists, skipped.", name));
+                }
+                self.create_trigger(name, *timing, *event, table, body)
+            }
+            Statement::Insert(insert) => self.insert(insert),
+            Statement::Update(update) => self.update(update),
+            Statement::Delete(delete) => self.delete(delete),
+            Statement::DropTable { name, if_exists } => {
+                if *if_exists && self.table_type(name)?.is_none() {
+                    return Ok(format!("Table {} does not exist, skipped.", name));
+                }
+                self.drop_table(name)?;
+                Ok(format!("Table {} dropped.", name))
+            }
+            Statement::DropView { name, if_exists } => {
+                if *if_exists && self.table_type(name)?.is_none() {
+                    return Ok(format!("View {} does not exist, skipped.", name));
+                }
+                self.drop_view(name)?;
+                Ok(format!("View {} dropped.", name))
+            }
+            Statement::DropTrigger { name, if_exists } => {
+                if *if_exists && !self.trigger_exists(name)? {
+                    return Ok(format!("Trigger {} does not exist, skipped.", name));
+                }
+                self.drop_trigger(name)?;
+                Ok(format!("Trigger {} dropped.", name))
+            }
+            Statement::Truncate {
+                table,
+                continue_identity,
+            } => {
+                self.truncate_table(table, *continue_identity)?;
+                Ok(format!("Table {} truncated.", table))
+            }
+            Statement::Explain(statement) => self.explain(statement),
+            Statement::Help => Ok(help()),
+            Statement::Exit => std::process::exit(0),
         }
-        let tables = self.table_table.search(None, DataType::Null, "=")?;
-        self.display(tables, &[0])
     }
 
-    fn display(&self, records: Vec<Record>, columns: &[usize]) -> Result<String, String> {
+    fn show(&mut self) -> Result<String> {
+        let tables = self
+            .table_table
+            .search(None, DataType::Null, "=")?
+            .into_iter()
+            .map(|t| {
+                let kind = match t.values[1] {
+                    DataType::TinyInt(SYSTEM_TABLE_TYPE) => "system",
+                    DataType::TinyInt(VIEW_TABLE_TYPE) => "view",
+                    _ => "table",
+                };
+                Record::new(
+                    vec![t.values[0].clone(), DataType::Text(kind.to_string())],
+                    t.row_id,
+                )
+            })
+            .collect();
+        self.display(tables, &[0, 1])
+    }
+
+    fn display(&self, records: Vec<Record>, columns: &[usize]) -> Result<String> {
         if records.is_empty() {
             return Ok("No records found.".to_string());
         }
@@ -313,567 +1030,4240 @@ impl Database {
         Ok(out)
     }
 
-    fn parse_select(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        let mut c_tokens: VecDeque<String> = tokens
-            .iter()
-            .take_while(|t| t.to_lowercase() != "from")
-            .cloned()
-            .collect();
-        for _ in 0..c_tokens.len() {
-            tokens.pop_front();
-        }
-        tokens.pop_front();
-        let table = tokens.pop_front().ok_or("No table specified.")?;
-        let mut table = self
-            .load_table(&table)
-            .ok_or(format!("Table {} not found.", table))?;
-        let columns = self.parse_columns(&mut c_tokens, &table)?;
-        let column_ids: Vec<usize> = columns
-            .iter()
-            .map(|c| table.columns.iter().position(|x| x.name == c.name).unwrap())
-            .collect();
-        let (search_column, operator, value) = self.parse_condition(tokens, &table)?;
-        let mut out = format!("Table: {}\n", table.name);
-        for c in &columns {
-            write!(&mut out, "{} ", c.name).expect("Error writing to str");
-        }
-        writeln!(&mut out).expect("Error writing to str");
-        let records = table.search(search_column.as_deref(), value, &operator)?;
-        let records = self.display(records, &column_ids);
-        write!(&mut out, "{}", records?).expect("Error writing to str");
-        Ok(out)
+    fn select_statement(&mut self, select: &Select) -> Result<String> {
+        let mut out = vec![];
+        self.write_select(select, &mut out)?;
+        Ok(String::from_utf8(out).expect("Error writing to str"))
     }
 
-    fn parse_create(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        match tokens.pop_front().as_deref() {
-            Some("table") => self.create_table(tokens),
-            Some("index") => self.create_index(tokens),
-            _ => Err("Invalid CREATE command. Expected TABLE or INDEX.".to_string()),
+    /// Runs a SELECT statement, writing each record to `out` as it is read.
+    fn write_select(&mut self, select: &Select, out: &mut dyn Write) -> Result<()> {
+        let query = self.resolve_select(select, &[])?;
+        let records = self.select_rows(&query)?;
+        writeln!(out, "Table: {}", query.source)?;
+        for c in query.columns() {
+            write!(out, "{} ", c.name)?;
         }
+        writeln!(out)?;
+        let mut empty = true;
+        for record in records {
+            for value in record?.values {
+                write!(out, "{} ", value)?;
+            }
+            writeln!(out)?;
+            empty = false;
+        }
+        if empty {
+            write!(out, "No records found.")?;
+        }
+        Ok(())
     }
 
-    fn create_table(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        let mut columns = vec![];
-        let mut flag_vec = vec![];
-        let table_name = tokens.pop_front().ok_or("No table name specified.")?;
-        if self.load_table(&table_name).is_some() {
-            return Err("Table already exists.".to_string());
+    /// Resolves a SELECT statement against a table, a view, or one of the common tables in
+    /// `scope` or defined by the statement itself.
+    fn resolve_select(
+        &mut self,
+        select: &Select,
+        scope: &[CommonTableRecords],
+    ) -> Result<SelectQuery> {
+        let mut scope = scope.to_vec();
+        for common_table in &select.with {
+            let records = self.resolve_common_table(common_table, select.recursive, &scope)?;
+            scope.push(records);
         }
-        if tokens.pop_front().as_deref() != Some("(") {
-            return Err("Expected '('.".to_string());
+        let source = select.source.clone();
+        let (common_table, mut source_columns) = self.resolve_source(&source, &scope)?;
+        let mut joins = vec![];
+        if !select.joins.is_empty() {
+            source_columns = qualify_columns(&source, source_columns);
         }
-        loop {
-            let column_name = tokens.pop_front().ok_or("No column name specified.")?;
-            let data_type = tokens
-                .pop_front()
-                .ok_or("No data type specified.")?
-                .parse()?;
-            let mut flags = (false, false, false);
-            let mut done = false;
-            while let Some(next) = tokens.pop_front().as_deref() {
-                match next {
-                    "primary_key" => flags.0 = true,
-                    "not_null" => flags.1 = true,
-                    "unique" => flags.2 = true,
-                    "," => break,
-                    ")" => {
-                        done = true;
+        for join in &select.joins {
+            let (common_table, columns) = self.resolve_source(&join.source, &scope)?;
+            let columns = qualify_columns(&join.source, columns);
+            let start = source_columns.len();
+            source_columns.extend(columns.iter().cloned());
+            let condition = Predicate::resolve(&join.condition, &source_columns)?;
+            joins.push(JoinQuery {
+                source: join.source.clone(),
+                common_table,
+                key: join_key(&condition, start..source_columns.len()),
+                columns,
+                search: every_record(),
+                condition,
+            });
+        }
+        let mut windows = vec![];
+        let mut aggregates = vec![];
+        let projections: Vec<_> = match &select.columns {
+            Some(exprs) => exprs
+                .iter()
+                .map(|expr| {
+                    if let Some(aggregate) = Aggregate::resolve(expr, &source_columns)? {
+                        let column =
+                            Column::new(&aggregate.to_string(), aggregate.data_type(), true, false);
+                        let expression = Expression::Column {
+                            index: aggregates.len(),
+                            name: column.name.clone(),
+                            data_type: column.data_type.clone(),
+                        };
+                        aggregates.push(aggregate);
+                        return Ok((column, expression));
+                    }
+                    if let Expr::Window { .. } = expr {
+                        let window = Window::resolve(expr, &source_columns)?;
+                        let column =
+                            Column::new(&window.to_string(), window.data_type(), true, false);
+                        let expression = Expression::Column {
+                            index: source_columns.len() + windows.len(),
+                            name: column.name.clone(),
+                            data_type: column.data_type.clone(),
+                        };
+                        windows.push(window);
+                        return Ok((column, expression));
+                    }
+                    let expression = Expression::resolve(expr, &source_columns, None)?;
+                    let column = match &expression {
+                        Expression::Column { index, .. } => source_columns[*index].clone(),
+                        _ => Column::new(&expr.to_string(), expression.data_type(), true, false),
+                    };
+                    Ok((column, expression))
+                })
+                .collect::<Result<_>>()?,
+            None => source_columns
+                .iter()
+                .enumerate()
+                .map(|(index, c)| {
+                    let expression = Expression::Column {
+                        index,
+                        name: c.name.clone(),
+                        data_type: c.data_type.clone(),
+                    };
+                    (c.clone(), expression)
+                })
+                .collect(),
+        };
+        if !aggregates.is_empty() && aggregates.len() != projections.len() {
+            return Err(DbError::Parse(
+                "MIN and MAX cannot be selected with other columns.".to_string(),
+            ));
+        }
+        let (mut condition, filter) = resolve_condition(&select.condition, &source_columns)?;
+        if let (Some(name), false) = (&condition.0, joins.is_empty()) {
+            // The search condition of a join is answered while reading the source of its
+            // column, so that it is costed like that of a single table.
+            let index = column_position(&source_columns, name)?;
+            let column = &source_columns[index].name;
+            let mut start =
+                source_columns.len() - joins.iter().map(|j| j.columns.len()).sum::<usize>();
+            let search = |source: &str| {
+                let name = column
+                    .strip_prefix(&format!("{}.", source))
+                    .unwrap_or(column);
+                (
+                    Some(name.to_string()),
+                    condition.1.clone(),
+                    condition.2.clone(),
+                )
+            };
+            if index < start {
+                condition = search(&source);
+            } else {
+                for join in joins.iter_mut() {
+                    if index < start + join.columns.len() {
+                        join.search = search(&join.source);
+                        condition = every_record();
                         break;
                     }
-                    _ => return Err("Expected ',' or ')' or flag.".to_string()),
+                    start += join.columns.len();
                 }
             }
-            flag_vec.push(flags);
-            let col = Column::new(&column_name, data_type, !flags.1, flags.2);
-            columns.push(col);
-            if done {
-                break;
-            }
-        }
-        if flag_vec.iter().filter(|(p, _, _)| *p).count() > 1 {
-            return Err("Multiple primary keys specified.".to_string());
         }
-        if !tokens.is_empty() && tokens.pop_front().as_deref() != Some(";") {
-            return Err("Unexpected tokens.".to_string());
-        }
-        let mut table = Table::new(&table_name, columns, USER_DIR);
-        self.new_table(&mut table, flag_vec)?;
-        Ok(format!("{} created.", table_name))
-    }
-
-    fn create_index(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        let table_name = tokens.pop_front().ok_or("No table name specified.")?;
-        let mut table = self.load_table(&table_name).ok_or("Table not found.")?;
-        let next = tokens.pop_front().ok_or("No column name specified.")?;
-        let column = if next == "(" {
-            let column = tokens.pop_front().ok_or("No column name specified.")?;
-            tokens.pop_front().ok_or("Expected ')'")?;
-            column
-        } else {
-            next
+        let mut query = SelectQuery {
+            source,
+            source_columns,
+            common_table,
+            joins,
+            projections,
+            windows,
+            aggregates,
+            condition,
+            filter,
+            order_by: vec![],
+            compound: vec![],
+            limit: select.limit,
         };
-        table.create_index(&column)?;
-        Ok(format!("Index created on {}({}).", table_name, column))
-    }
-
-    fn parse_insert(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        if tokens.pop_front().ok_or("Expected INTO")? != "into" {
-            return Err("Expected INTO.".to_string());
+        for (operator, all, select) in &select.compound {
+            let other = self.resolve_select(select, &scope)?;
+            if other.projections.len() != query.projections.len() {
+                return Err(DbError::Parse(format!(
+                    "Each SELECT in a {} must have the same number of columns, got {} and {}.",
+                    operator,
+                    query.projections.len(),
+                    other.projections.len()
+                )));
+            }
+            for ((column, _), (other_column, _)) in
+                query.projections.iter_mut().zip(&other.projections)
+            {
+                let data_type =
+                    unify(&column.data_type, &other_column.data_type).ok_or_else(|| {
+                        DbError::Type(format!(
+                            "Column {} has incompatible types {} and {} in {}.",
+                            column.name,
+                            column.data_type.type_name(),
+                            other_column.data_type.type_name(),
+                            operator
+                        ))
+                    })?;
+                *column = Column::new(&column.name, data_type, true, false);
+            }
+            query.compound.push((*operator, *all, other));
         }
-        let table_name = tokens.pop_front().ok_or("No table specified.")?;
-        let mut table = self.load_table(&table_name).ok_or("Table not found.")?;
-        let columns = match tokens
-            .pop_front()
-            .ok_or("Expected columns or VALUES.")?
-            .as_str()
-        {
-            "(" => self.parse_columns(tokens, &table)?,
-            "values" => table.columns.iter().collect(),
-            _ => return Err("Expected columns or VALUES.".to_string()),
+        // ORDER BY on combined records can only refer to the columns they have.
+        let order_by_columns = match query.compound.is_empty() {
+            true => query.source_columns.clone(),
+            false => query.columns(),
         };
-        let values = self.parse_values(tokens, &columns)?;
-        if values.len() != columns.len() {
-            return Err("Number of columns and values do not match.".to_string());
-        }
-        let mut col_val = columns.into_iter().zip(values);
-        let full_valls = table
-            .columns
+        query.order_by = select
+            .order_by
             .iter()
-            .map(|c| {
-                col_val
-                    .find(|(col, _)| col.name == c.name)
-                    .map_or(DataType::Null, |(_, val)| val)
+            .map(|(expr, descending)| {
+                Ok((
+                    Expression::resolve(expr, &order_by_columns, None)?,
+                    *descending,
+                ))
             })
-            .collect();
-        table.insert(full_valls)?;
-        Ok(format!("1 row inserted into {}.", table_name))
+            .collect::<Result<_>>()?;
+        Ok(query)
     }
 
-    fn parse_update(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        let table_name = tokens.pop_front().ok_or("No table specified.")?;
-        let mut table = self.load_table(&table_name).ok_or("Table not found.")?;
-        if tokens.pop_front().as_deref() != Some("set") {
-            return Err("Expected SET.".to_string());
-        }
-        let column_name = tokens.pop_front().ok_or("No column specified.")?;
-        let column = table
-            .columns
-            .iter()
-            .find(|c| c.name == column_name)
-            .ok_or(format!("Column {} not found.", column_name))?;
-        if tokens.pop_front().as_deref() != Some("=") {
-            return Err("Expected =.".to_string());
-        }
-        let value_str = tokens.pop_front().ok_or("No value specified.")?;
-        let value = DataType::parse_str(column.data_type.clone(), &value_str)
-            .map_err(|e| format!("Failed parsing {} into {}: {}", value_str, column_name, e))?;
-        let (search_column, search_operator, search_value) =
-            self.parse_condition(tokens, &table)?;
-        let updated = table.update(
-            search_column.as_deref(),
-            search_value,
-            &search_operator,
-            &column_name,
-            value,
-        )?;
-        Ok(format!("{} rows updated.", updated))
+    /// Returns the common table in `scope` named `source`, if any, and the columns of `source`.
+    fn resolve_source(
+        &mut self,
+        source: &str,
+        scope: &[CommonTableRecords],
+    ) -> Result<(Option<CommonTableRecords>, Vec<Column>)> {
+        // Common tables hide tables and views of the same name.
+        let common_table = scope.iter().rev().find(|t| t.name == source).cloned();
+        let columns = match (&common_table, self.load_view(source)?) {
+            (Some(common_table), _) => common_table.columns.clone(),
+            (None, Some(definition)) => self.resolve_view(&definition)?.columns(),
+            (None, None) => {
+                self.load_table(source)?
+                    .ok_or_else(|| DbError::Catalog(format!("Table {} not found.", source)))?
+                    .columns
+            }
+        };
+        Ok((common_table, columns))
     }
 
-    fn parse_delete(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        if tokens.pop_front().as_deref() != Some("from") {
-            return Err("Expected FROM.".to_string());
-        }
-        let table_name = tokens.pop_front().ok_or("No table specified.")?;
-        let mut table = self.load_table(&table_name).ok_or("Table not found.")?;
-        let (search_column, search_operator, search_value) =
-            self.parse_condition(tokens, &table)?;
-        let deleted = table.delete(search_column.as_deref(), &search_value, &search_operator)?;
-        Ok(format!("{} rows deleted.", deleted))
+    /// Resolves the stored definition of a view.
+    fn resolve_view(&mut self, definition: &str) -> Result<SelectQuery> {
+        let select = parser::parse_select(&mut tokenize(definition)?)?;
+        self.resolve_select(&select, &[])
     }
 
-    fn parse_drop(&mut self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        if tokens.pop_front().as_deref() != Some("table") {
-            return Err("Expected TABLE.".to_string());
+    /// Computes the records of a common table expression.
+    ///
+    /// With `recursive`, a query of the form `<anchor> UNION [ALL] <step>` whose step selects
+    /// from the common table itself is run repeatedly: each run of the step selects from the
+    /// records added by the previous one, until it adds none.
+    fn resolve_common_table(
+        &mut self,
+        common_table: &CommonTable,
+        recursive: bool,
+        scope: &[CommonTableRecords],
+    ) -> Result<CommonTableRecords> {
+        let name = &common_table.name;
+        let query = &common_table.query;
+        let selects_itself = |select: &Select| {
+            select.source == *name || select.joins.iter().any(|j| j.source == *name)
+        };
+        let refers_to_itself = selects_itself(query)
+            || query
+                .compound
+                .iter()
+                .any(|(_, _, select)| selects_itself(select));
+        if !recursive || !refers_to_itself {
+            let resolved = self.resolve_select(query, scope)?;
+            let records = self.select(&resolved)?;
+            return Ok(CommonTableRecords {
+                name: name.clone(),
+                columns: rename_columns(resolved.columns(), common_table)?,
+                records: Rc::new(records),
+                plan: Plan::CommonTable {
+                    name: name.clone(),
+                    input: Box::new(self.plan_select(&resolved)?),
+                },
+            });
         }
-        let table_name = tokens.pop_front().ok_or("No table specified.")?;
-        self.drop_table(&table_name)?;
-        Ok(format!("Table {} dropped.", table_name))
-    }
-
-    fn parse_columns<'a>(
-        &self,
-        tokens: &mut VecDeque<String>,
-        table: &'a Table,
-    ) -> Result<Vec<&'a Column>, String> {
-        if tokens[0] == "*" {
-            tokens.pop_front();
-            return Ok(table.columns.iter().collect());
+        let (all, step) = match query.compound.as_slice() {
+            [(SetOperator::Union, all, step)]
+                if !selects_itself(query) && query.order_by.is_empty() && query.limit.is_none() =>
+            {
+                (*all, step)
+            }
+            _ => {
+                return Err(DbError::Parse(format!(
+                    "Recursive common table {} must be written as <select> UNION [ALL] <select>.",
+                    name
+                )))
+            }
+        };
+        let anchor = self.resolve_select(
+            &Select {
+                compound: vec![],
+                ..query.clone()
+            },
+            scope,
+        )?;
+        let columns = rename_columns(anchor.columns(), common_table)?;
+        let mut step_scope = scope.to_vec();
+        step_scope.push(CommonTableRecords {
+            name: name.clone(),
+            columns: columns.clone(),
+            records: Rc::new(vec![]),
+            plan: Plan::WorkTable { name: name.clone() },
+        });
+        let mut step = self.resolve_select(step, &step_scope)?;
+        if step.projections.len() != columns.len() {
+            return Err(DbError::Parse(format!(
+                "Each SELECT in a UNION must have the same number of columns, got {} and {}.",
+                columns.len(),
+                step.projections.len()
+            )));
         }
-
-        let collect = tokens
-            .iter()
-            .take_while(|&t| t.to_lowercase() != "from" && t != ")")
-            .filter(|&t| t != ",")
-            .map(|t| {
-                table
-                    .columns
-                    .iter()
-                    .find(|&c| c.name == *t)
-                    .ok_or(format!("Column {} not found.", t))
-            })
-            .collect();
-        while let Some(t) = tokens.pop_front() {
-            if t == "from" || t == "values" {
-                break;
+        for (column, (step_column, _)) in columns.iter().zip(&step.projections) {
+            if unify(&column.data_type, &step_column.data_type).is_none() {
+                return Err(DbError::Type(format!(
+                    "Column {} has incompatible types {} and {} in UNION.",
+                    column.name,
+                    column.data_type.type_name(),
+                    step_column.data_type.type_name()
+                )));
             }
         }
-        collect
-    }
-
-    fn parse_values(
-        &self,
-        tokens: &mut VecDeque<String>,
-        columns: &[&Column],
-    ) -> Result<Vec<DataType>, String> {
-        if tokens.is_empty() {
-            return Err("No values specified.".to_string());
-        }
-        if let Some(t) = tokens.pop_front() {
-            if t != "(" {
-                return Err(format!("Expected '('. Found: {}", t));
-            }
-        }
-        let mut values = vec![];
-        let mut current = String::new();
-        while let Some(t) = tokens.pop_front() {
-            if t == "," || t == ")" {
-                if columns.len() < values.len() + 1 {
-                    return Err(format!(
-                        "Too many values specified. Expected {} but got {}.",
-                        columns.len(),
-                        values.len() + 1
-                    ));
-                }
-                values.push(
-                    DataType::parse_str(columns[values.len()].data_type.clone(), &current)
-                        .map_err(|e| {
-                            format!(
-                                "Failed parsing {} into {}: {}",
-                                current,
-                                columns[values.len()].name,
-                                e
-                            )
-                        })?,
-                );
-                current.clear();
-                continue;
+        let mut records = fit_records(self.select(&anchor)?, &columns)?;
+        if !all {
+            records = combine(records, SetOperator::Union, false, vec![]);
+        }
+        let mut added = records.clone();
+        let mut steps = 0;
+        while !added.is_empty() {
+            steps += 1;
+            if steps > MAX_RECURSIVE_STEPS {
+                return Err(DbError::Constraint(format!(
+                    "Recursive common table {} did not finish after {} steps.",
+                    name, MAX_RECURSIVE_STEPS
+                )));
+            }
+            // The step reads the records added by the previous one wherever it selects itself.
+            let added_records = Rc::new(added);
+            let work_tables = step
+                .common_table
+                .iter_mut()
+                .chain(
+                    step.joins
+                        .iter_mut()
+                        .filter_map(|j| j.common_table.as_mut()),
+                )
+                .filter(|work_table| work_table.name == *name);
+            for work_table in work_tables {
+                work_table.records = added_records.clone();
             }
-            if !current.is_empty() {
-                current.push(' ');
+            added = fit_records(self.select(&step)?, &columns)?;
+            if !all {
+                added = combine(added, SetOperator::Except, false, records.clone());
             }
-            current.push_str(&t);
+            records.extend(added.iter().cloned());
         }
-        Ok(values)
+        let plan = Plan::SetOperation {
+            operator: match all {
+                true => "RECURSIVE UNION ALL".to_string(),
+                false => "RECURSIVE UNION".to_string(),
+            },
+            left: Box::new(self.plan_select(&anchor)?),
+            right: Box::new(self.plan_select(&step)?),
+        };
+        Ok(CommonTableRecords {
+            name: name.clone(),
+            columns,
+            records: Rc::new(records),
+            plan: Plan::CommonTable {
+                name: name.clone(),
+                input: Box::new(plan),
+            },
+        })
     }
 
-    fn parse_condition(
-        &self,
-        tokens: &mut VecDeque<String>,
-        table: &Table,
-    ) -> Result<(Option<String>, String, DataType), String> {
-        if tokens.pop_front().as_deref() != Some("where") {
-            return Ok((None, "=".to_string(), DataType::Null));
-        }
-        let column_name = tokens.pop_front().ok_or("No column specified.")?;
-        let column = table
-            .columns
-            .iter()
-            .find(|c| c.name == column_name)
-            .ok_or(format!("Column {} not found.", column_name))?;
-        let operator = tokens.pop_front().ok_or("No operator specified.")?;
-        let value_str = tokens.pop_front().ok_or("No value specified.")?;
-        let value = DataType::parse_str(column.data_type.clone(), &value_str)
-            .map_err(|e| format!("Failed parsing {} into {}: {}", value_str, column_name, e))?;
-        Ok((Some(column_name), operator, value))
+    /// Runs a resolved SELECT statement.
+    ///
+    /// Returns the matching records, holding the values of the selected expressions.
+    fn select(&mut self, query: &SelectQuery) -> Result<Vec<Record>> {
+        self.select_rows(query)?.collect()
     }
 
-    fn parse_help(&self, tokens: &mut VecDeque<String>) -> Result<String, String> {
-        if tokens.len() > 1 {
-            return Err("Invalid HELP command. Expected no arguments.".to_string());
+    /// Returns the operators running a resolved SELECT statement, following the steps of
+    /// [`Database::plan_select`]. Records are read as they are pulled from the result.
+    fn select_rows(&mut self, query: &SelectQuery) -> Result<Rows> {
+        let index_aggregate = self.index_aggregate(query)?;
+        let aggregated = index_aggregate.is_some();
+        let index_order = self.index_order(query)?;
+        // The single record of the aggregates needs no sorting.
+        let sorted = index_order.is_some() || !query.aggregates.is_empty();
+        let mut input = match (index_aggregate, index_order) {
+            (Some((table, column, descending)), _) => {
+                index_aggregate_rows(table, &column, descending)?
+            }
+            (None, Some((table, column, descending))) => {
+                index_order_rows(table, &column, descending, &query.condition)?
+            }
+            (None, None) => self.source_rows(
+                &query.source,
+                query.common_table.as_ref(),
+                &query.condition,
+                query.own_source_columns(),
+            )?,
+        };
+        for join in &query.joins {
+            let right = self.join_rows(join)?;
+            input = Box::new(Join::new(input, right, join.condition.clone()));
         }
-        let mut out = String::new();
-        writeln!(&mut out, "Available commands:").expect("Error writing to str");
-        writeln!(&mut out, "SHOW TABLES;").expect("Error writing to str");
-        writeln!(&mut out, "\tDisplay a list of all tables in the database.")
-            .expect("Error writing to str");
-        writeln!(
-            &mut out,
-            "SELECT <columns> FROM <table> [WHERE <condition>];"
-        )
-        .expect("Error writing to str");
-        writeln!(&mut out, "\tDisplay the selected columns from the table.")
-            .expect("Error writing to str");
-        writeln!(
-            &mut out,
-            "CREATE TABLE <table> (<column_name> <data_type> [PRIMARY_KEY|NOT_NULL|UNIQUE], ...);"
-        )
-        .expect("Error writing to str");
-        writeln!(&mut out, "\tCreate a new table with the specified columns.")
-            .expect("Error writing to str");
-        writeln!(
-            &mut out,
-            "INSERT INTO <table> [(column1, ...)] VALUES (<value>, ...);"
-        )
-        .expect("Error writing to str");
-        writeln!(&mut out, "\tInsert a new row into the table.").expect("Error writing to str");
-        writeln!(
-            &mut out,
-            "UPDATE <table> SET <column> = <value> [WHERE <condition>];"
-        )
-        .expect("Error writing to str");
-        writeln!(&mut out, "\tUpdate the specified column in the table.")
-            .expect("Error writing to str");
-        writeln!(&mut out, "DELETE FROM <table> [WHERE <condition>];")
-            .expect("Error writing to str");
-        writeln!(&mut out, "\tDelete rows from the table.").expect("Error writing to str");
-        writeln!(&mut out, "DROP TABLE <table>;").expect("Error writing to str");
-        writeln!(&mut out, "\tDelete the table from the database.").expect("Error writing to str");
-        writeln!(&mut out, "HELP;").expect("Error writing to str");
-        writeln!(&mut out, "\tDisplay this help message.").expect("Error writing to str");
-        writeln!(&mut out, "EXIT;").expect("Error writing to str");
-        writeln!(&mut out, "\tExit the database.").expect("Error writing to str");
+        input = filter_rows(input, query.filter.clone());
+        if !query.aggregates.is_empty() && !aggregated {
+            input = rows(vec![aggregate_rows(input, &query.aggregates)?]);
+        }
+        if !query.windows.is_empty() {
+            // Window functions are computed over every record at once.
+            let records = input.collect::<Result<_>>()?;
+            input = rows(evaluate_windows(records, &query.windows)?);
+        }
+        let projections = query.projections.iter().map(|(_, e)| e.clone()).collect();
+        if query.compound.is_empty() {
+            if !sorted {
+                input = sort_rows(input, &query.order_by);
+            }
+            input = Box::new(Project::new(input, projections));
+        } else {
+            let columns = query.columns();
+            let records = Project::new(input, projections).collect::<Result<_>>()?;
+            let mut records = fit_records(records, &columns)?;
+            for (operator, all, other) in &query.compound {
+                let other = fit_records(self.select(other)?, &columns)?;
+                records = combine(records, *operator, *all, other);
+            }
+            input = sort_rows(rows(records), &query.order_by);
+        }
+        if let Some(count) = query.limit {
+            input = Box::new(Limit::new(input, count));
+        }
+        Ok(input)
+    }
+
+    /// Returns the table, column and direction of the ORDER BY clause of `query` if its records
+    /// can be read in that order from the index on the column instead of being sorted. This is
+    /// the case when it orders a single table by an indexed column, with no search condition on
+    /// another column.
+    fn index_order(&mut self, query: &SelectQuery) -> Result<Option<(Table, String, bool)>> {
+        let [(Expression::Column { index, .. }, descending)] = query.order_by.as_slice() else {
+            return Ok(None);
+        };
+        if query.common_table.is_some()
+            || !query.joins.is_empty()
+            || !query.windows.is_empty()
+            || !query.aggregates.is_empty()
+            || !query.compound.is_empty()
+            || self.load_view(&query.source)?.is_some()
+        {
+            return Ok(None);
+        }
+        let Some(mut table) = self.load_table(&query.source)? else {
+            return Ok(None);
+        };
+        let column = &query.source_columns[*index].name;
+        let other_column = query.condition.0.as_ref().is_some_and(|c| c != column);
+        if other_column || table.get_index_file(column)?.is_none() {
+            return Ok(None);
+        }
+        // Reading in index order saves sorting the records, but may touch more pages than the
+        // search would.
+        let ordered = plan_index_order(
+            &mut table,
+            column.clone(),
+            *descending,
+            &query.condition,
+            query.source_limit(),
+        )?;
+        if ordered.pages() > plan_search(&mut table, &query.condition)?.pages() {
+            return Ok(None);
+        }
+        Ok(Some((table, column.clone(), *descending)))
+    }
+
+    /// Returns the table, column and direction to read a single MIN or MAX of `query` from, if it
+    /// is that of an indexed column of a table, selected without a WHERE clause. The result is
+    /// then the first value read from the index in that direction.
+    fn index_aggregate(&mut self, query: &SelectQuery) -> Result<Option<(Table, String, bool)>> {
+        let [aggregate] = query.aggregates.as_slice() else {
+            return Ok(None);
+        };
+        let Expression::Column { index, .. } = aggregate.arg() else {
+            return Ok(None);
+        };
+        if query.common_table.is_some()
+            || !query.joins.is_empty()
+            || query.condition.0.is_some()
+            || query.filter.is_some()
+            || self.load_view(&query.source)?.is_some()
+        {
+            return Ok(None);
+        }
+        let Some(table) = self.load_table(&query.source)? else {
+            return Ok(None);
+        };
+        let column = query.source_columns[*index].name.clone();
+        if table.get_index_file(&column)?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some((table, column, aggregate.descending())))
+    }
+
+    /// Returns the records of `source` matching `condition`: those of `common_table` if given,
+    /// otherwise those of the view or table `source`. `columns` are the columns of `source`.
+    fn source_rows(
+        &mut self,
+        source: &str,
+        common_table: Option<&CommonTableRecords>,
+        condition: &Condition,
+        columns: &[Column],
+    ) -> Result<Rows> {
+        let input = match (common_table, self.load_view(source)?) {
+            (Some(common_table), _) => shared_rows(common_table.records.clone()),
+            (None, Some(definition)) => {
+                let view_query = self.resolve_view(&definition)?;
+                self.select_rows(&view_query)?
+            }
+            (None, None) => {
+                let table = self
+                    .load_table(source)?
+                    .ok_or_else(|| DbError::Catalog(format!("Table {} not found.", source)))?;
+                return search_rows(table, condition);
+            }
+        };
+        condition_rows(input, condition, columns)
+    }
+
+    /// Returns a function reading the records of the source of `join` matching its search
+    /// condition, each time it is called with a left record. Tables are read again on every
+    /// call, through the index on the key of the join if [`Database::join_lookup`] finds that
+    /// cheaper, while the records of a view are computed once and kept.
+    fn join_rows(&mut self, join: &JoinQuery) -> Result<impl FnMut(&Record) -> Result<Rows>> {
+        let source = match (&join.common_table, self.load_view(&join.source)?) {
+            (Some(common_table), _) => JoinSource::Records(common_table.records.clone()),
+            (None, Some(definition)) => {
+                let view_query = self.resolve_view(&definition)?;
+                JoinSource::Records(Rc::new(self.select(&view_query)?))
+            }
+            (None, None) => match self.join_lookup(join)? {
+                Some((table, column)) => JoinSource::Lookup(table, column),
+                None => JoinSource::Table(self.load_table(&join.source)?.ok_or_else(|| {
+                    DbError::Catalog(format!("Table {} not found.", join.source))
+                })?),
+            },
+        };
+        let (search, columns) = (join.search.clone(), join.columns.clone());
+        let key = join.key.map(|(left, _)| left);
+        Ok(move |left: &Record| match &source {
+            JoinSource::Records(records) => {
+                condition_rows(shared_rows(records.clone()), &search, &columns)
+            }
+            JoinSource::Table(table) => search_rows(table.reopen()?, &search),
+            JoinSource::Lookup(table, column) => {
+                let value = left.values[key.expect("Lookups have a key")].clone();
+                let data_type = &table.columns[column_position(&table.columns, column)?].data_type;
+                // A key that does not convert to the type of the column equals none of its values.
+                let value = match coerce(value, data_type) {
+                    Some(value) if value != DataType::Null => value,
+                    _ => return Ok(rows(vec![])),
+                };
+                let lookup = (Some(column.clone()), "=".to_string(), value);
+                let found = index_order_rows(table.reopen()?, column, false, &lookup)?;
+                condition_rows(found, &search, &table.columns)
+            }
+        })
+    }
+
+    /// Returns the table joined by `join` and the column of its key if the records matching each
+    /// left record are estimated to be read from fewer pages by looking up the value of the key
+    /// in the index on the column than by answering the search condition of the join.
+    fn join_lookup(&mut self, join: &JoinQuery) -> Result<Option<(Table, String)>> {
+        let Some((_, right)) = join.key else {
+            return Ok(None);
+        };
+        if join.common_table.is_some() || self.load_view(&join.source)?.is_some() {
+            return Ok(None);
+        }
+        let Some(mut table) = self.load_table(&join.source)? else {
+            return Ok(None);
+        };
+        let column = table.columns[right].name.clone();
+        if table.get_index_file(&column)?.is_none() {
+            return Ok(None);
+        }
+        let (lookup_pages, _) = table.estimate_lookup(&column)?;
+        if lookup_pages >= plan_search(&mut table, &join.search)?.pages() {
+            return Ok(None);
+        }
+        Ok(Some((table, column)))
+    }
+
+    fn explain(&mut self, statement: &Statement) -> Result<String> {
+        let plan = match statement {
+            Statement::Select(select) => {
+                let query = self.resolve_select(select, &[])?;
+                self.plan_select(&query)?
+            }
+            Statement::Update(update) => {
+                let mut query = self.resolve_update(update)?;
+                let search = plan_search(&mut query.table, &query.condition)?;
+                Plan::Update {
+                    table: query.table.name.clone(),
+                    column: query.column.clone(),
+                    input: Box::new(plan_filter(search, &query.filter)),
+                }
+            }
+            Statement::Delete(delete) => {
+                let mut query = self.resolve_delete(delete)?;
+                let search = plan_search(&mut query.table, &query.condition)?;
+                Plan::Delete {
+                    table: query.table.name.clone(),
+                    input: Box::new(plan_filter(search, &query.filter)),
+                }
+            }
+            _ => {
+                return Err(DbError::Parse(
+                    "Invalid EXPLAIN command. Expected SELECT, UPDATE or DELETE.".to_string(),
+                ))
+            }
+        };
+        Ok(format!(
+            "{}Estimated pages touched: {}\n",
+            plan,
+            plan.pages()
+        ))
+    }
+
+    fn plan_select(&mut self, query: &SelectQuery) -> Result<Plan> {
+        let index_aggregate = self.index_aggregate(query)?;
+        let index_order = self.index_order(query)?;
+        let sorted = index_order.is_some() || !query.aggregates.is_empty();
+        let mut plan = match (index_aggregate, index_order) {
+            (Some((mut table, column, descending)), _) => {
+                plan_index_order(&mut table, column, descending, &every_record(), Some(1))?
+            }
+            (None, Some((mut table, column, descending))) => {
+                let limit = query.source_limit();
+                plan_index_order(&mut table, column, descending, &query.condition, limit)?
+            }
+            (None, None) => {
+                self.plan_source(&query.source, query.common_table.as_ref(), &query.condition)?
+            }
+        };
+        for join in &query.joins {
+            let right = match (self.join_lookup(join)?, join.key) {
+                (Some((mut table, column)), Some((left, _))) => {
+                    let (pages, rows) = table.estimate_lookup(&column)?;
+                    let lookup = Plan::KeyLookup {
+                        table: table.name.clone(),
+                        column,
+                        key: query.source_columns[left].name.clone(),
+                        pages,
+                        rows,
+                    };
+                    plan_condition(lookup, &join.search)
+                }
+                _ => self.plan_source(&join.source, join.common_table.as_ref(), &join.search)?,
+            };
+            plan = Plan::Join {
+                condition: join.condition.to_string(),
+                left: Box::new(plan),
+                right: Box::new(right),
+            };
+        }
+        plan = plan_filter(plan, &query.filter);
+        if !query.aggregates.is_empty() {
+            plan = Plan::Aggregate {
+                functions: query.aggregates.iter().map(Aggregate::to_string).collect(),
+                input: Box::new(plan),
+            };
+        }
+        if !query.windows.is_empty() {
+            plan = Plan::Window {
+                functions: query.windows.iter().map(Window::to_string).collect(),
+                input: Box::new(plan),
+            };
+        }
+        if query.compound.is_empty() && !sorted {
+            plan = plan_sort(plan, &query.order_by);
+        }
+        plan = Plan::Project {
+            columns: query.columns().into_iter().map(|c| c.name).collect(),
+            input: Box::new(plan),
+        };
+        for (operator, all, other) in &query.compound {
+            plan = Plan::SetOperation {
+                operator: match all {
+                    true => format!("{} ALL", operator),
+                    false => operator.to_string(),
+                },
+                left: Box::new(plan),
+                right: Box::new(self.plan_select(other)?),
+            };
+        }
+        if !query.compound.is_empty() {
+            plan = plan_sort(plan, &query.order_by);
+        }
+        if let Some(count) = query.limit {
+            plan = Plan::Limit {
+                count,
+                input: Box::new(plan),
+            };
+        }
+        Ok(plan)
+    }
+
+    /// Returns the plan of [`Database::source_rows`].
+    fn plan_source(
+        &mut self,
+        source: &str,
+        common_table: Option<&CommonTableRecords>,
+        condition: &Condition,
+    ) -> Result<Plan> {
+        Ok(match (common_table, self.load_view(source)?) {
+            (Some(common_table), _) => plan_condition(common_table.plan.clone(), condition),
+            (None, Some(definition)) => {
+                let view_query = self.resolve_view(&definition)?;
+                let view = Plan::View {
+                    name: source.to_string(),
+                    input: Box::new(self.plan_select(&view_query)?),
+                };
+                plan_condition(view, condition)
+            }
+            (None, None) => {
+                let mut table = self
+                    .load_table(source)?
+                    .ok_or_else(|| DbError::Catalog(format!("Table {} not found.", source)))?;
+                plan_search(&mut table, condition)?
+            }
+        })
+    }
+
+    fn create_table(
+        &mut self,
+        table_name: &str,
+        columns: &[Column],
+        flags: &[(bool, bool, bool)],
+        keys: &[(bool, String)],
+    ) -> Result<String> {
+        if self.table_type(table_name)?.is_some() {
+            return Err(DbError::Catalog("Table already exists.".to_string()));
+        }
+        let mut columns = columns.to_vec();
+        let mut flags = flags.to_vec();
+        check_table_definition(&mut columns, &mut flags, keys)?;
+        let mut table = Table::new(table_name, columns, USER_DIR)?;
+        self.new_table(&mut table, flags)?;
+        Ok(format!("{} created.", table_name))
+    }
+
+    fn create_view(&mut self, view_name: &str, definition: &str, query: &Select) -> Result<String> {
+        if self.table_type(view_name)?.is_some() {
+            return Err(DbError::Catalog(format!(
+                "Table or view {} already exists.",
+                view_name
+            )));
+        }
+        // Resolve the query once so views over missing tables or columns are rejected up front.
+        self.resolve_select(query, &[])?;
+        self.new_view(view_name, definition)?;
+        Ok(format!("View {} created.", view_name))
+    }
+
+    fn create_trigger(
+        &mut self,
+        trigger_name: &str,
+        timing: TriggerTiming,
+        event: TriggerEvent,
+        table_name: &str,
+        body: &str,
+    ) -> Result<String> {
+        if self.trigger_exists(trigger_name)? {
+            return Err(DbError::Catalog(format!(
+                "Trigger {} already exists.",
+                trigger_name
+            )));
+        }
+        if self.table_type(table_name)? != Some(USER_TABLE_TYPE) {
+            return Err(DbError::Catalog(format!(
+                "Triggers can only be created on user tables, {} is not one.",
+                table_name
+            )));
+        }
+        // Check the row values the statement reads up front, as they are only replaced when the
+        // trigger fires.
+        let table = self.load_user_table(table_name)?;
+        for token in tokenize(body)? {
+            let (row, name) = match token.split_once('.') {
+                Some((row @ ("old" | "new"), name)) => (row, name),
+                _ => continue,
+            };
+            match (row, event) {
+                ("old", TriggerEvent::Insert) | ("new", TriggerEvent::Delete) => {
+                    return Err(DbError::Catalog(format!(
+                        "{} is not set for {} statements.",
+                        token.to_uppercase(),
+                        event.to_string().to_uppercase()
+                    )))
+                }
+                _ => column_position(&table.columns, name)?,
+            };
+        }
+        self.new_trigger(trigger_name, table_name, timing, event, body)?;
+        Ok(format!("Trigger {} created.", trigger_name))
+    }
+
+    fn trigger_exists(&mut self, trigger_name: &str) -> Result<bool> {
+        let triggers = self.trigger_table.search(
+            Some("trigger_name"),
+            DataType::Text(trigger_name.to_string()),
+            "=",
+        )?;
+        Ok(!triggers.is_empty())
+    }
+
+    fn create_index(
+        &mut self,
+        table_name: &str,
+        column: &str,
+        if_not_exists: bool,
+    ) -> Result<String> {
+        let mut table = self.load_user_table(table_name)?;
+        if if_not_exists && table.get_index_file(column)?.is_some() {
+            return Ok(format!(
+                "Index on {}({}) already exists, skipped.",
+                table_name, column
+            ));
+        }
+        table.create_index(column)?;
+        Ok(format!("Index created on {}({}).", table_name, column))
+    }
+
+    fn insert(&mut self, insert: &Insert) -> Result<String> {
+        let mut table = self.load_user_table(&insert.table)?;
+        let column_ids: Vec<usize> = match &insert.columns {
+            Some(names) => names
+                .iter()
+                .map(|name| column_position(&table.columns, name))
+                .collect::<Result<_, _>>()?,
+            None => (0..table.columns.len()).collect(),
+        };
+        if insert.values.len() > column_ids.len() {
+            return Err(DbError::Parse(format!(
+                "Too many values specified. Expected {} but got {}.",
+                column_ids.len(),
+                insert.values.len()
+            )));
+        }
+        if insert.values.len() != column_ids.len() {
+            return Err(DbError::Parse(
+                "Number of columns and values do not match.".to_string(),
+            ));
+        }
+        let mut values = table
+            .columns
+            .iter()
+            .map(|c| c.default.clone().unwrap_or(DataType::Null))
+            .collect::<Vec<_>>();
+        for (&i, value) in column_ids.iter().zip(&insert.values) {
+            values[i] = resolve_value(value, &table.columns[i])?;
+        }
+        let returning = resolve_returning(&table.columns, &insert.returning)?;
+        let triggers = self.load_triggers(&insert.table, TriggerEvent::Insert)?;
+        self.fire_triggers(
+            &triggers,
+            TriggerTiming::Before,
+            &table.columns,
+            None,
+            Some(&values),
+        )?;
+        if let Some(on_conflict) = &insert.on_conflict {
+            if let Some(target) = &on_conflict.target {
+                if !table.columns[column_position(&table.columns, target)?].unique {
+                    return Err(DbError::Catalog(format!(
+                        "ON CONFLICT column {} is not UNIQUE.",
+                        target
+                    )));
+                }
+            }
+            if let Some((i, record)) = table.find_conflict(&values)? {
+                let column = &table.columns[i].name;
+                if on_conflict
+                    .target
+                    .as_ref()
+                    .is_none_or(|target| target == column)
+                {
+                    let updated =
+                        match conflict_update(&table, &on_conflict.action, &record, values)? {
+                            Some((column_index, value)) => {
+                                let triggers =
+                                    self.load_triggers(&insert.table, TriggerEvent::Update)?;
+                                let mut new = record.values.clone();
+                                new[column_index] = value.clone();
+                                self.fire_triggers(
+                                    &triggers,
+                                    TriggerTiming::Before,
+                                    &table.columns,
+                                    Some(&record.values),
+                                    Some(&new),
+                                )?;
+                                let updated = table.update_checked(&record, column_index, value)?;
+                                self.changes.push(Change::Updated {
+                                    table: table.name.clone(),
+                                    old: record.clone(),
+                                    new: updated.clone(),
+                                    column_index,
+                                });
+                                self.fire_triggers(
+                                    &triggers,
+                                    TriggerTiming::After,
+                                    &table.columns,
+                                    Some(&record.values),
+                                    Some(&updated.values),
+                                )?;
+                                Some(updated)
+                            }
+                            None => None,
+                        };
+                    return match (returning, updated) {
+                        (Some(returning), updated) => {
+                            self.show_returning(&table, &returning, updated.as_slice())
+                        }
+                        (None, Some(_)) => Ok(format!("{}.", row_count(1, "updated"))),
+                        (None, None) => Ok(format!(
+                            "{} into {}.",
+                            row_count(0, "inserted"),
+                            insert.table
+                        )),
+                    };
+                }
+            }
+        }
+        let record = table.insert(values)?;
+        self.changes.push(Change::Inserted {
+            table: table.name.clone(),
+            record: record.clone(),
+        });
+        self.fire_triggers(
+            &triggers,
+            TriggerTiming::After,
+            &table.columns,
+            None,
+            Some(&record.values),
+        )?;
+        match returning {
+            Some(returning) => self.show_returning(&table, &returning, &[record]),
+            None => Ok(format!(
+                "{} into {}.",
+                row_count(1, "inserted"),
+                insert.table
+            )),
+        }
+    }
+
+    /// Shows the rows `projections`, as resolved by [`resolve_returning`], select from
+    /// `records`, the rows of `table` that a statement inserted, updated or deleted.
+    fn show_returning(
+        &self,
+        table: &Table,
+        projections: &[(Column, Expression)],
+        records: &[Record],
+    ) -> Result<String> {
+        let records = records
+            .iter()
+            .map(|record| {
+                let mut row = record.values.clone();
+                row.push(DataType::BigInt(record.row_id.into()));
+                let values = projections
+                    .iter()
+                    .map(|(_, expression)| expression.evaluate(&row))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Record::new(values, record.row_id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut out = format!("Table: {}\n", table.name);
+        for (c, _) in projections {
+            write!(&mut out, "{} ", c.name).expect("Error writing to str");
+        }
+        writeln!(&mut out).expect("Error writing to str");
+        let column_ids: Vec<usize> = (0..projections.len()).collect();
+        write!(&mut out, "{}", self.display(records, &column_ids)?).expect("Error writing to str");
         Ok(out)
     }
-}
 
-impl Default for Database {
-    fn default() -> Self {
-        Self::new()
+    fn update(&mut self, update: &Update) -> Result<String> {
+        let mut query = self.resolve_update(update)?;
+        let column_index = column_position(&query.table.columns, &query.column)?;
+        let returning = resolve_returning(&query.table.columns, &update.returning)?;
+        let records = search(&query.table, &query.condition, query.filter.as_ref())?;
+        // Compute and check every new value first so that an error leaves the table unchanged.
+        let values = records
+            .iter()
+            .map(|record| query.value.evaluate(&record.values))
+            .collect::<Result<Vec<_>>>()?;
+        let values = query.table.check_update(&records, column_index, values)?;
+        let triggers = self.load_triggers(&update.table, TriggerEvent::Update)?;
+        let mut updated = vec![];
+        for (record, value) in records.iter().zip(values) {
+            let mut new = record.values.clone();
+            new[column_index] = value.clone();
+            self.fire_triggers(
+                &triggers,
+                TriggerTiming::Before,
+                &query.table.columns,
+                Some(&record.values),
+                Some(&new),
+            )?;
+            let new = query.table.update_record(record, column_index, value)?;
+            self.changes.push(Change::Updated {
+                table: query.table.name.clone(),
+                old: record.clone(),
+                new: new.clone(),
+                column_index,
+            });
+            self.fire_triggers(
+                &triggers,
+                TriggerTiming::After,
+                &query.table.columns,
+                Some(&record.values),
+                Some(&new.values),
+            )?;
+            updated.push(new);
+        }
+        match returning {
+            Some(returning) => self.show_returning(&query.table, &returning, &updated),
+            None => Ok(format!("{}.", row_count(updated.len(), "updated"))),
+        }
+    }
+
+    fn resolve_update(&mut self, update: &Update) -> Result<UpdateQuery> {
+        let table = self.load_user_table(&update.table)?;
+        let column = &table.columns[column_position(&table.columns, &update.column)?];
+        let value = match &update.value {
+            Expr::Value(value) => Expression::Literal(resolve_value(value, column)?),
+            expr => Expression::resolve(expr, &table.columns, Some(&column.data_type))?,
+        };
+        if unify(&value.data_type(), &column.data_type).is_none() {
+            return Err(DbError::Type(format!(
+                "Cannot assign {} to column {} of type {}.",
+                value.data_type().type_name(),
+                column.name,
+                column.data_type.type_name()
+            )));
+        }
+        let (condition, filter) = resolve_condition(&update.condition, &table.columns)?;
+        Ok(UpdateQuery {
+            table,
+            column: update.column.clone(),
+            value,
+            condition,
+            filter,
+        })
+    }
+
+    fn delete(&mut self, delete: &Delete) -> Result<String> {
+        let mut query = self.resolve_delete(delete)?;
+        let returning = resolve_returning(&query.table.columns, &delete.returning)?;
+        let records = search(&query.table, &query.condition, query.filter.as_ref())?;
+        let triggers = self.load_triggers(&delete.table, TriggerEvent::Delete)?;
+        for record in &records {
+            let old = Some(record.values.as_slice());
+            self.fire_triggers(
+                &triggers,
+                TriggerTiming::Before,
+                &query.table.columns,
+                old,
+                None,
+            )?;
+            query.table.delete_record(record)?;
+            self.changes.push(Change::Deleted {
+                table: query.table.name.clone(),
+                record: record.clone(),
+            });
+            self.fire_triggers(
+                &triggers,
+                TriggerTiming::After,
+                &query.table.columns,
+                old,
+                None,
+            )?;
+        }
+        match returning {
+            Some(returning) => self.show_returning(&query.table, &returning, &records),
+            None => Ok(format!("{}.", row_count(records.len(), "deleted"))),
+        }
+    }
+
+    fn resolve_delete(&mut self, delete: &Delete) -> Result<DeleteQuery> {
+        let table = self.load_user_table(&delete.table)?;
+        let (condition, filter) = resolve_condition(&delete.condition, &table.columns)?;
+        Ok(DeleteQuery {
+            table,
+            condition,
+            filter,
+        })
+    }
+
+    /// Loads `table_name` for a statement changing or indexing it, which views cannot be.
+    fn load_user_table(&mut self, table_name: &str) -> Result<Table> {
+        if self.table_type(table_name)? == Some(VIEW_TABLE_TYPE) {
+            return Err(DbError::Catalog(format!("{} is a view.", table_name)));
+        }
+        self.load_table(table_name)?
+            .ok_or_else(|| DbError::Catalog(format!("Table {} does not exist.", table_name)))
     }
 }
 
-fn cols_vec(
-    table_name: &str,
-    col_name: &str,
-    data_type: i8,
-    pos: i8,
-    nullable: i8,
-    key: &str,
-) -> Vec<DataType> {
-    let ck = if key.is_empty() {
-        DataType::Null
-    } else {
-        DataType::Text(key.to_string())
+/// Resolves the columns a `RETURNING` clause selects from the rows of a table with `columns`.
+/// The row ID of each row is read as `rowid`, after the columns.
+fn resolve_returning(
+    columns: &[Column],
+    returning: &Option<Returning>,
+) -> Result<Option<Vec<(Column, Expression)>>> {
+    let Some(returning) = returning else {
+        return Ok(None);
     };
-    vec![
-        DataType::Text(table_name.to_string()),
-        DataType::Text(col_name.to_string()),
-        DataType::TinyInt(data_type),
-        DataType::TinyInt(pos),
-        DataType::TinyInt(nullable),
-        ck,
-    ]
+    let row_id = Column::new("rowid", DataType::BigInt(0), false, false);
+    let columns = columns
+        .iter()
+        .cloned()
+        .chain(std::iter::once(row_id))
+        .collect::<Vec<_>>();
+    let projections = match returning {
+        Returning::All => columns[..columns.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(index, c)| {
+                let expression = Expression::Column {
+                    index,
+                    name: c.name.clone(),
+                    data_type: c.data_type.clone(),
+                };
+                (c.clone(), expression)
+            })
+            .collect(),
+        Returning::Columns(exprs) => exprs
+            .iter()
+            .map(|expr| {
+                let expression = Expression::resolve(expr, &columns, None)?;
+                let column = match &expression {
+                    Expression::Column { index, .. } => columns[*index].clone(),
+                    _ => Column::new(&expr.to_string(), expression.data_type(), true, false),
+                };
+                Ok((column, expression))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+    Ok(Some(projections))
 }
 
-#[cfg(test)]
-#[serial_test::serial]
-mod tests {
-    use chrono::{NaiveDate, NaiveTime};
+/// Resolves the `action` of an `ON CONFLICT` clause for `record`, which holds a value of a
+/// UNIQUE column that `values`, the row being inserted, also has. Returns the index of the
+/// column to update and its new value, or [`None`] if the row is skipped.
+fn conflict_update(
+    table: &Table,
+    action: &ConflictAction,
+    record: &Record,
+    values: Vec<DataType>,
+) -> Result<Option<(usize, DataType)>> {
+    let ConflictAction::Update { column, value } = action else {
+        return Ok(None);
+    };
+    let column_index = column_position(&table.columns, column)?;
+    let data_type = table.columns[column_index].data_type.clone();
+    // The row that was not inserted is read as excluded.<column>, after the columns of `record`.
+    let mut columns = table.columns.clone();
+    columns.extend(table.columns.iter().map(|c| {
+        Column::new(
+            &format!("excluded.{}", c.name),
+            c.data_type.clone(),
+            c.nullable,
+            false,
+        )
+    }));
+    let expression = match value {
+        Expr::Value(value) => {
+            Expression::Literal(resolve_value(value, &table.columns[column_index])?)
+        }
+        expr => Expression::resolve(expr, &columns, Some(&data_type))?,
+    };
+    let row = record
+        .values
+        .iter()
+        .cloned()
+        .chain(values)
+        .collect::<Vec<_>>();
+    let result = expression.evaluate(&row)?;
+    let new_value = coerce(result.clone(), &data_type).ok_or_else(|| {
+        DbError::Type(format!(
+            "Value {} does not fit column {} of type {}.",
+            result,
+            column,
+            data_type.type_name()
+        ))
+    })?;
+    Ok(Some((column_index, new_value)))
+}
 
-    use super::*;
+/// Returns `value` as bound in place of `old.<column>` or `new.<column>` in a trigger statement.
+/// Text is bound by its content, which the statement stores like any text it is given.
+fn trigger_param(value: &DataType) -> DataType {
+    match value {
+        DataType::Text(text) => DataType::Text(text_content(text).into_owned()),
+        value => value.clone(),
+    }
+}
 
-    fn setup_db() -> Database {
-        teardown_db();
-        Database::new()
+/// Returns how many rows a statement `changed`, such as `1 row updated` or `2 rows updated`.
+fn row_count(count: usize, changed: &str) -> String {
+    let rows = if count == 1 { "row" } else { "rows" };
+    format!("{} {} {}", count, rows, changed)
+}
+
+/// Returns the position of the column `name` in `columns`.
+fn column_position(columns: &[Column], name: &str) -> Result<usize> {
+    column_index(columns, name).ok_or_else(|| column_not_found(name, columns))
+}
+
+/// Types `value` as a value of `column`.
+fn resolve_value(value: &Value, column: &Column) -> Result<DataType> {
+    match value {
+        Value::Literal(literal) => parse_literal(literal, &column.data_type).map_err(|e| {
+            DbError::Type(format!(
+                "Failed parsing {} into {}: {}",
+                literal, column.name, e
+            ))
+        }),
+        // Bound text is stored like a literal holding it.
+        Value::Bound(DataType::Text(text)) if matches!(column.data_type, DataType::Text(_)) => {
+            Ok(text_value(text))
+        }
+        Value::Bound(value) => coerce(value.clone(), &column.data_type).ok_or_else(|| {
+            DbError::Type(format!(
+                "Value {} does not match the type of column {}.",
+                value, column.name
+            ))
+        }),
+        Value::Param(index) => Err(DbError::Parse(format!(
+            "Parameter ${} is not bound.",
+            index
+        ))),
     }
+}
 
-    fn teardown_db() {
-        let system_dir = std::path::Path::new(SYSTEM_DIR);
-        if system_dir.exists() {
-            std::fs::remove_dir_all(SYSTEM_DIR).ok();
+/// The search condition that every record matches.
+fn every_record() -> Condition {
+    (None, "=".to_string(), DataType::Null)
+}
+
+/// Resolves the WHERE clause of a statement against `columns`.
+///
+/// `<column> <operator> <value>` becomes a [`Condition`] for [`Table::search`]. Any other clause
+/// becomes a [`Predicate`] filtering the records of a full search.
+fn resolve_condition(
+    condition: &Option<parser::Condition>,
+    columns: &[Column],
+) -> Result<(Condition, Option<Predicate>)> {
+    let Some(condition) = condition else {
+        return Ok((every_record(), None));
+    };
+    if let Expr::Column(name) = &condition.left {
+        if let Ok(index) = column_position(columns, name) {
+            let value = match &condition.right {
+                Expr::Value(value) => Some(value.clone()),
+                // Unquoted values, such as dates, that are not column names.
+                Expr::Column(word) if column_position(columns, word).is_err() => {
+                    Some(Value::Literal(word.clone()))
+                }
+                _ => None,
+            };
+            if let Some(value) = value {
+                let value = resolve_compared_value(&value, &columns[index].data_type)?;
+                return Ok((
+                    (Some(name.clone()), condition.operator.clone(), value),
+                    None,
+                ));
+            }
         }
-        let user_dir = std::path::Path::new(USER_DIR);
-        if user_dir.exists() {
-            std::fs::remove_dir_all(USER_DIR).ok();
+    }
+    Ok((
+        every_record(),
+        Some(Predicate::resolve(condition, columns)?),
+    ))
+}
+
+/// Returns the records of `table` matching `condition` and `filter`. They are all read before
+/// the statement changes any of them, so that its changes cannot affect which records match.
+fn search(table: &Table, condition: &Condition, filter: Option<&Predicate>) -> Result<Vec<Record>> {
+    filter_rows(search_rows(table.reopen()?, condition)?, filter.cloned()).collect()
+}
+
+/// Returns the records of `table` matching `condition`, read as [`plan_search`] plans.
+fn search_rows(mut table: Table, condition: &Condition) -> Result<Rows> {
+    let (column, operator, value) = condition;
+    // Comparisons with NULL match nothing, whether answered by an index or a full scan.
+    if column.is_some() && *value == DataType::Null {
+        return Ok(rows(vec![]));
+    }
+    Ok(
+        match table.access_path(column.as_deref(), operator, value)? {
+            AccessPath::IndexLookup { column } | AccessPath::IndexRange { column } => {
+                return index_order_rows(table, &column, false, condition);
+            }
+            AccessPath::FullScan => {
+                let column_index = match column {
+                    Some(column) => Some(table.column_name_to_index(column)?),
+                    None => None,
+                };
+                let scan = Box::new(table.scan()?);
+                let Some(column_index) = column_index else {
+                    return Ok(scan);
+                };
+                let (operator, value) = (operator.clone(), value.clone());
+                Box::new(Filter::new(scan, move |record: &Record| {
+                    Ok(record.compare_column(column_index, &value, &operator))
+                }))
+            }
+        },
+    )
+}
+
+/// Returns the records of `table` matching `condition` in the order of the values of the
+/// indexed `column`, read from its index. The condition, if it has a column, is on `column`.
+fn index_order_rows(
+    table: Table,
+    column: &str,
+    descending: bool,
+    condition: &Condition,
+) -> Result<Rows> {
+    let (condition_column, operator, value) = condition;
+    if condition_column.is_some() && *value == DataType::Null {
+        return Ok(rows(vec![]));
+    }
+    // The ranges of values to read, in ascending order. NULL sorts after every other value, but
+    // comes before them in an index.
+    let not_null = Bound::Excluded(DataType::Null);
+    let ranges = match (condition_column, operator_bounds(value, operator)) {
+        (None, _) => {
+            let nulls = Bound::Included(DataType::Null);
+            vec![(not_null, Bound::Unbounded), (nulls.clone(), nulls)]
+        }
+        (Some(_), Some(bounds)) => vec![bounds],
+        (Some(_), None) => vec![
+            (not_null, Bound::Excluded(value.clone())),
+            (Bound::Excluded(value.clone()), Bound::Unbounded),
+        ],
+    };
+    let index_file = table.open_index_file(column)?;
+    let mut ranges = ranges
+        .into_iter()
+        .map(|(lower, upper)| index_file.range(lower, upper, descending))
+        .collect::<Result<Vec<_>>>()?;
+    if descending {
+        ranges.reverse();
+    }
+    let row_ids = ranges
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.map(|(_, id)| id));
+    Ok(Box::new(IndexScan::new(table, row_ids)))
+}
+
+/// Returns the record holding the smallest value of the indexed `column` of `table`, or the
+/// largest if `descending`, read as the first value of its index in that direction.
+fn index_aggregate_rows(table: Table, column: &str, descending: bool) -> Result<Rows> {
+    let index_file = table.open_index_file(column)?;
+    let not_null = Bound::Excluded(DataType::Null);
+    let first = index_file
+        .range(not_null, Bound::Unbounded, descending)?
+        .next()
+        .transpose()?;
+    let value = first.map_or(DataType::Null, |(value, _)| value);
+    Ok(rows(vec![Record::new(vec![value], 0)]))
+}
+
+/// Returns the record holding the result of each of `aggregates` over the records of `input`.
+fn aggregate_rows(input: Rows, aggregates: &[Aggregate]) -> Result<Record> {
+    let mut values = vec![DataType::Null; aggregates.len()];
+    for record in input {
+        let record = record?;
+        for (value, aggregate) in values.iter_mut().zip(aggregates) {
+            let result = std::mem::replace(value, DataType::Null);
+            *value = aggregate.accumulate(result, &record)?;
         }
     }
+    Ok(Record::new(values, 0))
+}
+
+/// Returns the positions of the columns `condition` requires to be equal if one is a column of
+/// the records joined so far and the other one of the source joined to them, which holds the
+/// columns in `joined`. The position of the latter is counted from the start of `joined`.
+fn join_key(condition: &Predicate, joined: std::ops::Range<usize>) -> Option<(usize, usize)> {
+    let (Expression::Column { index: a, .. }, "=", Expression::Column { index: b, .. }) = (
+        &condition.left,
+        condition.operator.as_str(),
+        &condition.right,
+    ) else {
+        return None;
+    };
+    match (joined.contains(a), joined.contains(b)) {
+        (false, true) if *a < joined.start => Some((*a, b - joined.start)),
+        (true, false) if *b < joined.start => Some((*b, a - joined.start)),
+        _ => None,
+    }
+}
+
+/// Keeps the records of `columns` matching the search `condition`.
+fn condition_rows(input: Rows, condition: &Condition, columns: &[Column]) -> Result<Rows> {
+    let (Some(column_name), operator, value) = condition else {
+        return Ok(input);
+    };
+    let column_index = column_position(columns, column_name)?;
+    let (operator, value) = (operator.clone(), value.clone());
+    Ok(Box::new(Filter::new(input, move |record: &Record| {
+        Ok(record.compare_column(column_index, &value, &operator))
+    })))
+}
+
+/// Keeps the records matching `filter`.
+fn filter_rows(input: Rows, filter: Option<Predicate>) -> Rows {
+    match filter {
+        Some(filter) => Box::new(Filter::new(input, move |record: &Record| {
+            filter.evaluate(&record.values)
+        })),
+        None => input,
+    }
+}
+
+/// Sorts the records by the values of the ORDER BY expressions, if there are any.
+fn sort_rows(input: Rows, order_by: &[(Expression, bool)]) -> Rows {
+    match order_by.is_empty() {
+        true => input,
+        false => Box::new(Sort::new(input, order_by.to_vec())),
+    }
+}
+
+/// Names the columns of `source`, one of the sources of a join, `<source>.<column>`.
+fn qualify_columns(source: &str, columns: Vec<Column>) -> Vec<Column> {
+    columns
+        .into_iter()
+        .map(|column| Column {
+            name: format!("{}.{}", source, column.name),
+            ..column
+        })
+        .collect()
+}
+
+/// Adds a step filtering the records of a view or common table by the search `condition`.
+fn plan_condition(input: Plan, condition: &Condition) -> Plan {
+    match condition {
+        (Some(column), operator, value) => Plan::Filter {
+            condition: format!("{} {} {}", column, operator, display_literal(value)),
+            rows: input.rows(),
+            input: Box::new(input),
+        },
+        (None, _, _) => input,
+    }
+}
+
+/// Renames the columns selected by a common table expression to the names it gives them.
+fn rename_columns(columns: Vec<Column>, common_table: &CommonTable) -> Result<Vec<Column>> {
+    let Some(names) = &common_table.columns else {
+        return Ok(columns);
+    };
+    if names.len() != columns.len() {
+        return Err(DbError::Parse(format!(
+            "Common table {} selects {} columns but names {}.",
+            common_table.name,
+            columns.len(),
+            names.len()
+        )));
+    }
+    Ok(columns
+        .into_iter()
+        .zip(names)
+        .map(|(column, name)| Column::new(name, column.data_type, true, false))
+        .collect())
+}
+
+/// Adds a step sorting the records of `input` if there are ORDER BY expressions.
+fn plan_sort(input: Plan, order_by: &[(Expression, bool)]) -> Plan {
+    if order_by.is_empty() {
+        return input;
+    }
+    Plan::Sort {
+        keys: order_by
+            .iter()
+            .map(|(expression, descending)| match descending {
+                true => format!("{} desc", expression),
+                false => expression.to_string(),
+            })
+            .collect(),
+        input: Box::new(input),
+    }
+}
+
+/// Converts the values of the records of one SELECT in a set operation to the types of the
+/// combined `columns`.
+fn fit_records(records: Vec<Record>, columns: &[Column]) -> Result<Vec<Record>> {
+    records
+        .into_iter()
+        .map(|r| {
+            let values = r
+                .values
+                .into_iter()
+                .zip(columns)
+                .map(|(value, column)| {
+                    coerce(value.clone(), &column.data_type).ok_or_else(|| {
+                        DbError::Type(format!(
+                            "Value {} does not fit column {} of type {}.",
+                            value,
+                            column.name,
+                            column.data_type.type_name()
+                        ))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(Record::new(values, r.row_id))
+        })
+        .collect()
+}
+
+/// Checks the columns of a CREATE TABLE statement and applies its table constraints `keys` to
+/// them and their `(primary key, not null, unique)` flags. A `NULL` default is dropped, as
+/// columns without a default are `NULL` anyway.
+fn check_table_definition(
+    columns: &mut [Column],
+    flags: &mut [(bool, bool, bool)],
+    keys: &[(bool, String)],
+) -> Result<()> {
+    for (i, column) in columns.iter().enumerate() {
+        if columns[..i].iter().any(|c| c.name == column.name) {
+            return Err(DbError::Catalog(format!(
+                "Column {} is declared twice.",
+                column.name
+            )));
+        }
+    }
+    for column in columns.iter_mut() {
+        if let Some(max_length) = column.max_length {
+            if !(1..=MAX_TEXT_LENGTH).contains(&max_length) {
+                return Err(DbError::Constraint(format!(
+                    "VARCHAR length must be between 1 and {}, got {}.",
+                    MAX_TEXT_LENGTH, max_length
+                )));
+            }
+        }
+        match &column.default {
+            Some(DataType::Null) if !column.nullable => {
+                return Err(DbError::Constraint(format!(
+                    "Column {} is NOT NULL but defaults to NULL.",
+                    column.name
+                )));
+            }
+            Some(DataType::Null) => column.default = None,
+            Some(DataType::Text(text)) => {
+                if let Some(max_length) = column.max_length {
+                    if text_content(text).chars().count() > max_length {
+                        return Err(DbError::Constraint(format!(
+                            "Default of column {} is longer than {} characters.",
+                            column.name, max_length
+                        )));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    for (primary, column_name) in keys {
+        let i = column_index(columns, column_name)
+            .ok_or_else(|| column_not_found(column_name, columns))?;
+        if *primary {
+            flags[i].0 = true;
+        } else {
+            flags[i].2 = true;
+            columns[i].unique = true;
+        }
+    }
+    if flags.iter().filter(|(primary, _, _)| *primary).count() > 1 {
+        return Err(DbError::Catalog(
+            "Multiple primary keys specified.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Combines the records of two SELECT statements. Records are duplicates if all their values
+/// are equal by [`index_order`], with `NULL` equal to `NULL` and `NaN` to `NaN`. Without `all`,
+/// duplicates are removed from the result.
+fn combine(left: Vec<Record>, operator: SetOperator, all: bool, right: Vec<Record>) -> Vec<Record> {
+    let key = |record: &Record| RecordKey(record.values.clone());
+    let mut seen = BTreeSet::new();
+    match operator {
+        SetOperator::Union => left
+            .into_iter()
+            .chain(right)
+            .filter(|record| all || seen.insert(key(record)))
+            .collect(),
+        SetOperator::Intersect | SetOperator::Except => {
+            let mut counts: BTreeMap<RecordKey, usize> = BTreeMap::new();
+            for record in &right {
+                *counts.entry(key(record)).or_default() += 1;
+            }
+            left.into_iter()
+                .filter(|record| {
+                    if !all && !seen.insert(key(record)) {
+                        return false;
+                    }
+                    let count = counts.get_mut(&key(record)).filter(|count| **count > 0);
+                    let found = count.is_some();
+                    // With ALL, each record on the right matches only one record on the left.
+                    if let (true, Some(count)) = (all, count) {
+                        *count -= 1;
+                    }
+                    found == (operator == SetOperator::Intersect)
+                })
+                .collect()
+        }
+    }
+}
+
+/// The values of a record, ordered value by value by [`index_order`] so that records can be
+/// deduplicated in a sorted set.
+struct RecordKey(Vec<DataType>);
+
+impl Ord for RecordKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| index_order(a, b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| self.0.len().cmp(&other.0.len()))
+    }
+}
+
+impl PartialOrd for RecordKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for RecordKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RecordKey {}
+
+fn help() -> String {
+    let mut out = String::new();
+    writeln!(&mut out, "Available commands:").expect("Error writing to str");
+    writeln!(&mut out, "SHOW TABLES;").expect("Error writing to str");
+    writeln!(&mut out, "\tDisplay a list of all tables in the database.")
+        .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "SELECT <columns> FROM <table> [[INNER] JOIN <table> ON <condition> ...] \
+         [WHERE <condition>] [ORDER BY <expression> [ASC|DESC], ...] [LIMIT <count>];"
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "\tDisplay the selected columns from the table.")
+        .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tThe columns of joined tables are named <table>.<column>, or just <column> if only \
+         one of the tables has it."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tColumns, conditions and SET values may call UPPER, LOWER, LENGTH, SUBSTR, TRIM, \
+         REPLACE, CONCAT (or ||), COALESCE, NULLIF, ABS, ROUND, FLOOR, CEIL and MOD."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tDates and times may use NOW(), CURRENT_DATE, EXTRACT(<field> FROM <value>), \
+         DATE_TRUNC('<unit>', <value>), DATEDIFF(<date>, <date>) and \
+         <value> +/- INTERVAL '<n> <unit>'."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tCAST(<value> AS <type>) converts a value to another type. Numbers of different types \
+         compare by value."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tCASE [<value>] WHEN <condition or value> THEN <expression> ... [ELSE <expression>] END \
+         picks the result of the first matching branch."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tSELECT statements with matching columns may be combined with UNION, INTERSECT or \
+         EXCEPT, optionally followed by ALL to keep duplicates."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tSelected columns may be window functions: ROW_NUMBER(), RANK(), DENSE_RANK(), \
+         LAG/LEAD(<value> [, <offset> [, <default>]]), SUM, COUNT, AVG, MIN and MAX, followed by \
+         OVER ([PARTITION BY ...] [ORDER BY ...] [ROWS BETWEEN <start> AND <end>])."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "WITH [RECURSIVE] <name> [(<column>, ...)] AS (SELECT ...), ... SELECT ...;"
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tSelect from the results of named queries. A recursive query is written as \
+         <select> UNION [ALL] <select from itself>."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "CREATE TABLE [IF NOT EXISTS] <table> (<column_name> <data_type> [<constraint> ...], ...);"
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "\tCreate a new table with the specified columns.")
+        .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tConstraints: PRIMARY KEY, NOT NULL, NULL, UNIQUE, DEFAULT <literal> and REFERENCES \
+         <table> [(<column>)] [ON DELETE|UPDATE <action>], which is accepted but not enforced. \
+         PRIMARY KEY (<column>) and UNIQUE (<column>) may also be listed among the columns."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tINTEGER, BOOLEAN, REAL, DOUBLE PRECISION, TIMESTAMP, VARCHAR(n) and CHAR(n) are \
+         stored as INT, TINYINT, FLOAT, DOUBLE, DATETIME and TEXT. VARCHAR(n) and CHAR(n) \
+         reject text longer than n characters."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "INSERT INTO <table> [(column1, ...)] VALUES (<value>, ...) \
+         [ON CONFLICT [(<column>)] DO NOTHING | DO UPDATE SET <column> = <value>] \
+         [RETURNING * | <column>, ...];"
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "\tInsert a new row into the table.").expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "UPDATE <table> SET <column> = <value> [WHERE <condition>] [RETURNING * | <column>, ...];"
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "\tUpdate the specified column in the table.")
+        .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "DELETE FROM <table> [WHERE <condition>] [RETURNING * | <column>, ...];"
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "\tDelete rows from the table.").expect("Error writing to str");
+    writeln!(&mut out, "DROP TABLE [IF EXISTS] <table>;").expect("Error writing to str");
+    writeln!(&mut out, "\tDelete the table from the database.").expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "TRUNCATE [TABLE] <table> [RESTART IDENTITY | CONTINUE IDENTITY];"
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tDelete every row of the table. Row IDs start again at 1."
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "CREATE VIEW [IF NOT EXISTS] <view> AS SELECT ...;"
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tStore a SELECT statement that can be queried like a table."
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "DROP VIEW [IF EXISTS] <view>;").expect("Error writing to str");
+    writeln!(&mut out, "\tDelete the view from the database.").expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "CREATE TRIGGER [IF NOT EXISTS] <trigger> BEFORE | AFTER INSERT | UPDATE | DELETE \
+         ON <table> FOR EACH ROW <INSERT, UPDATE or DELETE statement>;"
+    )
+    .expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tRun a statement for each row the table changes. OLD.<column> and NEW.<column> are \
+         the row before and after the change."
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "DROP TRIGGER [IF EXISTS] <trigger>;").expect("Error writing to str");
+    writeln!(&mut out, "\tDelete the trigger from the database.").expect("Error writing to str");
+    writeln!(&mut out, "EXPLAIN <statement>;").expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "\tDisplay how a SELECT, UPDATE or DELETE statement finds its records."
+    )
+    .expect("Error writing to str");
+    writeln!(&mut out, "HELP;").expect("Error writing to str");
+    writeln!(&mut out, "\tDisplay this help message.").expect("Error writing to str");
+    writeln!(&mut out, "EXIT;").expect("Error writing to str");
+    writeln!(&mut out, "\tExit the database.").expect("Error writing to str");
+    writeln!(
+        &mut out,
+        "Names are lowercased unless written in double quotes, as in \"Order Items\", which keeps \
+         their case and allows spaces and keywords."
+    )
+    .expect("Error writing to str");
+    out
+}
+
+/// Returns the plan of [`Table::search`] for `condition`.
+fn plan_search(table: &mut Table, condition: &Condition) -> Result<Plan> {
+    let (column, operator, value) = condition;
+    let access_path = table.access_path(column.as_deref(), operator, value)?;
+    let comparison = column.as_ref().map(|_| (operator.as_str(), value));
+    let pages = table.estimate_pages(&access_path, comparison, None)?;
+    let rows = table.estimate_rows(column.as_deref(), operator, value)?;
+    Ok(match access_path {
+        AccessPath::IndexLookup { column } => Plan::IndexLookup {
+            table: table.name.clone(),
+            column,
+            value: value.clone(),
+            pages,
+            rows,
+        },
+        AccessPath::IndexRange { column } => Plan::IndexRange {
+            table: table.name.clone(),
+            column,
+            operator: operator.clone(),
+            value: value.clone(),
+            pages,
+            rows,
+        },
+        AccessPath::FullScan => {
+            let scan = Plan::FullScan {
+                table: table.name.clone(),
+                pages,
+                rows: table.estimate_rows(None, operator, value)?,
+            };
+            match column {
+                Some(column) => Plan::Filter {
+                    condition: format!("{} {} {}", column, operator, display_literal(value)),
+                    rows,
+                    input: Box::new(scan),
+                },
+                None => scan,
+            }
+        }
+    })
+}
+
+/// Returns the plan of [`index_order_rows`], which reads at most `limit` records if given.
+fn plan_index_order(
+    table: &mut Table,
+    column: String,
+    descending: bool,
+    condition: &Condition,
+    limit: Option<u64>,
+) -> Result<Plan> {
+    let (condition_column, operator, value) = condition;
+    let comparison = condition_column
+        .as_ref()
+        .map(|_| (operator.as_str(), value));
+    let access_path = AccessPath::IndexRange {
+        column: column.clone(),
+    };
+    let pages = table.estimate_pages(&access_path, comparison, limit)?;
+    let rows = table.estimate_rows(condition_column.as_deref(), operator, value)?;
+    Ok(Plan::IndexOrder {
+        table: table.name.clone(),
+        column,
+        condition: condition_column
+            .as_ref()
+            .map(|c| format!("{} {} {}", c, operator, display_literal(value))),
+        descending,
+        pages,
+        rows: limit.map_or(rows, |limit| rows.min(limit)),
+    })
+}
+
+/// Adds `filter`, if any, on top of `plan`.
+fn plan_filter(plan: Plan, filter: &Option<Predicate>) -> Plan {
+    match filter {
+        Some(filter) => Plan::Filter {
+            condition: filter.to_string(),
+            rows: plan.rows(),
+            input: Box::new(plan),
+        },
+        None => plan,
+    }
+}
+
+fn cols_vec(
+    table_name: &str,
+    col_name: &str,
+    data_type: i8,
+    pos: i8,
+    nullable: i8,
+    key: &str,
+) -> Vec<DataType> {
+    let ck = if key.is_empty() {
+        DataType::Null
+    } else {
+        DataType::Text(key.to_string())
+    };
+    vec![
+        DataType::Text(table_name.to_string()),
+        DataType::Text(col_name.to_string()),
+        DataType::TinyInt(data_type),
+        DataType::TinyInt(pos),
+        DataType::TinyInt(nullable),
+        ck,
+    ]
+}
+
+#[cfg(test)]
+#[serial_test::serial]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::*;
+    use crate::database_file::DatabaseFile;
+
+    fn setup_db() -> Database {
+        teardown_db();
+        Database::new().unwrap()
+    }
+
+    fn teardown_db() {
+        let system_dir = std::path::Path::new(SYSTEM_DIR);
+        if system_dir.exists() {
+            std::fs::remove_dir_all(SYSTEM_DIR).ok();
+        }
+        let user_dir = std::path::Path::new(USER_DIR);
+        if user_dir.exists() {
+            std::fs::remove_dir_all(USER_DIR).ok();
+        }
+    }
+
+    fn setup_db_with_table() -> Database {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
+            .expect("Failed creating table");
+        db
+    }
+
+    /// Creates the database and runs the statements of `script` on it.
+    fn setup_db_with(script: &str) -> Database {
+        let mut db = setup_db();
+        db.run_script(script).expect("Failed setting up");
+        db
+    }
+
+    /// Inserts the records `(id, 'test<id>')` into the table `test` for each of `ids`.
+    fn insert_test_records(db: &mut Database, ids: std::ops::RangeInclusive<i32>) {
+        for id in ids {
+            db.parse_user_input(&format!("INSERT INTO test VALUES ({}, 'test{}');", id, id))
+                .expect("Failed inserting");
+        }
+    }
+
+    /// Returns the values of a record written as SQL literals, such as `1, 'a', null`. Numbers
+    /// are read as INT.
+    fn row(literals: &str) -> Vec<DataType> {
+        tokenize(literals)
+            .unwrap()
+            .into_iter()
+            .filter(|token| token != ",")
+            .map(|token| match token.as_str() {
+                "null" => DataType::Null,
+                text if text.starts_with('\'') => DataType::Text(token),
+                number => DataType::Int(number.parse().expect("Not a literal")),
+            })
+            .collect()
+    }
+
+    /// Returns the records selected by the SELECT statement `sql`.
+    fn select_records(db: &mut Database, sql: &str) -> Vec<Record> {
+        let Statement::Select(select) = parser::parse(&mut tokenize(sql).unwrap()).unwrap() else {
+            panic!("Not a SELECT statement: {}", sql);
+        };
+        let query = db.resolve_select(&select, &[]).expect("Failed resolving");
+        db.select(&query).expect("Failed selecting")
+    }
+
+    /// Returns the values of the single record selected by `sql`.
+    fn select_values(db: &mut Database, sql: &str) -> Vec<DataType> {
+        let mut records = select_records(db, sql);
+        assert_eq!(records.len(), 1, "{}", sql);
+        records.remove(0).values
+    }
+
+    /// Returns the values of every record selected by `sql`, in order.
+    fn selected_values(db: &mut Database, sql: &str) -> Vec<Vec<DataType>> {
+        select_records(db, sql)
+            .into_iter()
+            .map(|record| record.values)
+            .collect()
+    }
+
+    #[test]
+    fn test_initialize() {
+        let mut db = setup_db();
+        assert!(!db.table_table.is_empty().unwrap());
+        assert!(!db.column_table.is_empty().unwrap());
+        let tables = db.table_table.search(None, DataType::Null, "=").unwrap();
+        assert_eq!(tables.len(), 6);
+        assert_eq!(
+            tables[0].values[0],
+            DataType::Text("meta_tables".to_string())
+        );
+        assert_eq!(
+            tables[1].values[0],
+            DataType::Text("meta_columns".to_string())
+        );
+        assert_eq!(
+            tables[2].values[0],
+            DataType::Text("meta_views".to_string())
+        );
+        assert_eq!(
+            tables[3].values[0],
+            DataType::Text("meta_defaults".to_string())
+        );
+        assert_eq!(
+            tables[4].values[0],
+            DataType::Text("meta_triggers".to_string())
+        );
+        assert_eq!(
+            tables[5].values[0],
+            DataType::Text("meta_row_ids".to_string())
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_create_table() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
+            .expect("Failed creating table");
+        let table = db.load_table("test").unwrap().expect("Table not found");
+        assert_eq!(table.columns.len(), 2);
+        assert_eq!(table.columns[0].name, "id");
+        assert_eq!(table.has_index().unwrap(), Some("id"));
+        teardown_db();
+    }
+
+    #[test]
+    fn test_drop_table() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
+            .expect("Failed creating table");
+        let index_path = format!("{}/test.id.ndx", USER_DIR);
+        let idx_file = std::path::Path::new(&index_path);
+        assert!(db.load_table("test").unwrap().is_some());
+        assert!(idx_file.exists());
+        db.parse_user_input("DROP TABLE test;")
+            .expect("Failed dropping table");
+        assert!(db.load_table("test").unwrap().is_none());
+        assert!(!idx_file.exists());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_drop_and_recreate_table() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT UNIQUE);")
+            .expect("Failed creating table");
+        db.parse_user_input("CREATE INDEX test (name);")
+            .expect("Failed creating index");
+        db.parse_user_input("INSERT INTO test VALUES (1, 'a');")
+            .expect("Failed inserting");
+        db.parse_user_input("DROP TABLE test;")
+            .expect("Failed dropping table");
+        for file in ["test.tbl", "test.id.ndx", "test.name.ndx"] {
+            let path = format!("{}/{}", USER_DIR, file);
+            assert!(!std::path::Path::new(&path).exists(), "{}", file);
+        }
+        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT UNIQUE);")
+            .expect("Failed creating table again");
+        assert!(select_records(&mut db, "SELECT * FROM test;").is_empty());
+        // The old key and unique value are gone with the old records.
+        db.parse_user_input("INSERT INTO test VALUES (1, 'a');")
+            .expect("Failed inserting again");
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM test;"),
+            [DataType::Int(1), DataType::Text("'a'".to_string())]
+        );
+        teardown_db();
+    }
+
+    /// The table `people`, with the standard column types and options.
+    const PEOPLE: &str = "CREATE TABLE IF NOT EXISTS people (id INTEGER PRIMARY KEY, \
+        name VARCHAR(20) NOT NULL DEFAULT 'anon', score REAL NULL DEFAULT 1.5, seen TIMESTAMP, \
+        email VARCHAR(100) UNIQUE REFERENCES emails (address));";
+
+    #[test]
+    fn test_standard_column_definitions() {
+        let mut db = setup_db_with(PEOPLE);
+        let table = db.load_table("people").unwrap().unwrap();
+        assert_eq!(table.columns[1].data_type, DataType::Text(String::new()));
+        assert_eq!(table.columns[2].data_type, DataType::Float(0.0));
+        assert_eq!(table.columns[3].data_type, DataType::DateTime(0));
+        assert!(!table.columns[1].nullable);
+        assert!(table.columns[4].unique);
+        assert_eq!(
+            table.columns[1].default,
+            Some(DataType::Text("'anon'".to_string()))
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_create_table_if_not_exists() {
+        let mut db = setup_db_with(PEOPLE);
+        assert_eq!(
+            "people already exists, skipped.",
+            db.parse_user_input("CREATE TABLE IF NOT EXISTS people (id INT);")
+                .unwrap()
+        );
+        assert!(db
+            .parse_user_input("CREATE TABLE people (id INT);")
+            .is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_defaults_and_not_null() {
+        let mut db = setup_db_with(PEOPLE);
+        db.parse_user_input("INSERT INTO people (id, email) VALUES (1, 'a@b');")
+            .unwrap();
+        db.parse_user_input("INSERT INTO people (id, name, score) VALUES (2, 'bob', null);")
+            .unwrap();
+        assert_eq!(
+            selected_values(&mut db, "SELECT id, name, score FROM people;"),
+            [
+                vec![
+                    DataType::Int(1),
+                    DataType::Text("'anon'".to_string()),
+                    DataType::Float(1.5)
+                ],
+                vec![
+                    DataType::Int(2),
+                    DataType::Text("'bob'".to_string()),
+                    DataType::Null
+                ],
+            ]
+        );
+        assert!(db
+            .parse_user_input("INSERT INTO people (id, name) VALUES (3, null);")
+            .is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_varchar_length() {
+        let mut db = setup_db_with(PEOPLE);
+        db.parse_user_input("INSERT INTO people (id) VALUES (2);")
+            .unwrap();
+        db.parse_user_input("UPDATE people SET name = 'twenty characters!!' WHERE id = 2;")
+            .unwrap();
+        let long = db
+            .parse_user_input("UPDATE people SET name = 'twenty-one characters' WHERE id = 2;")
+            .unwrap_err();
+        assert!(matches!(long, DbError::Constraint(_)), "{:?}", long);
+        assert!(db
+            .parse_user_input("INSERT INTO people (id, name) VALUES (3, 'twenty-one characters');")
+            .is_err());
+        assert_eq!(
+            select_values(&mut db, "SELECT name FROM people;"),
+            [DataType::Text("'twenty characters!!'".to_string())]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_table_constraints() {
+        let mut db = setup_db();
+        db.parse_user_input(
+            "CREATE TABLE keyed (id INTEGER NOT NULL, code VARCHAR(3), \
+             total DOUBLE PRECISION, PRIMARY KEY (id), UNIQUE (code));",
+        )
+        .unwrap();
+        let keyed = db.load_table("keyed").unwrap().unwrap();
+        assert_eq!(keyed.has_index().unwrap(), Some("id"));
+        assert!(keyed.columns[1].unique);
+        assert_eq!(keyed.columns[1].max_length, Some(3));
+        assert_eq!(keyed.columns[2].data_type, DataType::Double(0.0));
+        db.parse_user_input("INSERT INTO keyed VALUES (1, 'abc', 1.5);")
+            .unwrap();
+        assert!(db
+            .parse_user_input("INSERT INTO keyed VALUES (2, 'abc', 2.5);")
+            .is_err());
+        assert!(db
+            .parse_user_input("INSERT INTO keyed VALUES (2, 'abcd', 2.5);")
+            .is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_invalid_table_definitions() {
+        let mut db = setup_db();
+        for (sql, message) in [
+            (
+                "CREATE TABLE t (id INT, id TEXT);",
+                "Column id is declared twice.",
+            ),
+            (
+                "CREATE TABLE t (id INT PRIMARY KEY, name TEXT, PRIMARY KEY (name));",
+                "Multiple primary keys specified.",
+            ),
+        ] {
+            let error = db.parse_user_input(sql).unwrap_err();
+            assert!(matches!(error, DbError::Catalog(_)), "{:?}", error);
+            assert_eq!(error.to_string(), message);
+        }
+        for (sql, message) in [
+            (
+                "CREATE TABLE t (name VARCHAR(1000));",
+                "VARCHAR length must be between 1 and 243, got 1000.",
+            ),
+            (
+                "CREATE TABLE t (code CHAR(0));",
+                "VARCHAR length must be between 1 and 243, got 0.",
+            ),
+            (
+                "CREATE TABLE t (name VARCHAR(2) DEFAULT 'abc');",
+                "Default of column name is longer than 2 characters.",
+            ),
+            (
+                "CREATE TABLE t (id INT NOT NULL DEFAULT null);",
+                "Column id is NOT NULL but defaults to NULL.",
+            ),
+        ] {
+            let error = db.parse_user_input(sql).unwrap_err();
+            assert!(matches!(error, DbError::Constraint(_)), "{:?}", error);
+            assert_eq!(error.to_string(), message);
+        }
+        let error = db
+            .parse_user_input("CREATE TABLE t (id INT, UNIQUE (idd));")
+            .unwrap_err();
+        assert!(
+            matches!(&error, DbError::Column { name, .. } if name == "idd"),
+            "{:?}",
+            error
+        );
+        assert!(db.load_table("t").unwrap().is_none());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_foreign_key_actions_and_type_aliases() {
+        let mut db = setup_db();
+        db.parse_user_input(
+            "CREATE TABLE t (id INT REFERENCES p (id) ON DELETE CASCADE, code CHAR(2), \
+             done BOOLEAN DEFAULT 0);",
+        )
+        .unwrap();
+        db.parse_user_input("INSERT INTO t (id, code) VALUES (1, 'ab');")
+            .unwrap();
+        assert!(db
+            .parse_user_input("INSERT INTO t (id, code) VALUES (2, 'abc');")
+            .is_err());
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM t;"),
+            [
+                DataType::Int(1),
+                DataType::Text("'ab'".to_string()),
+                DataType::TinyInt(0)
+            ]
+        );
+        let error = db
+            .parse_user_input("CREATE TABLE u (at DATE DEFAULT CURRENT_DATE);")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Default CURRENT_DATE of column at is not supported, defaults must be literals.\n\
+             1 | CREATE TABLE u (at DATE DEFAULT CURRENT_DATE);\n  |                                 \
+             ^^^^^^^^^^^^"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_if_exists_ddl() {
+        let mut db = setup_db_with(PEOPLE);
+        db.parse_user_input("CREATE INDEX IF NOT EXISTS people (id);")
+            .unwrap();
+        db.parse_user_input("CREATE VIEW IF NOT EXISTS named AS SELECT name FROM people;")
+            .unwrap();
+        db.parse_user_input("CREATE VIEW IF NOT EXISTS named AS SELECT id FROM people;")
+            .unwrap();
+        db.parse_user_input("DROP VIEW IF EXISTS named;").unwrap();
+        db.parse_user_input("DROP VIEW IF EXISTS named;").unwrap();
+        assert!(db.parse_user_input("DROP VIEW named;").is_err());
+        db.parse_user_input("DROP TABLE IF EXISTS people;").unwrap();
+        assert_eq!(
+            "Table people does not exist, skipped.",
+            db.parse_user_input("DROP TABLE IF EXISTS people;").unwrap()
+        );
+        // Dropping the table drops the defaults of its columns.
+        assert!(db
+            .default_table
+            .search(None, DataType::Null, "=")
+            .unwrap()
+            .is_empty());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_run_script() {
+        let mut db = setup_db();
+        let output = db
+            .run_script(
+                "-- Schema\n\
+                 CREATE TABLE test (id INT, name TEXT); /* no index */\n\
+                 INSERT INTO test VALUES (1, 'a; b');\n\
+                 INSERT INTO test VALUES (2, '-- not a comment');\n\
+                 SELECT name FROM test;",
+            )
+            .unwrap();
+        assert_eq!(output.len(), 4);
+        assert_eq!(
+            output[3],
+            "Table: test\nname \n'a; b' \n'-- not a comment' \n"
+        );
+        assert_eq!(
+            db.run_script("SELECT id FROM test;\n\nSELECT missing\nFROM test;")
+                .map_err(|e| e.to_string()),
+            Err(
+                "Statement 2 (line 3): Column missing not found.\n3 | SELECT missing\n  |        \
+                 ^^^^^^^"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            db.run_script("SELECT id FROM test;\n\nSELECT id\nFORM test;")
+                .map_err(|e| e.to_string()),
+            Err(
+                "Statement 2 (line 3): Expected FROM.\n4 | FORM test;\n  | ^^^^ did you mean FROM?"
+                    .to_string()
+            )
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let mut db = setup_db_with_table();
+        let err =
+            |db: &mut Database, input: &str| db.parse_user_input(input).unwrap_err().to_string();
+        assert_eq!(
+            err(&mut db, "SELECT id, name form test;"),
+            "Expected FROM.\n1 | SELECT id, name form test;\n  |                 ^^^^ did you mean FROM?"
+        );
+        assert_eq!(
+            err(&mut db, "SELECT id FROM test WHER id = 1;"),
+            "Unexpected tokens: wher id = 1\n1 | SELECT id FROM test WHER id = 1;\n  |                     ^^^^ did you mean WHERE?"
+        );
+        assert_eq!(
+            err(&mut db, "SHOW TABELS;"),
+            "Invalid SHOW command. Expected TABLES.\n1 | SHOW TABELS;\n  |      ^^^^^^ did you mean TABLES?"
+        );
+        // Only keywords the parser accepts where the token is are suggested.
+        assert_eq!(
+            err(&mut db, "ALTER TABLE test;"),
+            "Invalid command: alter table test\n1 | ALTER TABLE test;\n  | ^^^^^"
+        );
+        assert_eq!(
+            err(&mut db, "CREATE TABLE t (name VARCHAR(abc));"),
+            "VARCHAR length must be between 1 and 243, got abc.\n\
+             1 | CREATE TABLE t (name VARCHAR(abc));\n  |                              ^^^"
+        );
+        // Running out of tokens points past the last one.
+        assert_eq!(
+            err(&mut db, "SELECT id FROM"),
+            "No table specified.\n1 | SELECT id FROM\n  |               ^"
+        );
+        assert_eq!(
+            err(&mut db, "SELECT nme FROM test;"),
+            "Column nme not found. Did you mean name?\n1 | SELECT nme FROM test;\n  |        ^^^"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_truncate_table() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
+            .expect("Failed creating table");
+        for i in 1..=100 {
+            db.parse_user_input(&format!("INSERT INTO test VALUES ({}, 'name {}');", i, i))
+                .expect("Failed inserting");
+        }
+        assert_eq!(
+            "Table test truncated.",
+            db.parse_user_input("TRUNCATE TABLE test RESTART IDENTITY;")
+                .unwrap()
+        );
+        let mut table = db.load_table("test").unwrap().unwrap();
+        assert!(table.is_empty().unwrap());
+        assert_eq!(
+            table
+                .get_index_file("id")
+                .unwrap()
+                .unwrap()
+                .num_pages()
+                .unwrap(),
+            1
+        );
+        db.parse_user_input("INSERT INTO test VALUES (5, 'again');")
+            .unwrap();
+        let records = table.search(Some("id"), DataType::Int(5), "=").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].row_id, 1);
+
+        assert!(db.parse_user_input("TRUNCATE missing;").is_err());
+        assert!(db.parse_user_input("TRUNCATE meta_tables;").is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_truncate_continue_identity() {
+        let mut db = setup_db_with_table();
+        for i in 1..=3 {
+            db.parse_user_input(&format!("INSERT INTO test VALUES ({}, 'a');", i))
+                .expect("Failed inserting");
+        }
+        db.parse_user_input("TRUNCATE test CONTINUE IDENTITY;")
+            .expect("Failed truncating");
+        assert!(select_records(&mut db, "SELECT * FROM test;").is_empty());
+        // The kept row ID is read from the catalog, so it outlives the database instance.
+        let mut db = Database::new().unwrap();
+        db.parse_user_input("INSERT INTO test VALUES (1, 'b');")
+            .expect("Failed inserting after truncating");
+        let row_ids: Vec<u32> = select_records(&mut db, "SELECT * FROM test;")
+            .iter()
+            .map(|r| r.row_id)
+            .collect();
+        assert_eq!(row_ids, [4]);
+        db.parse_user_input("TRUNCATE test;")
+            .expect("Failed truncating");
+        db.parse_user_input("INSERT INTO test VALUES (1, 'c');")
+            .expect("Failed inserting after restarting");
+        assert_eq!(select_records(&mut db, "SELECT * FROM test;")[0].row_id, 1);
+        assert!(db.parse_user_input("TRUNCATE test CONTINUE;").is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_insert_command() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        db.parse_user_input("INSERT INTO test VALUES (2, 'test2');")
+            .expect("Failed inserting 2");
+        db.parse_user_input("INSERT INTO test VALUES (3, 'test3');")
+            .expect("Failed inserting 3");
+
+        let records = db
+            .load_table("test")
+            .unwrap()
+            .expect("Table not found")
+            .search(None, DataType::Null, "=")
+            .expect("Failed searching");
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].values[0], DataType::Int(1));
+        teardown_db();
+    }
+
+    #[test]
+    fn test_nullable() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT, name TEXT);")
+            .expect("Failed creating table");
+        db.parse_user_input("INSERT INTO test (id) VALUES (1);")
+            .expect("Failed inserting 1");
+        let mut table = db.load_table("test").unwrap().expect("Table not found");
+        assert_eq!(table.len().unwrap(), 1);
+        let records = table
+            .search(None, DataType::Null, "=")
+            .expect("Failed searching");
+        let vals = &records[0].values;
+        assert_eq!(vals[0], DataType::Int(1));
+        assert_eq!(vals[1], DataType::Null);
+        teardown_db();
+    }
+
+    #[test]
+    fn test_unique() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT UNIQUE, name TEXT);")
+            .expect("Failed creating table");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        let res = db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test2');");
+        assert!(res.is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT UNIQUE, name TEXT NOT NULL);")
+            .expect("Failed creating table");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        assert!(matches!(
+            db.parse_user_input("SELEC * FROM test;"),
+            Err(DbError::Parse(_))
+        ));
+        assert!(matches!(
+            db.parse_user_input("SELECT * FROM missing;"),
+            Err(DbError::Catalog(_))
+        ));
+        assert!(matches!(
+            db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test2');"),
+            Err(DbError::Constraint(_))
+        ));
+        assert!(matches!(
+            db.parse_user_input("INSERT INTO test (id) VALUES (2);"),
+            Err(DbError::Constraint(_))
+        ));
+        assert!(matches!(
+            db.parse_user_input("UPDATE test SET id = UPPER(name);"),
+            Err(DbError::Type(_))
+        ));
+        teardown_db();
+    }
+
+    /// The table `test`, with a primary key, an indexed column and a UNIQUE column, holding the
+    /// record `(1, 'a', 'x')`.
+    const CONFLICT_TABLE: &str =
+        "CREATE TABLE test (id INT PRIMARY KEY, name TEXT, code TEXT UNIQUE); \
+        CREATE INDEX test (name); INSERT INTO test VALUES (1, 'a', 'x');";
+
+    #[test]
+    fn test_on_conflict_do_nothing() {
+        let mut db = setup_db_with(CONFLICT_TABLE);
+        assert_eq!(
+            db.parse_user_input("INSERT INTO test VALUES (1, 'b', 'y') ON CONFLICT DO NOTHING;")
+                .unwrap(),
+            "0 rows inserted into test."
+        );
+        assert_eq!(
+            db.parse_user_input(
+                "INSERT INTO test VALUES (2, 'c', 'z') ON CONFLICT (id) DO NOTHING;"
+            )
+            .unwrap(),
+            "1 row inserted into test."
+        );
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM test;"),
+            [row("1, 'a', 'x'"), row("2, 'c', 'z'")]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_on_conflict_do_update() {
+        let mut db = setup_db_with(CONFLICT_TABLE);
+        assert_eq!(
+            db.parse_user_input(
+                "INSERT INTO test VALUES (1, 'b', 'y') ON CONFLICT (id) \
+                 DO UPDATE SET name = name || excluded.name;"
+            )
+            .unwrap(),
+            "1 row updated."
+        );
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM test;"),
+            row("1, 'ab', 'x'")
+        );
+        // The index on the updated column follows the new value.
+        assert!(select_records(&mut db, "SELECT id FROM test WHERE name = 'a';").is_empty());
+        assert_eq!(
+            select_values(&mut db, "SELECT id FROM test WHERE name = 'ab';"),
+            [DataType::Int(1)]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_on_conflict_other_column() {
+        let mut db = setup_db_with(CONFLICT_TABLE);
+        // A conflict on another column than the one named is still an error.
+        let err = db
+            .parse_user_input("INSERT INTO test VALUES (3, 'd', 'x') ON CONFLICT (id) DO NOTHING;")
+            .unwrap_err();
+        assert!(matches!(err, DbError::Constraint(_)), "{}", err);
+        // The failed insert left no entry behind in the index.
+        assert!(select_records(&mut db, "SELECT id FROM test WHERE name = 'd';").is_empty());
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM test;"),
+            row("1, 'a', 'x'")
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_on_conflict_update_keeps_unique() {
+        let mut db = setup_db_with(CONFLICT_TABLE);
+        db.parse_user_input("INSERT INTO test VALUES (2, 'c', 'z');")
+            .unwrap();
+        let err = db
+            .parse_user_input(
+                "INSERT INTO test VALUES (2, 'e', 'w') ON CONFLICT (id) DO UPDATE SET code = 'x';",
+            )
+            .unwrap_err();
+        assert!(matches!(err, DbError::Constraint(_)), "{}", err);
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM test;"),
+            [row("1, 'a', 'x'"), row("2, 'c', 'z'")]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_on_conflict_target_not_unique() {
+        let mut db = setup_db_with(CONFLICT_TABLE);
+        assert!(db
+            .parse_user_input(
+                "INSERT INTO test VALUES (4, 'f', 'v') ON CONFLICT (name) DO NOTHING;"
+            )
+            .is_err());
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM test;"),
+            row("1, 'a', 'x'")
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_returning() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT UNIQUE, name TEXT DEFAULT 'none');")
+            .expect("Failed creating table");
+        assert_eq!(
+            db.parse_user_input("INSERT INTO test (id) VALUES (1) RETURNING *;")
+                .unwrap(),
+            "Table: test\nid name \n1 'none' \n"
+        );
+        assert_eq!(
+            db.parse_user_input("INSERT INTO test VALUES (2, 'b') RETURNING rowid, upper(name);")
+                .unwrap(),
+            "Table: test\nrowid upper(name) \n2 'B' \n"
+        );
+        assert_eq!(
+            db.parse_user_input(
+                "INSERT INTO test VALUES (2, 'c') ON CONFLICT (id) DO UPDATE SET name = excluded.name \
+                 RETURNING id, name;"
+            )
+            .unwrap(),
+            "Table: test\nid name \n2 'c' \n"
+        );
+        assert_eq!(
+            db.parse_user_input(
+                "INSERT INTO test VALUES (2, 'd') ON CONFLICT DO NOTHING RETURNING *;"
+            )
+            .unwrap(),
+            "Table: test\nid name \nNo records found."
+        );
+        assert_eq!(
+            db.parse_user_input("UPDATE test SET name = 'x' WHERE id >= 1 RETURNING id, name;")
+                .unwrap(),
+            "Table: test\nid name \n1 'x' \n2 'x' \n"
+        );
+        assert_eq!(
+            db.parse_user_input("DELETE FROM test WHERE id = 1 RETURNING *;")
+                .unwrap(),
+            "Table: test\nid name \n1 'x' \n"
+        );
+        assert!(db
+            .parse_user_input("DELETE FROM test RETURNING missing;")
+            .is_err());
+        assert_eq!(
+            db.parse_user_input("SELECT * FROM test;").unwrap(),
+            "Table: test\nid name \n2 'x' \n"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_trigger_binds_old_and_new() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE notes (id INT, body TEXT, due DATE);")
+            .expect("Failed creating table");
+        db.parse_user_input("CREATE TABLE history (body TEXT, due DATE);")
+            .expect("Failed creating table");
+        db.parse_user_input(
+            "CREATE TRIGGER keep BEFORE UPDATE ON notes FOR EACH ROW \
+             INSERT INTO history VALUES (old.body, old.due);",
+        )
+        .expect("Failed creating trigger");
+        db.parse_user_input("INSERT INTO notes VALUES (1, 'it''s due', '2024-02-29');")
+            .expect("Failed inserting");
+        db.parse_user_input("UPDATE notes SET body = 'done' WHERE id = 1;")
+            .expect("Failed updating");
+        // The values are bound as they are stored, rather than written back into the statement.
+        let notes = select_values(&mut db, "SELECT body, due FROM notes;");
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM history;"),
+            [DataType::Text("'it''s due'".to_string()), notes[1].clone()]
+        );
+        teardown_db();
+    }
+
+    /// The tables `accounts` and `audit`, with triggers writing each insert, update and delete of
+    /// `accounts` to `audit`.
+    const AUDITED_ACCOUNTS: &str = "CREATE TABLE accounts (id INT UNIQUE, balance INT); \
+        CREATE TABLE audit (id INT, old_balance INT, new_balance INT); \
+        CREATE TRIGGER log_update AFTER UPDATE ON accounts FOR EACH ROW \
+        INSERT INTO audit VALUES (NEW.id, OLD.balance, NEW.balance); \
+        CREATE TRIGGER log_insert AFTER INSERT ON accounts FOR EACH ROW \
+        INSERT INTO audit VALUES (new.id, null, new.balance); \
+        CREATE TRIGGER log_delete BEFORE DELETE ON accounts FOR EACH ROW \
+        INSERT INTO audit VALUES (old.id, old.balance, null);";
+
+    #[test]
+    fn test_trigger_old_and_new() {
+        let mut db = setup_db_with(AUDITED_ACCOUNTS);
+        db.parse_user_input("INSERT INTO accounts VALUES (1, 100);")
+            .unwrap();
+        db.parse_user_input("INSERT INTO accounts VALUES (2, 50);")
+            .unwrap();
+        assert_eq!(
+            db.parse_user_input("UPDATE accounts SET balance = 75 WHERE id >= 1;")
+                .unwrap(),
+            "2 rows updated."
+        );
+        db.parse_user_input(
+            "INSERT INTO accounts VALUES (2, 0) ON CONFLICT (id) DO UPDATE SET balance = excluded.balance;",
+        )
+        .unwrap();
+        db.parse_user_input("DELETE FROM accounts WHERE id = 1;")
+            .unwrap();
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM audit;"),
+            [
+                row("1, null, 100"),
+                row("2, null, 50"),
+                row("1, 100, 75"),
+                row("2, 50, 75"),
+                row("2, 75, 0"),
+                row("1, 75, null"),
+            ]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_create_trigger_errors() {
+        let mut db = setup_db_with(AUDITED_ACCOUNTS);
+        let err = db
+            .parse_user_input(
+                "CREATE TRIGGER bad AFTER INSERT ON accounts FOR EACH ROW \
+                 DELETE FROM audit WHERE id = old.id;",
+            )
+            .unwrap_err();
+        assert_eq!(err.to_string(), "OLD.ID is not set for INSERT statements.");
+        assert!(db
+            .parse_user_input(
+                "CREATE TRIGGER bad AFTER INSERT ON audit FOR EACH ROW SELECT * FROM accounts;"
+            )
+            .is_err());
+        assert!(db
+            .parse_user_input(
+                "CREATE TRIGGER bad AFTER INSERT ON meta_tables FOR EACH ROW DELETE FROM audit;"
+            )
+            .is_err());
+        assert!(db
+            .parse_user_input(
+                "CREATE TRIGGER log_insert AFTER INSERT ON audit FOR EACH ROW DELETE FROM audit;"
+            )
+            .is_err());
+        assert_eq!(
+            db.parse_user_input(
+                "CREATE TRIGGER log_audit AFTER INSERT ON audit FOR EACH ROW DELETE FROM audit;"
+            )
+            .unwrap(),
+            "Trigger log_audit created."
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_trigger_depth_limit() {
+        let mut db = setup_db_with(AUDITED_ACCOUNTS);
+        db.parse_user_input("INSERT INTO accounts VALUES (1, 100);")
+            .unwrap();
+        // A trigger whose statement fires itself stops instead of running forever.
+        db.parse_user_input(
+            "CREATE TRIGGER again AFTER INSERT ON audit FOR EACH ROW \
+             INSERT INTO audit VALUES (new.id, null, null);",
+        )
+        .expect("Failed creating trigger");
+        let err = db
+            .parse_user_input("INSERT INTO audit VALUES (3, null, null);")
+            .unwrap_err();
+        assert!(matches!(err, DbError::Constraint(_)), "{}", err);
+        assert_eq!(
+            err.to_string(),
+            "Trigger again: Triggers nested more than 16 levels deep."
+        );
+        // The rows the loop wrote before it stopped are gone again.
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM audit;"),
+            [row("1, null, 100")]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_failed_delete_restores_pages() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("CREATE TABLE seen (id INT UNIQUE);")
+            .unwrap();
+        insert_test_records(&mut db, 1..=200);
+        db.parse_user_input("INSERT INTO seen VALUES (150);")
+            .unwrap();
+        db.parse_user_input(
+            "CREATE TRIGGER log_delete BEFORE DELETE ON test FOR EACH ROW \
+             INSERT INTO seen VALUES (old.id);",
+        )
+        .unwrap();
+        // The records deleted before the trigger fails span several pages, each of which has to
+        // take its records back.
+        let err = db.parse_user_input("DELETE FROM test;").unwrap_err();
+        assert!(
+            err.to_string().starts_with("Trigger log_delete: "),
+            "{}",
+            err
+        );
+        assert_eq!(select_records(&mut db, "SELECT * FROM test;").len(), 200);
+        assert_eq!(
+            select_values(&mut db, "SELECT name FROM test WHERE id = 120;"),
+            [DataType::Text("'test120'".to_string())]
+        );
+        assert_eq!(
+            select_values(&mut db, "SELECT * FROM seen;"),
+            [DataType::Int(150)]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_trigger_failure_undoes_statement() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE accounts (id INT UNIQUE, balance INT);")
+            .unwrap();
+        db.parse_user_input("CREATE TABLE seen (id INT UNIQUE);")
+            .unwrap();
+        for (id, balance) in [(2, 0), (1, 10), (3, 30)] {
+            db.parse_user_input(&format!(
+                "INSERT INTO accounts VALUES ({}, {});",
+                id, balance
+            ))
+            .unwrap();
+        }
+        let accounts = selected_values(&mut db, "SELECT * FROM accounts;");
+        // A trigger failing on the second row undoes the first row's write too.
+        for event in ["UPDATE", "DELETE"] {
+            db.parse_user_input(&format!(
+                "CREATE TRIGGER once BEFORE {} ON accounts FOR EACH ROW \
+                 INSERT INTO seen VALUES (1);",
+                event
+            ))
+            .unwrap();
+            let statement = match event {
+                "UPDATE" => "UPDATE accounts SET balance = 5 WHERE id > 0;",
+                _ => "DELETE FROM accounts WHERE id > 0;",
+            };
+            let err = db.parse_user_input(statement).unwrap_err();
+            assert!(err.to_string().starts_with("Trigger once: "), "{}", err);
+            assert_eq!(
+                selected_values(&mut db, "SELECT * FROM accounts;"),
+                accounts
+            );
+            assert_eq!(
+                select_values(&mut db, "SELECT * FROM accounts WHERE id = 2;"),
+                [DataType::Int(2), DataType::Int(0)]
+            );
+            assert!(select_records(&mut db, "SELECT * FROM seen;").is_empty());
+            db.parse_user_input("DROP TRIGGER once;").unwrap();
+        }
+        teardown_db();
+    }
+
+    #[test]
+    fn test_drop_trigger() {
+        let mut db = setup_db_with(AUDITED_ACCOUNTS);
+        assert_eq!(
+            db.parse_user_input("DROP TRIGGER log_insert;").unwrap(),
+            "Trigger log_insert dropped."
+        );
+        assert!(db.parse_user_input("DROP TRIGGER log_insert;").is_err());
+        assert_eq!(
+            db.parse_user_input("DROP TRIGGER IF EXISTS log_insert;")
+                .unwrap(),
+            "Trigger log_insert does not exist, skipped."
+        );
+        db.parse_user_input("INSERT INTO accounts VALUES (1, 100);")
+            .unwrap();
+        assert!(select_records(&mut db, "SELECT * FROM audit;").is_empty());
+        // Dropping the table drops its remaining triggers.
+        db.parse_user_input("DROP TABLE accounts;").unwrap();
+        assert_eq!(
+            db.trigger_table
+                .search(None, DataType::Null, "=")
+                .unwrap()
+                .len(),
+            0
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_delete_command() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
+            .expect("Failed inserting 2");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
+            .expect("Failed inserting 3");
+        db.parse_user_input("DELETE FROM test WHERE id = 2;")
+            .expect("Failed deleting");
+        let mut table = db.load_table("test").unwrap().expect("Table not found");
+        let records = table
+            .search(None, DataType::Null, "=")
+            .expect("Failed searching");
+        assert_eq!(records[0].values[0], DataType::Int(1));
+        assert_eq!(records[1].values[0], DataType::Int(3));
+        assert_eq!(table.len().unwrap(), 2);
+        teardown_db();
+    }
+
+    #[test]
+    fn test_update_command() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
+            .expect("Failed inserting 2");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
+            .expect("Failed inserting 3");
+        let mut table = db.load_table("test").unwrap().expect("Table not found");
+        assert_eq!(table.len().unwrap(), 3);
+        let records = table
+            .search(Some("id"), DataType::Int(2), "=")
+            .expect("Failed searching");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].values[1], DataType::Text("'test2'".to_string()));
+        db.parse_user_input("UPDATE test SET name = 'test4' WHERE id = 2;")
+            .expect("Failed updating");
+        let records = table
+            .search(Some("id"), DataType::Int(2), "=")
+            .expect("Failed searching");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].values[1], DataType::Text("'test4'".to_string()));
+        teardown_db();
+    }
+
+    /// The table `test`, with an indexed UNIQUE column and a NOT NULL column, holding the records
+    /// `(1, 'a')`, `(2, 'b')` and `(3, 'c')`.
+    const UPDATE_CONSTRAINTS: &str = "CREATE TABLE test (id INT UNIQUE, name TEXT NOT NULL); \
+        CREATE INDEX test (id); INSERT INTO test VALUES (1, 'a'); \
+        INSERT INTO test VALUES (2, 'b'); INSERT INTO test VALUES (3, 'c');";
+
+    #[test]
+    fn test_update_unique_existing() {
+        let mut db = setup_db_with(UPDATE_CONSTRAINTS);
+        let err = db
+            .parse_user_input("UPDATE test SET id = 3 WHERE id = 1;")
+            .expect_err("Duplicate of a row not updated");
+        assert_eq!(err.to_string(), "Value 3 already exists in column id");
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM test;"),
+            [row("1, 'a'"), row("2, 'b'"), row("3, 'c'")]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_update_unique_among_updated() {
+        let mut db = setup_db_with(UPDATE_CONSTRAINTS);
+        db.parse_user_input("UPDATE test SET id = 5 WHERE id > 1;")
+            .expect_err("Two updated rows get the same value");
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM test;"),
+            [row("1, 'a'"), row("2, 'b'"), row("3, 'c'")]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_update_not_null_and_type() {
+        let mut db = setup_db_with(UPDATE_CONSTRAINTS);
+        db.parse_user_input("UPDATE test SET name = NULL WHERE id = 3;")
+            .expect_err("NULL in a NOT NULL column");
+        db.parse_user_input("UPDATE test SET id = 'x' WHERE id = 2;")
+            .expect_err("TEXT in an INT column");
+        assert_eq!(
+            selected_values(&mut db, "SELECT * FROM test;"),
+            [row("1, 'a'"), row("2, 'b'"), row("3, 'c'")]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_update_swaps_unique_values() {
+        let mut db = setup_db_with(UPDATE_CONSTRAINTS);
+        // Values may move between the updated rows, and the index follows them.
+        db.parse_user_input(
+            "UPDATE test SET id = CASE WHEN id = 1 THEN 4 ELSE 1 END WHERE id < 3;",
+        )
+        .expect("Failed updating");
+        assert_eq!(
+            select_values(&mut db, "SELECT name FROM test WHERE id = 4;"),
+            [DataType::Text("'a'".to_string())]
+        );
+        assert_eq!(
+            select_values(&mut db, "SELECT name FROM test WHERE id = 1;"),
+            [DataType::Text("'b'".to_string())]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_update_unique_nan() {
+        let mut db = setup_db();
+        // NaN equals itself in a UNIQUE column, for UPDATE as for INSERT.
+        db.parse_user_input("CREATE TABLE n (id INT, f DOUBLE UNIQUE);")
+            .expect("Failed creating table");
+        for (id, f) in [(1, "1.5"), (2, "2.5")] {
+            db.parse_user_input(&format!("INSERT INTO n VALUES ({}, {});", id, f))
+                .expect("Failed inserting");
+        }
+        db.parse_user_input("UPDATE n SET f = nan WHERE id > 0;")
+            .expect_err("Two updated rows get NaN");
+        db.parse_user_input("UPDATE n SET f = nan WHERE id = 1;")
+            .expect("Failed updating");
+        db.parse_user_input("INSERT INTO n VALUES (3, nan);")
+            .expect_err("NaN already exists");
+        db.parse_user_input("UPDATE n SET f = nan WHERE id = 2;")
+            .expect_err("NaN already exists");
+        assert_eq!(
+            select_values(&mut db, "SELECT f FROM n WHERE id = 2;"),
+            [DataType::Double(2.5)]
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_select_command() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
+            .expect("Failed inserting 2");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
+            .expect("Failed inserting 3");
+        let res = db
+            .parse_user_input("SELECT * FROM test;")
+            .expect("Failed selecting");
+        let res_str = "Table: test\nid name \n1 'test' \n2 'test2' \n3 'test3' \n";
+        assert_eq!(res, res_str);
+        teardown_db();
+    }
+
+    #[test]
+    fn test_execute_to() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, '10');")
+            .expect("Failed inserting 1");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'x');")
+            .expect("Failed inserting 2");
+        let mut out = vec![];
+        let statement = parser::parse(&mut tokenize("SELECT id FROM test;").unwrap()).unwrap();
+        db.execute_to(&statement, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Table: test\nid \n1 \n2 \n"
+        );
+        // Records are written as they are read, so those before a failing one are written.
+        let mut out = vec![];
+        let statement =
+            parser::parse(&mut tokenize("SELECT CAST(name AS INT) FROM test;").unwrap()).unwrap();
+        assert!(db.execute_to(&statement, &mut out).is_err());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Table: test\ncast(name as int) \n10 \n"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_select_from_system() {
+        let mut db = setup_db();
+        let res = db.parse_user_input("SELECT * FROM meta_tables;");
+        assert!(res.is_ok());
+        assert_ne!(res.unwrap(), "No records found.");
+        let res = db.parse_user_input("SELECT * FROM meta_columns;");
+        assert!(res.is_ok());
+        assert_ne!(res.unwrap(), "No records found.");
+        teardown_db();
+    }
+
+    #[test]
+    fn test_create_view() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
+            .expect("Failed inserting 2");
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
+            .expect("Failed inserting 3");
+        db.parse_user_input("CREATE VIEW big AS SELECT name, id FROM test WHERE id > 1;")
+            .expect("Failed creating view");
+        assert_eq!(db.table_type("big").unwrap(), Some(VIEW_TABLE_TYPE));
+        assert!(db.load_table("big").unwrap().is_none());
+        let res = db
+            .parse_user_input("SELECT * FROM big;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: big\nname id \n'test2' 2 \n'test3' 3 \n");
+        let res = db
+            .parse_user_input("SELECT id FROM big WHERE name = 'test3';")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: big\nid \n3 \n");
+        db.parse_user_input("CREATE VIEW bigger AS SELECT id FROM big WHERE id >= 3;")
+            .expect("Failed creating view over view");
+        let res = db
+            .parse_user_input("SELECT * FROM bigger;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: bigger\nid \n3 \n");
+        assert!(db
+            .parse_user_input("CREATE VIEW broken AS SELECT missing FROM test;")
+            .is_err());
+        assert!(db
+            .parse_user_input("CREATE VIEW big AS SELECT * FROM test;")
+            .is_err());
+        assert!(db
+            .parse_user_input("INSERT INTO big VALUES ('test4', 4);")
+            .is_err());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_drop_view() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("CREATE VIEW names AS SELECT name FROM test;")
+            .expect("Failed creating view");
+        assert!(db.parse_user_input("DROP TABLE names;").is_err());
+        for sql in [
+            "INSERT INTO names VALUES ('a');",
+            "UPDATE names SET name = 'a';",
+            "DELETE FROM names;",
+        ] {
+            assert_eq!(
+                db.parse_user_input(sql).unwrap_err().to_string(),
+                "names is a view.",
+                "{}",
+                sql
+            );
+        }
+        db.parse_user_input("DROP VIEW names;")
+            .expect("Failed dropping view");
+        assert_eq!(db.table_type("names").unwrap(), None);
+        assert!(db.load_view("names").unwrap().is_none());
+        assert!(db.parse_user_input("SELECT * FROM names;").is_err());
+        assert_eq!(
+            db.parse_user_input("DROP VIEW test;")
+                .unwrap_err()
+                .to_string(),
+            "View test does not exist."
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_show_tables() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("CREATE VIEW names AS SELECT name FROM test;")
+            .expect("Failed creating view");
+        let res = db.parse_user_input("SHOW TABLES;").expect("Failed showing");
+        let expected = "meta_tables system \nmeta_columns system \nmeta_views system \nmeta_defaults system \nmeta_triggers system \nmeta_row_ids system \ntest table \nnames view \n";
+        assert_eq!(res, expected);
+        teardown_db();
+    }
+
+    /// The table `test`, with an index on `id`, and the table `pets` holding a single record. The
+    /// tests fill `test` with 200 records over several pages.
+    const EXPLAIN_TABLES: &str = "CREATE TABLE test (id INT PRIMARY_KEY, name TEXT); \
+        CREATE TABLE pets (owner INT, name TEXT); INSERT INTO pets VALUES (7, 'rex');";
+
+    #[test]
+    fn test_explain_small_table() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+            .expect("Failed inserting 1");
+        // A table of a single page is read faster by a full scan than through its index.
+        let res = db
+            .parse_user_input("EXPLAIN SELECT name FROM test WHERE id = 1;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (name)\n\
+             └── Filter (id = 1)\n    \
+             └── Full scan on test (est. pages: 1)\n\
+             Estimated pages touched: 1\n"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_explain_index_choice() {
+        let mut db = setup_db_with(EXPLAIN_TABLES);
+        insert_test_records(&mut db, 1..=200);
+        let res = db
+            .parse_user_input("EXPLAIN SELECT name FROM test WHERE id = 1;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (name)\n\
+             └── Index lookup on test.id (id = 1) (est. pages: 4)\n\
+             Estimated pages touched: 4\n"
+        );
+        let res = db
+            .parse_user_input("EXPLAIN SELECT * FROM test WHERE name = 'test';")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (id, name)\n\
+             └── Filter (name = 'test')\n    \
+             └── Full scan on test (est. pages: 11)\n\
+             Estimated pages touched: 11\n"
+        );
+        // Almost every record has another id, so a full scan reads fewer pages.
+        let res = db
+            .parse_user_input("EXPLAIN SELECT * FROM test WHERE id <> 1;")
+            .expect("Failed explaining");
+        assert!(res.contains("└── Full scan on test"), "{}", res);
+        teardown_db();
+    }
+
+    #[test]
+    fn test_explain_update_and_delete() {
+        let mut db = setup_db_with(EXPLAIN_TABLES);
+        insert_test_records(&mut db, 1..=200);
+        // Almost every record has an id above 0, so a full scan reads fewer pages.
+        let res = db
+            .parse_user_input("EXPLAIN UPDATE test SET name = 'a' WHERE id > 0;")
+            .expect("Failed explaining");
+        assert!(res.starts_with("Update test set name\n└── Filter (id > 0)"));
+        let res = db
+            .parse_user_input("EXPLAIN UPDATE test SET name = 'a' WHERE id > 198;")
+            .expect("Failed explaining");
+        assert!(res.starts_with("Update test set name\n└── Index range scan on test.id (id > 198)"));
+        let res = db
+            .parse_user_input("EXPLAIN DELETE FROM test;")
+            .expect("Failed explaining");
+        assert!(res.starts_with("Delete from test\n└── Full scan on test"));
+        // EXPLAIN must not run the statement
+        assert_eq!(db.load_table("test").unwrap().unwrap().len().unwrap(), 200);
+        assert!(select_records(&mut db, "SELECT id FROM test WHERE name = 'a';").is_empty());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_explain_join() {
+        let mut db = setup_db_with(EXPLAIN_TABLES);
+        insert_test_records(&mut db, 1..=200);
+        // The search condition of a join is costed on the source of its column.
+        let res = db
+            .parse_user_input(
+                "EXPLAIN SELECT * FROM pets JOIN test ON owner = test.id WHERE test.id = 7;",
+            )
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (pets.owner, pets.name, test.id, test.name)\n\
+             └── Nested loop join (owner = test.id)\n    \
+             ├── Full scan on pets (est. pages: 1)\n    \
+             └── Index lookup on test.id (id = 7) (est. pages: 4)\n\
+             Estimated pages touched: 5\n"
+        );
+        assert_eq!(
+            select_values(
+                &mut db,
+                "SELECT pets.name, test.name FROM pets JOIN test ON owner = test.id WHERE id = 7;",
+            ),
+            [
+                DataType::Text("'rex'".to_string()),
+                DataType::Text("'test7'".to_string())
+            ]
+        );
+        let res = db
+            .parse_user_input(
+                "EXPLAIN SELECT * FROM test JOIN pets ON owner = test.id WHERE test.id > 198;",
+            )
+            .expect("Failed explaining");
+        assert!(
+            res.contains("├── Index range scan on test.id (id > 198)"),
+            "{}",
+            res
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_join_key_lookup() {
+        let mut db = setup_db_with(EXPLAIN_TABLES);
+        insert_test_records(&mut db, 1..=200);
+        db.parse_user_input("INSERT INTO pets VALUES (null, 'stray');")
+            .expect("Failed inserting");
+        db.parse_user_input("INSERT INTO pets VALUES (300, 'lost');")
+            .expect("Failed inserting");
+        // Looking up the owner of each pet in the index on test.id reads fewer pages than
+        // reading test again for each pet.
+        let res = db
+            .parse_user_input("EXPLAIN SELECT * FROM pets JOIN test ON owner = test.id;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (pets.owner, pets.name, test.id, test.name)\n\
+             └── Nested loop join (owner = test.id)\n    \
+             ├── Full scan on pets (est. pages: 1)\n    \
+             └── Index lookup on test.id (id = pets.owner) (est. pages: 4)\n\
+             Estimated pages touched: 13\n"
+        );
+        assert_eq!(
+            selected_values(
+                &mut db,
+                "SELECT pets.name, test.name FROM pets JOIN test ON test.id = owner;"
+            ),
+            [[
+                DataType::Text("'rex'".to_string()),
+                DataType::Text("'test7'".to_string())
+            ]]
+        );
+        // The search condition on the joined table still applies to the records looked up.
+        assert!(select_records(
+            &mut db,
+            "SELECT * FROM pets JOIN test ON owner = test.id WHERE test.name = 'test8';"
+        )
+        .is_empty());
+        // Without an index on pets.owner, pets is read again for each record of test.
+        let res = db
+            .parse_user_input("EXPLAIN SELECT * FROM test JOIN pets ON test.id = owner;")
+            .expect("Failed explaining");
+        assert!(
+            res.contains("└── Full scan on pets (est. pages: 1)"),
+            "{}",
+            res
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_explain_view() {
+        let mut db = setup_db_with(EXPLAIN_TABLES);
+        insert_test_records(&mut db, 1..=200);
+        db.parse_user_input("CREATE VIEW names AS SELECT name FROM test;")
+            .expect("Failed creating view");
+        // Both sources have a name column, so the condition is on the view's alone.
+        assert_eq!(
+            select_values(
+                &mut db,
+                "SELECT pets.name FROM pets JOIN names ON names.name = 'test7' \
+                 WHERE names.name = 'test7';",
+            ),
+            [DataType::Text("'rex'".to_string())]
+        );
+        let res = db
+            .parse_user_input("EXPLAIN SELECT * FROM names WHERE name = 'test';")
+            .expect("Failed explaining");
+        assert!(res.starts_with(
+            "Project (name)\n└── Filter (name = 'test')\n    └── View names\n        └── Project (name)\n"
+        ));
+        teardown_db();
+    }
+
+    #[test]
+    fn test_explain_rejects_ddl() {
+        let mut db = setup_db_with_table();
+        assert!(db.parse_user_input("EXPLAIN DROP TABLE test;").is_err());
+        assert!(db.load_table("test").unwrap().is_some());
+        teardown_db();
+    }
+
+    #[test]
+    fn test_join_and_limit() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("CREATE TABLE pets (id INT, owner INT, name TEXT);")
+            .expect("Failed creating table");
+        for (id, name) in [(1, "ann"), (2, "bob"), (3, "cat")] {
+            db.parse_user_input(&format!("INSERT INTO test VALUES ({}, '{}');", id, name))
+                .expect("Failed inserting");
+        }
+        for (id, owner, name) in [(1, 1, "rex"), (2, 3, "tom"), (3, 1, "fifi"), (4, 9, "lost")] {
+            db.parse_user_input(&format!(
+                "INSERT INTO pets VALUES ({}, {}, '{}');",
+                id, owner, name
+            ))
+            .expect("Failed inserting");
+        }
+        let res = db
+            .parse_user_input(
+                "SELECT test.name, pets.name FROM test JOIN pets ON test.id = owner \
+                 ORDER BY pets.name;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(
+            res,
+            "Table: test\ntest.name pets.name \n'ann' 'fifi' \n'ann' 'rex' \n'cat' 'tom' \n"
+        );
+        let res = db
+            .parse_user_input(
+                "SELECT owner FROM test INNER JOIN pets ON test.id = pets.owner \
+                 WHERE test.name = 'ann' LIMIT 1;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\npets.owner \n1 \n");
+        let err = db
+            .parse_user_input("SELECT name FROM test JOIN pets ON test.id = owner;")
+            .expect_err("Both tables have a name column");
+        assert_eq!(
+            err.to_string(),
+            "Column name is ambiguous. Did you mean test.name or pets.name?\n\
+             1 | SELECT name FROM test JOIN pets ON test.id = owner;\n  |        ^^^^"
+        );
+        let res = db
+            .parse_user_input("SELECT id FROM test ORDER BY id DESC LIMIT 2;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nid \n3 \n2 \n");
+        let res = db
+            .parse_user_input("SELECT id FROM test LIMIT 0;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nid \nNo records found.");
+        // The page of pets is read again for each of the 3 records of test.
+        let res = db
+            .parse_user_input(
+                "EXPLAIN SELECT pets.name FROM test JOIN pets ON test.id = owner LIMIT 5;",
+            )
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Limit (5)\n\
+             └── Project (pets.name)\n    \
+             └── Nested loop join (test.id = owner)\n        \
+             ├── Full scan on test (est. pages: 1)\n        \
+             └── Full scan on pets (est. pages: 1)\n\
+             Estimated pages touched: 4\n"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_min_max() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE scores (id INT, score INT, name TEXT);")
+            .expect("Failed creating table");
+        for values in ["1, 5, 'eve'", "2, NULL, 'bob'", "3, 9, NULL", "4, 2, 'dan'"] {
+            db.parse_user_input(&format!("INSERT INTO scores VALUES ({});", values))
+                .expect("Failed inserting");
+        }
+        assert_eq!(
+            select_values(&mut db, "SELECT min(score), max(score) FROM scores;"),
+            [DataType::Int(2), DataType::Int(9)]
+        );
+        assert_eq!(
+            select_values(&mut db, "SELECT min(name), max(name) FROM scores;"),
+            [
+                DataType::Text("'bob'".to_string()),
+                DataType::Text("'eve'".to_string())
+            ]
+        );
+        assert_eq!(
+            select_values(
+                &mut db,
+                "SELECT max(CAST(score AS BIGINT)) FROM scores WHERE id < 3;"
+            ),
+            [DataType::BigInt(5)]
+        );
+        // Without records, or without any that are not NULL, the result is NULL.
+        assert_eq!(
+            select_values(&mut db, "SELECT min(score) FROM scores WHERE id > 4;"),
+            [DataType::Null]
+        );
+        assert_eq!(
+            select_values(&mut db, "SELECT max(score) FROM scores WHERE id = 2;"),
+            [DataType::Null]
+        );
+        let err = db
+            .parse_user_input("SELECT id, min(score) FROM scores;")
+            .expect_err("Other columns have no single value");
+        assert_eq!(
+            err.to_string(),
+            "MIN and MAX cannot be selected with other columns."
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_min_max_index() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE scores (id INT, score INT);")
+            .expect("Failed creating table");
+        db.parse_user_input("CREATE INDEX scores (score);")
+            .expect("Failed creating index");
+        assert_eq!(
+            select_values(&mut db, "SELECT min(score) FROM scores;"),
+            [DataType::Null]
+        );
+        for id in 1..=150 {
+            let score = match id % 7 {
+                0 => "NULL".to_string(),
+                _ => ((id * 37) % 50 + 10).to_string(),
+            };
+            db.parse_user_input(&format!("INSERT INTO scores VALUES ({}, {});", id, score))
+                .expect("Failed inserting");
+        }
+        // The result read from the index matches that of scanning every record.
+        for function in ["min", "max"] {
+            let indexed =
+                select_values(&mut db, &format!("SELECT {}(score) FROM scores;", function));
+            let scanned = select_values(
+                &mut db,
+                &format!("SELECT {}(CAST(score AS INT)) FROM scores;", function),
+            );
+            assert_eq!(indexed, scanned, "{}", function);
+        }
+        assert_eq!(
+            select_values(&mut db, "SELECT min(score) FROM scores;"),
+            [DataType::Int(10)]
+        );
+        assert_eq!(
+            select_values(&mut db, "SELECT max(score) FROM scores;"),
+            [DataType::Int(59)]
+        );
+        let res = db
+            .parse_user_input("EXPLAIN SELECT max(score) FROM scores;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (max(score))\n\
+             └── Aggregate (max(score))\n    \
+             └── Index scan on scores.score in descending order (est. pages: 5)\n\
+             Estimated pages touched: 5\n"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_index_order() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE scores (id INT, score INT);")
+            .expect("Failed creating table");
+        db.parse_user_input("CREATE INDEX scores (score);")
+            .expect("Failed creating index");
+        for id in 1..=150 {
+            let score = match id % 7 {
+                0 => "NULL".to_string(),
+                _ => ((id * 37) % 50).to_string(),
+            };
+            db.parse_user_input(&format!("INSERT INTO scores VALUES ({}, {});", id, score))
+                .expect("Failed inserting");
+        }
+        // Ordering by an expression sorts the records, which the index order must match.
+        for (clause, order) in [
+            ("", ""),
+            ("", " DESC"),
+            (" WHERE score > 20", ""),
+            (" WHERE score <= 20", " DESC"),
+            (" WHERE score <> 30", ""),
+            (" WHERE score = 30", " DESC"),
+        ] {
+            let indexed = db
+                .parse_user_input(&format!(
+                    "SELECT id, score FROM scores{} ORDER BY score{};",
+                    clause, order
+                ))
+                .expect("Failed selecting");
+            let sorted = db
+                .parse_user_input(&format!(
+                    "SELECT id, score FROM scores{} ORDER BY CAST(score AS INT){};",
+                    clause, order
+                ))
+                .expect("Failed selecting");
+            assert_eq!(indexed, sorted, "{}{}", clause, order);
+        }
+        let res = db
+            .parse_user_input("SELECT score FROM scores ORDER BY score LIMIT 1;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: scores\nscore \n0 \n");
+        let res = db
+            .parse_user_input(
+                "SELECT score FROM scores WHERE score < 40 ORDER BY score DESC LIMIT 1;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: scores\nscore \n39 \n");
+        // Reading every record in index order touches more pages than scanning and sorting them,
+        // unless LIMIT stops it after a few.
+        let res = db
+            .parse_user_input("EXPLAIN SELECT id FROM scores WHERE score > 20 ORDER BY score DESC;")
+            .expect("Failed explaining");
+        assert!(res.starts_with("Project (id)\n└── Sort (score desc)\n"));
+        let res = db
+            .parse_user_input(
+                "EXPLAIN SELECT id FROM scores WHERE score > 20 ORDER BY score DESC LIMIT 1;",
+            )
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Limit (1)\n\
+             └── Project (id)\n    \
+             └── Index scan on scores.score in descending order (score > 20) (est. pages: 5)\n\
+             Estimated pages touched: 5\n"
+        );
+        teardown_db();
+    }
+
+    #[test]
+    fn test_prepare() {
+        let mut db = setup_db_with_table();
+        let mut insert = db
+            .prepare("INSERT INTO test (id, name) VALUES (?, ?);")
+            .expect("Failed preparing");
+        assert_eq!(insert.param_count(), 2);
+        let names = ["a, b", "(c)", "it's", "d); DROP TABLE test;"];
+        for (i, name) in names.iter().enumerate() {
+            insert.bind(1, DataType::Int(i as i32 + 1)).unwrap();
+            insert.bind(2, DataType::Text(name.to_string())).unwrap();
+            insert.execute(&mut db).expect("Failed inserting");
+        }
+        assert!(insert.bind(3, DataType::Int(0)).is_err());
+        insert.bind(1, DataType::Text("5".to_string())).unwrap();
+        assert!(insert.execute(&mut db).is_err());
+        let mut select = db
+            .prepare("SELECT name FROM test WHERE id >= $1;")
+            .expect("Failed preparing");
+        assert!(select.execute(&mut db).is_err());
+        select.bind(1, DataType::Int(2)).unwrap();
+        let res = select.execute(&mut db).expect("Failed selecting");
+        assert_eq!(
+            res,
+            "Table: test\nname \n'(c)' \n'it''s' \n'd); DROP TABLE test;' \n"
+        );
+        let mut update = db
+            .prepare("UPDATE test SET name = $2 WHERE id = $1;")
+            .expect("Failed preparing");
+        update.bind(1, DataType::Int(1)).unwrap();
+        update.bind(2, DataType::Text("e, f".to_string())).unwrap();
+        assert_eq!(update.execute(&mut db).unwrap(), "1 row updated.");
+        let records = db
+            .load_table("test")
+            .unwrap()
+            .unwrap()
+            .search(Some("id"), DataType::Int(1), "=")
+            .unwrap();
+        assert_eq!(records[0].values[1], DataType::Text("'e, f'".to_string()));
+        assert!(db
+            .prepare("CREATE VIEW v AS SELECT * FROM test WHERE id = ?;")
+            .is_err());
+        teardown_db();
+    }
 
-    fn setup_db_with_table() -> Database {
-        let mut db = setup_db();
-        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
-            .expect("Failed creating table");
-        db
+    #[test]
+    fn test_quoted_values() {
+        let mut db = setup_db_with_table();
+        db.parse_user_input("INSERT INTO test VALUES (1, 'Hello, (World)');")
+            .expect("Failed inserting");
+        db.parse_user_input("INSERT INTO test VALUES (2, null);")
+            .expect("Failed inserting null");
+        let res = db
+            .parse_user_input("SELECT name FROM test WHERE name = 'Hello, (World)';")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nname \n'Hello, (World)' \n");
+        teardown_db();
     }
 
     #[test]
-    fn test_initialize() {
+    fn test_quoted_identifiers() {
         let mut db = setup_db();
-        assert!(!db.table_table.is_empty());
-        assert!(!db.column_table.is_empty());
-        let tables = db.table_table.search(None, DataType::Null, "=").unwrap();
-        assert_eq!(tables.len(), 2);
+        db.parse_user_input(
+            "CREATE TABLE \"Order Items\" (\"Item Id\" INT PRIMARY KEY, \"select\" TEXT);",
+        )
+        .expect("Failed creating table");
+        db.parse_user_input(
+            "INSERT INTO \"Order Items\" (\"Item Id\", \"select\") VALUES (1, 'a');",
+        )
+        .expect("Failed inserting");
+        db.parse_user_input("UPDATE \"Order Items\" SET \"select\" = 'b' WHERE \"Item Id\" = 1;")
+            .expect("Failed updating");
         assert_eq!(
-            tables[0].values[0],
-            DataType::Text("meta_tables".to_string())
+            db.parse_user_input("SELECT \"select\", \"Item Id\" FROM \"Order Items\";")
+                .unwrap(),
+            "Table: Order Items\nselect Item Id \n'b' 1 \n"
         );
+        let columns = db
+            .column_table
+            .search(
+                Some("table_name"),
+                DataType::Text("Order Items".to_string()),
+                "=",
+            )
+            .unwrap();
+        assert_eq!(columns[0].values[1], DataType::Text("Item Id".to_string()));
+        // Unquoted names are lowercased, so they do not find the table.
+        assert!(db.parse_user_input("SELECT * FROM order_items;").is_err());
+        assert!(db
+            .parse_user_input("SELECT \"item id\" FROM \"Order Items\";")
+            .is_err());
+
+        // Names differing only in case are different tables with different files.
+        db.parse_user_input("CREATE TABLE \"Users\" (id INT);")
+            .expect("Failed creating table");
+        db.parse_user_input("CREATE TABLE users (id INT);")
+            .expect("Failed creating table");
+        db.parse_user_input("INSERT INTO \"Users\" VALUES (1);")
+            .unwrap();
         assert_eq!(
-            tables[1].values[0],
-            DataType::Text("meta_columns".to_string())
+            db.parse_user_input("SELECT * FROM users;").unwrap(),
+            "Table: users\nid \nNo records found."
         );
-        teardown_db();
-    }
 
-    #[test]
-    fn test_create_table() {
-        let mut db = setup_db();
-        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
+        // Names cannot reach outside the data directory.
+        db.parse_user_input("CREATE TABLE \"../Escape\" (\"a.b\" INT UNIQUE);")
             .expect("Failed creating table");
-        let table = db.load_table("test").expect("Table not found");
-        assert_eq!(table.columns.len(), 2);
-        assert_eq!(table.columns[0].name, "id");
-        assert_eq!(table.has_index(), Some("id"));
+        db.parse_user_input("CREATE INDEX \"../Escape\" (\"a.b\");")
+            .expect("Failed creating index");
+        assert!(Path::new(&format!("{}/%2E%2E%2F%45scape.tbl", USER_DIR)).exists());
+        assert!(Path::new(&format!("{}/%2E%2E%2F%45scape.a%2Eb.ndx", USER_DIR)).exists());
+        assert!(db.parse_user_input("CREATE TABLE \"\" (id INT);").is_err());
         teardown_db();
     }
 
     #[test]
-    fn test_drop_table() {
+    fn test_float_range() {
         let mut db = setup_db();
-        db.parse_user_input("CREATE TABLE test (id INT PRIMARY_KEY, name TEXT);")
+        db.parse_user_input("CREATE TABLE test (id INT, v FLOAT);")
             .expect("Failed creating table");
-        let index_path = format!("{}/test.id.ndx", USER_DIR);
-        let idx_file = std::path::Path::new(&index_path);
-        assert!(db.load_table("test").is_some());
-        assert!(idx_file.exists());
-        db.parse_user_input("DROP TABLE test;")
-            .expect("Failed dropping table");
-        assert!(db.load_table("test").is_none());
-        assert!(!idx_file.exists());
+        let err = db
+            .parse_user_input("INSERT INTO test VALUES (1, 1e39);")
+            .expect_err("1e39 is beyond the range of FLOAT");
+        assert_eq!(
+            err.to_string(),
+            "Failed parsing 1e39 into v: Value is out of the range of FLOAT"
+        );
+        db.parse_user_input("INSERT INTO test VALUES (2, -3e38);")
+            .expect("Failed inserting");
+        assert_eq!(
+            select_values(&mut db, "SELECT v FROM test;"),
+            [DataType::Float(-3e38)]
+        );
+        // Literals, inserted values and casts are checked alike.
+        assert!(db
+            .parse_user_input("SELECT CAST('1e39' AS FLOAT) FROM test;")
+            .is_err());
+        assert!(db
+            .parse_user_input("UPDATE test SET v = '-1e39' WHERE id = 2;")
+            .is_err());
         teardown_db();
     }
 
     #[test]
-    fn test_insert_command() {
-        let mut db = setup_db_with_table();
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
-            .expect("Failed inserting 1");
-        db.parse_user_input("INSERT INTO test VALUES (2, 'test2');")
-            .expect("Failed inserting 2");
-        db.parse_user_input("INSERT INTO test VALUES (3, 'test3');")
-            .expect("Failed inserting 3");
-
-        let records = db
-            .load_table("test")
-            .expect("Table not found")
-            .search(None, DataType::Null, "=")
-            .expect("Failed searching");
-        assert_eq!(records.len(), 3);
-        assert_eq!(records[0].values[0], DataType::Int(1));
+    fn test_cast_and_coercion() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT UNIQUE, price DOUBLE, code TEXT);")
+            .expect("Failed creating table");
+        db.parse_user_input("CREATE INDEX test (id);")
+            .expect("Failed creating index");
+        db.parse_user_input("INSERT INTO test VALUES (1, 2.5, '10');")
+            .unwrap();
+        db.parse_user_input("INSERT INTO test VALUES (2, 10, '9');")
+            .unwrap();
+        assert_eq!(
+            db.parse_user_input(
+                "SELECT id, CAST(price AS INT), CAST(code AS INT) FROM test WHERE CAST(code AS INT) > 9;"
+            )
+            .unwrap(),
+            "Table: test\nid cast(price as int) cast(code as int) \n1 2 10 \n"
+        );
+        assert_eq!(
+            db.parse_user_input("SELECT id FROM test WHERE price = 10;")
+                .unwrap(),
+            "Table: test\nid \n2 \n"
+        );
+        assert_eq!(
+            db.parse_user_input("SELECT id FROM test WHERE id < 1.5;")
+                .unwrap(),
+            "Table: test\nid \n1 \n"
+        );
+        // Bound values of a narrower numeric type are stored as the column's type.
+        let mut statement = db.prepare("INSERT INTO test VALUES (?, ?, 'x');").unwrap();
+        statement.bind(1, DataType::SmallInt(3)).unwrap();
+        statement.bind(2, DataType::Int(4)).unwrap();
+        statement.execute(&mut db).unwrap();
+        assert_eq!(
+            db.parse_user_input("SELECT * FROM test WHERE id = 3;")
+                .unwrap(),
+            "Table: test\nid price code \n3 4 'x' \n"
+        );
         teardown_db();
     }
 
     #[test]
-    fn test_nullable() {
+    fn test_scalar_functions() {
         let mut db = setup_db();
         db.parse_user_input("CREATE TABLE test (id INT, name TEXT);")
             .expect("Failed creating table");
-        db.parse_user_input("INSERT INTO test (id) VALUES (1);")
+        db.parse_user_input("INSERT INTO test VALUES (1, ' Alice ');")
             .expect("Failed inserting 1");
-        let mut table = db.load_table("test").expect("Table not found");
-        assert_eq!(table.len(), 1);
-        let records = table
-            .search(None, DataType::Null, "=")
-            .expect("Failed searching");
-        let vals = &records[0].values;
-        assert_eq!(vals[0], DataType::Int(1));
-        assert_eq!(vals[1], DataType::Null);
+        db.parse_user_input("INSERT INTO test VALUES (-2, 'bob');")
+            .expect("Failed inserting 2");
+        let res = db
+            .parse_user_input("SELECT id, UPPER(TRIM(name)) || '!', ABS(id) FROM test;")
+            .expect("Failed selecting");
+        assert_eq!(
+            res,
+            "Table: test\nid concat(upper(trim(name)), '!') abs(id) \n1 'ALICE!' 1 \n-2 'BOB!' 2 \n"
+        );
+        let res = db
+            .parse_user_input("SELECT id FROM test WHERE LENGTH(TRIM(name)) = 3;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nid \n-2 \n");
+        let res = db
+            .parse_user_input("SELECT name FROM test WHERE id = MOD(7, 3);")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nname \n' Alice ' \n");
+        db.parse_user_input(
+            "UPDATE test SET name = LOWER(TRIM(name)) WHERE UPPER(name) = ' ALICE ';",
+        )
+        .expect("Failed updating");
+        db.parse_user_input("UPDATE test SET id = ABS(id) * 1 WHERE id < 0;")
+            .expect_err("Arithmetic is not supported");
+        db.parse_user_input("UPDATE test SET id = ABS(id) WHERE id < 0;")
+            .expect("Failed updating");
+        let res = db
+            .parse_user_input("SELECT * FROM test;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nid name \n1 'alice' \n2 'bob' \n");
+        let err = db
+            .parse_user_input("UPDATE test SET id = UPPER(name);")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "Cannot assign TEXT to column id of type INT.");
+        let err = db
+            .parse_user_input("SELECT LENGTH(id) FROM test;")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "LENGTH expects TEXT as argument 1, got INT.");
+        assert!(db
+            .parse_user_input("SELECT * FROM test WHERE UPPER(name) = id;")
+            .is_err());
+        db.parse_user_input("DELETE FROM test WHERE SUBSTR(name, 1, 1) = 'a';")
+            .expect("Failed deleting");
+        assert_eq!(db.load_table("test").unwrap().unwrap().len().unwrap(), 1);
         teardown_db();
     }
 
     #[test]
-    fn test_unique() {
+    fn test_date_functions() {
         let mut db = setup_db();
-        db.parse_user_input("CREATE TABLE test (id INT UNIQUE, name TEXT);")
+        db.parse_user_input("CREATE TABLE test (id INT, day DATE, at DATETIME);")
             .expect("Failed creating table");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
+        db.parse_user_input("INSERT INTO test VALUES (1, 2024-01-31, 2024-02-01 08:30:00);")
             .expect("Failed inserting 1");
-        let res = db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test2');");
-        assert!(res.is_err());
+        db.parse_user_input("INSERT INTO test VALUES (2, 2024-03-10, 2024-03-01 23:00:00);")
+            .expect("Failed inserting 2");
+        let res = db
+            .parse_user_input(
+                "SELECT id, day + INTERVAL '1 month', EXTRACT(HOUR FROM at) FROM test \
+                 WHERE DATEDIFF(day, at) > 1;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(
+            res,
+            "Table: test\nid day + interval '1 month' extract('hour', at) \n2 2024-04-10 23 \n"
+        );
+        db.parse_user_input("UPDATE test SET at = DATE_TRUNC('day', at) - INTERVAL '1 day';")
+            .expect("Failed updating");
+        db.parse_user_input("UPDATE test SET day = at + INTERVAL '1 hour' WHERE id = 1;")
+            .expect_err("A DATETIME with a time of day does not fit a DATE");
+        let res = db
+            .parse_user_input("SELECT at FROM test WHERE at < day;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nat \n2024-02-29 00:00:00 \n");
+        assert!(db
+            .parse_user_input("SELECT * FROM test WHERE at < NOW();")
+            .unwrap()
+            .contains("2024-01-31"));
         teardown_db();
     }
 
     #[test]
-    fn test_delete_command() {
-        let mut db = setup_db_with_table();
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
-            .expect("Failed inserting 1");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
-            .expect("Failed inserting 2");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
-            .expect("Failed inserting 3");
-        db.parse_user_input("DELETE FROM test WHERE id = 2;")
-            .expect("Failed deleting");
-        let mut table = db.load_table("test").expect("Table not found");
-        let records = table
-            .search(None, DataType::Null, "=")
-            .expect("Failed searching");
-        assert_eq!(records[0].values[0], DataType::Int(1));
-        assert_eq!(records[1].values[0], DataType::Int(3));
-        assert_eq!(table.len(), 2);
+    fn test_case_and_order_by() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT, name TEXT, score DOUBLE);")
+            .expect("Failed creating table");
+        for values in [
+            "1, 'bob', 2.5",
+            "2, 'alice', null",
+            "3, 'carol', 9",
+            "4, 'dave', 2.5",
+        ] {
+            db.parse_user_input(&format!("INSERT INTO test VALUES ({});", values))
+                .expect("Failed inserting");
+        }
+        let res = db
+            .parse_user_input(
+                "SELECT name, CASE WHEN score > 5 THEN 'high' WHEN score > 0 THEN 'low' END \
+                 FROM test ORDER BY score DESC, name;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(
+            res,
+            "Table: test\nname case when score > 5 then 'high' when score > 0 then 'low' end \n\
+             'alice' NULL \n'carol' 'high' \n'bob' 'low' \n'dave' 'low' \n"
+        );
+        let res = db
+            .parse_user_input(
+                "SELECT id FROM test WHERE CASE id WHEN 2 THEN 0 ELSE score END < 3 \
+                 ORDER BY CASE WHEN id = 4 THEN 0 ELSE id END;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nid \n4 \n1 \n2 \n");
+        db.parse_user_input("UPDATE test SET score = CASE WHEN id > 2 THEN id ELSE 0.5 END;")
+            .expect("Failed updating");
+        let res = db
+            .parse_user_input("SELECT score FROM test ORDER BY id DESC;")
+            .expect("Failed selecting");
+        assert_eq!(res, "Table: test\nscore \n4 \n3 \n0.5 \n0.5 \n");
+        db.parse_user_input("UPDATE test SET id = CASE WHEN id > 2 THEN 'x' END;")
+            .expect_err("TEXT does not fit INT");
+        let res = db
+            .parse_user_input("EXPLAIN SELECT id FROM test ORDER BY name DESC;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (id)\n└── Sort (name desc)\n    └── Full scan on test (est. pages: 1)\n\
+             Estimated pages touched: 1\n"
+        );
         teardown_db();
     }
 
     #[test]
-    fn test_update_command() {
-        let mut db = setup_db_with_table();
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
-            .expect("Failed inserting 1");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
-            .expect("Failed inserting 2");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
-            .expect("Failed inserting 3");
-        let mut table = db.load_table("test").expect("Table not found");
-        assert_eq!(table.len(), 3);
-        let records = table
-            .search(Some("id"), DataType::Int(2), "=")
-            .expect("Failed searching");
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].values[1], DataType::Text("'test2'".to_string()));
-        db.parse_user_input("UPDATE test SET name = 'test4' WHERE id = 2;")
-            .expect("Failed updating");
-        let records = table
-            .search(Some("id"), DataType::Int(2), "=")
-            .expect("Failed searching");
-        assert_eq!(records.len(), 1);
-        assert_eq!(records[0].values[1], DataType::Text("'test4'".to_string()));
+    fn test_set_operations() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE a (id INT, name TEXT);")
+            .expect("Failed creating a");
+        db.parse_user_input("CREATE TABLE b (id BIGINT, name TEXT, score DOUBLE);")
+            .expect("Failed creating b");
+        for values in ["1, 'x'", "2, 'y'", "2, 'y'", "3, null"] {
+            db.parse_user_input(&format!("INSERT INTO a VALUES ({});", values))
+                .expect("Failed inserting into a");
+        }
+        for values in ["2, 'y', 1.5", "3, null, 2", "4, 'z', 2.5"] {
+            db.parse_user_input(&format!("INSERT INTO b VALUES ({});", values))
+                .expect("Failed inserting into b");
+        }
+        let select = |db: &mut Database, sql: &str| {
+            let res = db.parse_user_input(sql).expect("Failed selecting");
+            res.lines()
+                .skip(2)
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        assert_eq!(
+            select(
+                &mut db,
+                "SELECT * FROM a UNION SELECT id, name FROM b ORDER BY id DESC;"
+            ),
+            "4 'z' | 3 NULL | 2 'y' | 1 'x'"
+        );
+        assert_eq!(
+            select(
+                &mut db,
+                "SELECT * FROM a UNION ALL SELECT id, name FROM b WHERE id = 4;"
+            ),
+            "1 'x' | 2 'y' | 2 'y' | 3 NULL | 4 'z'"
+        );
+        assert_eq!(
+            select(&mut db, "SELECT * FROM a INTERSECT SELECT id, name FROM b;"),
+            "2 'y' | 3 NULL"
+        );
+        assert_eq!(
+            select(
+                &mut db,
+                "SELECT * FROM a EXCEPT ALL SELECT id, name FROM b;"
+            ),
+            "1 'x' | 2 'y'"
+        );
+        assert_eq!(
+            select(&mut db, "SELECT id FROM a EXCEPT SELECT id FROM b;"),
+            "1"
+        );
+        // NaN rows are duplicates of each other.
+        db.parse_user_input("CREATE TABLE f (x DOUBLE);")
+            .expect("Failed creating f");
+        for value in ["nan", "nan", "-0", "0", "1"] {
+            db.parse_user_input(&format!("INSERT INTO f VALUES ({});", value))
+                .expect("Failed inserting into f");
+        }
+        assert_eq!(
+            select(&mut db, "SELECT x FROM f UNION SELECT x FROM f;"),
+            "NaN | -0 | 1"
+        );
+        assert_eq!(
+            select(
+                &mut db,
+                "SELECT x FROM f INTERSECT SELECT x FROM f WHERE x <> 1;"
+            ),
+            "NaN | -0"
+        );
+        assert_eq!(
+            select(
+                &mut db,
+                "SELECT x FROM f EXCEPT ALL SELECT x FROM f WHERE x <> 1;"
+            ),
+            "1"
+        );
+        // Columns take the widest type of each SELECT.
+        assert_eq!(
+            select(
+                &mut db,
+                "SELECT id FROM a WHERE id = 1 UNION SELECT score FROM b ORDER BY id;"
+            ),
+            "1 | 1.5 | 2 | 2.5"
+        );
+        let err = db
+            .parse_user_input("SELECT * FROM a UNION SELECT * FROM b;")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Each SELECT in a UNION must have the same number of columns, got 2 and 3."
+        );
+        let err = db
+            .parse_user_input("SELECT id FROM a INTERSECT SELECT name FROM b;")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Column id has incompatible types INT and TEXT in INTERSECT."
+        );
+        assert!(db
+            .parse_user_input("SELECT id FROM a UNION SELECT id FROM b ORDER BY name;")
+            .is_err());
+        db.parse_user_input("CREATE VIEW ids AS SELECT id FROM a UNION SELECT id FROM b;")
+            .expect("Failed creating view");
+        assert_eq!(
+            select(&mut db, "SELECT * FROM ids WHERE id > 1 ORDER BY id DESC;"),
+            "4 | 3 | 2"
+        );
+        let res = db
+            .parse_user_input("EXPLAIN SELECT id FROM a UNION ALL SELECT id FROM b ORDER BY id;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Sort (id)\n\
+             └── UNION ALL\n    \
+             ├── Project (id)\n    \
+             │   └── Full scan on a (est. pages: 1)\n    \
+             └── Project (id)\n        \
+             └── Full scan on b (est. pages: 1)\n\
+             Estimated pages touched: 2\n"
+        );
         teardown_db();
     }
 
     #[test]
-    fn test_select_command() {
-        let mut db = setup_db_with_table();
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (1, 'test');")
-            .expect("Failed inserting 1");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (2, 'test2');")
-            .expect("Failed inserting 2");
-        db.parse_user_input("INSERT INTO test (id, name) VALUES (3, 'test3');")
-            .expect("Failed inserting 3");
+    fn test_common_tables() {
+        let mut db = setup_db();
+        db.parse_user_input("CREATE TABLE test (id INT, name TEXT, day DATE);")
+            .expect("Failed creating table");
+        for values in ["1, 'a', 2024-01-30", "2, 'b', 2024-02-27", "3, 'c', null"] {
+            db.parse_user_input(&format!("INSERT INTO test VALUES ({});", values))
+                .expect("Failed inserting");
+        }
+        let select = |db: &mut Database, sql: &str| {
+            let res = db.parse_user_input(sql).expect("Failed selecting");
+            res.lines()
+                .skip(1)
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        assert_eq!(
+            select(
+                &mut db,
+                "WITH named AS (SELECT id, UPPER(name) FROM test WHERE id < 3), \
+                 test (n, label) AS (SELECT * FROM named WHERE id > 1) \
+                 SELECT label FROM test;"
+            ),
+            "label | 'B'"
+        );
+        // Each step adds a day to the dates added by the previous one.
+        assert_eq!(
+            select(
+                &mut db,
+                "WITH RECURSIVE days (d) AS (SELECT day FROM test WHERE id < 3 \
+                 UNION SELECT d + INTERVAL '1 day' FROM days WHERE d < '2024-02-01') \
+                 SELECT * FROM days WHERE d > '2024-01-31' ORDER BY d;"
+            ),
+            "d | 2024-02-01 | 2024-02-27"
+        );
+        // The step may select itself on either side of a join.
+        db.parse_user_input("CREATE TABLE emp (id INT, parent INT);")
+            .expect("Failed creating table");
+        for (id, parent) in [(1, "NULL"), (2, "1"), (3, "2"), (4, "9"), (5, "3")] {
+            db.parse_user_input(&format!("INSERT INTO emp VALUES ({}, {});", id, parent))
+                .expect("Failed inserting");
+        }
+        for join in [
+            "emp JOIN sub ON emp.parent = sub.id",
+            "sub JOIN emp ON emp.parent = sub.id",
+        ] {
+            assert_eq!(
+                select(
+                    &mut db,
+                    &format!(
+                        "WITH RECURSIVE sub AS (SELECT id, parent FROM emp WHERE id = 1 \
+                         UNION ALL SELECT emp.id, emp.parent FROM {}) \
+                         SELECT id FROM sub ORDER BY id;",
+                        join
+                    )
+                ),
+                "id | 1 | 2 | 3 | 5",
+                "{}",
+                join
+            );
+        }
+        let err = db
+            .parse_user_input(
+                "WITH RECURSIVE t AS (SELECT id FROM test UNION ALL SELECT id FROM t) \
+                 SELECT * FROM t;",
+            )
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Recursive common table t did not finish after 10000 steps."
+        );
+        let err = db
+            .parse_user_input("WITH t (a, b) AS (SELECT id FROM test) SELECT * FROM t;")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "Common table t selects 1 columns but names 2.");
+        // Without RECURSIVE, a common table named like a table selects from the table.
+        assert_eq!(
+            select(
+                &mut db,
+                "WITH test AS (SELECT id FROM test) SELECT * FROM test;"
+            ),
+            "id | 1 | 2 | 3"
+        );
         let res = db
-            .parse_user_input("SELECT * FROM test;")
-            .expect("Failed selecting");
-        let res_str = "Table: test\nid name \n1 'test' \n2 'test2' \n3 'test3' \n";
-        assert_eq!(res, res_str);
+            .parse_user_input(
+                "EXPLAIN WITH RECURSIVE t (n) AS (SELECT id FROM test UNION SELECT n FROM t \
+                 WHERE n = 1) SELECT n FROM t WHERE n = 2;",
+            )
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (n)\n\
+             └── Filter (n = 2)\n    \
+             └── Common table t\n        \
+             └── RECURSIVE UNION\n            \
+             ├── Project (id)\n            \
+             │   └── Full scan on test (est. pages: 1)\n            \
+             └── Project (n)\n                \
+             └── Filter (n = 1)\n                    \
+             └── Work table t\n\
+             Estimated pages touched: 1\n"
+        );
         teardown_db();
     }
 
     #[test]
-    fn test_select_from_system() {
+    fn test_window_functions() {
         let mut db = setup_db();
-        let res = db.parse_user_input("SELECT * FROM meta_tables;");
-        assert!(res.is_ok());
-        assert_ne!(res.unwrap(), "No records found.");
-        let res = db.parse_user_input("SELECT * FROM meta_columns;");
-        assert!(res.is_ok());
-        assert_ne!(res.unwrap(), "No records found.");
+        db.parse_user_input("CREATE TABLE sales (id INT, region TEXT, amount INT);")
+            .expect("Failed creating table");
+        for values in [
+            "1, 'east', 10",
+            "2, 'west', 5",
+            "3, 'east', 20",
+            "4, 'east', 10",
+        ] {
+            db.parse_user_input(&format!("INSERT INTO sales VALUES ({});", values))
+                .expect("Failed inserting");
+        }
+        let res = db
+            .parse_user_input(
+                "SELECT id, RANK() OVER (PARTITION BY region ORDER BY amount DESC), \
+                 SUM(amount) OVER (PARTITION BY region ORDER BY id), \
+                 LAG(amount, 1, 0) OVER (ORDER BY id) FROM sales WHERE id > 1 ORDER BY id;",
+            )
+            .expect("Failed selecting");
+        assert_eq!(
+            res,
+            "Table: sales\nid rank() over (partition by region order by amount desc) \
+             sum(amount) over (partition by region order by id) \
+             lag(amount, 1, 0) over (order by id) \n\
+             2 1 5 0 \n3 1 20 5 \n4 2 30 20 \n"
+        );
+        let err = db
+            .parse_user_input("SELECT id FROM sales WHERE ROW_NUMBER() OVER () = 1;")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Window function ROW_NUMBER is only allowed as a selected column."
+        );
+        let err = db
+            .parse_user_input("SELECT SUM(amount) FROM sales;")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "SUM requires an OVER clause.");
+        let res = db
+            .parse_user_input("EXPLAIN SELECT COUNT(*) OVER (PARTITION BY region) FROM sales;")
+            .expect("Failed explaining");
+        assert_eq!(
+            res,
+            "Project (count(*) over (partition by region))\n\
+             └── Window (count(*) over (partition by region))\n    \
+             └── Full scan on sales (est. pages: 1)\n\
+             Estimated pages touched: 1\n"
+        );
         teardown_db();
     }
 
@@ -888,8 +5278,8 @@ mod tests {
             "INSERT INTO test VALUES (1, 2021-01-01, 12:00:00, 2021-01-01 12:00:00, 2021);",
         )
         .expect("Failed inserting");
-        let mut table = db.load_table("test").expect("Table not found");
-        assert_eq!(table.len(), 1);
+        let mut table = db.load_table("test").unwrap().expect("Table not found");
+        assert_eq!(table.len().unwrap(), 1);
         let records = table
             .search(None, DataType::Null, "=")
             .expect("Failed searching");
@@ -941,7 +5331,7 @@ mod tests {
         let out = db
             .read_file_input(file_path)
             .expect("Failed reading file input");
-        let mut table = db.load_table("test").expect("Table not found");
+        let mut table = db.load_table("test").unwrap().expect("Table not found");
         assert_eq!(table.name, "test");
         let columns = &table.columns;
         assert_eq!(columns.len(), 2);
@@ -1,116 +1,143 @@
 use crate::constants::{PageType, PAGE_SIZE};
+use crate::error::{DbError, Result};
 use crate::read_write_types::ReadWriteTypes;
 use std::io::{Read, Seek, SeekFrom, Write};
 
+/// Returns the path of the file storing the records of `table_name` in `dir`.
+pub fn table_file_path(dir: &str, table_name: &str) -> String {
+    format!("{}/{}.tbl", dir, file_name(table_name))
+}
+
+/// Returns the path of the file storing the index on `column_name` of `table_name` in `dir`.
+pub fn index_file_path(dir: &str, table_name: &str, column_name: &str) -> String {
+    format!(
+        "{}/{}.{}.ndx",
+        dir,
+        file_name(table_name),
+        file_name(column_name)
+    )
+}
+
+/// Returns `name` as written in file names. Lowercase letters, digits, `_` and `-` are kept and
+/// every other byte is written as `%` and two hex digits, so that names cannot leave the data
+/// directory, are not split at the `.` before the column name of an index file and do not clash
+/// on file systems that ignore case.
+fn file_name(name: &str) -> String {
+    let mut file_name = String::new();
+    for byte in name.bytes() {
+        match byte {
+            b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' => file_name.push(byte as char),
+            _ => file_name.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    file_name
+}
+
 pub trait DatabaseFile: Read + Write + Seek + ReadWriteTypes {
-    fn set_len(&self, length: u64);
-    fn len(&self) -> u64;
-    fn is_empty(&self) -> bool {
-        self.len() == 0
+    fn set_len(&self, length: u64) -> Result<()>;
+    fn len(&self) -> Result<u64>;
+    fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
     }
 
-    fn seek_to_page(&mut self, page: u32) -> u64 {
-        self.seek(SeekFrom::Start(page as u64 * PAGE_SIZE))
-            .expect("Failed to seek to page")
+    fn seek_to_page(&mut self, page: u32) -> Result<u64> {
+        Ok(self.seek(SeekFrom::Start(page as u64 * PAGE_SIZE))?)
     }
 
-    fn seek_to_page_offset(&mut self, page: u32, offset: u16) -> u64 {
+    fn seek_to_page_offset(&mut self, page: u32, offset: u16) -> Result<u64> {
         if offset as u64 >= PAGE_SIZE {
-            panic!(
+            return Err(DbError::Corruption(format!(
                 "Offset {:02X} larger than page size ({:02X})",
                 offset, PAGE_SIZE
-            );
+            )));
         }
-        if page as u64 >= self.len() / PAGE_SIZE {
-            panic!(
-                "Page {:02X} out of bounds ({:02X})",
+        if page as u64 >= self.num_pages()? {
+            return Err(DbError::Corruption(format!(
+                "Page {} out of bounds ({} pages)",
                 page,
-                self.len() / PAGE_SIZE - 1
-            );
+                self.num_pages()?
+            )));
         }
-        self.seek(SeekFrom::Start(page as u64 * PAGE_SIZE + offset as u64))
-            .expect("Failed to seek to page offset")
+        Ok(self.seek(SeekFrom::Start(page as u64 * PAGE_SIZE + offset as u64))?)
     }
 
-    fn skip_bytes(&mut self, bytes: i64) -> u64 {
-        self.seek(SeekFrom::Current(bytes))
-            .expect("Failed to skip bytes")
+    fn skip_bytes(&mut self, bytes: i64) -> Result<u64> {
+        Ok(self.seek(SeekFrom::Current(bytes))?)
     }
 
     /// Creates a new page in the database file.
     ///
     /// * `parent_page` - The page number of the parent page.
     /// * `page_type` - The type of page to create.
-    fn create_page(&mut self, parent_page: u32, page_type: PageType) -> u32 {
-        let mut last_page = self.len() / PAGE_SIZE;
-        for p in 0..self.len() / PAGE_SIZE {
-            if self.get_page_type(p as u32) == PageType::Empty {
+    fn create_page(&mut self, parent_page: u32, page_type: PageType) -> Result<u32> {
+        let mut last_page = self.num_pages()?;
+        for p in 0..self.num_pages()? {
+            if self.get_page_type(p as u32)? == PageType::Empty {
                 last_page = p;
             }
         }
-        self.set_len((last_page + 1) * PAGE_SIZE);
-        self.seek(SeekFrom::Start(last_page * PAGE_SIZE))
-            .expect("Failed to seek to new page");
-        self.write_u8(page_type as u8); // page type 0x00
-        self.write_u8(0x00); // unused 0x01
-        self.write_u16(0x00); // number of cells 0x02-0x03
-        self.write_u16(PAGE_SIZE as u16); // start of content area, set to end of page 0x04-0x05
-        self.write_u32(0xFFFFFFFF); // rightmost child page if interior, right sibling if leaf 0x06-0x09
-        self.write_u32(parent_page); // parent page 0x0A-0x0D
-        self.write_u16(0x00); // unused 0x0E-0x0F
-        last_page as u32
-    }
-
-    fn get_content_start(&mut self, page: u32) -> u16 {
-        self.seek_to_page_offset(page, 0x04);
+        self.set_len((last_page + 1) * PAGE_SIZE)?;
+        self.seek(SeekFrom::Start(last_page * PAGE_SIZE))?;
+        self.write_u8(page_type as u8)?; // page type 0x00
+        self.write_u8(0x00)?; // unused 0x01
+        self.write_u16(0x00)?; // number of cells 0x02-0x03
+        self.write_u16(PAGE_SIZE as u16)?; // start of content area, set to end of page 0x04-0x05
+        self.write_u32(0xFFFFFFFF)?; // rightmost child page if interior, right sibling if leaf 0x06-0x09
+        self.write_u32(parent_page)?; // parent page 0x0A-0x0D
+        self.write_u16(0x00)?; // unused 0x0E-0x0F
+        Ok(last_page as u32)
+    }
+
+    fn get_content_start(&mut self, page: u32) -> Result<u16> {
+        self.seek_to_page_offset(page, 0x04)?;
         self.read_u16()
     }
 
-    fn get_parent_page(&mut self, page: u32) -> u32 {
-        self.seek_to_page_offset(page, 0x0A);
+    fn get_parent_page(&mut self, page: u32) -> Result<u32> {
+        self.seek_to_page_offset(page, 0x0A)?;
         self.read_u32()
     }
 
-    fn get_root_page(&mut self) -> u32 {
+    fn get_root_page(&mut self) -> Result<u32> {
         let mut current_page = 0;
-        while self.get_parent_page(current_page) != 0xFFFFFFFF {
-            current_page = self.get_parent_page(current_page);
+        while self.get_parent_page(current_page)? != 0xFFFFFFFF {
+            current_page = self.get_parent_page(current_page)?;
         }
-        current_page
+        Ok(current_page)
     }
 
-    fn set_content_start(&mut self, page: u32, cell_size: i32) -> u16 {
-        let old_content_start = self.get_content_start(page) as i32;
+    fn set_content_start(&mut self, page: u32, cell_size: i32) -> Result<u16> {
+        let old_content_start = self.get_content_start(page)? as i32;
         let new_content_start = (old_content_start - cell_size) as u16;
-        self.seek_to_page_offset(page, 0x04);
-        self.write_u16(new_content_start);
-        new_content_start
+        self.seek_to_page_offset(page, 0x04)?;
+        self.write_u16(new_content_start)?;
+        Ok(new_content_start)
     }
 
-    fn get_num_cells(&mut self, page: u32) -> u16 {
-        self.seek_to_page_offset(page, 0x02);
+    fn get_num_cells(&mut self, page: u32) -> Result<u16> {
+        self.seek_to_page_offset(page, 0x02)?;
         self.read_u16()
     }
 
-    fn increment_num_cells(&mut self, page: u32) -> u16 {
-        let old_num_cells = self.get_num_cells(page);
+    fn increment_num_cells(&mut self, page: u32) -> Result<u16> {
+        let old_num_cells = self.get_num_cells(page)?;
         let num_cells = old_num_cells + 1;
-        self.seek_to_page_offset(page, 0x02);
-        self.write_u16(num_cells);
-        num_cells
+        self.seek_to_page_offset(page, 0x02)?;
+        self.write_u16(num_cells)?;
+        Ok(num_cells)
     }
 
     /// Returns whether the page should be split after adding a new cell.
-    fn should_split(&mut self, page: u32, cell_size: i32) -> bool {
-        let num_cells = self.get_num_cells(page);
+    fn should_split(&mut self, page: u32, cell_size: i32) -> Result<bool> {
+        let num_cells = self.get_num_cells(page)?;
         let header_size = 0x10 + 2 * (num_cells + 1);
-        let content_start = self.get_content_start(page);
-        (content_start as i32 - cell_size) < (header_size as i32)
+        let content_start = self.get_content_start(page)?;
+        Ok((content_start as i32 - cell_size) < (header_size as i32))
     }
 
-    fn get_cell_offset(&mut self, page: u32, cell_num: u16) -> u16 {
+    fn get_cell_offset(&mut self, page: u32, cell_num: u16) -> Result<u16> {
         let offset = 0x10 + 2 * cell_num;
-        self.seek_to_page_offset(page, offset);
+        self.seek_to_page_offset(page, offset)?;
         self.read_u16()
     }
 
@@ -121,26 +148,47 @@ pub trait DatabaseFile: Read + Write + Seek + ReadWriteTypes {
     ///    * content start: `u16`
     ///    * rightmost child/neighboring page: `u32`
     ///    * parent page: `u32`
-    fn get_page_info(&mut self, page: u32) -> (PageType, u16, u16, u32, u32) {
-        self.seek_to_page(page);
-        let page_type = self.read_u8().into();
-        self.read_u8(); // unused
-        let num_cells = self.read_u16();
-        let content_start = self.read_u16();
-        let rightmost_child = self.read_u32();
-        let parent_page = self.read_u32();
-        (
+    fn get_page_info(&mut self, page: u32) -> Result<(PageType, u16, u16, u32, u32)> {
+        self.seek_to_page(page)?;
+        let page_type = self.read_u8()?.into();
+        self.read_u8()?; // unused
+        let num_cells = self.read_u16()?;
+        let content_start = self.read_u16()?;
+        let rightmost_child = self.read_u32()?;
+        let parent_page = self.read_u32()?;
+        Ok((
             page_type,
             num_cells,
             content_start,
             rightmost_child,
             parent_page,
-        )
+        ))
+    }
+
+    /// Returns the number of pages on the path from the root page to the leftmost leaf page.
+    fn depth(&mut self) -> Result<u64> {
+        let mut page = self.get_root_page()?;
+        let mut depth = 1;
+        while matches!(
+            self.get_page_type(page)?,
+            PageType::TableInterior | PageType::IndexInterior
+        ) {
+            // Interior cells of both file types start with the child page pointer
+            let offset = self.get_cell_offset(page, 0)?;
+            self.seek_to_page_offset(page, offset)?;
+            page = self.read_u32()?;
+            depth += 1;
+        }
+        Ok(depth)
+    }
+
+    fn num_pages(&self) -> Result<u64> {
+        Ok(self.len()? / PAGE_SIZE)
     }
 
-    fn get_page_type(&mut self, page: u32) -> PageType {
-        self.seek_to_page(page);
-        self.read_u8().into()
+    fn get_page_type(&mut self, page: u32) -> Result<PageType> {
+        self.seek_to_page(page)?;
+        Ok(self.read_u8()?.into())
     }
 
     /// Shifts all cells after `preceding_cell` on `page` towards the front of the page by `shift`
@@ -159,64 +207,65 @@ pub trait DatabaseFile: Read + Write + Seek + ReadWriteTypes {
         preceding_cell: i32,
         shift: i32,
         new_record_num: i32,
-    ) -> u32 {
-        if self.should_split(page, shift) {
-            panic!("Shifting more than page can hold");
+    ) -> Result<u32> {
+        if self.should_split(page, shift)? {
+            return Err(DbError::Corruption(format!(
+                "Shifting {} bytes on page {} is more than the page can hold",
+                shift, page
+            )));
         }
-        if preceding_cell == self.get_num_cells(page) as i32 - 1 {
-            return self.set_content_start(page, shift) as u32;
+        if preceding_cell == self.get_num_cells(page)? as i32 - 1 {
+            return Ok(self.set_content_start(page, shift)? as u32);
         }
 
-        let old_content_start = self.get_content_start(page);
-        let content_offset = self.set_content_start(page, shift);
+        let old_content_start = self.get_content_start(page)?;
+        let content_offset = self.set_content_start(page, shift)?;
 
         if content_offset == PAGE_SIZE as u16 {
-            return (PAGE_SIZE as i32 - shift) as u32;
+            return Ok((PAGE_SIZE as i32 - shift) as u32);
         }
 
         let start_offset = if preceding_cell >= 0 {
-            self.get_cell_offset(page, preceding_cell as u16)
+            self.get_cell_offset(page, preceding_cell as u16)?
         } else {
             PAGE_SIZE as u16
         };
-        self.seek_to_page_offset(page, old_content_start);
+        self.seek_to_page_offset(page, old_content_start)?;
         let mut bytes_to_shift = (start_offset - old_content_start) as i32;
         if shift < 0 {
             bytes_to_shift += shift;
         }
         let mut shifted_bytes: Vec<u8> = vec![0; bytes_to_shift as usize];
-        self.read_exact(&mut shifted_bytes)
-            .expect("Failed to read shifted bytes");
+        self.read_exact(&mut shifted_bytes)?;
 
-        self.seek_to_page_offset(page, content_offset);
-        self.write_all(&shifted_bytes)
-            .expect("Failed to write shifted bytes");
+        self.seek_to_page_offset(page, content_offset)?;
+        self.write_all(&shifted_bytes)?;
 
-        let num_cells = self.get_num_cells(page);
+        let num_cells = self.get_num_cells(page)?;
         let num_shifted_cells = num_cells as i32 - preceding_cell - 1;
 
         let offset = 0x10 + 2 * (preceding_cell + 1);
-        self.seek_to_page_offset(page, offset as u16);
+        self.seek_to_page_offset(page, offset as u16)?;
 
         let mut cell_offsets: Vec<u8> = vec![0; 2 * (num_shifted_cells as usize)];
-        self.read_exact(&mut cell_offsets)
-            .expect("Failed to read cell offsets");
+        self.read_exact(&mut cell_offsets)?;
 
         let offset = 0x10 + 2 * (preceding_cell + new_record_num + 1);
-        self.seek_to_page_offset(page, offset as u16);
+        self.seek_to_page_offset(page, offset as u16)?;
 
         for i in (0..cell_offsets.len()).step_by(2) {
             let old_offset = u16::from_le_bytes([cell_offsets[i], cell_offsets[i + 1]]);
-            self.write_u16((old_offset as i32 - shift) as u16);
+            self.write_u16((old_offset as i32 - shift) as u16)?;
         }
 
         let final_offset = start_offset as i32 - shift;
-        final_offset as u32
+        Ok(final_offset as u32)
     }
 
-    fn delete_page(&mut self, page: u32) {
-        self.seek_to_page(page);
+    fn delete_page(&mut self, page: u32) -> Result<()> {
+        self.seek_to_page(page)?;
         let empty_page = vec![0; PAGE_SIZE as usize];
-        self.write_all(&empty_page).expect("Failed to delete page");
+        self.write_all(&empty_page)?;
+        Ok(())
     }
 }
@@ -0,0 +1,371 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike, Utc};
+
+use crate::constants::DataType;
+use crate::error::{DbError, Result};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A length of time added to or subtracted from dates and times. Months are kept apart from
+/// seconds since their length depends on the date they are added to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Interval {
+    pub months: i32,
+    pub seconds: i64,
+}
+
+impl Interval {
+    /// Parses intervals written as `<number> <unit>` pairs, such as `3 days` or
+    /// `1 year 2 months`.
+    pub fn parse(text: &str) -> Result<Interval> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() || !words.len().is_multiple_of(2) {
+            return Err(DbError::Type(format!(
+                "Invalid interval '{}'. Expected '<number> <unit> ...'.",
+                text
+            )));
+        }
+        let out_of_range = || DbError::Type(format!("Interval '{}' is out of range.", text));
+        let mut months: i64 = 0;
+        let mut seconds: i64 = 0;
+        for pair in words.chunks(2) {
+            let n: i64 = pair[0].parse().map_err(|_| {
+                DbError::Type(format!(
+                    "Invalid interval '{}'. {} is not a whole number.",
+                    text, pair[0]
+                ))
+            })?;
+            let unit = pair[1].to_lowercase();
+            let (to_months, to_seconds) = match unit.trim_end_matches('s') {
+                "second" => (0, 1),
+                "minute" => (0, 60),
+                "hour" => (0, 3600),
+                "day" => (0, SECONDS_PER_DAY),
+                "week" => (0, 7 * SECONDS_PER_DAY),
+                "month" => (1, 0),
+                "year" => (12, 0),
+                _ => return Err(DbError::Type(format!("Invalid interval unit {}.", pair[1]))),
+            };
+            months = n
+                .checked_mul(to_months)
+                .and_then(|m| months.checked_add(m))
+                .ok_or_else(out_of_range)?;
+            seconds = n
+                .checked_mul(to_seconds)
+                .and_then(|s| seconds.checked_add(s))
+                .ok_or_else(out_of_range)?;
+        }
+        Ok(Interval {
+            months: months.try_into().map_err(|_| out_of_range())?,
+            seconds,
+        })
+    }
+
+    pub fn negate(&self) -> Interval {
+        Interval {
+            months: -self.months,
+            seconds: -self.seconds,
+        }
+    }
+}
+
+/// Returns the type of a value of type `data_type` shifted by `interval`. Dates shifted by
+/// anything other than whole days become date-times.
+pub fn shift_type(data_type: &DataType, interval: &Interval) -> Result<DataType> {
+    match data_type {
+        DataType::Null | DataType::DateTime(_) => Ok(data_type.clone()),
+        DataType::Date(_) if interval.seconds % SECONDS_PER_DAY == 0 => Ok(data_type.clone()),
+        DataType::Date(_) => Ok(DataType::DateTime(0)),
+        DataType::Time(_) if interval.months == 0 => Ok(data_type.clone()),
+        DataType::Time(_) => Err(DbError::Type(
+            "Cannot add months or years to TIME.".to_string(),
+        )),
+        v => Err(DbError::Type(format!(
+            "Cannot add an interval to {}.",
+            v.type_name()
+        ))),
+    }
+}
+
+/// Shifts a date, date-time or time by `interval`. Times wrap around midnight.
+pub fn shift(value: &DataType, interval: &Interval) -> Result<DataType> {
+    let data_type = shift_type(value, interval)?;
+    match value {
+        DataType::Null => Ok(DataType::Null),
+        DataType::Date(seconds) | DataType::DateTime(seconds) => {
+            let datetime = to_datetime(*seconds)?;
+            let months = Months::new(interval.months.unsigned_abs());
+            let shifted = if interval.months >= 0 {
+                datetime.checked_add_months(months)
+            } else {
+                datetime.checked_sub_months(months)
+            }
+            .and_then(|d| d.checked_add_signed(Duration::try_seconds(interval.seconds)?))
+            .ok_or_else(|| {
+                DbError::Type(format!(
+                    "{} shifted by the interval is out of range.",
+                    value
+                ))
+            })?;
+            let seconds = shifted.and_utc().timestamp();
+            Ok(match data_type {
+                DataType::Date(_) => DataType::Date(seconds),
+                _ => DataType::DateTime(seconds),
+            })
+        }
+        DataType::Time(seconds) => Ok(DataType::Time(
+            (*seconds as i64 + interval.seconds).rem_euclid(SECONDS_PER_DAY) as i32,
+        )),
+        _ => unreachable!("Checked by shift_type"),
+    }
+}
+
+/// Returns the type of `EXTRACT(field FROM <value of data_type>)`.
+pub fn extract_type(field: &str, data_type: &DataType) -> Result<DataType> {
+    let fields: &[&str] = match data_type {
+        DataType::Null | DataType::Date(_) | DataType::DateTime(_) => &[
+            "year", "month", "day", "hour", "minute", "second", "dow", "doy", "epoch",
+        ],
+        DataType::Time(_) => &["hour", "minute", "second", "epoch"],
+        DataType::Year(_) => &["year"],
+        v => {
+            return Err(DbError::Type(format!(
+                "EXTRACT expects a DATE, DATETIME, TIME or YEAR, got {}.",
+                v.type_name()
+            )))
+        }
+    };
+    if !fields.contains(&field) {
+        return Err(DbError::Type(format!(
+            "Cannot extract {} from {}. Expected one of: {}.",
+            field,
+            data_type.type_name(),
+            fields.join(", ")
+        )));
+    }
+    Ok(match field {
+        "epoch" => DataType::BigInt(0),
+        _ => DataType::Int(0),
+    })
+}
+
+/// Returns `field` of a date, date-time, time or year. `dow` counts from Sunday as 0, and `epoch`
+/// is the number of seconds since 1970-01-01, or since midnight for times.
+pub fn extract(field: &str, value: &DataType) -> Result<DataType> {
+    let datetime = match value {
+        DataType::Date(seconds) | DataType::DateTime(seconds) => to_datetime(*seconds)?,
+        DataType::Time(seconds) => {
+            let seconds = *seconds as i64;
+            return Ok(match field {
+                "hour" => DataType::Int((seconds / 3600) as i32),
+                "minute" => DataType::Int((seconds / 60 % 60) as i32),
+                "second" => DataType::Int((seconds % 60) as i32),
+                _ => DataType::BigInt(seconds),
+            });
+        }
+        DataType::Year(year) => return Ok(DataType::Int(2000 + *year as i32)),
+        v => {
+            return Err(DbError::Type(format!(
+                "EXTRACT expects a DATE, DATETIME, TIME or YEAR, got {}.",
+                v.type_name()
+            )))
+        }
+    };
+    let field = match field {
+        "year" => datetime.year(),
+        "month" => datetime.month() as i32,
+        "day" => datetime.day() as i32,
+        "hour" => datetime.hour() as i32,
+        "minute" => datetime.minute() as i32,
+        "second" => datetime.second() as i32,
+        "dow" => datetime.weekday().num_days_from_sunday() as i32,
+        "doy" => datetime.ordinal() as i32,
+        _ => return Ok(DataType::BigInt(datetime.and_utc().timestamp())),
+    };
+    Ok(DataType::Int(field))
+}
+
+/// Returns the type of `DATE_TRUNC(unit, <value of data_type>)`.
+pub fn trunc_type(unit: &str, data_type: &DataType) -> Result<DataType> {
+    let units = ["year", "month", "week", "day", "hour", "minute", "second"];
+    if !units.contains(&unit) {
+        return Err(DbError::Type(format!(
+            "Cannot truncate to {}. Expected one of: {}.",
+            unit,
+            units.join(", ")
+        )));
+    }
+    match data_type {
+        DataType::Null | DataType::Date(_) | DataType::DateTime(_) => Ok(data_type.clone()),
+        v => Err(DbError::Type(format!(
+            "DATE_TRUNC expects a DATE or DATETIME, got {}.",
+            v.type_name()
+        ))),
+    }
+}
+
+/// Truncates a date or date-time to the start of its `unit`. Weeks start on Monday.
+pub fn trunc(unit: &str, value: &DataType) -> Result<DataType> {
+    let seconds = match value {
+        DataType::Date(seconds) | DataType::DateTime(seconds) => *seconds,
+        v => {
+            return Err(DbError::Type(format!(
+                "DATE_TRUNC expects a DATE or DATETIME, got {}.",
+                v.type_name()
+            )))
+        }
+    };
+    let datetime = to_datetime(seconds)?;
+    let date = datetime.date();
+    let truncated = match unit {
+        "year" => NaiveDate::from_ymd_opt(date.year(), 1, 1).map(midnight),
+        "month" => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).map(midnight),
+        "week" => date
+            .checked_sub_signed(Duration::days(date.weekday().num_days_from_monday() as i64))
+            .map(midnight),
+        "day" => Some(midnight(date)),
+        "hour" => datetime.with_minute(0).and_then(|d| d.with_second(0)),
+        "minute" => datetime.with_second(0),
+        _ => Some(datetime),
+    }
+    .ok_or_else(|| DbError::Type(format!("{} truncated to {} is out of range.", value, unit)))?;
+    let seconds = truncated.and_utc().timestamp();
+    Ok(match value {
+        DataType::Date(_) => DataType::Date(seconds),
+        _ => DataType::DateTime(seconds),
+    })
+}
+
+/// Returns the number of days from the date of `b` to the date of `a`, ignoring times of day.
+pub fn datediff(a: &DataType, b: &DataType) -> Result<DataType> {
+    match (a, b) {
+        (DataType::Date(a) | DataType::DateTime(a), DataType::Date(b) | DataType::DateTime(b)) => {
+            let days = a.div_euclid(SECONDS_PER_DAY) - b.div_euclid(SECONDS_PER_DAY);
+            i32::try_from(days)
+                .map(DataType::Int)
+                .map_err(|_| DbError::Type("DATEDIFF is out of range.".to_string()))
+        }
+        (a, b) => Err(DbError::Type(format!(
+            "DATEDIFF expects DATE or DATETIME values, got {} and {}.",
+            a.type_name(),
+            b.type_name()
+        ))),
+    }
+}
+
+/// Returns the current date and time.
+pub fn now() -> DataType {
+    DataType::DateTime(Utc::now().timestamp())
+}
+
+/// Returns the current date.
+pub fn current_date() -> DataType {
+    DataType::Date(midnight(Utc::now().date_naive()).and_utc().timestamp())
+}
+
+/// Returns the timestamp at which a YEAR value, stored as years since 2000, begins.
+pub fn year_start(year: i8) -> i64 {
+    let date = NaiveDate::from_ymd_opt(2000 + year as i32, 1, 1).expect("YEAR values are valid");
+    midnight(date).and_utc().timestamp()
+}
+
+/// Returns the year a timestamp falls in as a YEAR value, or [`None`] if YEAR cannot hold it.
+pub fn year_of(seconds: i64) -> Option<i8> {
+    let year = DateTime::<Utc>::from_timestamp(seconds, 0)?.year();
+    (year - 2000).try_into().ok()
+}
+
+fn to_datetime(seconds: i64) -> Result<NaiveDateTime> {
+    DateTime::<Utc>::from_timestamp(seconds, 0)
+        .map(|d| d.naive_utc())
+        .ok_or_else(|| DbError::Type(format!("Timestamp {} is out of range.", seconds)))
+}
+
+fn midnight(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).expect("Midnight exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> DataType {
+        DataType::parse_str(DataType::Date(0), text).unwrap()
+    }
+
+    fn datetime(text: &str) -> DataType {
+        DataType::parse_str(DataType::DateTime(0), text).unwrap()
+    }
+
+    #[test]
+    fn test_interval() {
+        let interval = Interval::parse("1 year 2 months 3 days").unwrap();
+        assert_eq!(interval.months, 14);
+        assert_eq!(interval.seconds, 3 * SECONDS_PER_DAY);
+        assert_eq!(Interval::parse("1 hour").unwrap().seconds, 3600);
+        assert!(Interval::parse("3").is_err());
+        assert!(Interval::parse("3 fortnights").is_err());
+        assert!(Interval::parse("x days").is_err());
+
+        let shifted = shift(&date("2024-01-31"), &Interval::parse("1 month").unwrap());
+        assert_eq!(shifted.unwrap().to_string(), "2024-02-29");
+        let shifted = shift(
+            &date("2024-03-01"),
+            &Interval::parse("1 day").unwrap().negate(),
+        );
+        assert_eq!(shifted.unwrap().to_string(), "2024-02-29");
+        let shifted = shift(&date("2024-03-01"), &Interval::parse("90 minutes").unwrap());
+        assert_eq!(shifted.unwrap().to_string(), "2024-03-01 01:30:00");
+        let shifted = shift(&DataType::Time(3600), &Interval::parse("-2 hours").unwrap());
+        assert_eq!(shifted.unwrap().to_string(), "23:00:00");
+        assert!(shift(&DataType::Time(0), &Interval::parse("1 month").unwrap()).is_err());
+        assert!(shift(&DataType::Int(0), &Interval::parse("1 day").unwrap()).is_err());
+        // Shifting by more seconds than a duration holds is an error rather than a panic.
+        let err = shift(
+            &date("2024-03-01"),
+            &Interval::parse("999999999999 days").unwrap(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, DbError::Type(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_extract_and_trunc() {
+        let value = datetime("2024-02-29 13:45:10");
+        assert_eq!(extract("year", &value).unwrap(), DataType::Int(2024));
+        assert_eq!(extract("month", &value).unwrap(), DataType::Int(2));
+        assert_eq!(extract("minute", &value).unwrap(), DataType::Int(45));
+        assert_eq!(extract("dow", &value).unwrap(), DataType::Int(4));
+        assert_eq!(extract("doy", &value).unwrap(), DataType::Int(60));
+        assert_eq!(
+            extract("hour", &DataType::Time(7200)).unwrap(),
+            DataType::Int(2)
+        );
+        assert_eq!(
+            extract("year", &DataType::Year(24)).unwrap(),
+            DataType::Int(2024)
+        );
+        assert!(extract_type("month", &DataType::Time(0)).is_err());
+        assert!(extract_type("fortnight", &DataType::Date(0)).is_err());
+
+        assert_eq!(
+            trunc("month", &value).unwrap().to_string(),
+            "2024-02-01 00:00:00"
+        );
+        assert_eq!(
+            trunc("week", &value).unwrap().to_string(),
+            "2024-02-26 00:00:00"
+        );
+        assert_eq!(
+            trunc("hour", &value).unwrap().to_string(),
+            "2024-02-29 13:00:00"
+        );
+        assert_eq!(
+            trunc("year", &date("2024-02-29")).unwrap(),
+            date("2024-01-01")
+        );
+        assert!(trunc_type("decade", &value).is_err());
+
+        let diff = datediff(&date("2024-03-01"), &datetime("2024-02-28 23:59:59"));
+        assert_eq!(diff.unwrap(), DataType::Int(2));
+    }
+}
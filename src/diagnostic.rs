@@ -0,0 +1,228 @@
+use std::cmp::Reverse;
+
+use crate::{
+    error::DbError,
+    lexer::{unquote_identifier, Span},
+    table::Column,
+};
+
+/// Renders `message` followed by the line of `source` that `span` is on, with carets under the
+/// span and `hint` after them:
+///
+/// ```text
+/// Expected FROM.
+/// 1 | SELECT name form people;
+///   |             ^^^^ did you mean FROM?
+/// ```
+pub fn render(source: &str, span: Span, message: &str, hint: Option<&str>) -> String {
+    let start = span.start.min(source.len());
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+    let number = source[..start].matches('\n').count() + 1;
+    let column = source[line_start..start].chars().count();
+    let width = source[start..span.end.clamp(start, line_end)]
+        .chars()
+        .count()
+        .max(1);
+    let gutter = " ".repeat(number.to_string().len());
+    let mut out = format!(
+        "{}\n{} | {}\n{} | {}{}",
+        message,
+        number,
+        line,
+        gutter,
+        " ".repeat(column),
+        "^".repeat(width)
+    );
+    if let Some(hint) = hint {
+        out.push(' ');
+        out.push_str(hint);
+    }
+    out
+}
+
+/// Returns the candidate closest to `word`, if it is close enough to be a misspelling of it.
+/// A third of the letters of `word` may be wrong, so words shorter than three letters never get
+/// a suggestion. Of equally close candidates, one with the same letters as `word` wins, as
+/// swapped letters are the most common typo, and then the one sharing the longest prefix.
+pub fn suggest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    let letters = sorted_letters(&word);
+    let limit = word.chars().count() / 3;
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lowercase = candidate.to_lowercase();
+            let distance = edit_distance(&word, &lowercase);
+            let prefix = word
+                .chars()
+                .zip(lowercase.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let anagram = sorted_letters(&lowercase) == letters;
+            (distance > 0 && distance <= limit)
+                .then_some(((distance, !anagram, Reverse(prefix)), candidate))
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The error for a column `name` missing from `columns`, suggesting a column it may be a
+/// misspelling of. If several sources of a join have the column, it names them instead.
+pub fn column_not_found(name: &str, columns: &[Column]) -> DbError {
+    let qualified = columns
+        .iter()
+        .filter(|c| {
+            c.name
+                .rsplit_once('.')
+                .is_some_and(|(_, column)| column == name)
+        })
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    let message = if qualified.len() > 1 {
+        format!(
+            "Column {} is ambiguous. Did you mean {}?",
+            name,
+            qualified.join(" or ")
+        )
+    } else {
+        let names = columns.iter().map(|c| c.name.as_str());
+        match suggest(name, names) {
+            Some(column) => format!("Column {} not found. Did you mean {}?", name, column),
+            None => format!("Column {} not found.", name),
+        }
+    };
+    DbError::Column {
+        name: name.to_string(),
+        message,
+    }
+}
+
+/// Renders a [`column_not_found`] error of the statement `source`, whose tokens were read from
+/// `spans`, like a parse error: with a caret under the first token naming the column. Other
+/// errors are returned unchanged.
+pub fn locate_column(error: DbError, source: &str, spans: &[Span]) -> DbError {
+    let DbError::Column { name, message } = error else {
+        return error;
+    };
+    let qualified = format!(".{}", name);
+    let span = spans.iter().find(|span| {
+        let token = &source[span.start..span.end];
+        let token = unquote_identifier(token).unwrap_or_else(|| token.to_lowercase());
+        token == name || token.ends_with(&qualified)
+    });
+    let message = match span {
+        Some(span) => render(source, *span, &message, None),
+        None => message,
+    };
+    DbError::Column { name, message }
+}
+
+fn sorted_letters(word: &str) -> Vec<char> {
+    let mut letters = word.chars().collect::<Vec<_>>();
+    letters.sort_unstable();
+    letters
+}
+
+/// The number of insertions, deletions, substitutions and swaps of adjacent letters that turn
+/// `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // distances[i][j] is the distance between the first i letters of a and the first j of b.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DataType;
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(edit_distance("form", "from"), 1);
+        assert_eq!(edit_distance("tabels", "tables"), 1);
+        assert_eq!(edit_distance("selct", "select"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        let keywords = ["select", "from", "where"];
+        assert_eq!(suggest("FORM", keywords), Some("from"));
+        assert_eq!(suggest("wehre", keywords), Some("where"));
+        assert_eq!(suggest("wher", ["when", "where"]), Some("where"));
+        assert_eq!(suggest("form", ["for", "from"]), Some("from"));
+        assert_eq!(suggest("from", keywords), None);
+        assert_eq!(suggest("fr", keywords), None);
+        assert_eq!(suggest("people", keywords), None);
+    }
+
+    #[test]
+    fn test_locate_column() {
+        let source = "SELECT idd FROM t WHERE t.idd = 1;";
+        let spans = [(0, 6), (7, 10), (11, 15), (16, 17), (18, 23), (24, 29)]
+            .map(|(start, end)| Span { start, end });
+        let columns = [Column::new("id", DataType::Int(0), false, false)];
+        assert_eq!(
+            locate_column(column_not_found("idd", &columns), source, &spans).to_string(),
+            "Column idd not found. Did you mean id?\n1 | SELECT idd FROM t WHERE t.idd = 1;\n  \
+             |        ^^^"
+        );
+        assert_eq!(
+            locate_column(column_not_found("t.idd", &columns), source, &spans).to_string(),
+            "Column t.idd not found.\n1 | SELECT idd FROM t WHERE t.idd = 1;\n  \
+             |                         ^^^^^"
+        );
+        // The column is carried by the error, whatever its message says.
+        let error = column_not_found("idd", &columns).context("Invalid view v: ");
+        assert!(matches!(&error, DbError::Column { name, .. } if name == "idd"));
+        assert_eq!(
+            locate_column(error, source, &spans).to_string(),
+            "Invalid view v: Column idd not found. Did you mean id?\n\
+             1 | SELECT idd FROM t WHERE t.idd = 1;\n  |        ^^^"
+        );
+        let error = DbError::Catalog("Table t not found.".to_string());
+        assert_eq!(
+            locate_column(error, source, &spans).to_string(),
+            "Table t not found."
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let source = "SELECT a\nFROM t\n  WHRE b = 1;";
+        let span = Span { start: 18, end: 22 };
+        assert_eq!(
+            render(
+                source,
+                span,
+                "Unexpected tokens.",
+                Some("did you mean WHERE?")
+            ),
+            "Unexpected tokens.\n3 |   WHRE b = 1;\n  |   ^^^^ did you mean WHERE?"
+        );
+        // An empty span at the end of the input gets a single caret.
+        let span = Span { start: 8, end: 8 };
+        assert_eq!(
+            render(source, span, "Expected FROM.", None),
+            "Expected FROM.\n1 | SELECT a\n  |         ^"
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::io::Read;
 
 use crate::constants::{DataType, PageType, PAGE_SIZE};
 use crate::database_file::DatabaseFile;
+use crate::error::Result;
 use crate::index_file::IndexFile;
 use crate::read_write_types::ReadWriteTypes;
 use crate::table_file::TableFile;
@@ -9,8 +10,8 @@ use crate::utils::rainbow;
 use owo_colors::OwoColorize;
 
 pub trait DumpFile: DatabaseFile + ReadWriteTypes {
-    fn dump(&mut self);
-    fn dump_page(&mut self, page_num: u32);
+    fn dump(&mut self) -> Result<()>;
+    fn dump_page(&mut self, page_num: u32) -> Result<()>;
 
     fn dump_bytes(&mut self, bytes: &[u8]) -> String {
         let mut bytes_str = String::new();
@@ -22,34 +23,35 @@ pub trait DumpFile: DatabaseFile + ReadWriteTypes {
 }
 
 impl DumpFile for TableFile {
-    fn dump(&mut self) {
-        let num_pages = self.len() / PAGE_SIZE;
+    fn dump(&mut self) -> Result<()> {
+        let num_pages = self.num_pages()?;
         println!("num_pages: {}", num_pages);
         for i in 0..num_pages as u32 {
-            self.dump_page(i);
+            self.dump_page(i)?;
             println!();
         }
+        Ok(())
     }
 
-    fn dump_page(&mut self, page_num: u32) {
+    fn dump_page(&mut self, page_num: u32) -> Result<()> {
         let page_offset = page_num * PAGE_SIZE as u32;
         let bytes_per_row = 16;
         let num_rows = PAGE_SIZE as u32 / bytes_per_row;
-        self.seek_to_page(page_num);
+        self.seek_to_page(page_num)?;
         print!("{:08X}  | ", page_offset);
-        let page_type = self.read_u8();
+        let page_type = self.read_u8()?;
         print!("{}", self.dump_bytes(&[page_type]).green());
-        let unused = self.read_u8();
+        let unused = self.read_u8()?;
         print!("{}", self.dump_bytes(&[unused]));
-        let num_cells = self.read_u16();
+        let num_cells = self.read_u16()?;
         print!("{}", self.dump_bytes(&num_cells.to_le_bytes()).blue());
-        let content_start = self.read_u16();
+        let content_start = self.read_u16()?;
         print!("{}", self.dump_bytes(&content_start.to_le_bytes()).purple());
-        let next_page = self.read_u32();
+        let next_page = self.read_u32()?;
         print!("{}", self.dump_bytes(&next_page.to_le_bytes()).yellow());
-        let parent_page = self.read_u32();
+        let parent_page = self.read_u32()?;
         print!("{}", self.dump_bytes(&parent_page.to_le_bytes()).cyan());
-        let unused = self.read_u16();
+        let unused = self.read_u16()?;
         print!("{}", self.dump_bytes(&unused.to_le_bytes()));
         print!(" | ");
         print!("{:?} ", PageType::from(page_type).green());
@@ -69,8 +71,7 @@ impl DumpFile for TableFile {
         for i in 1..num_rows {
             let mut row_bytes = vec![0; bytes_per_row as usize];
             let mut pretty_row: Vec<String> = vec![];
-            self.read_exact(&mut row_bytes)
-                .expect("Failed to read row bytes");
+            self.read_exact(&mut row_bytes)?;
             if row_bytes.iter().all(|&b| b == 0) {
                 if !skip {
                     print!("{:08X}  | ", page_offset + i * bytes_per_row);
@@ -223,37 +224,39 @@ impl DumpFile for TableFile {
             }
             println!(" | {}", pretty_row.join(" "));
         }
+        Ok(())
     }
 }
 
 impl DumpFile for IndexFile {
-    fn dump(&mut self) {
-        let num_pages = self.len() / PAGE_SIZE;
+    fn dump(&mut self) -> Result<()> {
+        let num_pages = self.num_pages()?;
         for i in 0..num_pages as u32 {
-            self.dump_page(i);
+            self.dump_page(i)?;
             println!();
         }
+        Ok(())
     }
 
-    fn dump_page(&mut self, page_num: u32) {
+    fn dump_page(&mut self, page_num: u32) -> Result<()> {
         let page_offset = page_num * PAGE_SIZE as u32;
         let bytes_per_row = 16;
         let num_rows = PAGE_SIZE as u32 / bytes_per_row;
-        self.seek_to_page(page_num);
+        self.seek_to_page(page_num)?;
         print!("{:08X}  | ", page_offset);
-        let page_type = self.read_u8();
+        let page_type = self.read_u8()?;
         print!("{}", self.dump_bytes(&[page_type]).green());
-        let unused = self.read_u8();
+        let unused = self.read_u8()?;
         print!("{}", self.dump_bytes(&[unused]));
-        let num_cells = self.read_u16();
+        let num_cells = self.read_u16()?;
         print!("{}", self.dump_bytes(&num_cells.to_le_bytes()).blue());
-        let content_start = self.read_u16();
+        let content_start = self.read_u16()?;
         print!("{}", self.dump_bytes(&content_start.to_le_bytes()).purple());
-        let next_page = self.read_u32();
+        let next_page = self.read_u32()?;
         print!("{}", self.dump_bytes(&next_page.to_le_bytes()).yellow());
-        let parent_page = self.read_u32();
+        let parent_page = self.read_u32()?;
         print!("{}", self.dump_bytes(&parent_page.to_le_bytes()).cyan());
-        let unused = self.read_u16();
+        let unused = self.read_u16()?;
         print!("{}", self.dump_bytes(&unused.to_le_bytes()));
         print!(" | ");
         print!("{:02X} ", page_num);
@@ -275,8 +278,7 @@ impl DumpFile for IndexFile {
         for i in 1..num_rows {
             let mut row_bytes = vec![0; bytes_per_row as usize];
             let mut pretty_row: Vec<String> = vec![];
-            self.read_exact(&mut row_bytes)
-                .expect("Failed to read row bytes");
+            self.read_exact(&mut row_bytes)?;
             if row_bytes.iter().all(|&b| b == 0) && (i + 1) * bytes_per_row < content_start as u32 {
                 if !skip {
                     print!("{:08X}  | ", page_offset + i * bytes_per_row);
@@ -415,5 +417,6 @@ impl DumpFile for IndexFile {
             }
             println!(" | {}", pretty_row.join(" "));
         }
+        Ok(())
     }
 }
@@ -0,0 +1,64 @@
+use crate::constants::ParseDataTypeError;
+
+/// The errors returned by every layer of the database, from reading pages to running statements.
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    /// The statement could not be tokenized or parsed.
+    #[error("{0}")]
+    Parse(String),
+
+    /// A table, view, column or index does not exist, or already exists. Columns missing from a
+    /// statement are reported as [`DbError::Column`] instead.
+    #[error("{0}")]
+    Catalog(String),
+
+    /// The column `name`, as written in the statement, does not exist, or several sources of a
+    /// join have it. Kept apart from [`DbError::Catalog`] so the error can point at the column.
+    #[error("{message}")]
+    Column { name: String, message: String },
+
+    /// A value would break a NOT NULL or UNIQUE constraint, or a statement goes past a limit of
+    /// the database.
+    #[error("{0}")]
+    Constraint(String),
+
+    /// A value or expression does not have the type it is used as.
+    #[error("{0}")]
+    Type(String),
+
+    /// Reading or writing a file failed.
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    /// A file does not hold the pages or values it should.
+    #[error("Corrupted file: {0}")]
+    Corruption(String),
+}
+
+impl DbError {
+    /// Returns the error with `context` in front of its message, keeping its kind.
+    pub fn context(self, context: &str) -> DbError {
+        match self {
+            DbError::Parse(e) => DbError::Parse(format!("{}{}", context, e)),
+            DbError::Catalog(e) => DbError::Catalog(format!("{}{}", context, e)),
+            DbError::Column { name, message } => DbError::Column {
+                name,
+                message: format!("{}{}", context, message),
+            },
+            DbError::Constraint(e) => DbError::Constraint(format!("{}{}", context, e)),
+            DbError::Type(e) => DbError::Type(format!("{}{}", context, e)),
+            DbError::Io(e) => {
+                DbError::Io(std::io::Error::new(e.kind(), format!("{}{}", context, e)))
+            }
+            DbError::Corruption(e) => DbError::Corruption(format!("{}{}", context, e)),
+        }
+    }
+}
+
+impl From<ParseDataTypeError> for DbError {
+    fn from(e: ParseDataTypeError) -> Self {
+        DbError::Type(e.to_string())
+    }
+}
+
+pub type Result<T, E = DbError> = std::result::Result<T, E>;
@@ -0,0 +1,334 @@
+use std::rc::Rc;
+
+use crate::{
+    error::Result,
+    expression::{compare_sort_keys, Expression, Predicate},
+    record::Record,
+    table::Table,
+};
+
+/// The records produced by a step of a query, one at a time. Each step pulls the records it
+/// needs from the steps below it, so only [`Sort`] holds more than one record at once.
+pub type Rows = Box<dyn Iterator<Item = Result<Record>>>;
+
+/// Returns the right records of a [`Join`] for the left record it is given.
+type RightRows = Box<dyn FnMut(&Record) -> Result<Rows>>;
+
+/// Reads the records of a table with the row IDs read from an index, each as it is needed.
+pub struct IndexScan {
+    table: Table,
+    row_ids: Box<dyn Iterator<Item = Result<u32>>>,
+}
+
+impl IndexScan {
+    pub fn new(table: Table, row_ids: impl Iterator<Item = Result<u32>> + 'static) -> IndexScan {
+        IndexScan {
+            table,
+            row_ids: Box::new(row_ids),
+        }
+    }
+}
+
+impl Iterator for IndexScan {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for row_id in self.row_ids.by_ref() {
+            match row_id.and_then(|row_id| self.table.get_record(row_id)) {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// Keeps the records of `input` for which `keep` returns true.
+pub struct Filter<F> {
+    input: Rows,
+    keep: F,
+}
+
+impl<F: FnMut(&Record) -> Result<bool>> Filter<F> {
+    pub fn new(input: Rows, keep: F) -> Filter<F> {
+        Filter { input, keep }
+    }
+}
+
+impl<F: FnMut(&Record) -> Result<bool>> Iterator for Filter<F> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for record in self.input.by_ref() {
+            let keep = record.and_then(|record| Ok((self.keep)(&record)?.then_some(record)));
+            match keep {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// Replaces the values of each record of `input` with those of `projections` evaluated on it.
+pub struct Project {
+    input: Rows,
+    projections: Vec<Expression>,
+}
+
+impl Project {
+    pub fn new(input: Rows, projections: Vec<Expression>) -> Project {
+        Project { input, projections }
+    }
+}
+
+impl Iterator for Project {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.input.next()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+        let values = self
+            .projections
+            .iter()
+            .map(|expression| expression.evaluate(&record.values))
+            .collect::<Result<Vec<_>>>();
+        Some(values.map(|values| Record::new(values, record.row_id)))
+    }
+}
+
+/// Sorts the records of `input` by the values of the ORDER BY expressions, each with whether it
+/// sorts in descending order. Every record of `input` is read before the first is returned.
+pub struct Sort {
+    input: Option<Rows>,
+    order_by: Vec<(Expression, bool)>,
+    sorted: std::vec::IntoIter<Record>,
+}
+
+impl Sort {
+    pub fn new(input: Rows, order_by: Vec<(Expression, bool)>) -> Sort {
+        Sort {
+            input: Some(input),
+            order_by,
+            sorted: vec![].into_iter(),
+        }
+    }
+}
+
+impl Iterator for Sort {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(input) = self.input.take() {
+            let sorted = input
+                .collect::<Result<Vec<_>>>()
+                .and_then(|records| sort_records(records, &self.order_by));
+            match sorted {
+                Ok(records) => self.sorted = records.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.sorted.next().map(Ok)
+    }
+}
+
+/// Returns the first `count` records of `input`, reading no further.
+pub struct Limit {
+    input: Rows,
+    count: u64,
+}
+
+impl Limit {
+    pub fn new(input: Rows, count: u64) -> Limit {
+        Limit { input, count }
+    }
+}
+
+impl Iterator for Limit {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        self.input.next()
+    }
+}
+
+/// Pairs each record of `left` with each record of `right` matching `condition`, which is
+/// evaluated on the values of the left record followed by those of the right one. The joined
+/// record keeps the row ID of the left record. `right` returns the right records anew for the
+/// left record it is given, and is called once for each left record, so neither side is held in
+/// memory. It may leave out right records that cannot match the left record.
+pub struct Join {
+    left: Rows,
+    right: RightRows,
+    condition: Predicate,
+    /// The left record being paired, and the right records left to try.
+    current: Option<(Record, Rows)>,
+}
+
+impl Join {
+    pub fn new(
+        left: Rows,
+        right: impl FnMut(&Record) -> Result<Rows> + 'static,
+        condition: Predicate,
+    ) -> Join {
+        Join {
+            left,
+            right: Box::new(right),
+            condition,
+            current: None,
+        }
+    }
+}
+
+impl Iterator for Join {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (left, right_records) = match &mut self.current {
+                Some(current) => current,
+                None => {
+                    let paired = self.left.next()?.and_then(|record| {
+                        let right = (self.right)(&record)?;
+                        Ok((record, right))
+                    });
+                    match paired {
+                        Ok(paired) => self.current.insert(paired),
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+            };
+            let right = match right_records.next() {
+                Some(Ok(right)) => right,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+            let mut values = left.values.clone();
+            values.extend(right.values);
+            match self.condition.evaluate(&values) {
+                Ok(true) => return Some(Ok(Record::new(values, left.row_id))),
+                Ok(false) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Sorts the records by the values of the ORDER BY expressions.
+pub fn sort_records(records: Vec<Record>, order_by: &[(Expression, bool)]) -> Result<Vec<Record>> {
+    if order_by.is_empty() {
+        return Ok(records);
+    }
+    let mut keyed = records
+        .into_iter()
+        .map(|record| {
+            let keys = order_by
+                .iter()
+                .map(|(expression, _)| expression.evaluate(&record.values))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((keys, record))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let descending: Vec<bool> = order_by.iter().map(|(_, descending)| *descending).collect();
+    keyed.sort_by(|(a, _), (b, _)| compare_sort_keys(a, b, &descending));
+    Ok(keyed.into_iter().map(|(_, record)| record).collect())
+}
+
+/// Returns the records of `records` as [`Rows`].
+pub fn rows(records: Vec<Record>) -> Rows {
+    Box::new(records.into_iter().map(Ok))
+}
+
+/// Returns the records of `records` as [`Rows`], copying each one as it is pulled.
+pub fn shared_rows(records: Rc<Vec<Record>>) -> Rows {
+    Box::new((0..records.len()).map(move |i| Ok(records[i].clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::constants::DataType;
+
+    fn record(row_id: u32, values: &[i32]) -> Record {
+        Record::new(values.iter().map(|v| DataType::Int(*v)).collect(), row_id)
+    }
+
+    fn column(index: usize) -> Expression {
+        Expression::Column {
+            index,
+            name: format!("c{}", index),
+            data_type: DataType::Int(0),
+        }
+    }
+
+    #[test]
+    fn test_operators() {
+        let input = vec![record(1, &[3, 1]), record(2, &[1, 2]), record(3, &[2, 1])];
+
+        // Limit stops pulling records from its input once it has enough.
+        let pulled = Rc::new(Cell::new(0));
+        let counter = pulled.clone();
+        let counted = Box::new(input.clone().into_iter().map(move |r| {
+            counter.set(counter.get() + 1);
+            Ok(r)
+        }));
+        let limited = Limit::new(counted, 2).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(limited.len(), 2);
+        assert_eq!(pulled.get(), 2);
+
+        let sorted = Sort::new(rows(input.clone()), vec![(column(0), true)]);
+        let projected = Project::new(Box::new(sorted), vec![column(1)]);
+        let filtered = Filter::new(Box::new(projected), |r: &Record| {
+            Ok(r.values[0] == DataType::Int(1))
+        });
+        let row_ids = filtered.map(|r| r.unwrap().row_id).collect::<Vec<_>>();
+        assert_eq!(row_ids, [1, 3]);
+
+        let condition = Predicate {
+            left: column(1),
+            operator: "=".to_string(),
+            right: column(2),
+        };
+        let right = vec![record(7, &[1]), record(8, &[2]), record(9, &[1])];
+        // The right records are read again for each left record, rather than kept.
+        let reads = Rc::new(Cell::new(0));
+        let counter = reads.clone();
+        let right_rows = move |_: &Record| {
+            counter.set(counter.get() + 1);
+            Ok(rows(right.clone()))
+        };
+        let joined = Join::new(rows(input), right_rows, condition)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(reads.get(), 3);
+        assert_eq!(
+            joined,
+            [
+                record(1, &[3, 1, 1]),
+                record(1, &[3, 1, 1]),
+                record(2, &[1, 2, 2]),
+                record(3, &[2, 1, 1]),
+                record(3, &[2, 1, 1]),
+            ]
+        );
+
+        // An error ends the records at the step it happens in.
+        let failing = Filter::new(rows(vec![record(1, &[1])]), |_: &Record| {
+            Err(crate::error::DbError::Type("bad".to_string()))
+        });
+        assert!(failing.collect::<Result<Vec<_>>>().is_err());
+    }
+}
@@ -0,0 +1,1364 @@
+use std::{borrow::Cow, cmp::Ordering, fmt::Display, mem::discriminant};
+
+use crate::{
+    constants::{DataType, ParseDataTypeError, MAX_TEXT_LENGTH},
+    datetime::{self, Interval},
+    diagnostic::column_not_found,
+    error::{DbError, Result},
+    lexer::unquote,
+    parser::{Condition, Expr, Value},
+    table::{column_index, Column},
+    window::WindowFunction,
+};
+
+/// An expression resolved against the columns of the records it is evaluated on.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    Column {
+        index: usize,
+        name: String,
+        data_type: DataType,
+    },
+    Literal(DataType),
+    Call {
+        function: Function,
+        args: Vec<Expression>,
+        /// The type of the result.
+        data_type: DataType,
+    },
+    /// A date, date-time or time shifted by an interval.
+    Shift {
+        value: Box<Expression>,
+        interval: Interval,
+        /// The interval as written.
+        text: String,
+        operator: String,
+        data_type: DataType,
+    },
+    /// A value converted to another type by `CAST`.
+    Cast {
+        value: Box<Expression>,
+        data_type: DataType,
+    },
+    /// The result of the first branch whose predicate matches, else `otherwise` or `NULL`.
+    Case {
+        branches: Vec<(Predicate, Expression)>,
+        otherwise: Option<Box<Expression>>,
+        data_type: DataType,
+    },
+}
+
+impl Expression {
+    /// Resolves `expr` against `columns`.
+    ///
+    /// `hint` is the type the expression is compared with or stored as. Literals are parsed as
+    /// that type, and names that are not columns are read as unquoted literals of that type.
+    pub fn resolve(expr: &Expr, columns: &[Column], hint: Option<&DataType>) -> Result<Expression> {
+        let hint = hint.filter(|data_type| **data_type != DataType::Null);
+        match expr {
+            Expr::Column(name) => match column_index(columns, name) {
+                Some(index) => Ok(Expression::Column {
+                    index,
+                    name: name.clone(),
+                    data_type: columns[index].data_type.clone(),
+                }),
+                None => match hint {
+                    Some(data_type) => parse_literal(name, data_type)
+                        .map(Expression::Literal)
+                        .map_err(|_| column_not_found(name, columns)),
+                    None => Err(column_not_found(name, columns)),
+                },
+            },
+            Expr::Value(value) => resolve_value(value, hint).map(Expression::Literal),
+            Expr::Function { name, args } => {
+                let function = Function::from_name(name).ok_or_else(|| {
+                    DbError::Parse(match WindowFunction::from_name(name) {
+                        Some(function) => format!("{} requires an OVER clause.", function.name()),
+                        None => format!("Unknown function {}.", name.to_uppercase()),
+                    })
+                })?;
+                function.check_arity(args.len())?;
+                let mut resolved: Vec<Expression> = vec![];
+                for (i, arg) in args.iter().enumerate() {
+                    // Arguments compared with or replacing the first one take its type.
+                    let arg_hint = match function {
+                        Function::Coalesce | Function::NullIf => {
+                            resolved.first().map(Expression::data_type)
+                        }
+                        _ => None,
+                    };
+                    resolved.push(match (function, i) {
+                        (Function::DateDiff, _) | (Function::Extract | Function::DateTrunc, 1) => {
+                            Expression::resolve_temporal(arg, columns)?
+                        }
+                        _ => Expression::resolve(arg, columns, arg_hint.as_ref())?,
+                    });
+                }
+                let data_type = function.return_type(&resolved)?;
+                Ok(Expression::Call {
+                    function,
+                    args: resolved,
+                    data_type,
+                })
+            }
+            Expr::Interval {
+                value,
+                operator,
+                interval: text,
+            } => {
+                let value = Expression::resolve_temporal(value, columns)?;
+                let mut interval = Interval::parse(text)?;
+                if operator == "-" {
+                    interval = interval.negate();
+                }
+                let data_type = datetime::shift_type(&value.data_type(), &interval)?;
+                Ok(Expression::Shift {
+                    value: Box::new(value),
+                    interval,
+                    text: text.clone(),
+                    operator: operator.clone(),
+                    data_type,
+                })
+            }
+            Expr::Cast { value, data_type } => Ok(Expression::Cast {
+                value: Box::new(Expression::resolve(value, columns, None)?),
+                data_type: data_type.clone(),
+            }),
+            Expr::Window { function, .. } => Err(DbError::Type(format!(
+                "Window function {} is only allowed as a selected column.",
+                function.to_uppercase()
+            ))),
+            Expr::Case {
+                branches,
+                otherwise,
+            } => {
+                let mut resolved = vec![];
+                let mut data_type = DataType::Null;
+                for (condition, result) in branches {
+                    let predicate = Predicate::resolve(condition, columns)?;
+                    let result = Expression::resolve(result, columns, hint)?;
+                    data_type = unify_branches(&data_type, &result.data_type())?;
+                    resolved.push((predicate, result));
+                }
+                let otherwise = match otherwise {
+                    Some(otherwise) => {
+                        let otherwise = Expression::resolve(otherwise, columns, hint)?;
+                        data_type = unify_branches(&data_type, &otherwise.data_type())?;
+                        Some(Box::new(otherwise))
+                    }
+                    None => None,
+                };
+                Ok(Expression::Case {
+                    branches: resolved,
+                    otherwise,
+                    data_type,
+                })
+            }
+        }
+    }
+
+    /// Resolves `expr` as an argument expecting a date, date-time or time. Text literals, and
+    /// names that are not columns such as an unquoted `2024-01-01`, are read as whichever of
+    /// those they parse as.
+    fn resolve_temporal(expr: &Expr, columns: &[Column]) -> Result<Expression> {
+        if let Expr::Column(name) = expr {
+            if column_index(columns, name).is_none() {
+                if let Some(value) = temporal_literal(name) {
+                    return Ok(Expression::Literal(value));
+                }
+            }
+        }
+        let resolved = Expression::resolve(expr, columns, None)?;
+        Ok(match &resolved {
+            Expression::Literal(DataType::Text(text)) => temporal_literal(&text_content(text))
+                .map(Expression::Literal)
+                .unwrap_or(resolved),
+            _ => resolved,
+        })
+    }
+
+    /// Returns the type of the values the expression evaluates to. [`DataType::Null`] if it is
+    /// always `NULL`.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Expression::Column { data_type, .. }
+            | Expression::Call { data_type, .. }
+            | Expression::Shift { data_type, .. }
+            | Expression::Cast { data_type, .. }
+            | Expression::Case { data_type, .. } => data_type.clone(),
+            Expression::Literal(value) => value.clone(),
+        }
+    }
+
+    /// Evaluates the expression on the values of a record.
+    pub fn evaluate(&self, values: &[DataType]) -> Result<DataType> {
+        match self {
+            Expression::Column { index, .. } => Ok(values[*index].clone()),
+            Expression::Literal(value) => Ok(value.clone()),
+            Expression::Call {
+                function,
+                args,
+                data_type,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate(values))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.call(args, data_type)
+            }
+            Expression::Shift {
+                value, interval, ..
+            } => datetime::shift(&value.evaluate(values)?, interval),
+            Expression::Cast { value, data_type } => cast(value.evaluate(values)?, data_type),
+            Expression::Case {
+                branches,
+                otherwise,
+                data_type,
+            } => {
+                let mut result = None;
+                for (predicate, value) in branches {
+                    if predicate.evaluate(values)? {
+                        result = Some(value);
+                        break;
+                    }
+                }
+                let value = match result.or(otherwise.as_deref()) {
+                    Some(value) => value.evaluate(values)?,
+                    None => DataType::Null,
+                };
+                coerce(value.clone(), data_type).ok_or_else(|| {
+                    DbError::Type(format!(
+                        "CASE result {} does not fit {}.",
+                        value,
+                        data_type.type_name()
+                    ))
+                })
+            }
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Column { name, .. } => write!(f, "{}", name),
+            Expression::Literal(value) => write!(f, "{}", display_literal(value)),
+            Expression::Call { function, args, .. } => {
+                let args = args.iter().map(Expression::to_string).collect::<Vec<_>>();
+                write!(f, "{}({})", function.name().to_lowercase(), args.join(", "))
+            }
+            Expression::Shift {
+                value,
+                text,
+                operator,
+                ..
+            } => write!(f, "{} {} interval '{}'", value, operator, text),
+            Expression::Cast { value, data_type } => {
+                write!(
+                    f,
+                    "cast({} as {})",
+                    value,
+                    data_type.type_name().to_lowercase()
+                )
+            }
+            Expression::Case {
+                branches,
+                otherwise,
+                ..
+            } => {
+                write!(f, "case")?;
+                for (predicate, result) in branches {
+                    write!(f, " when {} then {}", predicate, result)?;
+                }
+                if let Some(otherwise) = otherwise {
+                    write!(f, " else {}", otherwise)?;
+                }
+                write!(f, " end")
+            }
+        }
+    }
+}
+
+/// A WHERE clause evaluated on each record.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Predicate {
+    pub left: Expression,
+    pub operator: String,
+    pub right: Expression,
+}
+
+impl Predicate {
+    pub fn resolve(condition: &Condition, columns: &[Column]) -> Result<Predicate> {
+        let left = Expression::resolve(&condition.left, columns, None)?;
+        let right = Expression::resolve(&condition.right, columns, Some(&left.data_type()))?;
+        if !comparable(&left.data_type(), &right.data_type()) {
+            return Err(DbError::Type(format!(
+                "Cannot compare {} with {}.",
+                left.data_type().type_name(),
+                right.data_type().type_name()
+            )));
+        }
+        Ok(Predicate {
+            left,
+            operator: condition.operator.clone(),
+            right,
+        })
+    }
+
+    /// Returns whether the record with `values` matches. Comparisons with `NULL` never match.
+    pub fn evaluate(&self, values: &[DataType]) -> Result<bool> {
+        let left = self.left.evaluate(values)?;
+        let right = self.right.evaluate(values)?;
+        Ok(matches_operator(compare(&left, &right), &self.operator))
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.left, self.operator, self.right)
+    }
+}
+
+/// The built-in scalar functions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Function {
+    Upper,
+    Lower,
+    Length,
+    Substr,
+    Trim,
+    Replace,
+    Concat,
+    Coalesce,
+    NullIf,
+    Abs,
+    Round,
+    Floor,
+    Ceil,
+    Mod,
+    Now,
+    CurrentDate,
+    Extract,
+    DateTrunc,
+    DateDiff,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Function> {
+        let function = match name {
+            "upper" => Function::Upper,
+            "lower" => Function::Lower,
+            "length" => Function::Length,
+            "substr" | "substring" => Function::Substr,
+            "trim" => Function::Trim,
+            "replace" => Function::Replace,
+            "concat" => Function::Concat,
+            "coalesce" => Function::Coalesce,
+            "nullif" => Function::NullIf,
+            "abs" => Function::Abs,
+            "round" => Function::Round,
+            "floor" => Function::Floor,
+            "ceil" | "ceiling" => Function::Ceil,
+            "mod" => Function::Mod,
+            "now" | "current_timestamp" => Function::Now,
+            "current_date" => Function::CurrentDate,
+            "extract" => Function::Extract,
+            "date_trunc" => Function::DateTrunc,
+            "datediff" => Function::DateDiff,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Function::Upper => "UPPER",
+            Function::Lower => "LOWER",
+            Function::Length => "LENGTH",
+            Function::Substr => "SUBSTR",
+            Function::Trim => "TRIM",
+            Function::Replace => "REPLACE",
+            Function::Concat => "CONCAT",
+            Function::Coalesce => "COALESCE",
+            Function::NullIf => "NULLIF",
+            Function::Abs => "ABS",
+            Function::Round => "ROUND",
+            Function::Floor => "FLOOR",
+            Function::Ceil => "CEIL",
+            Function::Mod => "MOD",
+            Function::Now => "NOW",
+            Function::CurrentDate => "CURRENT_DATE",
+            Function::Extract => "EXTRACT",
+            Function::DateTrunc => "DATE_TRUNC",
+            Function::DateDiff => "DATEDIFF",
+        }
+    }
+
+    /// Returns the smallest and largest number of arguments.
+    fn arity(self) -> (usize, usize) {
+        match self {
+            Function::Upper
+            | Function::Lower
+            | Function::Length
+            | Function::Trim
+            | Function::Abs
+            | Function::Floor
+            | Function::Ceil => (1, 1),
+            Function::Now | Function::CurrentDate => (0, 0),
+            Function::NullIf
+            | Function::Mod
+            | Function::Extract
+            | Function::DateTrunc
+            | Function::DateDiff => (2, 2),
+            Function::Replace => (3, 3),
+            Function::Round => (1, 2),
+            Function::Substr => (2, 3),
+            Function::Concat | Function::Coalesce => (1, usize::MAX),
+        }
+    }
+
+    fn check_arity(self, count: usize) -> Result<()> {
+        check_arity(self.name(), self.arity(), count)
+    }
+
+    /// Checks the types of the arguments and returns the type of the result.
+    fn return_type(self, args: &[Expression]) -> Result<DataType> {
+        let unit_arg = args.first();
+        let args: Vec<DataType> = args.iter().map(Expression::data_type).collect();
+        let expect = |i: usize, accepts: fn(&DataType) -> bool, expected: &str| {
+            if args[i] == DataType::Null || accepts(&args[i]) {
+                Ok(())
+            } else {
+                Err(DbError::Type(format!(
+                    "{} expects {} as argument {}, got {}.",
+                    self.name(),
+                    expected,
+                    i + 1,
+                    args[i].type_name()
+                )))
+            }
+        };
+        let text = DataType::Text(String::new());
+        match self {
+            Function::Upper | Function::Lower | Function::Trim => {
+                expect(0, is_text, "TEXT")?;
+                Ok(text)
+            }
+            Function::Length => {
+                expect(0, is_text, "TEXT")?;
+                Ok(DataType::Int(0))
+            }
+            Function::Substr => {
+                expect(0, is_text, "TEXT")?;
+                for i in 1..args.len() {
+                    expect(i, is_integer, "an integer")?;
+                }
+                Ok(text)
+            }
+            Function::Replace => {
+                for i in 0..args.len() {
+                    expect(i, is_text, "TEXT")?;
+                }
+                Ok(text)
+            }
+            Function::Concat => Ok(text),
+            Function::Coalesce => args.iter().try_fold(DataType::Null, |result, arg| {
+                unify(&result, arg).ok_or_else(|| {
+                    DbError::Type(format!(
+                        "COALESCE arguments must have compatible types, got {} and {}.",
+                        result.type_name(),
+                        arg.type_name()
+                    ))
+                })
+            }),
+            Function::NullIf => {
+                if !comparable(&args[0], &args[1]) {
+                    return Err(DbError::Type(format!(
+                        "NULLIF cannot compare {} with {}.",
+                        args[0].type_name(),
+                        args[1].type_name()
+                    )));
+                }
+                Ok(args[0].clone())
+            }
+            Function::Abs | Function::Floor | Function::Ceil => {
+                expect(0, is_numeric, "a number")?;
+                Ok(args[0].clone())
+            }
+            Function::Round => {
+                expect(0, is_numeric, "a number")?;
+                if args.len() == 2 {
+                    expect(1, is_integer, "an integer")?;
+                }
+                Ok(args[0].clone())
+            }
+            Function::Mod => {
+                expect(0, is_numeric, "a number")?;
+                expect(1, is_numeric, "a number")?;
+                Ok(unify(&args[0], &args[1]).expect("Numbers unify"))
+            }
+            Function::Now => Ok(DataType::DateTime(0)),
+            Function::CurrentDate => Ok(DataType::Date(0)),
+            Function::Extract => datetime::extract_type(&self.unit(unit_arg)?, &args[1]),
+            Function::DateTrunc => datetime::trunc_type(&self.unit(unit_arg)?, &args[1]),
+            Function::DateDiff => {
+                expect(0, is_temporal, "a DATE or DATETIME")?;
+                expect(1, is_temporal, "a DATE or DATETIME")?;
+                Ok(DataType::Int(0))
+            }
+        }
+    }
+
+    /// Returns the unit written as the first argument of EXTRACT and DATE_TRUNC.
+    fn unit(self, arg: Option<&Expression>) -> Result<Cow<'_, str>> {
+        match arg {
+            Some(Expression::Literal(DataType::Text(unit))) => Ok(text_content(unit)),
+            _ => Err(DbError::Type(format!(
+                "{} expects a unit such as 'day' as argument 1.",
+                self.name()
+            ))),
+        }
+    }
+
+    /// Calls the function. `data_type` is the type returned by [`Function::return_type`].
+    fn call(self, args: Vec<DataType>, data_type: &DataType) -> Result<DataType> {
+        match self {
+            Function::Coalesce => {
+                return match args.into_iter().find(|arg| *arg != DataType::Null) {
+                    Some(value) => self.fit(value, data_type),
+                    None => Ok(DataType::Null),
+                }
+            }
+            Function::NullIf => {
+                return Ok(match compare(&args[0], &args[1]) {
+                    Some(Ordering::Equal) => DataType::Null,
+                    _ => args[0].clone(),
+                })
+            }
+            _ => {}
+        }
+        if args.contains(&DataType::Null) {
+            return Ok(DataType::Null);
+        }
+        let result = match self {
+            Function::Upper => text_value(&self.text(&args[0])?.to_uppercase()),
+            Function::Lower => text_value(&self.text(&args[0])?.to_lowercase()),
+            Function::Trim => text_value(self.text(&args[0])?.trim()),
+            Function::Length => DataType::Int(self.text(&args[0])?.chars().count() as i32),
+            Function::Substr => {
+                let chars: Vec<char> = self.text(&args[0])?.chars().collect();
+                let len = chars.len() as i64;
+                // Positions count from 1, and may start before the text.
+                let from = self.integer(&args[1])?.saturating_sub(1);
+                let to = match args.get(2) {
+                    Some(count) => {
+                        let count = self.integer(count)?;
+                        if count < 0 {
+                            return Err(DbError::Type(
+                                "SUBSTR length must not be negative.".to_string(),
+                            ));
+                        }
+                        from.saturating_add(count)
+                    }
+                    None => len,
+                };
+                let (from, to) = (from.clamp(0, len) as usize, to.clamp(0, len) as usize);
+                text_value(&chars[from..to.max(from)].iter().collect::<String>())
+            }
+            Function::Replace => {
+                let text = self.text(&args[0])?;
+                let from = self.text(&args[1])?;
+                let to = self.text(&args[2])?;
+                if from.is_empty() {
+                    text_value(&text)
+                } else {
+                    text_value(&text.replace(&*from, &to))
+                }
+            }
+            Function::Concat => text_value(
+                &args
+                    .iter()
+                    .map(|arg| match arg {
+                        DataType::Text(text) => text_content(text).into_owned(),
+                        arg => arg.to_string(),
+                    })
+                    .collect::<String>(),
+            ),
+            Function::Abs => match &args[0] {
+                DataType::Float(v) => DataType::Float(v.abs()),
+                DataType::Double(v) => DataType::Double(v.abs()),
+                v => {
+                    let abs = self
+                        .integer(v)?
+                        .checked_abs()
+                        .ok_or_else(|| DbError::Type(format!("ABS({}) is out of range.", v)))?;
+                    self.fit(DataType::BigInt(abs), data_type)?
+                }
+            },
+            Function::Floor | Function::Ceil => {
+                let round = match self {
+                    Function::Floor => f64::floor,
+                    _ => f64::ceil,
+                };
+                match &args[0] {
+                    DataType::Float(v) => DataType::Float(round(*v as f64) as f32),
+                    DataType::Double(v) => DataType::Double(round(*v)),
+                    v => v.clone(),
+                }
+            }
+            Function::Round => {
+                let digits = match args.get(1) {
+                    Some(digits) => self.integer(digits)?.clamp(-20, 20) as i32,
+                    None => 0,
+                };
+                let factor = 10f64.powi(digits);
+                match &args[0] {
+                    DataType::Float(v) => {
+                        DataType::Float(((*v as f64 * factor).round() / factor) as f32)
+                    }
+                    DataType::Double(v) => DataType::Double((v * factor).round() / factor),
+                    v if digits >= 0 => v.clone(),
+                    v => {
+                        let factor = 10f64.powi(-digits);
+                        let rounded = (self.integer(v)? as f64 / factor).round() * factor;
+                        self.fit(DataType::Double(rounded), data_type)?
+                    }
+                }
+            }
+            Function::Mod => {
+                if as_f64(&args[1]) == Some(0.0) {
+                    return Err(DbError::Type("Division by zero in MOD.".to_string()));
+                }
+                match (as_i64(&args[0]), as_i64(&args[1])) {
+                    (Some(a), Some(b)) => {
+                        self.fit(DataType::BigInt(a.wrapping_rem(b)), data_type)?
+                    }
+                    _ => {
+                        let a = as_f64(&args[0]).expect("MOD arguments are numbers");
+                        let b = as_f64(&args[1]).expect("MOD arguments are numbers");
+                        self.fit(DataType::Double(a % b), data_type)?
+                    }
+                }
+            }
+            Function::Now => datetime::now(),
+            Function::CurrentDate => datetime::current_date(),
+            Function::Extract => datetime::extract(&self.text(&args[0])?, &args[1])?,
+            Function::DateTrunc => datetime::trunc(&self.text(&args[0])?, &args[1])?,
+            Function::DateDiff => datetime::datediff(&args[0], &args[1])?,
+            Function::Coalesce | Function::NullIf => unreachable!("Handled above"),
+        };
+        match &result {
+            DataType::Text(text) if text.len() > MAX_TEXT_LENGTH => Err(DbError::Type(format!(
+                "Result of {} is longer than {} characters.",
+                self.name(),
+                MAX_TEXT_LENGTH
+            ))),
+            _ => Ok(result),
+        }
+    }
+
+    fn text(self, value: &DataType) -> Result<Cow<'_, str>> {
+        match value {
+            DataType::Text(text) => Ok(text_content(text)),
+            v => Err(DbError::Type(format!(
+                "{} expects TEXT, got {}.",
+                self.name(),
+                v.type_name()
+            ))),
+        }
+    }
+
+    fn integer(self, value: &DataType) -> Result<i64> {
+        as_i64(value).ok_or_else(|| {
+            DbError::Type(format!(
+                "{} expects an integer, got {}.",
+                self.name(),
+                value.type_name()
+            ))
+        })
+    }
+
+    /// Converts a result to `data_type`.
+    fn fit(self, value: DataType, data_type: &DataType) -> Result<DataType> {
+        coerce(value.clone(), data_type).ok_or_else(|| {
+            DbError::Type(format!(
+                "Result {} of {} does not fit {}.",
+                value,
+                self.name(),
+                data_type.type_name()
+            ))
+        })
+    }
+}
+
+/// Checks that a function called `name` accepting between `min` and `max` arguments is called
+/// with `count`.
+pub fn check_arity(name: &str, (min, max): (usize, usize), count: usize) -> Result<()> {
+    if (min..=max).contains(&count) {
+        return Ok(());
+    }
+    let expected = match (min, max) {
+        (0, 0) => "no arguments".to_string(),
+        (1, 1) => "1 argument".to_string(),
+        (min, max) if min == max => format!("{} arguments", min),
+        (min, usize::MAX) => format!("at least {} argument", min),
+        (min, max) => format!("{} to {} arguments", min, max),
+    };
+    Err(DbError::Type(format!(
+        "{} expects {}, got {}.",
+        name, expected, count
+    )))
+}
+
+/// Returns `value` as written in a statement.
+pub fn display_literal(value: &DataType) -> String {
+    match value {
+        DataType::Text(text) if unquote(text).is_some() => text.clone(),
+        DataType::Text(text) => quote(text),
+        v => v.to_string(),
+    }
+}
+
+/// Returns the text a TEXT value holds. Text written as a literal is stored as written, quotes
+/// included, while text the database stores itself, such as the names in the catalog, has none.
+pub fn text_content(text: &str) -> Cow<'_, str> {
+    match unquote(text) {
+        Some(content) => Cow::Owned(content),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Returns `text` as a TEXT value, quoted like the text literals stored from statements.
+pub fn text_value(text: &str) -> DataType {
+    DataType::Text(quote(text))
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Reads `text` as a date, date-time or time, whichever it parses as.
+fn temporal_literal(text: &str) -> Option<DataType> {
+    [DataType::Date(0), DataType::DateTime(0), DataType::Time(0)]
+        .into_iter()
+        .find_map(|data_type| DataType::parse_str(data_type, text).ok())
+}
+
+/// Parses a literal as written in a statement into a value of `data_type`. Unquoted `null` is
+/// [`DataType::Null`].
+pub fn parse_literal(literal: &str, data_type: &DataType) -> Result<DataType, ParseDataTypeError> {
+    match (unquote(literal), data_type) {
+        // Quoted text is stored as written, quotes and surrounding whitespace included.
+        (Some(_), DataType::Text(_)) => Ok(DataType::Text(literal.to_string())),
+        (None, _) if literal == "null" => Ok(DataType::Null),
+        (text, data_type) => {
+            DataType::parse_str(data_type.clone(), text.as_deref().unwrap_or(literal))
+        }
+    }
+}
+
+/// Types a value compared with a column of type `data_type`. Numbers keep their own type and
+/// compare with the column by value; other literals are parsed as `data_type`.
+pub fn resolve_compared_value(value: &Value, data_type: &DataType) -> Result<DataType> {
+    resolve_value(value, Some(data_type))
+}
+
+/// Types a value that is not compared with or stored in a column of known type.
+fn resolve_value(value: &Value, hint: Option<&DataType>) -> Result<DataType> {
+    match (value, hint) {
+        // Numbers keep their own type and compare with other numeric types by value.
+        (Value::Literal(literal), Some(data_type))
+            if is_numeric(data_type) && literal.parse::<f64>().is_ok() =>
+        {
+            resolve_value(value, None)
+        }
+        (Value::Literal(literal), Some(data_type)) => {
+            parse_literal(literal, data_type).map_err(|e| {
+                DbError::Type(format!(
+                    "Failed parsing {} into {}: {}",
+                    literal,
+                    data_type.type_name(),
+                    e
+                ))
+            })
+        }
+        (Value::Literal(literal), None) => {
+            if unquote(literal).is_some() {
+                Ok(DataType::Text(literal.to_string()))
+            } else if literal == "null" {
+                Ok(DataType::Null)
+            } else if let Ok(v) = literal.parse() {
+                Ok(DataType::Int(v))
+            } else if let Ok(v) = literal.parse() {
+                Ok(DataType::BigInt(v))
+            } else if let Ok(v) = literal.parse() {
+                Ok(DataType::Double(v))
+            } else {
+                Err(DbError::Type(format!(
+                    "Cannot tell the type of {}.",
+                    literal
+                )))
+            }
+        }
+        // Bound text is stored like a literal holding it.
+        (Value::Bound(DataType::Text(text)), None | Some(DataType::Text(_))) => {
+            Ok(text_value(text))
+        }
+        (Value::Bound(value), Some(data_type)) => {
+            coerce(value.clone(), data_type).ok_or_else(|| {
+                DbError::Type(format!(
+                    "Value {} does not match type {}.",
+                    value,
+                    data_type.type_name()
+                ))
+            })
+        }
+        (Value::Bound(value), None) => Ok(value.clone()),
+        (Value::Param(index), _) => Err(DbError::Parse(format!(
+            "Parameter ${} is not bound.",
+            index
+        ))),
+    }
+}
+
+fn is_text(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Text(_))
+}
+
+fn is_temporal(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Date(_) | DataType::DateTime(_))
+}
+
+fn is_integer(data_type: &DataType) -> bool {
+    as_i64(data_type).is_some()
+}
+
+fn is_numeric(data_type: &DataType) -> bool {
+    as_f64(data_type).is_some()
+}
+
+fn as_i64(value: &DataType) -> Option<i64> {
+    match value {
+        DataType::TinyInt(v) => Some(*v as i64),
+        DataType::SmallInt(v) => Some(*v as i64),
+        DataType::Int(v) => Some(*v as i64),
+        DataType::BigInt(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Returns the value of a number as a [`f64`], or [`None`] if `value` is not a number.
+pub fn as_f64(value: &DataType) -> Option<f64> {
+    match value {
+        DataType::Float(v) => Some(*v as f64),
+        DataType::Double(v) => Some(*v),
+        v => as_i64(v).map(|v| v as f64),
+    }
+}
+
+/// Orders the numeric types from narrowest to widest.
+fn numeric_rank(data_type: &DataType) -> Option<u8> {
+    match data_type {
+        DataType::TinyInt(_) => Some(1),
+        DataType::SmallInt(_) => Some(2),
+        DataType::Int(_) => Some(3),
+        DataType::BigInt(_) => Some(4),
+        DataType::Float(_) => Some(5),
+        DataType::Double(_) => Some(6),
+        _ => None,
+    }
+}
+
+/// Returns the point in time a YEAR, DATE or DATETIME value starts at, or [`None`] for other
+/// values. TIME is a time of day rather than a point in time.
+fn as_timestamp(value: &DataType) -> Option<i64> {
+    match value {
+        DataType::Year(v) => Some(datetime::year_start(*v)),
+        DataType::Date(v) | DataType::DateTime(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Returns the type both `a` and `b` convert to: the wider of two numeric types, DATETIME for two
+/// different types among YEAR, DATE and DATETIME, or the type itself if both are the same. `NULL`
+/// converts to any type.
+pub fn unify(a: &DataType, b: &DataType) -> Option<DataType> {
+    match (a, b) {
+        (DataType::Null, t) | (t, DataType::Null) => Some(t.clone()),
+        _ if discriminant(a) == discriminant(b) => Some(a.clone()),
+        _ if as_timestamp(a).is_some() && as_timestamp(b).is_some() => Some(DataType::DateTime(0)),
+        _ => match (numeric_rank(a), numeric_rank(b)) {
+            (Some(rank_a), Some(rank_b)) if rank_a >= rank_b => Some(a.clone()),
+            (Some(_), Some(_)) => Some(b.clone()),
+            _ => None,
+        },
+    }
+}
+
+fn unify_branches(a: &DataType, b: &DataType) -> Result<DataType> {
+    unify(a, b).ok_or_else(|| {
+        DbError::Type(format!(
+            "CASE branches must have compatible types, got {} and {}.",
+            a.type_name(),
+            b.type_name()
+        ))
+    })
+}
+
+/// Returns whether values of types `a` and `b` can be compared.
+pub fn comparable(a: &DataType, b: &DataType) -> bool {
+    unify(a, b).is_some()
+}
+
+/// Converts `value` to the type of `data_type`. Numbers convert to other numeric types if they
+/// fit, and FLOAT rejects finite numbers beyond its range. Years and dates convert to date-times
+/// and years to dates, starting on January 1. Date-times at midnight convert to dates and those
+/// at the start of a year to years. TIME converts to no other type. `NULL` stays `NULL`.
+pub fn coerce(value: DataType, data_type: &DataType) -> Option<DataType> {
+    if value == DataType::Null || discriminant(&value) == discriminant(data_type) {
+        return Some(value);
+    }
+    let integer = || {
+        as_i64(&value).or_else(|| {
+            as_f64(&value)
+                .filter(|v| v.fract() == 0.0 && *v >= i64::MIN as f64 && *v <= i64::MAX as f64)
+                .map(|v| v as i64)
+        })
+    };
+    match data_type {
+        DataType::TinyInt(_) => integer()?.try_into().ok().map(DataType::TinyInt),
+        DataType::SmallInt(_) => integer()?.try_into().ok().map(DataType::SmallInt),
+        DataType::Int(_) => integer()?.try_into().ok().map(DataType::Int),
+        DataType::BigInt(_) => integer().map(DataType::BigInt),
+        DataType::Float(_) => as_f64(&value)
+            .filter(|v| !v.is_finite() || v.abs() <= f32::MAX as f64)
+            .map(|v| DataType::Float(v as f32)),
+        DataType::Double(_) => as_f64(&value).map(DataType::Double),
+        DataType::DateTime(_) => as_timestamp(&value).map(DataType::DateTime),
+        DataType::Date(_) => match value {
+            DataType::Year(v) => Some(DataType::Date(datetime::year_start(v))),
+            DataType::DateTime(v) if v % 86_400 == 0 => Some(DataType::Date(v)),
+            _ => None,
+        },
+        DataType::Year(_) => {
+            let start = as_timestamp(&value)?;
+            datetime::year_of(start)
+                .filter(|year| datetime::year_start(*year) == start)
+                .map(DataType::Year)
+        }
+        _ => None,
+    }
+}
+
+/// Converts `value` to the type of `data_type` for `CAST`. Besides the conversions of [`coerce`],
+/// numbers are truncated toward zero when cast to an integer type, date-times are truncated to
+/// their date, year or time of day, dates to their year, any value can be cast to TEXT and text
+/// is parsed as a value of the type.
+pub fn cast(value: DataType, data_type: &DataType) -> Result<DataType> {
+    if let Some(value) = coerce(value.clone(), data_type) {
+        return Ok(value);
+    }
+    let converted = match (&value, data_type) {
+        (_, DataType::Text(_)) => Some(text_value(&value.to_string())),
+        (DataType::Text(text), _) => {
+            DataType::parse_str(data_type.clone(), &text_content(text)).ok()
+        }
+        (DataType::DateTime(v), DataType::Date(_)) => {
+            Some(DataType::Date(v - v.rem_euclid(86_400)))
+        }
+        (DataType::DateTime(v), DataType::Time(_)) => {
+            Some(DataType::Time(v.rem_euclid(86_400) as i32))
+        }
+        (DataType::Date(v) | DataType::DateTime(v), DataType::Year(_)) => {
+            datetime::year_of(*v).map(DataType::Year)
+        }
+        (value, data_type) if numeric_rank(data_type).is_some() => {
+            as_f64(value).and_then(|v| coerce(DataType::Double(v.trunc()), data_type))
+        }
+        _ => None,
+    };
+    converted.ok_or_else(|| {
+        DbError::Type(format!(
+            "Cannot cast {} to {}.",
+            display_literal(&value),
+            data_type.type_name()
+        ))
+    })
+}
+
+/// Compares two values. Numbers of different types compare by value, and years, dates and
+/// date-times by the point in time they start at. Times compare only with times, and text by
+/// [`text_content`]. Floating point numbers are ordered by [`float_order`]. Returns [`None`] if either value is `NULL` or the
+/// types cannot be compared.
+pub fn compare(a: &DataType, b: &DataType) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (as_timestamp(a), as_timestamp(b)) {
+        return Some(a.cmp(&b));
+    }
+    if let (Some(a), Some(b)) = (as_i64(a), as_i64(b)) {
+        return Some(a.cmp(&b));
+    }
+    if let (Some(a), Some(b)) = (as_f64(a), as_f64(b)) {
+        return Some(float_order(a, b));
+    }
+    if let (DataType::Text(a), DataType::Text(b)) = (a, b) {
+        return Some(text_content(a).cmp(&text_content(b)));
+    }
+    match (a, b) {
+        (DataType::Time(a), DataType::Time(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Orders two floating point numbers totally: `NaN` equals itself and comes after every other
+/// number, infinity included, and `-0.0` equals `0.0`.
+pub fn float_order(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a
+            .partial_cmp(&b)
+            .expect("Numbers other than NaN are ordered"),
+    }
+}
+
+/// Orders two values stored in an index. Values compare as [`compare`] does, so an index can be
+/// searched with a value of another numeric type than its column. `NULL` comes first, and values
+/// that cannot be compared, which an index of a single column type never holds, are equal.
+pub fn index_order(a: &DataType, b: &DataType) -> Ordering {
+    match (a, b) {
+        (DataType::Null, DataType::Null) => Ordering::Equal,
+        (DataType::Null, _) => Ordering::Less,
+        (_, DataType::Null) => Ordering::Greater,
+        (a, b) => compare(a, b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Orders two lists of sort keys, each sorting in descending order if `descending` says so.
+/// `NULL` sorts after every other value in ascending order.
+pub fn compare_sort_keys(a: &[DataType], b: &[DataType], descending: &[bool]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .zip(descending)
+        .map(|((a, b), descending)| {
+            let ordering = match (a, b) {
+                (DataType::Null, DataType::Null) => Ordering::Equal,
+                (DataType::Null, _) => Ordering::Greater,
+                (_, DataType::Null) => Ordering::Less,
+                (a, b) => compare(a, b).unwrap_or(Ordering::Equal),
+            };
+            if *descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Returns whether `ordering`, the result of [`compare`], satisfies `operator`.
+pub fn matches_operator(ordering: Option<Ordering>, operator: &str) -> bool {
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match operator {
+        "=" => ordering == Ordering::Equal,
+        "<>" => ordering != Ordering::Equal,
+        "<" => ordering == Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">" => ordering == Ordering::Greater,
+        ">=" => ordering != Ordering::Less,
+        _ => unreachable!("Invalid operator"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::tokenize, parser};
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("name", DataType::Text(String::new()), true, false),
+            Column::new("age", DataType::Int(0), true, false),
+            Column::new("score", DataType::Double(0.0), true, false),
+            Column::new("born", DataType::DateTime(0), true, false),
+        ]
+    }
+
+    fn eval(expr: &str, values: &[DataType]) -> Result<DataType, String> {
+        let evaluate = || {
+            let select = parser::parse_select(&mut tokenize(&format!("{} FROM t", expr))?)?;
+            let expr = &select.columns.expect("An expression")[0];
+            Expression::resolve(expr, &columns(), None)?.evaluate(values)
+        };
+        evaluate().map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_string_functions() {
+        let values = [
+            DataType::Text("  Hello, World ".to_string()),
+            DataType::Int(-42),
+            DataType::Double(2.345),
+        ];
+        let text = |s: &str| Ok(text_value(s));
+        assert_eq!(eval("upper(name)", &values), text("  HELLO, WORLD "));
+        assert_eq!(eval("lower(trim(name))", &values), text("hello, world"));
+        assert_eq!(eval("length(name)", &values), Ok(DataType::Int(15)));
+        assert_eq!(eval("substr(trim(name), 8)", &values), text("World"));
+        assert_eq!(eval("substr(trim(name), 0, 3)", &values), text("He"));
+        assert_eq!(eval("substr(trim(name), 20, 3)", &values), text(""));
+        assert_eq!(
+            eval("replace(name, 'l', 'L')", &values),
+            text("  HeLLo, WorLd ")
+        );
+        assert_eq!(
+            eval("trim(name) || '!' || age", &values),
+            text("Hello, World!-42")
+        );
+        assert_eq!(eval("concat('a', null)", &values), Ok(DataType::Null));
+        assert!(eval("upper(age)", &values)
+            .unwrap_err()
+            .contains("UPPER expects TEXT as argument 1, got INT"));
+        assert!(eval("substr(name, 'a')", &values).is_err());
+        assert!(eval("trim(name, name)", &values).is_err());
+        assert!(eval("nope(name)", &values).is_err());
+    }
+
+    #[test]
+    fn test_numeric_functions() {
+        let values = [DataType::Null, DataType::Int(-42), DataType::Double(2.345)];
+        assert_eq!(eval("abs(age)", &values), Ok(DataType::Int(42)));
+        assert_eq!(eval("round(score, 2)", &values), Ok(DataType::Double(2.35)));
+        assert_eq!(eval("round(score)", &values), Ok(DataType::Double(2.0)));
+        assert_eq!(eval("round(age, -1)", &values), Ok(DataType::Int(-40)));
+        assert_eq!(eval("floor(score)", &values), Ok(DataType::Double(2.0)));
+        assert_eq!(eval("ceil(score)", &values), Ok(DataType::Double(3.0)));
+        assert_eq!(eval("mod(age, 5)", &values), Ok(DataType::Int(-2)));
+        assert_eq!(
+            eval("mod(score, 1)", &values),
+            Ok(DataType::Double(2.345 % 1.0))
+        );
+        assert!(eval("mod(age, 0)", &values).is_err());
+        assert!(eval("abs(name)", &values).is_err());
+        assert_eq!(
+            eval("coalesce(name, 'none')", &values),
+            Ok(text_value("none"))
+        );
+        assert_eq!(
+            eval("coalesce(age, 1.5)", &values),
+            Ok(DataType::Double(-42.0))
+        );
+        assert!(eval("coalesce(age, 'none')", &values).is_err());
+        assert_eq!(eval("nullif(age, -42)", &values), Ok(DataType::Null));
+        assert_eq!(eval("nullif(age, 1)", &values), Ok(DataType::Int(-42)));
+    }
+
+    #[test]
+    fn test_date_functions() {
+        let born = DataType::parse_str(DataType::DateTime(0), "2024-02-29 13:45:10").unwrap();
+        let values = [DataType::Null, DataType::Null, DataType::Null, born];
+        let show = |expr: &str| eval(expr, &values).map(|v| v.to_string());
+        assert_eq!(show("extract(year from born)"), Ok("2024".to_string()));
+        assert_eq!(
+            show("extract(epoch from '1970-01-02')"),
+            Ok("86400".to_string())
+        );
+        assert_eq!(
+            show("date_trunc('month', born)"),
+            Ok("2024-02-01 00:00:00".to_string())
+        );
+        assert_eq!(
+            show("born + interval '1 year' - interval '2 hours'"),
+            Ok("2025-02-28 11:45:10".to_string())
+        );
+        assert_eq!(
+            show("'2024-01-31' + interval '1' month"),
+            Ok("2024-02-29".to_string())
+        );
+        assert_eq!(show("datediff(born, '2024-01-01')"), Ok("59".to_string()));
+        // Unquoted dates are read as dates, as in comparisons.
+        assert_eq!(show("datediff(born, 2024-01-01)"), Ok("59".to_string()));
+        assert_eq!(
+            show("extract(year from 2021-06-01)"),
+            Ok("2021".to_string())
+        );
+        assert_eq!(
+            eval("born + interval '999999999999 days'", &values),
+            Err("2024-02-29 13:45:10 shifted by the interval is out of range.".to_string())
+        );
+        assert_eq!(
+            eval("extract(year from now())", &values),
+            eval("extract(year from current_date)", &values)
+        );
+        assert!(eval("extract(year from age)", &values).is_err());
+        assert!(eval("date_trunc(name, born)", &values).is_err());
+        assert!(eval("name + interval '1 day'", &values).is_err());
+        assert!(eval("born + interval '1 fortnight'", &values).is_err());
+    }
+
+    #[test]
+    fn test_case() {
+        let values = [DataType::Null, DataType::Int(-42), DataType::Double(2.5)];
+        let grade = "case when score >= 3 then 'high' when score >= 2 then 'mid' else 'low' end";
+        assert_eq!(eval(grade, &values), Ok(text_value("mid")));
+        assert_eq!(
+            eval("case age when 1 then 'one' end", &values),
+            Ok(DataType::Null)
+        );
+        // The result takes the widest numeric type of the branches.
+        assert_eq!(
+            eval("case when age < 0 then age else score end", &values),
+            Ok(DataType::Double(-42.0))
+        );
+        assert_eq!(
+            eval("case when name = 'x' then 1 else 2 end", &values),
+            Ok(DataType::Int(2))
+        );
+        assert!(eval("case when age < 0 then age else 'none' end", &values)
+            .unwrap_err()
+            .contains("CASE branches must have compatible types, got INT and TEXT"));
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(
+            compare(&DataType::Int(1), &DataType::Double(1.5)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare(&DataType::TinyInt(3), &DataType::BigInt(3)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare(&DataType::Null, &DataType::Null), None);
+        assert_eq!(
+            compare(&DataType::Int(1), &DataType::Text("1".to_string())),
+            None
+        );
+        assert_eq!(coerce(DataType::BigInt(300), &DataType::TinyInt(0)), None);
+        assert_eq!(
+            coerce(DataType::Double(3.0), &DataType::Int(0)),
+            Some(DataType::Int(3))
+        );
+        assert_eq!(
+            compare(&DataType::Date(86_400), &DataType::DateTime(86_401)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            coerce(DataType::DateTime(86_400), &DataType::Date(0)),
+            Some(DataType::Date(86_400))
+        );
+        assert_eq!(coerce(DataType::DateTime(1), &DataType::Date(0)), None);
+        assert_eq!(coerce(DataType::Double(1e300), &DataType::Float(0.0)), None);
+        assert_eq!(
+            coerce(DataType::Double(f64::INFINITY), &DataType::Float(0.0)),
+            Some(DataType::Float(f32::INFINITY))
+        );
+
+        let year = DataType::parse_str(DataType::Year(0), "2024").unwrap();
+        let new_year = DataType::parse_str(DataType::Date(0), "2024-01-01").unwrap();
+        let later = DataType::parse_str(DataType::DateTime(0), "2024-03-01 12:00:00").unwrap();
+        assert_eq!(compare(&year, &new_year), Some(Ordering::Equal));
+        assert_eq!(compare(&year, &later), Some(Ordering::Less));
+        assert_eq!(
+            coerce(year.clone(), &DataType::Date(0)),
+            Some(new_year.clone())
+        );
+        assert_eq!(coerce(new_year, &DataType::Year(0)), Some(year.clone()));
+        assert_eq!(coerce(later.clone(), &DataType::Year(0)), None);
+        assert_eq!(cast(later.clone(), &DataType::Year(0)).ok(), Some(year));
+        assert_eq!(
+            cast(later.clone(), &DataType::Time(0)).ok(),
+            DataType::parse_str(DataType::Time(0), "12:00:00").ok()
+        );
+        assert_eq!(
+            compare(&DataType::Time(60), &DataType::Time(30)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare(&DataType::Time(0), &later), None);
+        assert_eq!(coerce(later, &DataType::Time(0)), None);
+    }
+
+    #[test]
+    fn test_float_order() {
+        let nan = DataType::Double(f64::NAN);
+        assert_eq!(compare(&nan, &nan), Some(Ordering::Equal));
+        assert_eq!(
+            compare(&nan, &DataType::Double(f64::INFINITY)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare(&DataType::Float(f32::NAN), &DataType::BigInt(i64::MAX)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare(&DataType::Double(-0.0), &DataType::Float(0.0)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare(
+                &DataType::Double(f64::NEG_INFINITY),
+                &DataType::TinyInt(-128)
+            ),
+            Some(Ordering::Less)
+        );
+        let mut values = [
+            3.0,
+            f64::NAN,
+            f64::INFINITY,
+            -0.0,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            0.0,
+        ]
+        .map(DataType::Double)
+        .to_vec();
+        values.push(DataType::Null);
+        values.sort_by(index_order);
+        assert_eq!(
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            ["NULL", "-inf", "-0", "0", "3", "inf", "NaN", "NaN"]
+        );
+    }
+
+    #[test]
+    fn test_cast() {
+        let values = [
+            DataType::Text("42".to_string()),
+            DataType::Int(7),
+            DataType::Double(-2.75),
+            DataType::DateTime(86_400 + 3_600),
+        ];
+        assert_eq!(eval("cast(name as int)", &values), Ok(DataType::Int(42)));
+        assert_eq!(eval("cast(score as int)", &values), Ok(DataType::Int(-2)));
+        assert_eq!(
+            eval("cast(age as double)", &values),
+            Ok(DataType::Double(7.0))
+        );
+        assert_eq!(
+            eval("cast(age as varchar(10)) || '!'", &values),
+            Ok(text_value("7!"))
+        );
+        assert_eq!(
+            eval("cast(born as date)", &values),
+            Ok(DataType::Date(86_400))
+        );
+        assert_eq!(
+            eval("cast('2021-03-01' as date)", &values),
+            Ok(DataType::Date(1_614_556_800))
+        );
+        assert_eq!(eval("cast(null as int)", &values), Ok(DataType::Null));
+        assert_eq!(
+            eval("cast(300 as tinyint)", &values),
+            Err("Cannot cast 300 to TINYINT.".to_string())
+        );
+        assert_eq!(
+            eval("cast('abc' as int)", &values),
+            Err("Cannot cast 'abc' to INT.".to_string())
+        );
+        assert!(eval("cast(age int)", &values).is_err());
+        assert!(eval("cast(age as number)", &values).is_err());
+    }
+}
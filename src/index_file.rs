@@ -1,5 +1,8 @@
+use std::cmp::Ordering;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, Write};
+use std::ops::Bound;
+use std::path::Path;
 
 use owo_colors::OwoColorize;
 
@@ -8,7 +11,9 @@ use crate::record::Record;
 use crate::utils::rainbow;
 use crate::{
     constants::{DataType, PageType},
-    database_file::DatabaseFile,
+    database_file::{index_file_path, DatabaseFile},
+    error::{DbError, Result},
+    expression::index_order,
     read_write_types::ReadWriteTypes,
 };
 /// Indexfile page structure:
@@ -36,19 +41,22 @@ pub struct IndexFile {
 }
 
 impl DatabaseFile for IndexFile {
-    fn set_len(&self, length: u64) {
-        self.file
-            .set_len(length)
-            .expect("Error setting file length");
+    fn set_len(&self, length: u64) -> Result<()> {
+        Ok(self.file.set_len(length)?)
     }
 
-    fn len(&self) -> u64 {
-        self.file.metadata().expect("Error getting metadata").len()
+    fn len(&self) -> Result<u64> {
+        Ok(self.file.metadata()?.len())
     }
 }
 
 impl ReadWriteTypes for IndexFile {}
 
+/// The most bytes a cell and its offset may take on an interior page, where cells are largest.
+/// Two such cells fit below the page header, so splitting a page until the new cell fits always
+/// ends before the page is left with a single cell.
+const MAX_CELL_SIZE: u16 = (PAGE_SIZE as u16 - 0x10) / 2;
+
 impl Read for IndexFile {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
         self.file.read(buf)
@@ -72,20 +80,31 @@ impl Seek for IndexFile {
 }
 
 impl IndexFile {
-    pub fn new(table_name: &str, column_name: &str, dir: &str) -> Self {
-        let path = format!("{}/{}.{}.ndx", dir, table_name, column_name);
+    pub fn new(table_name: &str, column_name: &str, dir: &str) -> Result<Self> {
+        IndexFile::open(index_file_path(dir, table_name, column_name))
+    }
+
+    /// Opens the index file at `path` as it is named on disk, creating it with an empty root
+    /// page if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
             .truncate(false)
-            .open(path.clone())
-            .expect("Error opening index file");
+            .open(path)?;
         let mut idx = Self { file };
-        if idx.len() == 0 {
-            idx.create_page(0xFFFFFFFF, PageType::IndexLeaf);
+        if idx.len()? == 0 {
+            idx.create_page(0xFFFFFFFF, PageType::IndexLeaf)?;
         }
-        idx
+        Ok(idx)
+    }
+
+    /// Removes every value, leaving the file with a single empty root page.
+    pub fn truncate(&mut self) -> Result<()> {
+        self.set_len(0)?;
+        self.create_page(0xFFFFFFFF, PageType::IndexLeaf)?;
+        Ok(())
     }
 
     /// Reads a value from the index file at `page` and `offset`.
@@ -96,20 +115,20 @@ impl IndexFile {
     ///
     /// Returns:
     ///    * [`Option<DataType>`]: The value read from the index file. Returns [`None`] if the payload size is 0.
-    pub fn read_index_value(&mut self, page: u32, offset: u16) -> Option<DataType> {
-        let page_type = self.get_page_type(page);
-        self.seek_to_page_offset(page, offset);
+    pub fn read_index_value(&mut self, page: u32, offset: u16) -> Result<Option<DataType>> {
+        let page_type = self.get_page_type(page)?;
+        self.seek_to_page_offset(page, offset)?;
         if page_type == PageType::IndexInterior {
-            self.skip_bytes(4);
+            self.skip_bytes(4)?;
         }
-        let payload_size = self.read_u16();
+        let payload_size = self.read_u16()?;
         if payload_size == 0 {
-            return None;
+            return Ok(None);
         }
-        self.skip_bytes(1);
-        let payload_type = self.read_u8();
-        let value = self.read_value(payload_type);
-        Some(value)
+        self.skip_bytes(1)?;
+        let payload_type = self.read_u8()?;
+        let value = self.read_value(payload_type)?;
+        Ok(Some(value))
     }
 
     /// Reads a full index value from the index file at `page` and `offset`.
@@ -126,206 +145,216 @@ impl IndexFile {
         &mut self,
         page: u32,
         offset: u16,
-    ) -> (Option<DataType>, Option<u32>, Vec<u32>) {
-        let page_type = self.get_page_type(page);
-        self.seek_to_page_offset(page, offset);
+    ) -> Result<(Option<DataType>, Option<u32>, Vec<u32>)> {
+        let page_type = self.get_page_type(page)?;
+        self.seek_to_page_offset(page, offset)?;
         let child_page = match page_type {
-            PageType::IndexInterior => Some(self.read_u32()),
+            PageType::IndexInterior => Some(self.read_u32()?),
             _ => None,
         };
-        let payload_size = self.read_u16();
+        let payload_size = self.read_u16()?;
         if payload_size == 0 {
-            return (None, child_page, vec![]);
+            return Ok((None, child_page, vec![]));
         }
-        let num_row_ids = self.read_u8();
-        let payload_type = self.read_u8();
-        let value = self.read_value(payload_type);
+        let num_row_ids = self.read_u8()?;
+        let payload_type = self.read_u8()?;
+        let value = self.read_value(payload_type)?;
         if num_row_ids == 0 {
-            return (Some(value), child_page, vec![]);
+            return Ok((Some(value), child_page, vec![]));
         }
         let row_ids = (0..num_row_ids)
             .map(|_| self.read_u32())
-            .collect::<Vec<u32>>();
-        (Some(value), child_page, row_ids)
+            .collect::<Result<Vec<u32>>>()?;
+        Ok((Some(value), child_page, row_ids))
     }
 
     pub fn read_full_index_value_index(
         &mut self,
         page: u32,
         index: u16,
-    ) -> (Option<DataType>, Option<u32>, Vec<u32>) {
-        let offset = self.get_cell_offset(page, index);
+    ) -> Result<(Option<DataType>, Option<u32>, Vec<u32>)> {
+        let offset = self.get_cell_offset(page, index)?;
         self.read_full_index_value(page, offset)
     }
 
-    pub fn create_interior_page(&mut self, parent: u32, child: u32) -> u32 {
-        let page = self.create_page(parent, PageType::IndexInterior);
-        self.set_rightmost_child(page, child);
-        page
+    pub fn create_interior_page(&mut self, parent: u32, child: u32) -> Result<u32> {
+        let page = self.create_page(parent, PageType::IndexInterior)?;
+        self.set_rightmost_child(page, child)?;
+        Ok(page)
     }
 
-    pub fn split_page(&mut self, page: u32, split_value: &DataType) -> u32 {
-        let page_type = self.get_page_type(page);
-        let mut parent_page = self.get_parent_page(page);
+    pub fn split_page(&mut self, page: u32, split_value: &DataType) -> Result<u32> {
+        let page_type = self.get_page_type(page)?;
+        let mut parent_page = self.get_parent_page(page)?;
         if parent_page == 0xFFFFFFFF {
-            parent_page = self.create_interior_page(parent_page, page);
+            parent_page = self.create_interior_page(parent_page, page)?;
             // Set the parent page
-            self.update_parent_page(page, parent_page);
+            self.update_parent_page(page, parent_page)?;
         }
 
-        let middle = self.get_num_cells(page) / 2;
-        let middle_offset = self.get_cell_offset(page, middle);
-        let (value, child_pointer, row_ids) = self.read_full_index_value(page, middle_offset);
+        let middle = self.get_num_cells(page)? / 2;
+        let middle_offset = self.get_cell_offset(page, middle)?;
+        let (value, child_pointer, row_ids) = self.read_full_index_value(page, middle_offset)?;
 
         // Create a new sibling page
         let new_page = match child_pointer {
-            Some(p) => self.create_interior_page(parent_page, p),
-            None => self.create_page(parent_page, PageType::IndexLeaf),
+            Some(p) => self.create_interior_page(parent_page, p)?,
+            None => self.create_page(parent_page, PageType::IndexLeaf)?,
         };
 
-        let value = match value {
-            Some(v) => v,
-            None => panic!("Value must be present"),
-        };
+        let value = value.ok_or_else(|| {
+            DbError::Corruption(format!("Cell {} on page {} has no value", middle, page))
+        })?;
         let num_row_ids = row_ids.len();
-        self.write_cell(parent_page, &value, row_ids, new_page);
+        self.write_cell(parent_page, &value, row_ids, new_page)?;
 
-        self.seek_to_page_offset(page, middle_offset);
+        self.seek_to_page_offset(page, middle_offset)?;
         let cell_size = self.cell_size(&value, num_row_ids, page_type);
         let zero_bytes = vec![0; cell_size as usize];
-        self.write_all(&zero_bytes)
-            .expect("Error writing zero bytes");
+        self.write_all(&zero_bytes)?;
 
-        self.move_cells(page, new_page, middle);
+        self.move_cells(page, new_page, middle)?;
         if page_type == PageType::IndexInterior {
-            self.update_parent_pages(new_page);
+            self.update_parent_pages(new_page)?;
         }
 
-        self.seek_to_page_offset(page, 0x10 + middle * 2);
-        self.write_u16(0);
+        self.seek_to_page_offset(page, 0x10 + middle * 2)?;
+        self.write_u16(0)?;
 
-        let remaining_cells_offset = self.get_cell_offset(page, middle - 1);
-        self.seek_to_page_offset(page, 0x02);
-        self.write_u16(middle);
-        self.write_u16(remaining_cells_offset);
+        let remaining_cells_offset = self.get_cell_offset(page, middle - 1)?;
+        self.seek_to_page_offset(page, 0x02)?;
+        self.write_u16(middle)?;
+        self.write_u16(remaining_cells_offset)?;
 
-        if split_value > &value {
-            new_page
+        if index_order(split_value, &value) == Ordering::Greater {
+            Ok(new_page)
         } else {
-            page
+            Ok(page)
         }
     }
 
-    fn update_parent_pages(&mut self, new_parent: u32) {
-        let num_cells = self.get_num_cells(new_parent);
+    fn update_parent_pages(&mut self, new_parent: u32) -> Result<()> {
+        let num_cells = self.get_num_cells(new_parent)?;
         for i in 0..num_cells {
-            let offset = self.get_cell_offset(new_parent, i);
-            let (_, child, _) = self.read_full_index_value(new_parent, offset);
+            let offset = self.get_cell_offset(new_parent, i)?;
+            let (_, child, _) = self.read_full_index_value(new_parent, offset)?;
             if let Some(child) = child {
-                self.update_parent_page(child, new_parent);
+                self.update_parent_page(child, new_parent)?;
             }
         }
+        Ok(())
     }
 
-    fn update_parent_page(&mut self, page: u32, parent: u32) {
-        self.seek_to_page_offset(page, 0x0A);
-        self.write_u32(parent);
+    fn update_parent_page(&mut self, page: u32, parent: u32) -> Result<()> {
+        self.seek_to_page_offset(page, 0x0A)?;
+        self.write_u32(parent)?;
+        Ok(())
     }
 
-    fn find_value_position(&mut self, page: u32, value: &DataType) -> Option<u16> {
-        let num_cells = self.get_num_cells(page);
+    /// Returns the cell of `page` holding `value`, or else the last cell before it. The first
+    /// cell of an interior page comes before every value, while [`None`] means that `value` comes
+    /// before every cell of a leaf page or that the page is empty.
+    fn find_value_position(&mut self, page: u32, value: &DataType) -> Result<Option<u16>> {
+        let num_cells = self.get_num_cells(page)?;
         if num_cells == 0 {
-            return None;
+            return Ok(None);
         }
         let mut mid = num_cells / 2;
         let mut low = 0;
         let mut high = num_cells - 1;
         while low < high {
-            let current_offset = self.get_cell_offset(page, mid);
-            let currtent_value = self.read_index_value(page, current_offset);
+            let current_offset = self.get_cell_offset(page, mid)?;
+            let currtent_value = self.read_index_value(page, current_offset)?;
             if let Some(currtent_value) = currtent_value {
-                if currtent_value == *value {
-                    return Some(mid);
-                } else if currtent_value < *value {
-                    low = mid;
-                } else {
-                    high = mid - 1;
+                match index_order(&currtent_value, value) {
+                    Ordering::Equal => return Ok(Some(mid)),
+                    Ordering::Less => low = mid,
+                    Ordering::Greater => high = mid - 1,
                 }
             } else {
                 high = mid - 1;
             }
             mid = (low + high).div_ceil(2);
         }
-        Some(mid)
+        if mid == 0 && self.get_page_type(page)? == PageType::IndexLeaf {
+            let offset = self.get_cell_offset(page, 0)?;
+            let first = self.read_index_value(page, offset)?;
+            if first.is_some_and(|first| index_order(&first, value) == Ordering::Greater) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(mid))
     }
 
-    pub fn move_cells(&mut self, source_page: u32, destination_page: u32, preceding_cell: u16) {
-        let cell_offset = self.get_cell_offset(source_page, preceding_cell);
-        let num_cells = self.get_num_cells(source_page);
+    pub fn move_cells(
+        &mut self,
+        source_page: u32,
+        destination_page: u32,
+        preceding_cell: u16,
+    ) -> Result<()> {
+        let cell_offset = self.get_cell_offset(source_page, preceding_cell)?;
+        let num_cells = self.get_num_cells(source_page)?;
         let num_cells_to_move = num_cells - preceding_cell - 1;
-        let content_start = self.get_content_start(source_page);
+        let content_start = self.get_content_start(source_page)?;
 
         // Read the bytes to be moved
         let mut cell_bytes = vec![0; (cell_offset - content_start) as usize];
-        self.seek_to_page_offset(source_page, content_start);
-        self.read_exact(&mut cell_bytes)
-            .expect("Error reading cell bytes");
+        self.seek_to_page_offset(source_page, content_start)?;
+        self.read_exact(&mut cell_bytes)?;
 
         // Read the offsets of the cells to be moved
         let mut cell_offsets = vec![0; num_cells_to_move as usize * 2];
         let offset_location = 0x10 + (preceding_cell + 1) * 2;
-        self.seek_to_page_offset(source_page, offset_location);
-        self.read_exact(&mut cell_offsets)
-            .expect("Error reading cell offsets");
+        self.seek_to_page_offset(source_page, offset_location)?;
+        self.read_exact(&mut cell_offsets)?;
 
         // Overwrite the cells to be moved with zero bytes
         let zero_bytes = vec![0; (cell_offset - content_start) as usize];
-        self.seek_to_page_offset(source_page, content_start);
-        self.write_all(&zero_bytes)
-            .expect("Error writing zero bytes");
+        self.seek_to_page_offset(source_page, content_start)?;
+        self.write_all(&zero_bytes)?;
 
         // Write the cells to the destination page
-        let new_content_start = self.get_content_start(destination_page) - cell_bytes.len() as u16;
-        self.seek_to_page_offset(destination_page, new_content_start);
-        self.write_all(&cell_bytes)
-            .expect("Error writing cell bytes");
+        let new_content_start = self.get_content_start(destination_page)? - cell_bytes.len() as u16;
+        self.seek_to_page_offset(destination_page, new_content_start)?;
+        self.write_all(&cell_bytes)?;
 
         // Set the new content start on the destination page
-        self.seek_to_page_offset(destination_page, 0x04);
-        self.write_u16(new_content_start);
+        self.seek_to_page_offset(destination_page, 0x04)?;
+        self.write_u16(new_content_start)?;
 
         // Write the modified offsets to the destination page
         let offset_diff = content_start as i32 - new_content_start as i32;
-        let dest_num_cells = self.get_num_cells(destination_page);
-        self.seek_to_page_offset(destination_page, 0x10 + dest_num_cells * 2);
+        let dest_num_cells = self.get_num_cells(destination_page)?;
+        self.seek_to_page_offset(destination_page, 0x10 + dest_num_cells * 2)?;
         let modified_offsets = cell_offsets
             .chunks(2)
-            .map(|offset| (u16::from_le_bytes([offset[0], offset[1]]) as i32 - offset_diff as i32))
+            .map(|offset| u16::from_le_bytes([offset[0], offset[1]]) as i32 - offset_diff)
             .flat_map(|offset| (offset as u16).to_le_bytes().to_vec())
             .collect::<Vec<u8>>();
-        self.write_all(&modified_offsets)
-            .expect("Error writing modified offsets");
+        self.write_all(&modified_offsets)?;
 
         // Update the number of cells on the destination page
-        self.seek_to_page_offset(destination_page, 0x02);
-        self.write_u16(num_cells_to_move + dest_num_cells);
+        self.seek_to_page_offset(destination_page, 0x02)?;
+        self.write_u16(num_cells_to_move + dest_num_cells)?;
         // Update the number of cells on the source page
-        self.seek_to_page_offset(source_page, 0x02);
-        self.write_u16(preceding_cell);
+        self.seek_to_page_offset(source_page, 0x02)?;
+        self.write_u16(preceding_cell)?;
 
         // Fill the moved offsets with zero bytes
         let zero_bytes = vec![0; cell_offsets.len()];
-        self.seek_to_page_offset(source_page, offset_location);
-        self.write_all(&zero_bytes)
-            .expect("Error writing zero bytes");
+        self.seek_to_page_offset(source_page, offset_location)?;
+        self.write_all(&zero_bytes)?;
+        Ok(())
     }
 
-    pub fn initialize_index(&mut self, values: Vec<Record>, column_index: usize) {
+    pub fn initialize_index(&mut self, values: Vec<Record>, column_index: usize) -> Result<()> {
         let mut values_and_ids: Vec<(DataType, Vec<u32>)> =
             values.iter().fold(Vec::new(), |mut acc, r| {
                 let id = r.row_id;
                 let value = &r.values[column_index];
-                let idx = acc.iter().position(|(v, _)| v == value);
+                let idx = acc
+                    .iter()
+                    .position(|(v, _)| index_order(v, value) == Ordering::Equal);
                 if let Some(idx) = idx {
                     acc[idx].1.push(id);
                 } else {
@@ -333,482 +362,544 @@ impl IndexFile {
                 }
                 acc
             });
-        values_and_ids.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+        values_and_ids.sort_by(|(a, _), (b, _)| index_order(a, b));
         for (value, ids) in values_and_ids {
-            let page = match self.find_value(&value) {
+            let page = match self.find_value(&value)? {
                 Ok((page, _)) => page,
                 Err((page, _)) => page,
             };
-            self.write_cell(page, &value, ids.to_vec(), 0xFFFFFFFF);
+            self.write_cell(page, &value, ids.to_vec(), 0xFFFFFFFF)?;
         }
+        Ok(())
     }
 
-    fn write_cell(&mut self, page: u32, value: &DataType, row_ids: Vec<u32>, child_page: u32) {
-        let page_type = self.get_page_type(page);
+    fn write_cell(
+        &mut self,
+        page: u32,
+        value: &DataType,
+        row_ids: Vec<u32>,
+        child_page: u32,
+    ) -> Result<()> {
+        check_cell_size(value, row_ids.len())?;
+        let page_type = self.get_page_type(page)?;
         let payload_size = self.payload_size(value, row_ids.len());
         let cell_size = self.cell_size(value, row_ids.len(), page_type);
 
-        let page = if self.should_split(page, cell_size as i32) {
-            let page = self.split_page(page, value);
+        let mut page = page;
+        while self.should_split(page, cell_size as i32)? {
+            if self.get_num_cells(page)? < 2 {
+                return Err(DbError::Corruption(format!(
+                    "Cell of {} does not fit on page {}",
+                    value, page
+                )));
+            }
+            page = self.split_page(page, value)?;
             if child_page != 0xFFFFFFFF {
-                self.update_parent_page(child_page, page);
+                self.update_parent_page(child_page, page)?;
             }
-            page
-        } else {
-            page
-        };
+        }
 
-        let insert_point = match self.find_value_position(page, value) {
+        let insert_point = match self.find_value_position(page, value)? {
             Some(offset) => offset as i32,
             None => -1,
         };
-        let offset = if insert_point == self.get_num_cells(page) as i32 {
-            self.set_content_start(page, cell_size as i32)
+        let offset = if insert_point == self.get_num_cells(page)? as i32 {
+            self.set_content_start(page, cell_size as i32)?
         } else {
-            self.shift_cells(page, insert_point, cell_size as i32, 1) as u16
+            self.shift_cells(page, insert_point, cell_size as i32, 1)? as u16
         };
-        self.increment_num_cells(page);
+        self.increment_num_cells(page)?;
 
-        self.seek_to_page_offset(page, 0x10 + 2 * (insert_point + 1) as u16);
-        self.write_u16(offset);
+        self.seek_to_page_offset(page, 0x10 + 2 * (insert_point + 1) as u16)?;
+        self.write_u16(offset)?;
 
-        self.seek_to_page_offset(page, offset);
+        self.seek_to_page_offset(page, offset)?;
         if page_type == PageType::IndexInterior {
             if child_page == 0xFFFFFFFF {
-                panic!("Child page must be set for interior index pages");
+                return Err(DbError::Corruption(format!(
+                    "No child page for a cell on interior page {}",
+                    page
+                )));
             }
-            self.write_u32(child_page);
+            self.write_u32(child_page)?;
         }
-        self.write_u16(payload_size);
-        self.write_u8(row_ids.len() as u8);
+        self.write_u16(payload_size)?;
+        self.write_u8(row_ids.len() as u8)?;
 
         let value_type = value.into();
-        self.write_u8(value_type);
+        self.write_u8(value_type)?;
 
-        self.write_value(value.clone());
+        self.write_value(value.clone())?;
         let row_ids_bytes = row_ids
             .iter()
             .flat_map(|id| id.to_le_bytes().to_vec())
             .collect::<Vec<u8>>();
-        self.write_all(&row_ids_bytes)
-            .expect("Error writing row IDs");
+        self.write_all(&row_ids_bytes)?;
+        Ok(())
     }
 
-    pub fn update_record(&mut self, row_id: u32, old_value: &DataType, new_value: &DataType) {
-        self.remove_item_from_cell(row_id, old_value);
-        self.insert_item_into_cell(row_id, new_value);
+    pub fn update_record(
+        &mut self,
+        row_id: u32,
+        old_value: &DataType,
+        new_value: &DataType,
+    ) -> Result<()> {
+        self.remove_item_from_cell(row_id, old_value)?;
+        if let Err(e) = self.insert_item_into_cell(row_id, new_value) {
+            self.insert_item_into_cell(row_id, old_value)?;
+            return Err(e);
+        }
+        Ok(())
     }
 
-    pub fn remove_item_from_cell(&mut self, row_id: u32, value: &DataType) {
+    pub fn remove_item_from_cell(&mut self, row_id: u32, value: &DataType) -> Result<()> {
         let (page, index) = self
-            .find_value(value)
-            .unwrap_or_else(|(_, _)| panic!("Value not found: {}", value));
-        let offset = self.get_cell_offset(page, index);
-        let (_, _, mut row_ids) = self.read_full_index_value(page, offset);
+            .find_value(value)?
+            .map_err(|_| DbError::Corruption(format!("Value {} not found in index", value)))?;
+        let offset = self.get_cell_offset(page, index)?;
+        let (_, _, mut row_ids) = self.read_full_index_value(page, offset)?;
         let id_index = match row_ids.iter().position(|id| id == &row_id) {
             Some(idx) => idx,
-            None => panic!("Row ID not found in cell"),
+            None => {
+                return Err(DbError::Corruption(format!(
+                    "Row ID {} not found in index cell of {}",
+                    row_id, value
+                )))
+            }
         };
         row_ids.remove(id_index);
-        let page_type = self.get_page_type(page);
-        self.seek_to_page_offset(page, offset);
+        let page_type = self.get_page_type(page)?;
+        self.seek_to_page_offset(page, offset)?;
         if page_type == PageType::IndexInterior {
-            self.skip_bytes(4);
+            self.skip_bytes(4)?;
         }
-        let payload_size = self.read_u16() - 4;
-        self.skip_bytes(-2);
-        self.write_u16(payload_size);
-        self.write_u8(row_ids.len() as u8);
+        let payload_size = self.read_u16()? - 4;
+        self.skip_bytes(-2)?;
+        self.write_u16(payload_size)?;
+        self.write_u8(row_ids.len() as u8)?;
         let value_size = value.size();
-        self.skip_bytes(value_size as i64 + 1);
+        self.skip_bytes(value_size as i64 + 1)?;
         let row_ids_bytes = row_ids
             .iter()
             .flat_map(|id| id.to_le_bytes().to_vec())
             .collect::<Vec<u8>>();
-        self.write_all(&row_ids_bytes)
-            .expect("Error writing row IDs");
-        self.shift_cells(page, index as i32 - 1, -4, 0);
+        self.write_all(&row_ids_bytes)?;
+        self.shift_cells(page, index as i32 - 1, -4, 0)?;
 
         if row_ids.is_empty() {
-            self.remove_cell(page, index, true);
+            self.remove_cell(page, index, true)?;
         }
+        Ok(())
     }
 
-    fn remove_cell(&mut self, page: u32, index: u16, steal: bool) {
-        let page_type = self.get_page_type(page);
-        let offset = self.get_cell_offset(page, index);
-        let (_, child_page, _) = self.read_full_index_value(page, offset);
-        self.seek_to_page_offset(page, offset);
+    fn remove_cell(&mut self, page: u32, index: u16, steal: bool) -> Result<()> {
+        let page_type = self.get_page_type(page)?;
+        let offset = self.get_cell_offset(page, index)?;
+        let (_, child_page, _) = self.read_full_index_value(page, offset)?;
+        self.seek_to_page_offset(page, offset)?;
         if page_type == PageType::IndexInterior {
-            self.skip_bytes(4);
+            self.skip_bytes(4)?;
         }
-        let cell_size = (self.read_u16()
+        let cell_size = (self.read_u16()?
             + 2
             + match page_type {
                 PageType::IndexInterior => 4,
                 _ => 0,
             }) as i32;
 
-        self.shift_cells(page, index as i32 - 1, -cell_size, -1);
-        let num_cells = self.get_num_cells(page) - 1;
-        self.seek_to_page_offset(page, 0x02); // Number of cells
-        self.write_u16(num_cells);
-        let content_start = self.get_content_start(page);
+        self.shift_cells(page, index as i32 - 1, -cell_size, -1)?;
+        let num_cells = self.get_num_cells(page)? - 1;
+        self.seek_to_page_offset(page, 0x02)?; // Number of cells
+        self.write_u16(num_cells)?;
+        let content_start = self.get_content_start(page)?;
         let cell_pointer_list_end = 0x10 + num_cells * 2;
-        self.seek_to_page_offset(page, cell_pointer_list_end);
+        self.seek_to_page_offset(page, cell_pointer_list_end)?;
         let zero_bytes = vec![0; (content_start - cell_pointer_list_end) as usize];
-        self.write_all(&zero_bytes)
-            .expect("Error writing zero bytes");
+        self.write_all(&zero_bytes)?;
 
         if page_type == PageType::IndexInterior && steal {
-            let (_, child_to_steal_from, _) = self.read_full_index_value_index(page, index - 1);
-            self.steal_from_child(page, child_to_steal_from.unwrap(), child_page.unwrap());
-        } else if page_type == PageType::IndexLeaf && num_cells == 0 {
-            self.remove_page(page, None);
+            let (_, child_to_steal_from, _) = self.read_full_index_value_index(page, index - 1)?;
+            let (Some(child_to_steal_from), Some(child_page)) = (child_to_steal_from, child_page)
+            else {
+                return Err(DbError::Corruption(format!(
+                    "Cell {} on interior page {} has no child page",
+                    index, page
+                )));
+            };
+            self.steal_from_child(page, child_to_steal_from, child_page)?;
+        } else if page_type == PageType::IndexLeaf
+            && num_cells == 0
+            && self.get_parent_page(page)? != 0xFFFFFFFF
+        {
+            // An empty root page stays, as in a new index.
+            self.remove_page(page, None)?;
         }
+        Ok(())
     }
 
-    fn remove_page(&mut self, page: u32, child: Option<u32>) {
-        let parent_page = self.get_parent_page(page);
+    fn remove_page(&mut self, page: u32, child: Option<u32>) -> Result<()> {
+        let parent_page = self.get_parent_page(page)?;
         if parent_page == 0xFFFFFFFF && child.is_none() {
-            let rightmost_child = self.get_rightmost_child(page);
-            self.seek_to_page_offset(rightmost_child, 0x0A);
-            self.write_u32(0xFFFFFFFF);
-            self.delete_page(page);
-            return;
+            let rightmost_child = self.get_rightmost_child(page)?;
+            self.seek_to_page_offset(rightmost_child, 0x0A)?;
+            self.write_u32(0xFFFFFFFF)?;
+            self.delete_page(page)?;
+            return Ok(());
         }
-        if let Some(right) = self.right_sibling(page) {
-            match self.get_num_cells(right) {
-                0 => panic!(
-                    "Right sibling ({}) of {} has no cells: {}",
-                    right,
-                    page,
-                    self.get_num_cells(right)
-                ),
-                2.. => self.steal_from_sibling(page, right, true),
+        if let Some(right) = self.right_sibling(page)? {
+            match self.get_num_cells(right)? {
+                0 => {
+                    return Err(DbError::Corruption(format!(
+                        "Right sibling ({}) of {} has no cells",
+                        right, page
+                    )))
+                }
+                2.. => self.steal_from_sibling(page, right, true)?,
                 1 => {
-                    self.merge_pages(parent_page, right, page, true);
-                    self.set_rightmost_child(parent_page, right);
-                    self.delete_page(page);
-                    if self.get_num_cells(parent_page) == 1 {
-                        self.remove_page(parent_page, None);
+                    self.merge_pages(parent_page, right, page, true)?;
+                    self.set_rightmost_child(parent_page, right)?;
+                    self.delete_page(page)?;
+                    if self.get_num_cells(parent_page)? == 1 {
+                        self.remove_page(parent_page, None)?;
                     }
                 }
             }
-        } else if let Some(left) = self.left_sibling(page) {
-            match self.get_num_cells(left) {
-                0 => panic!(
-                    "Left sibling ({}) of {} has no cells: {}",
-                    left,
-                    page,
-                    self.get_num_cells(left)
-                ),
-                2.. => self.steal_from_sibling(page, left, false),
+        } else if let Some(left) = self.left_sibling(page)? {
+            match self.get_num_cells(left)? {
+                0 => {
+                    return Err(DbError::Corruption(format!(
+                        "Left sibling ({}) of {} has no cells",
+                        left, page
+                    )))
+                }
+                2.. => self.steal_from_sibling(page, left, false)?,
                 1 => {
-                    self.merge_pages(parent_page, left, page, false);
-                    self.delete_page(page);
-                    if self.get_num_cells(parent_page) == 1 {
-                        self.remove_page(parent_page, None);
+                    self.merge_pages(parent_page, left, page, false)?;
+                    self.delete_page(page)?;
+                    if self.get_num_cells(parent_page)? == 1 {
+                        self.remove_page(parent_page, None)?;
                     }
                 }
             }
         } else {
-            panic!(
-                "Page has no siblings: {} {}",
-                page,
-                self.get_num_cells(page)
-            );
+            return Err(DbError::Corruption(format!(
+                "Page {} has no siblings",
+                page
+            )));
         }
+        Ok(())
     }
 
-    fn delete_page(&mut self, page: u32) {
-        if self.len() == (page + 1) as u64 * PAGE_SIZE {
-            self.set_len(self.len() - PAGE_SIZE);
-            return;
+    fn delete_page(&mut self, page: u32) -> Result<()> {
+        if self.len()? == (page + 1) as u64 * PAGE_SIZE {
+            return self.set_len(self.len()? - PAGE_SIZE);
         }
-        let num_pages = (self.len() / PAGE_SIZE) as u32;
+        let num_pages = (self.len()? / PAGE_SIZE) as u32;
         let mut last_page_bytes = vec![0; PAGE_SIZE as usize];
-        self.update_page_number(num_pages - 1, page);
-        self.seek_to_page_offset(num_pages - 1, 0);
-        self.read_exact(&mut last_page_bytes)
-            .expect("Error reading following bytes");
-        self.seek_to_page_offset(page, 0);
-        self.write_all(&last_page_bytes)
-            .expect("Error writing zero bytes");
-        self.set_len(self.len() - PAGE_SIZE);
-    }
-
-    fn update_page_number(&mut self, old_page: u32, new_page: u32) {
-        let parent_page = self.get_parent_page(old_page);
-        let index = self.find_page_pointer_index(parent_page, old_page);
+        self.update_page_number(num_pages - 1, page)?;
+        self.seek_to_page_offset(num_pages - 1, 0)?;
+        self.read_exact(&mut last_page_bytes)?;
+        self.seek_to_page_offset(page, 0)?;
+        self.write_all(&last_page_bytes)?;
+        self.set_len(self.len()? - PAGE_SIZE)
+    }
+
+    fn update_page_number(&mut self, old_page: u32, new_page: u32) -> Result<()> {
+        let parent_page = self.get_parent_page(old_page)?;
+        let index = self.find_page_pointer_index(parent_page, old_page)?;
         if index == 0 {
-            self.set_rightmost_child(parent_page, new_page);
-            return;
+            return self.set_rightmost_child(parent_page, new_page);
         }
-        let offset = self.get_cell_offset(parent_page, index);
-        self.seek_to_page_offset(parent_page, offset);
-        self.write_u32(new_page);
+        let offset = self.get_cell_offset(parent_page, index)?;
+        self.seek_to_page_offset(parent_page, offset)?;
+        self.write_u32(new_page)?;
+        Ok(())
     }
 
-    fn right_sibling(&mut self, page: u32) -> Option<u32> {
-        let parent_page = self.get_parent_page(page);
-        let num_cells = self.get_num_cells(parent_page);
-        let index = self.find_page_pointer_index(parent_page, page);
+    fn right_sibling(&mut self, page: u32) -> Result<Option<u32>> {
+        let parent_page = self.get_parent_page(page)?;
+        let num_cells = self.get_num_cells(parent_page)?;
+        let index = self.find_page_pointer_index(parent_page, page)?;
         if index < num_cells - 1 {
-            let offset = self.get_cell_offset(parent_page, index + 1);
-            let (_, child, _) = self.read_full_index_value(parent_page, offset);
-            child
+            let offset = self.get_cell_offset(parent_page, index + 1)?;
+            let (_, child, _) = self.read_full_index_value(parent_page, offset)?;
+            Ok(child)
         } else {
-            None
+            Ok(None)
         }
     }
 
-    fn left_sibling(&mut self, page: u32) -> Option<u32> {
-        let parent_page = self.get_parent_page(page);
-        let index = self.find_page_pointer_index(parent_page, page);
+    fn left_sibling(&mut self, page: u32) -> Result<Option<u32>> {
+        let parent_page = self.get_parent_page(page)?;
+        let index = self.find_page_pointer_index(parent_page, page)?;
         if index > 0 {
-            let offset = self.get_cell_offset(parent_page, index - 1);
-            let (_, child, _) = self.read_full_index_value(parent_page, offset);
-            child
+            let offset = self.get_cell_offset(parent_page, index - 1)?;
+            let (_, child, _) = self.read_full_index_value(parent_page, offset)?;
+            Ok(child)
         } else {
-            None
+            Ok(None)
         }
     }
 
-    fn get_rightmost_child(&mut self, page: u32) -> u32 {
-        self.seek_to_page_offset(page, 6);
+    fn get_rightmost_child(&mut self, page: u32) -> Result<u32> {
+        self.seek_to_page_offset(page, 6)?;
         self.read_u32()
     }
 
-    fn steal_from_sibling(&mut self, page: u32, sibling: u32, right: bool) {
-        if self.get_page_type(page) != PageType::IndexLeaf {
+    fn steal_from_sibling(&mut self, page: u32, sibling: u32, right: bool) -> Result<()> {
+        if self.get_page_type(page)? != PageType::IndexLeaf {
             unimplemented!("Stealing from interior pages not yet implemented");
         }
-        if self.get_num_cells(page) != 0 {
-            panic!("Page must be empty to steal from sibling");
+        if self.get_num_cells(page)? != 0 {
+            return Err(DbError::Corruption(format!(
+                "Page {} must be empty to steal from sibling",
+                page
+            )));
         }
-        let parent_page = self.get_parent_page(page);
+        let parent_page = self.get_parent_page(page)?;
         let index = match right {
-            false => self.find_page_pointer_index(parent_page, page),
-            true => self.find_page_pointer_index(parent_page, sibling),
+            false => self.find_page_pointer_index(parent_page, page)?,
+            true => self.find_page_pointer_index(parent_page, sibling)?,
         };
-        let (value, _, row_ids) = self.read_full_index_value_index(parent_page, index);
-        let value = value.unwrap();
-        self.remove_cell(parent_page, index, false);
+        let (value, _, row_ids) = self.read_full_index_value_index(parent_page, index)?;
+        let value = cell_value(value, parent_page, index)?;
+        self.remove_cell(parent_page, index, false)?;
 
-        let num_cells = self.get_num_cells(sibling);
+        let num_cells = self.get_num_cells(sibling)?;
         let sibling_index = if right { 0 } else { num_cells - 1 };
         if num_cells < 2 {
-            panic!(
+            return Err(DbError::Corruption(format!(
                 "Sibling {} has too few cells {} to steal from",
                 sibling, num_cells
-            );
+            )));
         }
         let (sibling_value, _, sibling_row_ids) =
-            self.read_full_index_value_index(sibling, sibling_index);
-        let sibling_value = sibling_value.unwrap();
-        self.remove_cell(sibling, sibling_index, false);
+            self.read_full_index_value_index(sibling, sibling_index)?;
+        let sibling_value = cell_value(sibling_value, sibling, sibling_index)?;
+        self.remove_cell(sibling, sibling_index, false)?;
 
-        self.write_cell(page, &value, row_ids, 0xFFFFFFFF);
+        self.write_cell(page, &value, row_ids, 0xFFFFFFFF)?;
         if right {
-            self.write_cell(parent_page, &sibling_value, sibling_row_ids, sibling);
+            self.write_cell(parent_page, &sibling_value, sibling_row_ids, sibling)?;
         } else {
-            self.write_cell(parent_page, &sibling_value, sibling_row_ids, page);
+            self.write_cell(parent_page, &sibling_value, sibling_row_ids, page)?;
         }
+        Ok(())
     }
 
-    fn steal_from_child(&mut self, parent: u32, child_to_steal_from: u32, child: u32) {
-        let num_cells = self.get_num_cells(child_to_steal_from);
+    fn steal_from_child(
+        &mut self,
+        parent: u32,
+        child_to_steal_from: u32,
+        child: u32,
+    ) -> Result<()> {
+        let num_cells = self.get_num_cells(child_to_steal_from)?;
         if num_cells < 1 {
-            panic!("Child page must have at least one cell to steal");
+            return Err(DbError::Corruption(format!(
+                "Child page {} has no cell to steal",
+                child_to_steal_from
+            )));
         }
         let index = num_cells - 1;
-        let offset = self.get_cell_offset(child_to_steal_from, index);
-        let (value, _, row_ids) = self.read_full_index_value(child_to_steal_from, offset);
-        let value = value.unwrap();
-        self.write_cell(parent, &value, row_ids, child);
-        self.remove_cell(child_to_steal_from, index, true);
-    }
-
-    fn set_rightmost_child(&mut self, page: u32, child: u32) {
-        if self.get_num_cells(page) == 0 {
-            self.seek_to_page_offset(page, 0x02);
-            self.write_u16(1); // Set the number of cells to 1
-            self.write_u16(PAGE_SIZE as u16 - 6); // Set the start of the content area
-            self.seek_to_page_offset(page, 0x10);
-            self.write_u16(PAGE_SIZE as u16 - 6); // Set the offset of the first cell
+        let offset = self.get_cell_offset(child_to_steal_from, index)?;
+        let (value, _, row_ids) = self.read_full_index_value(child_to_steal_from, offset)?;
+        let value = cell_value(value, child_to_steal_from, index)?;
+        self.write_cell(parent, &value, row_ids, child)?;
+        self.remove_cell(child_to_steal_from, index, true)
+    }
+
+    fn set_rightmost_child(&mut self, page: u32, child: u32) -> Result<()> {
+        if self.get_num_cells(page)? == 0 {
+            self.seek_to_page_offset(page, 0x02)?;
+            self.write_u16(1)?; // Set the number of cells to 1
+            self.write_u16(PAGE_SIZE as u16 - 6)?; // Set the start of the content area
+            self.seek_to_page_offset(page, 0x10)?;
+            self.write_u16(PAGE_SIZE as u16 - 6)?; // Set the offset of the first cell
         }
-        self.seek_to_page_offset(page, PAGE_SIZE as u16 - 6);
-        self.write_u32(child);
-        self.seek_to_page_offset(page, 0x6);
-        self.write_u32(child);
+        self.seek_to_page_offset(page, PAGE_SIZE as u16 - 6)?;
+        self.write_u32(child)?;
+        self.seek_to_page_offset(page, 0x6)?;
+        self.write_u32(child)?;
+        Ok(())
     }
 
-    fn merge_pages(&mut self, parent: u32, merge: u32, deleted: u32, right: bool) {
-        if self.get_num_cells(merge) != 1 {
-            panic!(
+    fn merge_pages(&mut self, parent: u32, merge: u32, deleted: u32, right: bool) -> Result<()> {
+        if self.get_num_cells(merge)? != 1 {
+            return Err(DbError::Corruption(format!(
                 "Child page must have only one cell to merge, found: {}",
-                self.get_num_cells(merge)
-            );
+                self.get_num_cells(merge)?
+            )));
         }
         let index = match right {
-            true => self.find_page_pointer_index(parent, merge),
-            false => self.find_page_pointer_index(parent, deleted),
+            true => self.find_page_pointer_index(parent, merge)?,
+            false => self.find_page_pointer_index(parent, deleted)?,
         };
-        let (value, _, row_ids) = self.read_full_index_value_index(parent, index);
-        let value = value.unwrap();
-        self.remove_cell(parent, index, false);
-        self.write_cell(merge, &value, row_ids, 0xFFFFFFFF);
+        let (value, _, row_ids) = self.read_full_index_value_index(parent, index)?;
+        let value = cell_value(value, parent, index)?;
+        self.remove_cell(parent, index, false)?;
+        self.write_cell(merge, &value, row_ids, 0xFFFFFFFF)
     }
 
-    fn find_page_pointer_index(&mut self, page: u32, child_page: u32) -> u16 {
-        let num_cells = self.get_num_cells(page);
+    fn find_page_pointer_index(&mut self, page: u32, child_page: u32) -> Result<u16> {
+        let num_cells = self.get_num_cells(page)?;
         for i in 0..num_cells {
-            let offset = self.get_cell_offset(page, i);
-            let (_, child, _) = self.read_full_index_value(page, offset);
+            let offset = self.get_cell_offset(page, i)?;
+            let (_, child, _) = self.read_full_index_value(page, offset)?;
             if let Some(child) = child {
                 if child == child_page {
-                    return i;
+                    return Ok(i);
                 }
             }
         }
-        panic!("Child page not found: {}", child_page);
+        Err(DbError::Corruption(format!(
+            "Child page {} not found on page {}",
+            child_page, page
+        )))
     }
 
-    pub fn insert_item_into_cell(&mut self, row_id: u32, new_value: &DataType) {
-        let (mut page, mut index) = match self.find_value(new_value) {
+    pub fn insert_item_into_cell(&mut self, row_id: u32, new_value: &DataType) -> Result<()> {
+        let (mut page, mut index) = match self.find_value(new_value)? {
             Ok((p, i)) => (p, i as i32),
             Err((p, _)) => {
                 return self.write_cell(p, new_value, vec![row_id], 0xFFFFFFFF);
             }
         };
-        if self.should_split(page, 4) {
-            page = self.split_page(page, new_value);
-            index = match self.find_value_position(page, new_value) {
-                Some(offset) => offset as i32,
-                None => unreachable!("Value has already been found in the page"),
+        let (_, _, row_ids) = self.read_full_index_value_index(page, index as u16)?;
+        check_cell_size(new_value, row_ids.len() + 1)?;
+        while self.should_split(page, 4)? {
+            if self.get_num_cells(page)? < 2 {
+                return Err(DbError::Corruption(format!(
+                    "Cell of {} does not fit on page {}",
+                    new_value, page
+                )));
+            }
+            // The cell may have moved to the parent page if it was the middle one.
+            self.split_page(page, new_value)?;
+            (page, index) = match self.find_value(new_value)? {
+                Ok((p, i)) => (p, i as i32),
+                Err((p, _)) => {
+                    return Err(DbError::Corruption(format!(
+                        "Value {} missing after splitting page {}",
+                        new_value, p
+                    )))
+                }
             };
         }
-        let page_type = self.get_page_type(page);
-        self.shift_cells(page, index - 1, 4, 0);
-        let new_offset = self.get_cell_offset(page, index as u16);
-        let (_, _, mut row_ids) = self.read_full_index_value(page, new_offset);
+        let page_type = self.get_page_type(page)?;
+        self.shift_cells(page, index - 1, 4, 0)?;
+        let new_offset = self.get_cell_offset(page, index as u16)?;
+        let (_, _, mut row_ids) = self.read_full_index_value(page, new_offset)?;
         row_ids.push(row_id);
         row_ids.sort();
-        self.seek_to_page_offset(page, new_offset);
+        self.seek_to_page_offset(page, new_offset)?;
         if page_type == PageType::IndexInterior {
-            self.skip_bytes(4);
+            self.skip_bytes(4)?;
         }
-        let payload_size = self.read_u16() + 4;
-        self.skip_bytes(-2);
-        self.write_u16(payload_size);
-        self.write_u8(row_ids.len() as u8);
-        self.skip_bytes(1);
+        let payload_size = self.read_u16()? + 4;
+        self.skip_bytes(-2)?;
+        self.write_u16(payload_size)?;
+        self.write_u8(row_ids.len() as u8)?;
+        self.skip_bytes(1)?;
         let value_size = new_value.size();
-        self.skip_bytes(value_size as i64);
+        self.skip_bytes(value_size as i64)?;
         let row_ids_bytes = row_ids
             .iter()
             .flat_map(|id| id.to_le_bytes().to_vec())
             .collect::<Vec<u8>>();
-        self.write_all(&row_ids_bytes)
-            .expect("Error writing row IDs");
+        self.write_all(&row_ids_bytes)?;
+        Ok(())
     }
 
-    fn find_value(&mut self, value: &DataType) -> Result<(u32, u16), (u32, u16)> {
-        let mut current_page = self.get_root_page();
+    /// Returns the page and cell of `value`, or as the error, where it would be inserted.
+    fn find_value(&mut self, value: &DataType) -> Result<Result<(u32, u16), (u32, u16)>> {
+        let mut current_page = self.get_root_page()?;
         loop {
-            let index = match self.find_value_position(current_page, value) {
+            let index = match self.find_value_position(current_page, value)? {
                 Some(i) => i,
                 None => {
-                    return Err((current_page, 0));
+                    return Ok(Err((current_page, 0)));
                 }
             };
-            let offset = self.get_cell_offset(current_page, index);
-            let (cell_value, child_page, _) = self.read_full_index_value(current_page, offset);
-            if cell_value.as_ref() == Some(value) {
-                return Ok((current_page, index));
+            let offset = self.get_cell_offset(current_page, index)?;
+            let (cell_value, child_page, _) = self.read_full_index_value(current_page, offset)?;
+            if cell_value.is_some_and(|v| index_order(&v, value) == Ordering::Equal) {
+                return Ok(Ok((current_page, index)));
             } else if let Some(child_page) = child_page {
                 current_page = child_page;
             } else {
-                return Err((current_page, index));
+                return Ok(Err((current_page, index)));
             }
         }
     }
 
-    fn traverse(&mut self, page: u32, start: i32, end: i32, direction: i32) -> Vec<u32> {
-        if start > end {
-            return vec![];
-        }
-        let start = start as u16;
-        let mut row_ids = vec![];
-        let mut current_cell = start;
-        let cur_offset = self.get_cell_offset(page, current_cell);
-        while current_cell as i32 <= end {
-            let offset = self.get_cell_offset(page, current_cell);
-            let (_, child_page, cell_row_ids) = self.read_full_index_value(page, offset);
-            row_ids.extend(cell_row_ids);
-            if let Some(child_page) = child_page {
-                let num_cells = self.get_num_cells(child_page);
-                let new_row_ids = self.traverse(child_page, 0, num_cells as i32 - 1, direction);
-                row_ids.extend(new_row_ids);
-            }
-            current_cell += 1;
-        }
-
-        let parent_page = self.get_parent_page(page);
-        if parent_page == 0xFFFFFFFF {
-            return row_ids;
-        }
-
-        let v = self.read_index_value(page, cur_offset).unwrap();
-        let index = self.find_value_position(parent_page, &v).unwrap() as i32;
-        let offset = self.get_cell_offset(parent_page, index as u16);
-        let (_, _, parent_row_ids) = self.read_full_index_value(parent_page, offset);
-        let num_cells = self.get_num_cells(parent_page);
-        match direction {
-            ..=-1 => {
-                let new_row_ids = self.traverse(parent_page, 0, index - 1, direction);
-                row_ids.extend(new_row_ids);
-                row_ids.extend(parent_row_ids);
-            }
-            0 => {
-                let new_row_ids =
-                    self.traverse(parent_page, index + 1, num_cells as i32 - 1, direction);
-                row_ids.extend(new_row_ids);
-            }
-            1.. => {
-                let new_row_ids =
-                    self.traverse(parent_page, index + 1, num_cells as i32 - 1, direction);
-                row_ids.extend(new_row_ids);
-            }
-        }
-        row_ids
+    /// Returns the row IDs of the values that compare to `value` with `operator`, in index order.
+    /// `NULL` values are never returned, as comparisons with `NULL` match nothing.
+    pub fn search(&mut self, value: &DataType, operator: &str) -> Result<Vec<u32>> {
+        let Some((lower, upper)) = operator_bounds(value, operator) else {
+            let mut row_ids = self.search(value, "<")?;
+            row_ids.extend(self.search(value, ">")?);
+            return Ok(row_ids);
+        };
+        self.range(lower, upper, false)?
+            .map(|entry| entry.map(|(_, row_id)| row_id))
+            .collect()
     }
 
-    pub fn search(&mut self, value: &DataType, operator: &str) -> Vec<u32> {
-        let pos = self.find_value(value);
-        let (page, index, exists) = match pos {
-            Ok((p, i)) => (p, i, true),
-            Err((p, i)) => (p, i, false),
+    /// Returns the values between `lower` and `upper` with each of their row IDs, in index order,
+    /// or in reverse if `descending`. The row IDs of a value come in ascending order. Pages are
+    /// read as the values on them are needed, through a file handle of the range's own.
+    pub fn range(
+        &self,
+        lower: Bound<DataType>,
+        upper: Bound<DataType>,
+        descending: bool,
+    ) -> Result<IndexRange> {
+        let mut file = IndexFile {
+            file: self.file.try_clone()?,
         };
-        let offset = self.get_cell_offset(page, index);
-        let (_, _, cell_row_ids) = self.read_full_index_value(page, offset);
-        let num_cells = self.get_num_cells(page) as i32;
-
-        let index = index as i32;
-        match operator {
-            "=" => exists.then_some(cell_row_ids).unwrap_or_default(),
-            "<>" => {
-                let mut temp = self.traverse(page, 0, index - 1, -1);
-                temp.extend(self.traverse(page, index + 1, num_cells - 1, 1));
-                temp
+        let root_page = file.get_root_page()?;
+        Ok(IndexRange {
+            file,
+            lower,
+            upper,
+            descending,
+            steps: vec![RangeStep::Page(root_page)],
+            current: None,
+        })
+    }
+
+    /// Returns statistics of the index for estimating the cost of reading through it.
+    pub fn statistics(&mut self) -> Result<IndexStatistics> {
+        let depth = self.depth()?;
+        let pages = self.num_pages()?;
+        // Sample the number of values per page on the path to the leftmost leaf page.
+        let mut page = self.get_root_page()?;
+        let mut values = 0;
+        loop {
+            let num_cells = self.get_num_cells(page)? as u64;
+            if self.get_page_type(page)? != PageType::IndexInterior {
+                values += num_cells;
+                break;
             }
-            "<" => self.traverse(page, 0, index - 1, -1),
-            "<=" => self.traverse(page, 0, index, -1),
-            ">" => self.traverse(page, index + 1, num_cells - 1, 1),
-            ">=" => self.traverse(page, index, num_cells - 1, 1),
-            _ => unreachable!("Invalid operator"),
+            // The first cell of an interior page has no value.
+            values += num_cells - 1;
+            let (_, child_page, _) = self.read_full_index_value_index(page, 0)?;
+            page = child_page.ok_or_else(|| {
+                DbError::Corruption(format!("Cell 0 on page {} has no child page", page))
+            })?;
         }
+        let not_null = || Bound::Excluded(DataType::Null);
+        let first = |descending| -> Result<Option<DataType>> {
+            let mut range = self.range(not_null(), Bound::Unbounded, descending)?;
+            Ok(range.next().transpose()?.map(|(value, _)| value))
+        };
+        Ok(IndexStatistics {
+            depth,
+            pages,
+            distinct: values as f64 / depth as f64 * pages as f64,
+            min: first(false)?,
+            max: first(true)?,
+        })
     }
 
     /// Calculates the size of the payload for an index value.
@@ -825,26 +916,27 @@ impl IndexFile {
             }
     }
 
-    pub fn print(&mut self) {
-        let num_pages = self.len() / PAGE_SIZE;
+    pub fn print(&mut self) -> Result<()> {
+        let num_pages = self.num_pages()?;
         if num_pages == 0 {
             println!("Empty index file");
-            return;
+            return Ok(());
         }
         for i in 0..num_pages {
-            self.print_page(i as u32);
+            self.print_page(i as u32)?;
         }
+        Ok(())
     }
 
-    pub fn print_page(&mut self, page: u32) {
-        self.seek_to_page(page);
-        let page_type = self.read_u8();
-        let unused_space = self.read_u8();
-        let num_cells = self.read_u16();
-        let content_start = self.read_u16();
-        let next_page = self.read_u32();
-        let parent_page = self.read_u32();
-        let unused_2 = self.read_u16();
+    pub fn print_page(&mut self, page: u32) -> Result<()> {
+        self.seek_to_page(page)?;
+        let page_type = self.read_u8()?;
+        let unused_space = self.read_u8()?;
+        let num_cells = self.read_u16()?;
+        let content_start = self.read_u16()?;
+        let next_page = self.read_u32()?;
+        let parent_page = self.read_u32()?;
+        let unused_2 = self.read_u16()?;
 
         let page_start = page as u64 * PAGE_SIZE;
         println!("Page: {:02X}", page);
@@ -878,28 +970,29 @@ impl IndexFile {
         if num_cells > 0 {
             print!("{:08X}  ", 0x10 + page_start);
             for i in 0..num_cells {
-                let offset = self.read_u16();
+                let offset = self.read_u16()?;
                 offsets[i as usize] = offset;
                 print!("{} ", rainbow(&format!("{:04X}", offset), i as usize));
             }
             println!();
             for offset in offsets {
-                self.print_cell(page, offset);
+                self.print_cell(page, offset)?;
                 println!();
             }
         }
+        Ok(())
     }
 
-    pub fn print_cell(&mut self, page: u32, offset: u16) {
+    pub fn print_cell(&mut self, page: u32, offset: u16) -> Result<()> {
         let total_offset = page as u64 * PAGE_SIZE + offset as u64;
         print!("{:08X}  ", total_offset);
-        let (value, child_page, row_ids) = self.read_full_index_value(page, offset);
-        self.seek_to_page_offset(page, offset);
+        let (value, child_page, row_ids) = self.read_full_index_value(page, offset)?;
+        self.seek_to_page_offset(page, offset)?;
         if let Some(child_page) = child_page {
             print!("{:08X} ", child_page.green());
-            self.skip_bytes(4);
+            self.skip_bytes(4)?;
         }
-        let payload_size = self.read_u16();
+        let payload_size = self.read_u16()?;
         print!("{:04X} ", payload_size.blue());
         print!("{:?} ", row_ids.len().yellow());
         if let Some(value) = value {
@@ -917,12 +1010,171 @@ impl IndexFile {
             }
         }
         print!(" ]");
+        Ok(())
     }
 }
 
+/// Statistics of an index, see [`IndexFile::statistics`].
+pub struct IndexStatistics {
+    /// The number of pages on the path from the root page to a leaf page.
+    pub depth: u64,
+    pub pages: u64,
+    /// The estimated number of distinct values, from the values per page on one path.
+    pub distinct: f64,
+    /// The lowest and highest value other than `NULL`, if any.
+    pub min: Option<DataType>,
+    pub max: Option<DataType>,
+}
+
+/// The values of an index in a range, see [`IndexFile::range`].
+pub struct IndexRange {
+    file: IndexFile,
+    lower: Bound<DataType>,
+    upper: Bound<DataType>,
+    descending: bool,
+    /// The cells and pages left to read, the next one last.
+    steps: Vec<RangeStep>,
+    /// The value being returned, and its row IDs left to return.
+    current: Option<(DataType, std::vec::IntoIter<u32>)>,
+}
+
+enum RangeStep {
+    Cell(DataType, Vec<u32>),
+    Page(u32),
+}
+
+impl IndexRange {
+    /// Adds the steps reading the cells of `page` in the range. The child of a cell of an
+    /// interior page holds the values between that of the cell and that of the next one, and the
+    /// first cell has no value, so a child is read only if those values can be in the range.
+    fn read_page(&mut self, page: u32) -> Result<()> {
+        let num_cells = self.file.get_num_cells(page)?;
+        let cells = (0..num_cells)
+            .map(|i| self.file.read_full_index_value_index(page, i))
+            .collect::<Result<Vec<_>>>()?;
+        let mut steps = vec![];
+        for (i, (value, child_page, row_ids)) in cells.iter().enumerate() {
+            if let Some(value) = value {
+                if self.contains(value) {
+                    steps.push(RangeStep::Cell(value.clone(), row_ids.clone()));
+                }
+            }
+            let Some(child_page) = child_page else {
+                continue;
+            };
+            let next_value = cells.get(i + 1).and_then(|(value, _, _)| value.as_ref());
+            let below = next_value.is_some_and(|next| match &self.lower {
+                Bound::Included(lower) | Bound::Excluded(lower) => {
+                    index_order(next, lower) != Ordering::Greater
+                }
+                Bound::Unbounded => false,
+            });
+            let above = value.as_ref().is_some_and(|value| match &self.upper {
+                Bound::Included(upper) | Bound::Excluded(upper) => {
+                    index_order(value, upper) != Ordering::Less
+                }
+                Bound::Unbounded => false,
+            });
+            if !below && !above {
+                steps.push(RangeStep::Page(*child_page));
+            }
+        }
+        if !self.descending {
+            steps.reverse();
+        }
+        self.steps.extend(steps);
+        Ok(())
+    }
+
+    /// Returns whether `value` is between the bounds of the range.
+    fn contains(&self, value: &DataType) -> bool {
+        let above_lower = match &self.lower {
+            Bound::Included(lower) => index_order(value, lower) != Ordering::Less,
+            Bound::Excluded(lower) => index_order(value, lower) == Ordering::Greater,
+            Bound::Unbounded => true,
+        };
+        let below_upper = match &self.upper {
+            Bound::Included(upper) => index_order(value, upper) != Ordering::Greater,
+            Bound::Excluded(upper) => index_order(value, upper) == Ordering::Less,
+            Bound::Unbounded => true,
+        };
+        above_lower && below_upper
+    }
+}
+
+impl Iterator for IndexRange {
+    type Item = Result<(DataType, u32)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((value, row_ids)) = &mut self.current {
+                match row_ids.next() {
+                    Some(row_id) => return Some(Ok((value.clone(), row_id))),
+                    None => self.current = None,
+                }
+            }
+            match self.steps.pop()? {
+                RangeStep::Cell(value, mut row_ids) => {
+                    row_ids.sort_unstable();
+                    self.current = Some((value, row_ids.into_iter()));
+                }
+                RangeStep::Page(page) => {
+                    if let Err(e) = self.read_page(page) {
+                        self.steps.clear();
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the bounds of the range of values that compare to `value` with `operator`, or
+/// [`None`] for `<>`, which matches two ranges. The range never holds `NULL`.
+pub fn operator_bounds(
+    value: &DataType,
+    operator: &str,
+) -> Option<(Bound<DataType>, Bound<DataType>)> {
+    let not_null = Bound::Excluded(DataType::Null);
+    Some(match operator {
+        "=" => (
+            Bound::Included(value.clone()),
+            Bound::Included(value.clone()),
+        ),
+        "<" => (not_null, Bound::Excluded(value.clone())),
+        "<=" => (not_null, Bound::Included(value.clone())),
+        ">" => (Bound::Excluded(value.clone()), Bound::Unbounded),
+        ">=" => (Bound::Included(value.clone()), Bound::Unbounded),
+        "<>" => return None,
+        _ => unreachable!("Invalid operator"),
+    })
+}
+
+/// Fails if a cell of `value` with `num_row_ids` row IDs would be larger than [`MAX_CELL_SIZE`]
+/// or hold more row IDs than its count can store.
+fn check_cell_size(value: &DataType, num_row_ids: usize) -> Result<()> {
+    // Child page, payload size, number of row IDs, payload type and the cell's offset.
+    let size = 4 + 2 + 2 + value.size() as usize + num_row_ids * 4 + 2;
+    if size > MAX_CELL_SIZE as usize || num_row_ids > u8::MAX as usize {
+        return Err(DbError::Constraint(format!(
+            "Value {} is too long or held by too many rows to be indexed",
+            value
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the value of a cell read from `page`, which only the leftmost cell of an interior page
+/// may lack.
+fn cell_value(value: Option<DataType>, page: u32, index: u16) -> Result<DataType> {
+    value
+        .ok_or_else(|| DbError::Corruption(format!("Cell {} on page {} has no value", index, page)))
+}
+
 #[cfg(test)]
 mod test {
     use crate::constants::DataType;
+    use crate::expression::{compare, matches_operator};
     use crate::utils::{setup_records, setup_table, teardown};
 
     use super::*;
@@ -933,14 +1185,17 @@ mod test {
 
     fn setup(test_name: &str, col_index: usize, test_data: &str) -> IndexFile {
         let table = setup_table(test_name, test_data);
-        let mut index_file = IndexFile::new(test_name, &table.columns[col_index].name, "data/test");
-        index_file.initialize_index(setup_records(test_data), col_index);
+        let mut index_file =
+            IndexFile::new(test_name, &table.columns[col_index].name, "data/test").unwrap();
+        index_file
+            .initialize_index(setup_records(test_data), col_index)
+            .unwrap();
         index_file
     }
 
     fn setup_uninitialized(test_name: &str, col_index: usize) -> IndexFile {
         let table = setup_table(test_name, "data/testdata.txt");
-        IndexFile::new(test_name, &table.columns[col_index].name, "data/test")
+        IndexFile::new(test_name, &table.columns[col_index].name, "data/test").unwrap()
     }
 
     #[test]
@@ -948,28 +1203,165 @@ mod test {
         let col_index = 1;
         let mut index_file = setup_uninitialized("test_initialize_index", col_index);
         let records = setup_records("data/testdata.txt");
-        index_file.initialize_index(records, col_index);
+        index_file.initialize_index(records, col_index).unwrap();
         teardown("test_initialize_index");
     }
 
     #[test]
-    fn test_index_update() {
-        let mut index_file = setup_index_file("test_index_update");
-        index_file.update_record(
-            9,
-            &DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string()),
-            &DataType::Text("2345 Test St, Testville, Testland, 12345".to_string()),
+    fn test_index_float_order() {
+        let test_name = "test_index_float_order";
+        std::fs::create_dir_all("data/test").unwrap();
+        teardown(test_name);
+        let mut index_file = IndexFile::new(test_name, "score", "data/test").unwrap();
+        let special = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0, 0.0];
+        let values = (0..200)
+            .map(|i| match i % 10 {
+                0..=4 => special[i % 5],
+                _ => (i as f64 - 100.0) / 4.0,
+            })
+            .map(DataType::Double)
+            .collect::<Vec<_>>();
+        for (row_id, value) in values.iter().enumerate() {
+            index_file
+                .insert_item_into_cell(row_id as u32 + 1, value)
+                .unwrap();
+        }
+        let probes = [
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            0.0,
+            -0.0,
+            3.5,
+            1000.0,
+        ];
+        for probe in probes.map(DataType::Double) {
+            for operator in ["=", "<>", "<", "<=", ">", ">="] {
+                let mut found = index_file.search(&probe, operator).unwrap();
+                found.sort_unstable();
+                let expected = values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| matches_operator(compare(v, &probe), operator))
+                    .map(|(i, _)| i as u32 + 1)
+                    .collect::<Vec<_>>();
+                assert_eq!(found, expected, "{} {}", operator, probe);
+            }
+        }
+        teardown(test_name);
+    }
+
+    #[test]
+    fn test_index_range() {
+        let test_name = "test_index_range";
+        std::fs::create_dir_all("data/test").unwrap();
+        teardown(test_name);
+        let mut index_file = IndexFile::new(test_name, "value", "data/test").unwrap();
+        let values = (0..300).map(|i| (i * 7919) % 100).collect::<Vec<i32>>();
+        for (row_id, value) in values.iter().enumerate() {
+            index_file
+                .insert_item_into_cell(row_id as u32, &DataType::Int(*value))
+                .unwrap();
+        }
+        let mut expected = values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| (21..=50).contains(*v))
+            .map(|(row_id, v)| (*v, row_id as u32))
+            .collect::<Vec<_>>();
+        let entries = |expected: &[(i32, u32)]| {
+            expected
+                .iter()
+                .map(|(v, row_id)| (DataType::Int(*v), *row_id))
+                .collect::<Vec<_>>()
+        };
+        expected.sort();
+        let lower = Bound::Excluded(DataType::Int(20));
+        let upper = Bound::Included(DataType::BigInt(50));
+        let ascending = index_file
+            .range(lower.clone(), upper.clone(), false)
+            .unwrap();
+        assert_eq!(
+            ascending.collect::<Result<Vec<_>>>().unwrap(),
+            entries(&expected)
         );
-        let search_result = index_file.search(
-            &DataType::Text("2345 Test St, Testville, Testland, 12345".to_string()),
-            "=",
+        // A descending range returns the values in reverse, each with its row IDs ascending.
+        expected.sort_by_key(|(v, row_id)| (std::cmp::Reverse(*v), *row_id));
+        let descending = index_file.range(lower, upper, true).unwrap();
+        assert_eq!(
+            descending.collect::<Result<Vec<_>>>().unwrap(),
+            entries(&expected)
         );
+
+        let statistics = index_file.statistics().unwrap();
+        assert_eq!(statistics.min, Some(DataType::Int(0)));
+        assert_eq!(statistics.max, Some(DataType::Int(99)));
+        assert!((50.0..200.0).contains(&statistics.distinct));
+        teardown(test_name);
+    }
+
+    #[test]
+    fn test_index_full_cell() {
+        let test_name = "test_index_full_cell";
+        std::fs::create_dir_all("data/test").unwrap();
+        teardown(test_name);
+        let mut index_file = IndexFile::new(test_name, "value", "data/test").unwrap();
+        for row_id in 0..40 {
+            index_file
+                .insert_item_into_cell(row_id, &DataType::Int(row_id as i32))
+                .unwrap();
+        }
+        // The row IDs of a value fill its cell, which then fails to grow instead of overflowing
+        // its page.
+        let value = DataType::Int(20);
+        let added = (40..200)
+            .take_while(|row_id| index_file.insert_item_into_cell(*row_id, &value).is_ok())
+            .count();
+        assert!(added > 10 && added < 160);
+        assert_eq!(index_file.search(&value, "=").unwrap().len(), added + 1);
+        assert_eq!(
+            index_file.search(&DataType::Int(0), ">=").unwrap().len(),
+            40 + added
+        );
+        teardown(test_name);
+    }
+
+    #[test]
+    fn test_remove_last_item() {
+        let mut index_file = setup_uninitialized("test_remove_last_item", 1);
+        let value = DataType::Text("only".to_string());
+        index_file.insert_item_into_cell(1, &value).unwrap();
+        index_file.remove_item_from_cell(1, &value).unwrap();
+        assert!(index_file.search(&value, "=").unwrap().is_empty());
+        index_file.insert_item_into_cell(2, &value).unwrap();
+        assert_eq!(index_file.search(&value, "=").unwrap(), [2]);
+        teardown("test_remove_last_item");
+    }
+
+    #[test]
+    fn test_index_update() {
+        let mut index_file = setup_index_file("test_index_update");
+        index_file
+            .update_record(
+                9,
+                &DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string()),
+                &DataType::Text("2345 Test St, Testville, Testland, 12345".to_string()),
+            )
+            .unwrap();
+        let search_result = index_file
+            .search(
+                &DataType::Text("2345 Test St, Testville, Testland, 12345".to_string()),
+                "=",
+            )
+            .unwrap();
         assert_eq!(1, search_result.len());
         assert_eq!(9, search_result[0]);
-        let search_result = index_file.search(
-            &DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string()),
-            "=",
-        );
+        let search_result = index_file
+            .search(
+                &DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string()),
+                "=",
+            )
+            .unwrap();
         assert_eq!(0, search_result.len());
         teardown("test_index_update");
     }
@@ -979,9 +1371,9 @@ mod test {
         let mut index_file = setup_index_file("test_index_add_item_to_cell");
         let value =
             DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string());
-        index_file.insert_item_into_cell(10, &value);
-        index_file.insert_item_into_cell(11, &value);
-        let search_result = index_file.search(&value, "=");
+        index_file.insert_item_into_cell(10, &value).unwrap();
+        index_file.insert_item_into_cell(11, &value).unwrap();
+        let search_result = index_file.search(&value, "=").unwrap();
         assert_eq!(3, search_result.len());
         assert_eq!(9, search_result[0]);
         assert_eq!(10, search_result[1]);
@@ -994,14 +1386,14 @@ mod test {
         let mut index_file = setup_index_file("test_index_find_value");
         let value =
             DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string());
-        let (page, index) = index_file.find_value(&value).unwrap();
+        let (page, index) = index_file.find_value(&value).unwrap().unwrap();
         assert_eq!(2, page);
         assert_eq!(3, index);
         let value =
             DataType::Text("ZEndOfAlphabet St 176, Summerholm, Guadeloupe, 673843".to_string());
-        if index_file.find_value(&value).is_ok() {
+        if index_file.find_value(&value).unwrap().is_ok() {
             panic!("Value should not exist");
-        } else if let Err((page, index)) = index_file.find_value(&value) {
+        } else if let Err((page, index)) = index_file.find_value(&value).unwrap() {
             assert_eq!(2, page);
             assert_eq!(4, index);
         }
@@ -1013,23 +1405,23 @@ mod test {
         let mut index_file = setup_index_file("test_index_search");
         let value =
             DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string());
-        let search_result = index_file.search(&value, "=");
+        let search_result = index_file.search(&value, "=").unwrap();
         assert_eq!(1, search_result.len());
         assert_eq!(9, search_result[0]);
-        let search_result = index_file.search(&value, ">");
+        let search_result = index_file.search(&value, ">").unwrap();
         assert_eq!(1, search_result.len());
         assert_eq!(5, search_result[0]);
-        let search_result = index_file.search(&value, "<");
+        let search_result = index_file.search(&value, "<").unwrap();
         assert_eq!(8, search_result.len());
-        let search_result = index_file.search(&value, ">=");
+        let search_result = index_file.search(&value, ">=").unwrap();
         assert_eq!(2, search_result.len());
-        let search_result = index_file.search(&value, "<=");
+        let search_result = index_file.search(&value, "<=").unwrap();
         assert_eq!(9, search_result.len());
-        let search_result = index_file.search(&value, "<>");
+        let search_result = index_file.search(&value, "<>").unwrap();
         assert_eq!(9, search_result.len());
         let non_existent_value =
             DataType::Text("ZEndOfAlphabet St 176, Summerholm, Guadeloupe, 673843".to_string());
-        let search_result = index_file.search(&non_existent_value, "=");
+        let search_result = index_file.search(&non_existent_value, "=").unwrap();
         assert_eq!(0, search_result.len());
         teardown("test_index_search");
     }
@@ -1038,16 +1430,36 @@ mod test {
     fn test_index_search_intager() {
         let mut index_file = setup("test_index_search_integer", 2, "data/testdata.txt");
         let value = DataType::Int(9);
-        let search_result = index_file.search(&value, "=");
+        let search_result = index_file.search(&value, "=").unwrap();
         assert_eq!(1, search_result.len());
         assert_eq!(6, search_result[0]);
+        // Values of other numeric types are found by value.
+        assert_eq!(index_file.search(&DataType::BigInt(9), "=").unwrap(), [6]);
+        assert_eq!(index_file.search(&DataType::Double(9.0), "=").unwrap(), [6]);
+        assert!(index_file
+            .search(&DataType::Double(8.5), "=")
+            .unwrap()
+            .is_empty());
+        assert_eq!(index_file.search(&DataType::Double(8.5), "<").unwrap(), [3]);
+        let missing = DataType::Int(300);
+        for (operator, count) in [("<", 3), ("<=", 3), (">", 7), (">=", 7), ("<>", 10)] {
+            let search_result = index_file.search(&missing, operator).unwrap();
+            assert_eq!(search_result.len(), count, "{}", operator);
+        }
+        assert_eq!(
+            index_file
+                .search(&DataType::SmallInt(8), ">")
+                .unwrap()
+                .len(),
+            9
+        );
         teardown("test_index_search_integer");
     }
 
     #[test]
     fn test_index_long_file() {
         let index_file = setup("test_index_long_file", 2, "data/longdata.txt");
-        assert_ne!(10, index_file.len() / PAGE_SIZE);
+        assert_ne!(10, index_file.len().unwrap() / PAGE_SIZE);
         teardown("test_index_long_file");
     }
 
@@ -1056,14 +1468,14 @@ mod test {
         let mut index_file = setup_index_file("test_index_remove_item_from_cell");
         let value =
             DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string());
-        index_file.remove_item_from_cell(9, &value);
-        let search_result = index_file.search(&value, "=");
+        index_file.remove_item_from_cell(9, &value).unwrap();
+        let search_result = index_file.search(&value, "=").unwrap();
         assert_eq!(0, search_result.len());
         teardown("test_index_remove_item_from_cell");
         let mut index_file = setup_index_file("test_index_remove_item_from_cell");
-        index_file.insert_item_into_cell(10, &value);
-        index_file.remove_item_from_cell(9, &value);
-        let search_result = index_file.search(&value, "=");
+        index_file.insert_item_into_cell(10, &value).unwrap();
+        index_file.remove_item_from_cell(9, &value).unwrap();
+        let search_result = index_file.search(&value, "=").unwrap();
         assert_eq!(1, search_result.len());
         assert_eq!(10, search_result[0]);
         teardown("test_index_remove_item_from_cell");
@@ -1074,13 +1486,15 @@ mod test {
         let mut index_file = setup_index_file("test_index_remove_cell");
         let value =
             DataType::Text("Terminology St 176, Summerholm, Guadeloupe, 673843".to_string());
-        let (page, index) = index_file.find_value(&value).unwrap();
-        let num_cells = index_file.get_num_cells(page);
-        let (_, _, row_ids) = index_file.read_full_index_value_index(page, index);
-        index_file.remove_item_from_cell(row_ids[0], &value);
-        let new_num_cells = index_file.get_num_cells(page);
+        let (page, index) = index_file.find_value(&value).unwrap().unwrap();
+        let num_cells = index_file.get_num_cells(page).unwrap();
+        let (_, _, row_ids) = index_file.read_full_index_value_index(page, index).unwrap();
+        index_file
+            .remove_item_from_cell(row_ids[0], &value)
+            .unwrap();
+        let new_num_cells = index_file.get_num_cells(page).unwrap();
         assert_eq!(num_cells, new_num_cells + 1);
-        let search_result = index_file.search(&value, "=");
+        let search_result = index_file.search(&value, "=").unwrap();
         assert_eq!(0, search_result.len());
         teardown("test_index_remove_cell");
     }
@@ -1090,16 +1504,16 @@ mod test {
         let mut index_file = setup_index_file("test_index_remove_last_cell_right");
         let page = 2;
         for _ in 0..8 {
-            let offset = index_file.get_cell_offset(page, 0);
-            let (value, _, row_ids) = index_file.read_full_index_value(page, offset);
+            let offset = index_file.get_cell_offset(page, 0).unwrap();
+            let (value, _, row_ids) = index_file.read_full_index_value(page, offset).unwrap();
             if let Some(value) = value {
                 for id in row_ids {
-                    index_file.remove_item_from_cell(id, &value);
+                    index_file.remove_item_from_cell(id, &value).unwrap();
                 }
             }
         }
-        assert_eq!(1, index_file.len() / PAGE_SIZE);
-        assert_eq!(2, index_file.get_num_cells(0));
+        assert_eq!(1, index_file.len().unwrap() / PAGE_SIZE);
+        assert_eq!(2, index_file.get_num_cells(0).unwrap());
         teardown("test_index_remove_last_cell_right");
     }
 
@@ -1108,16 +1522,16 @@ mod test {
         let mut index_file = setup_index_file("test_index_remove_last_cell_left");
         let page = 0;
         for _ in 0..8 {
-            let offset = index_file.get_cell_offset(page, 0);
-            let (value, _, row_ids) = index_file.read_full_index_value(page, offset);
+            let offset = index_file.get_cell_offset(page, 0).unwrap();
+            let (value, _, row_ids) = index_file.read_full_index_value(page, offset).unwrap();
             if let Some(value) = value {
                 for id in row_ids {
-                    index_file.remove_item_from_cell(id, &value);
+                    index_file.remove_item_from_cell(id, &value).unwrap();
                 }
             }
         }
-        assert_eq!(1, index_file.len() / PAGE_SIZE);
-        assert_eq!(2, index_file.get_num_cells(0));
+        assert_eq!(1, index_file.len().unwrap() / PAGE_SIZE);
+        assert_eq!(2, index_file.get_num_cells(0).unwrap());
         teardown("test_index_remove_last_cell_left");
     }
 
@@ -1126,16 +1540,16 @@ mod test {
         let mut index_file = setup_index_file("test_index_remove_last_cell_interior");
         let page = 1;
         for _ in 0..8 {
-            let offset = index_file.get_cell_offset(page, 1);
-            let (value, _, row_ids) = index_file.read_full_index_value(page, offset);
+            let offset = index_file.get_cell_offset(page, 1).unwrap();
+            let (value, _, row_ids) = index_file.read_full_index_value(page, offset).unwrap();
             if let Some(value) = value {
                 for id in row_ids {
-                    index_file.remove_item_from_cell(id, &value);
+                    index_file.remove_item_from_cell(id, &value).unwrap();
                 }
             }
         }
-        assert_eq!(1, index_file.len() / PAGE_SIZE);
-        assert_eq!(2, index_file.get_num_cells(0));
+        assert_eq!(1, index_file.len().unwrap() / PAGE_SIZE);
+        assert_eq!(2, index_file.get_num_cells(0).unwrap());
         teardown("test_index_remove_last_cell_interior");
     }
 }
@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+
+use crate::{
+    diagnostic,
+    error::{DbError, Result},
+};
+
+/// A statement of a script, as split by [`tokenize_script`].
+#[derive(Debug, PartialEq)]
+pub struct ScriptStatement {
+    /// The line of the script the statement starts on, counting from 1.
+    pub line: usize,
+    pub tokens: VecDeque<String>,
+    /// Where each token was read from the script.
+    pub spans: Vec<Span>,
+}
+
+/// The bytes of the input a token was read from.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits `input`, a single statement, into tokens.
+///
+/// * Words are separated by whitespace and lowercased.
+/// * `(`, `)`, `,` and `||` are tokens of their own.
+/// * String literals keep their quotes and case, and may contain any character. A quote inside a
+///   literal is written as `''`.
+/// * `?` placeholders are numbered in order of appearance and returned as `$1`, `$2`, ...
+/// * `--` comments up to the end of the line and `/* */` comments are dropped.
+/// * `;` ends the statement and is dropped.
+pub fn tokenize(input: &str) -> Result<VecDeque<String>> {
+    tokenize_statement(input).map(|statement| statement.tokens)
+}
+
+/// Splits `input`, a single statement, into tokens as [`tokenize`] does, keeping where each
+/// token was read from. Errors show where in `input` they occur.
+pub fn tokenize_statement(input: &str) -> Result<ScriptStatement> {
+    let mut statements = lex(input).map_err(|e| DbError::Parse(e.render(input)))?;
+    match statements.len() {
+        0 => Ok(ScriptStatement {
+            line: 1,
+            tokens: VecDeque::new(),
+            spans: vec![],
+        }),
+        1 => Ok(statements.remove(0)),
+        n => Err(DbError::Parse(format!(
+            "Expected a single statement, found {}.",
+            n
+        ))),
+    }
+}
+
+/// Splits `input` into statements separated by `;`, each tokenized as by [`tokenize`].
+///
+/// Errors name the statement, counting from 1, and the line they occur on.
+pub fn tokenize_script(input: &str) -> Result<Vec<ScriptStatement>> {
+    lex(input).map_err(|e| {
+        let line = input[..e.span.start].matches('\n').count() + 1;
+        DbError::Parse(format!(
+            "Statement {} (line {}): {}",
+            e.statement,
+            line,
+            e.render(input)
+        ))
+    })
+}
+
+struct LexError {
+    statement: usize,
+    span: Span,
+    message: String,
+}
+
+impl LexError {
+    fn render(&self, input: &str) -> String {
+        diagnostic::render(input, self.span, &self.message, None)
+    }
+}
+
+/// The statement being split off by [`lex`].
+#[derive(Default)]
+struct Pending {
+    line: usize,
+    tokens: VecDeque<String>,
+    spans: Vec<Span>,
+    current: String,
+    /// Where the word read so far starts and ends.
+    current_span: Option<Span>,
+    placeholders: usize,
+    numbered_placeholders: bool,
+}
+
+impl Pending {
+    /// Adds the word read so far as a token.
+    fn push_word(&mut self) {
+        let Some(span) = self.current_span.take() else {
+            return;
+        };
+        if parameter_index(&self.current).is_some() {
+            self.numbered_placeholders = true;
+        }
+        let word = std::mem::take(&mut self.current);
+        self.push(word, span);
+    }
+
+    /// Adds `token`, read from `span`, after the word read so far.
+    fn push_token(&mut self, token: String, span: Span) {
+        self.push_word();
+        self.push(token, span);
+    }
+
+    fn push(&mut self, token: String, span: Span) {
+        self.tokens.push_back(token);
+        self.spans.push(span);
+    }
+
+    /// Notes that the statement has content on `line`.
+    fn start(&mut self, line: usize) {
+        if self.tokens.is_empty() && self.current.is_empty() {
+            self.line = line;
+        }
+    }
+}
+
+fn lex(input: &str) -> Result<Vec<ScriptStatement>, LexError> {
+    let mut statements = vec![];
+    let mut pending = Pending::default();
+    let mut line = 1;
+    let mut chars = input.char_indices().peekable();
+    let error = |statements: &Vec<ScriptStatement>, span, message: &str| LexError {
+        statement: statements.len() + 1,
+        span,
+        message: message.to_string(),
+    };
+    let span = |start: usize, end: usize| Span { start, end };
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        match c {
+            '\n' => {
+                pending.push_word();
+                line += 1;
+            }
+            '-' if next == Some('-') => {
+                pending.push_word();
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            '/' if next == Some('*') => {
+                pending.push_word();
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some((_, '*')) if chars.peek().map(|(_, c)| *c) == Some('/') => {
+                            chars.next();
+                            break;
+                        }
+                        Some((_, '\n')) => line += 1,
+                        Some(_) => {}
+                        None => {
+                            return Err(error(&statements, span(i, i + 2), "Unterminated comment."))
+                        }
+                    }
+                }
+            }
+            '\'' => {
+                pending.start(line);
+                let mut literal = String::from('\'');
+                let end = loop {
+                    match chars.next() {
+                        Some((_, '\'')) if chars.peek().map(|(_, c)| *c) == Some('\'') => {
+                            chars.next();
+                            literal.push_str("''");
+                        }
+                        Some((j, '\'')) => {
+                            literal.push('\'');
+                            break j + 1;
+                        }
+                        Some((_, c)) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            literal.push(c);
+                        }
+                        None => {
+                            let message = "Unterminated string literal.";
+                            return Err(error(&statements, span(i, input.len()), message));
+                        }
+                    }
+                };
+                pending.push_token(literal, span(i, end));
+            }
+            '"' => {
+                pending.start(line);
+                let mut identifier = String::from('"');
+                let end = loop {
+                    match chars.next() {
+                        Some((_, '"')) if chars.peek().map(|(_, c)| *c) == Some('"') => {
+                            chars.next();
+                            identifier.push_str("\"\"");
+                        }
+                        Some((j, '"')) => {
+                            identifier.push('"');
+                            break j + 1;
+                        }
+                        Some((_, c)) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            identifier.push(c);
+                        }
+                        None => {
+                            let message = "Unterminated quoted identifier.";
+                            return Err(error(&statements, span(i, input.len()), message));
+                        }
+                    }
+                };
+                pending.push_token(identifier, span(i, end));
+            }
+            '(' | ')' | ',' => {
+                pending.start(line);
+                pending.push_token(c.to_string(), span(i, i + 1));
+            }
+            '|' if next == Some('|') => {
+                chars.next();
+                pending.start(line);
+                pending.push_token("||".to_string(), span(i, i + 2));
+            }
+            '?' => {
+                pending.start(line);
+                pending.placeholders += 1;
+                let placeholder = format!("${}", pending.placeholders);
+                pending.push_token(placeholder, span(i, i + 1));
+            }
+            ';' => {
+                pending.push_word();
+                finish(&mut statements, std::mem::take(&mut pending))
+                    .map_err(|e| error(&statements, span(i, i + 1), e))?;
+            }
+            c if c.is_whitespace() => pending.push_word(),
+            c => {
+                pending.start(line);
+                pending.current.extend(c.to_lowercase());
+                let start = pending.current_span.map_or(i, |span| span.start);
+                pending.current_span = Some(span(start, i + c.len_utf8()));
+            }
+        }
+    }
+    pending.push_word();
+    finish(&mut statements, pending)
+        .map_err(|e| error(&statements, span(input.len(), input.len()), e))?;
+    Ok(statements)
+}
+
+/// Adds `pending` to `statements` unless it is empty.
+fn finish(statements: &mut Vec<ScriptStatement>, pending: Pending) -> Result<(), &'static str> {
+    if pending.tokens.is_empty() {
+        return Ok(());
+    }
+    if pending.placeholders > 0 && pending.numbered_placeholders {
+        return Err("Cannot mix ? and $n placeholders.");
+    }
+    statements.push(ScriptStatement {
+        line: pending.line,
+        tokens: pending.tokens,
+        spans: pending.spans,
+    });
+    Ok(())
+}
+
+/// Returns the index `n` of a `$n` placeholder token.
+pub fn parameter_index(token: &str) -> Option<usize> {
+    token
+        .strip_prefix('$')
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+}
+
+/// Returns the content of a string literal token, or [`None`] if `token` is not quoted.
+pub fn unquote(token: &str) -> Option<String> {
+    if token.len() >= 2 && token.starts_with('\'') && token.ends_with('\'') {
+        Some(token[1..token.len() - 1].replace("''", "'"))
+    } else {
+        None
+    }
+}
+
+/// Returns the name a quoted identifier token stands for, or [`None`] if `token` is not quoted.
+pub fn unquote_identifier(token: &str) -> Option<String> {
+    if token.len() >= 2 && token.starts_with('"') && token.ends_with('"') {
+        Some(token[1..token.len() - 1].replace("\"\"", "\""))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("INSERT INTO t VALUES (1, 'A, (b)', 'it''s');").unwrap();
+        assert_eq!(
+            tokens,
+            ["insert", "into", "t", "values", "(", "1", ",", "'A, (b)'", ",", "'it''s'", ")"]
+        );
+        assert_eq!(unquote(&tokens[7]).unwrap(), "A, (b)");
+        assert_eq!(unquote(&tokens[9]).unwrap(), "it's");
+        assert_eq!(unquote(&tokens[5]), None);
+        assert!(tokenize("SELECT * FROM t WHERE name = 'oops;").is_err());
+        let tokens = tokenize("SELECT a||'|'|| b FROM t").unwrap();
+        assert_eq!(tokens, ["select", "a", "||", "'|'", "||", "b", "from", "t"]);
+        let tokens = tokenize("SELECT \"First Name\" FROM \"My \"\"Table\"\"\"").unwrap();
+        assert_eq!(
+            tokens,
+            ["select", "\"First Name\"", "from", "\"My \"\"Table\"\"\""]
+        );
+        assert_eq!(unquote_identifier(&tokens[1]).unwrap(), "First Name");
+        assert_eq!(unquote_identifier(&tokens[3]).unwrap(), "My \"Table\"");
+        assert_eq!(unquote_identifier(&tokens[0]), None);
+        assert!(tokenize("SELECT \"oops FROM t;").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_placeholders() {
+        let tokens = tokenize("UPDATE t SET a = ? WHERE b = ?").unwrap();
+        assert_eq!(
+            tokens,
+            ["update", "t", "set", "a", "=", "$1", "where", "b", "=", "$2"]
+        );
+        let tokens = tokenize("INSERT INTO t VALUES ($2,$1)").unwrap();
+        assert_eq!(
+            tokens,
+            ["insert", "into", "t", "values", "(", "$2", ",", "$1", ")"]
+        );
+        assert_eq!(parameter_index("$2"), Some(2));
+        assert_eq!(parameter_index("$0"), None);
+        assert_eq!(parameter_index("'$1'"), None);
+        assert!(tokenize("INSERT INTO t VALUES (?, $1)").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_script() {
+        let script = "-- people\nCREATE TABLE t (id INT, name TEXT);\n\n/* two\n rows */ INSERT INTO t\n  VALUES (1, 'a;b'); -- first\nINSERT INTO t VALUES (2, 'c--d /* e */');";
+        let statements = tokenize_script(script).unwrap();
+        assert_eq!(
+            statements.iter().map(|s| s.line).collect::<Vec<_>>(),
+            [2, 5, 7]
+        );
+        assert_eq!(
+            statements[1].tokens,
+            ["insert", "into", "t", "values", "(", "1", ",", "'a;b'", ")"]
+        );
+        assert_eq!(statements[2].tokens[7], "'c--d /* e */'");
+        assert_eq!(
+            tokenize_script("SELECT 1;\nSELECT 'a\n;\nSELECT 2;").map_err(|e| e.to_string()),
+            Err(
+                "Statement 2 (line 2): Unterminated string literal.\n2 | SELECT 'a\n  |        ^^"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            tokenize_script("SELECT 1; /* open").map_err(|e| e.to_string()),
+            Err(
+                "Statement 2 (line 1): Unterminated comment.\n1 | SELECT 1; /* open\n  |           ^^"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            tokenize("SELECT a -- b\n FROM t").unwrap(),
+            ["select", "a", "from", "t"]
+        );
+        assert!(tokenize("SELECT 1; SELECT 2;").is_err());
+        let statement = tokenize_statement("SELECT 'it''s',\n  a||b").unwrap();
+        let spans = statement.spans.iter().map(|s| (s.start, s.end));
+        assert_eq!(
+            spans.collect::<Vec<_>>(),
+            [(0, 6), (7, 14), (14, 15), (18, 19), (19, 21), (21, 22)]
+        );
+        // Placeholders are numbered per statement.
+        assert!(tokenize_script("SELECT ? FROM t; SELECT $1 FROM t").is_ok());
+    }
+}
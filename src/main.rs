@@ -1,62 +1,88 @@
 pub mod constants;
 pub mod database;
 pub mod database_file;
+pub mod datetime;
+pub mod diagnostic;
 pub mod dump_file;
+pub mod error;
+pub mod executor;
+pub mod expression;
 pub mod index_file;
+pub mod lexer;
+pub mod parser;
+pub mod plan;
+pub mod prepared_statement;
 pub mod read_write_types;
 pub mod record;
 pub mod table;
 pub mod table_file;
 pub mod utils;
+pub mod window;
 
 use database::Database;
 use dump_file::DumpFile;
+use error::DbError;
 
-fn main() -> Result<(), String> {
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Dumps the `.tbl` or `.ndx` file, or runs the `.sql` script, named by the arguments, or starts
+/// the prompt without any.
+fn run() -> Result<(), DbError> {
     if std::env::args().len() == 2 || std::env::args().len() == 3 {
         let file_path = std::env::args().nth(1).unwrap();
         let file_path = std::path::Path::new(&file_path);
         if !file_path.exists() {
-            return Err(format!("File not found: {}", file_path.display()));
+            return Err(DbError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("File not found: {}", file_path.display()),
+            )));
         }
-        let file_dir = file_path.parent().unwrap().to_str().unwrap();
-        let file_name = file_path.file_stem().unwrap().to_str().unwrap();
-        let file_ext = file_path.extension().unwrap().to_str().unwrap();
+        let file_ext = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
         let page = if std::env::args().len() == 3 {
             Some(std::env::args().nth(2).unwrap().parse::<u32>().unwrap())
         } else {
             None
         };
         if file_ext == "tbl" {
-            let mut table_file = table_file::TableFile::new(file_name, file_dir);
+            // The file name is already escaped, so the file is opened by its path rather than
+            // by a table name.
+            let mut table_file = table_file::TableFile::open(file_path)?;
             if let Some(page) = page {
-                table_file.dump_page(page);
-                table_file.print_page(page);
+                table_file.dump_page(page)?;
+                table_file.print_page(page)?;
             } else {
-                table_file.dump();
-                table_file.print();
+                table_file.dump()?;
+                table_file.print()?;
             }
         } else if file_ext == "ndx" {
-            let parts: Vec<&str> = file_name.split('.').collect();
-            let table_name = parts[0];
-            let column_name = parts[1];
-            let mut index_file = index_file::IndexFile::new(table_name, column_name, file_dir);
+            let mut index_file = index_file::IndexFile::open(file_path)?;
             if let Some(page) = page {
-                index_file.dump_page(page);
-                index_file.print_page(page);
+                index_file.dump_page(page)?;
+                index_file.print_page(page)?;
             } else {
-                index_file.dump();
-                index_file.print();
+                index_file.dump()?;
+                index_file.print()?;
             }
         } else if file_ext == "sql" {
-            let mut database = Database::new();
+            let mut database = Database::new()?;
             database.read_file_input(file_path)?;
         } else {
-            println!("Invalid file extension: {}", file_ext);
+            return Err(DbError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid file extension: {}", file_ext),
+            )));
         }
         return Ok(());
     }
-    let mut database = Database::new();
+    let mut database = Database::new()?;
     database.run();
     Ok(())
 }
@@ -0,0 +1,2161 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use crate::{
+    constants::{DataType, MAX_TEXT_LENGTH},
+    diagnostic,
+    error::{DbError, Result},
+    expression::parse_literal,
+    lexer::{parameter_index, unquote, unquote_identifier, ScriptStatement, Span},
+    table::Column,
+};
+
+/// A value written in a statement.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    /// The literal as written, typed once the column it is compared to or stored in is known.
+    Literal(String),
+    /// A `$n` placeholder that has not been bound yet.
+    Param(usize),
+    /// A value bound to a placeholder of a prepared statement.
+    Bound(DataType),
+}
+
+/// An expression as written in a statement.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    /// A column name, or an unquoted literal compared to or stored in a column.
+    Column(String),
+    Value(Value),
+    /// A function call. `a || b` is written as a call to `concat`.
+    Function {
+        name: String,
+        args: Vec<Expr>,
+    },
+    /// `<value> + INTERVAL '<interval>'` or `<value> - INTERVAL '<interval>'`.
+    Interval {
+        value: Box<Expr>,
+        operator: String,
+        /// The interval as written, without quotes, such as `3 days`.
+        interval: String,
+    },
+    /// `CAST(<expression> AS <type>)`.
+    Cast {
+        value: Box<Expr>,
+        data_type: DataType,
+    },
+    /// `CASE WHEN <condition> THEN <expression> ... [ELSE <expression>] END`. The simple form
+    /// `CASE <operand> WHEN <value> THEN ...` is written as `WHEN <operand> = <value>`.
+    Case {
+        branches: Vec<(Condition, Expr)>,
+        otherwise: Option<Box<Expr>>,
+    },
+    /// `<function>(<args>) OVER ([PARTITION BY ...] [ORDER BY ...] [ROWS ...])`.
+    /// `COUNT(*)` has no arguments.
+    Window {
+        function: String,
+        args: Vec<Expr>,
+        partition_by: Vec<Expr>,
+        order_by: Vec<(Expr, bool)>,
+        /// The first and last row of the frame, relative to the current row.
+        frame: Option<(FrameBound, FrameBound)>,
+    },
+}
+
+/// A bound of the rows a window function is computed over.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+impl Display for FrameBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameBound::UnboundedPreceding => write!(f, "unbounded preceding"),
+            FrameBound::Preceding(n) => write!(f, "{} preceding", n),
+            FrameBound::CurrentRow => write!(f, "current row"),
+            FrameBound::Following(n) => write!(f, "{} following", n),
+            FrameBound::UnboundedFollowing => write!(f, "unbounded following"),
+        }
+    }
+}
+
+impl Expr {
+    fn values_mut<'a>(&'a mut self, values: &mut Vec<&'a mut Value>) {
+        match self {
+            Expr::Column(_) => {}
+            Expr::Value(value) => values.push(value),
+            Expr::Function { args, .. } => args.iter_mut().for_each(|arg| arg.values_mut(values)),
+            Expr::Interval { value, .. } | Expr::Cast { value, .. } => value.values_mut(values),
+            Expr::Case {
+                branches,
+                otherwise,
+            } => {
+                for (condition, result) in branches {
+                    condition.values_mut(values);
+                    result.values_mut(values);
+                }
+                if let Some(otherwise) = otherwise {
+                    otherwise.values_mut(values);
+                }
+            }
+            Expr::Window {
+                args,
+                partition_by,
+                order_by,
+                ..
+            } => {
+                let order_by = order_by.iter_mut().map(|(expr, _)| expr);
+                for expr in args.iter_mut().chain(partition_by).chain(order_by) {
+                    expr.values_mut(values);
+                }
+            }
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Value(Value::Literal(literal)) => write!(f, "{}", literal),
+            Expr::Value(Value::Param(index)) => write!(f, "${}", index),
+            Expr::Value(Value::Bound(value)) => write!(f, "{}", value),
+            Expr::Function { name, args } => {
+                let args = args.iter().map(Expr::to_string).collect::<Vec<_>>();
+                write!(f, "{}({})", name, args.join(", "))
+            }
+            Expr::Interval {
+                value,
+                operator,
+                interval,
+            } => write!(f, "{} {} interval '{}'", value, operator, interval),
+            Expr::Cast { value, data_type } => {
+                write!(
+                    f,
+                    "cast({} as {})",
+                    value,
+                    data_type.type_name().to_lowercase()
+                )
+            }
+            Expr::Case {
+                branches,
+                otherwise,
+            } => {
+                write!(f, "case")?;
+                for (condition, result) in branches {
+                    write!(f, " when {} then {}", condition, result)?;
+                }
+                if let Some(otherwise) = otherwise {
+                    write!(f, " else {}", otherwise)?;
+                }
+                write!(f, " end")
+            }
+            Expr::Window {
+                function,
+                args,
+                partition_by,
+                order_by,
+                frame,
+            } => {
+                let args = args.iter().map(Expr::to_string).collect::<Vec<_>>();
+                match function.as_str() {
+                    "count" if args.is_empty() => write!(f, "count(*) over (")?,
+                    _ => write!(f, "{}({}) over (", function, args.join(", "))?,
+                }
+                let mut clauses = vec![];
+                if !partition_by.is_empty() {
+                    let exprs = partition_by.iter().map(Expr::to_string).collect::<Vec<_>>();
+                    clauses.push(format!("partition by {}", exprs.join(", ")));
+                }
+                if !order_by.is_empty() {
+                    let exprs = order_by
+                        .iter()
+                        .map(|(expr, descending)| match descending {
+                            true => format!("{} desc", expr),
+                            false => expr.to_string(),
+                        })
+                        .collect::<Vec<_>>();
+                    clauses.push(format!("order by {}", exprs.join(", ")));
+                }
+                if let Some((start, end)) = frame {
+                    clauses.push(format!("rows between {} and {}", start, end));
+                }
+                write!(f, "{})", clauses.join(" "))
+            }
+        }
+    }
+}
+
+/// `WHERE <expression> <operator> <expression>`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Condition {
+    pub left: Expr,
+    pub operator: String,
+    pub right: Expr,
+}
+
+impl Condition {
+    fn values_mut<'a>(&'a mut self, values: &mut Vec<&'a mut Value>) {
+        self.left.values_mut(values);
+        self.right.values_mut(values);
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.left, self.operator, self.right)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Select {
+    /// The selected expressions, [`None`] for `*`.
+    pub columns: Option<Vec<Expr>>,
+    /// The table or view selected from.
+    pub source: String,
+    /// The tables joined to `source`, in order.
+    pub joins: Vec<Join>,
+    pub condition: Option<Condition>,
+    /// The `ORDER BY` expressions, each with whether it sorts in descending order.
+    pub order_by: Vec<(Expr, bool)>,
+    /// The most records to return (`LIMIT`), counted after `order_by` sorts them.
+    pub limit: Option<u64>,
+    /// The queries combined with this one from left to right, each with its operator and whether
+    /// duplicates are kept (`ALL`). `order_by` then sorts the combined records.
+    pub compound: Vec<(SetOperator, bool, Select)>,
+    /// The common table expressions of a leading `WITH`, in order of definition.
+    pub with: Vec<CommonTable>,
+    /// Whether the common tables may refer to themselves (`WITH RECURSIVE`).
+    pub recursive: bool,
+}
+
+/// `[INNER] JOIN <source> ON <condition>`. The records of the join hold the columns of the
+/// joined sources in order, each named `<source>.<column>`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Join {
+    pub source: String,
+    pub condition: Condition,
+}
+
+/// A common table expression: `<name> [(<column>, ...)] AS (<select>)`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CommonTable {
+    pub name: String,
+    /// Names replacing those of the selected columns.
+    pub columns: Option<Vec<String>>,
+    pub query: Select,
+}
+
+impl Select {
+    fn values_mut<'a>(&'a mut self, values: &mut Vec<&'a mut Value>) {
+        for column in self.columns.iter_mut().flatten() {
+            column.values_mut(values);
+        }
+        for join in &mut self.joins {
+            join.condition.values_mut(values);
+        }
+        if let Some(condition) = &mut self.condition {
+            condition.values_mut(values);
+        }
+        for common_table in &mut self.with {
+            common_table.query.values_mut(values);
+        }
+        for (_, _, select) in &mut self.compound {
+            select.values_mut(values);
+        }
+        for (expr, _) in &mut self.order_by {
+            expr.values_mut(values);
+        }
+    }
+}
+
+/// An operation combining the records of two SELECT statements.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SetOperator {
+    Union,
+    Intersect,
+    Except,
+}
+
+impl SetOperator {
+    fn from_keyword(keyword: &str) -> Option<SetOperator> {
+        match keyword {
+            "union" => Some(SetOperator::Union),
+            "intersect" => Some(SetOperator::Intersect),
+            "except" => Some(SetOperator::Except),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SetOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetOperator::Union => write!(f, "UNION"),
+            SetOperator::Intersect => write!(f, "INTERSECT"),
+            SetOperator::Except => write!(f, "EXCEPT"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Insert {
+    pub table: String,
+    /// The columns the values are given for, [`None`] for all columns in order.
+    pub columns: Option<Vec<String>>,
+    pub values: Vec<Value>,
+    pub on_conflict: Option<OnConflict>,
+    pub returning: Option<Returning>,
+}
+
+/// `RETURNING *` or `RETURNING <expression>, ...`, the rows an INSERT, UPDATE or DELETE
+/// returns. The expressions read the columns of each row the statement inserted, updated or
+/// deleted, and its row ID as `rowid`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Returning {
+    All,
+    Columns(Vec<Expr>),
+}
+
+impl Returning {
+    fn values_mut<'a>(&'a mut self, values: &mut Vec<&'a mut Value>) {
+        if let Returning::Columns(exprs) = self {
+            exprs.iter_mut().for_each(|expr| expr.values_mut(values));
+        }
+    }
+}
+
+/// `ON CONFLICT [(<column>)] DO NOTHING | DO UPDATE SET <column> = <expression>`, what an INSERT
+/// does instead when the row has a value that a UNIQUE column already holds.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OnConflict {
+    /// The UNIQUE column the action applies to, [`None`] for any.
+    pub target: Option<String>,
+    pub action: ConflictAction,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConflictAction {
+    /// Skips the row.
+    Nothing,
+    /// Updates the row already holding the value. The expression reads the values of that row
+    /// as `<column>` and the values of the row that was not inserted as `excluded.<column>`.
+    Update { column: String, value: Expr },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Update {
+    pub table: String,
+    pub column: String,
+    pub value: Expr,
+    pub condition: Option<Condition>,
+    pub returning: Option<Returning>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Delete {
+    pub table: String,
+    pub condition: Option<Condition>,
+    pub returning: Option<Returning>,
+}
+
+/// Whether a trigger runs before or after the row it fires for is changed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TriggerTiming {
+    Before,
+    After,
+}
+
+/// The kind of statement a trigger fires for.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TriggerEvent {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl TriggerTiming {
+    pub fn from_keyword(keyword: &str) -> Option<TriggerTiming> {
+        match keyword {
+            "before" => Some(TriggerTiming::Before),
+            "after" => Some(TriggerTiming::After),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TriggerTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerTiming::Before => write!(f, "before"),
+            TriggerTiming::After => write!(f, "after"),
+        }
+    }
+}
+
+impl TriggerEvent {
+    pub fn from_keyword(keyword: &str) -> Option<TriggerEvent> {
+        match keyword {
+            "insert" => Some(TriggerEvent::Insert),
+            "update" => Some(TriggerEvent::Update),
+            "delete" => Some(TriggerEvent::Delete),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TriggerEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerEvent::Insert => write!(f, "insert"),
+            TriggerEvent::Update => write!(f, "update"),
+            TriggerEvent::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statement {
+    ShowTables,
+    Select(Select),
+    CreateTable {
+        name: String,
+        columns: Vec<Column>,
+        /// `(primary key, not null, unique)` for each column.
+        flags: Vec<(bool, bool, bool)>,
+        /// The table constraints `PRIMARY KEY (<column>)` (`true`) and `UNIQUE (<column>)`.
+        keys: Vec<(bool, String)>,
+        if_not_exists: bool,
+    },
+    CreateIndex {
+        table: String,
+        column: String,
+        if_not_exists: bool,
+    },
+    CreateView {
+        name: String,
+        /// The SELECT statement without the leading `SELECT`, as stored in the catalog.
+        definition: String,
+        query: Select,
+        if_not_exists: bool,
+    },
+    CreateTrigger {
+        name: String,
+        timing: TriggerTiming,
+        event: TriggerEvent,
+        table: String,
+        /// The statement run for each row, as stored in the catalog.
+        body: String,
+        if_not_exists: bool,
+    },
+    Insert(Insert),
+    Update(Update),
+    Delete(Delete),
+    DropTable {
+        name: String,
+        if_exists: bool,
+    },
+    DropView {
+        name: String,
+        if_exists: bool,
+    },
+    DropTrigger {
+        name: String,
+        if_exists: bool,
+    },
+    Truncate {
+        table: String,
+        /// Whether new records continue the row IDs of the removed ones, rather than restart.
+        continue_identity: bool,
+    },
+    Explain(Box<Statement>),
+    Help,
+    Exit,
+}
+
+impl Statement {
+    /// Returns every value written in the statement.
+    pub fn values_mut(&mut self) -> Vec<&mut Value> {
+        fn condition_values<'a>(
+            condition: &'a mut Option<Condition>,
+            values: &mut Vec<&'a mut Value>,
+        ) {
+            if let Some(condition) = condition {
+                condition.values_mut(values);
+            }
+        }
+        fn returning_values<'a>(
+            returning: &'a mut Option<Returning>,
+            values: &mut Vec<&'a mut Value>,
+        ) {
+            if let Some(returning) = returning {
+                returning.values_mut(values);
+            }
+        }
+        let mut values = vec![];
+        match self {
+            Statement::Select(select) => select.values_mut(&mut values),
+            Statement::Insert(insert) => {
+                values.extend(insert.values.iter_mut());
+                if let Some(OnConflict {
+                    action: ConflictAction::Update { value, .. },
+                    ..
+                }) = &mut insert.on_conflict
+                {
+                    value.values_mut(&mut values);
+                }
+                returning_values(&mut insert.returning, &mut values);
+            }
+            Statement::Update(update) => {
+                update.value.values_mut(&mut values);
+                condition_values(&mut update.condition, &mut values);
+                returning_values(&mut update.returning, &mut values);
+            }
+            Statement::Delete(delete) => {
+                condition_values(&mut delete.condition, &mut values);
+                returning_values(&mut delete.returning, &mut values);
+            }
+            Statement::Explain(statement) => return statement.values_mut(),
+            _ => {}
+        }
+        values
+    }
+}
+
+/// Parses the tokens of a single statement.
+pub fn parse(tokens: &mut VecDeque<String>) -> Result<Statement> {
+    parse_statement(tokens)
+        .and_then(|statement| expect_end(tokens).map(|_| statement))
+        .map_err(DbError::Parse)
+}
+
+/// The words the parser reads as keywords or type names, suggested for misspelled tokens.
+const KEYWORDS: &[&str] = &[
+    "after",
+    "all",
+    "and",
+    "as",
+    "action",
+    "asc",
+    "before",
+    "between",
+    "bigint",
+    "boolean",
+    "by",
+    "cascade",
+    "case",
+    "cast",
+    "char",
+    "conflict",
+    "continue",
+    "create",
+    "current",
+    "date",
+    "datetime",
+    "default",
+    "delete",
+    "desc",
+    "do",
+    "double",
+    "drop",
+    "each",
+    "else",
+    "end",
+    "except",
+    "exists",
+    "exit",
+    "explain",
+    "extract",
+    "float",
+    "following",
+    "for",
+    "from",
+    "help",
+    "identity",
+    "if",
+    "index",
+    "inner",
+    "insert",
+    "int",
+    "integer",
+    "intersect",
+    "interval",
+    "into",
+    "join",
+    "key",
+    "limit",
+    "not",
+    "nothing",
+    "null",
+    "on",
+    "order",
+    "over",
+    "partition",
+    "preceding",
+    "precision",
+    "primary",
+    "real",
+    "recursive",
+    "references",
+    "restart",
+    "restrict",
+    "returning",
+    "row",
+    "rows",
+    "select",
+    "set",
+    "show",
+    "smallint",
+    "table",
+    "tables",
+    "text",
+    "then",
+    "time",
+    "timestamp",
+    "tinyint",
+    "trigger",
+    "truncate",
+    "unbounded",
+    "union",
+    "unique",
+    "update",
+    "values",
+    "varchar",
+    "view",
+    "when",
+    "where",
+    "with",
+    "year",
+];
+
+/// Parses `statement`, read from `source`, as [`parse`] does. Errors show the line of `source`
+/// with the token they were found at, and suggest a keyword if that token is misspelled.
+pub fn parse_located(statement: &mut ScriptStatement, source: &str) -> Result<Statement> {
+    let original = statement.tokens.clone();
+    let tokens = &mut statement.tokens;
+    let count = tokens.len();
+    let (message, index) = match parse_statement(tokens) {
+        Ok(parsed) => match expect_end(tokens) {
+            Ok(()) => return Ok(parsed),
+            // The first token left over is the unexpected one.
+            Err(e) => (e, count - tokens.len()),
+        },
+        // The parser fails right after reading the token it does not expect, or after running
+        // out of tokens. A misspelled keyword at the end is taken to be the culprit.
+        Err(e) if !tokens.is_empty() => (e, count - tokens.len() - 1),
+        Err(e)
+            if count > 0
+                && misspelled_keyword(source_token(source, statement, count - 1)).is_some() =>
+        {
+            (e, count - 1)
+        }
+        Err(e) => (e, count),
+    };
+    let Some(last) = statement.spans.last() else {
+        return Err(DbError::Parse(message));
+    };
+    let (span, hint) = match statement.spans.get(index) {
+        Some(span) => {
+            let token = source_token(source, statement, index);
+            let hint =
+                accepted_keyword(token, &original, index).map(|k| format!("did you mean {}?", k));
+            (*span, hint)
+        }
+        None => (
+            Span {
+                start: last.end,
+                end: last.end,
+            },
+            None,
+        ),
+    };
+    Err(DbError::Parse(diagnostic::render(
+        source,
+        span,
+        &message,
+        hint.as_deref(),
+    )))
+}
+
+/// Returns the text of the token at `index` as written in `source`.
+fn source_token<'a>(source: &'a str, statement: &ScriptStatement, index: usize) -> &'a str {
+    let span = statement.spans[index];
+    &source[span.start..span.end]
+}
+
+/// Returns the keyword `token` is a misspelling of, in upper case.
+fn misspelled_keyword(token: &str) -> Option<String> {
+    if token.starts_with('\'') || KEYWORDS.contains(&token.to_lowercase().as_str()) {
+        return None;
+    }
+    diagnostic::suggest(token, KEYWORDS.iter().copied()).map(str::to_uppercase)
+}
+
+/// Returns the keyword `token`, read as the token at `index` of `tokens`, is a misspelling of, in
+/// upper case, if the parser accepts the keyword in its place: parsing `tokens` with the keyword
+/// instead gets further than with `token`. Of the keywords accepted, the closest is returned.
+fn accepted_keyword(token: &str, tokens: &VecDeque<String>, index: usize) -> Option<String> {
+    misspelled_keyword(token)?;
+    let reached = parse_progress(tokens.clone());
+    let mut candidates = KEYWORDS.to_vec();
+    while let Some(keyword) = diagnostic::suggest(token, candidates.iter().copied()) {
+        let mut replaced = tokens.clone();
+        replaced[index] = keyword.to_string();
+        if parse_progress(replaced) > reached {
+            return Some(keyword.to_uppercase());
+        }
+        candidates.retain(|candidate| *candidate != keyword);
+    }
+    None
+}
+
+/// Returns how far parsing `tokens` gets: how many of them are read before it fails, plus one if
+/// a whole statement was read. An empty token, which no statement accepts, is added after them,
+/// so that a statement missing tokens at its end fails only after reading all of them.
+fn parse_progress(mut tokens: VecDeque<String>) -> usize {
+    tokens.push_back(String::new());
+    let count = tokens.len();
+    let parsed = parse_statement(&mut tokens).is_ok();
+    count - tokens.len() + usize::from(parsed)
+}
+
+fn parse_statement(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    match tokens.pop_front().as_deref().unwrap_or("") {
+        "show" => parse_show(tokens),
+        "select" => Ok(Statement::Select(parse_query(tokens)?)),
+        "with" => Ok(Statement::Select(parse_with(tokens)?)),
+        "create" => parse_create(tokens),
+        "insert" => parse_insert(tokens),
+        "update" => parse_update(tokens),
+        "delete" => parse_delete(tokens),
+        "drop" => parse_drop(tokens),
+        "truncate" => parse_truncate(tokens),
+        "explain" => Ok(Statement::Explain(Box::new(parse_statement(tokens)?))),
+        "help" => parse_help(tokens),
+        "exit" => Ok(Statement::Exit),
+        command => Err(format!(
+            "Invalid command: {}",
+            std::iter::once(command)
+                .chain(tokens.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )),
+    }
+}
+
+/// Fails if any tokens are left after a statement.
+pub fn expect_end(tokens: &VecDeque<String>) -> Result<(), String> {
+    if tokens.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Unexpected tokens: {}",
+        tokens.iter().cloned().collect::<Vec<_>>().join(" ")
+    ))
+}
+
+fn parse_show(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    if tokens.pop_front().as_deref() != Some("tables") {
+        return Err("Invalid SHOW command. Expected TABLES.".to_string());
+    }
+    Ok(Statement::ShowTables)
+}
+
+/// Parses a SELECT statement without the leading `SELECT`, including the statements combined
+/// with it by set operations and the final `ORDER BY`.
+pub fn parse_select(tokens: &mut VecDeque<String>) -> Result<Select> {
+    parse_query(tokens).map_err(DbError::Parse)
+}
+
+/// Parses a SELECT statement as [`parse_select`] does, for the statements containing one.
+fn parse_query(tokens: &mut VecDeque<String>) -> Result<Select, String> {
+    let mut select = parse_select_core(tokens)?;
+    while let Some(operator) = tokens.front().and_then(|t| SetOperator::from_keyword(t)) {
+        tokens.pop_front();
+        let all = tokens.front().map(String::as_str) == Some("all");
+        if all {
+            tokens.pop_front();
+        }
+        if tokens.pop_front().as_deref() != Some("select") {
+            return Err(format!("Expected SELECT after {}.", operator));
+        }
+        select
+            .compound
+            .push((operator, all, parse_select_core(tokens)?));
+    }
+    select.order_by = parse_order_by(tokens)?;
+    select.limit = parse_limit(tokens)?;
+    Ok(select)
+}
+
+/// Parses `[RECURSIVE] <name> [(<column>, ...)] AS (<select>), ... SELECT ...` after `WITH`.
+fn parse_with(tokens: &mut VecDeque<String>) -> Result<Select, String> {
+    let recursive = tokens.front().map(String::as_str) == Some("recursive");
+    if recursive {
+        tokens.pop_front();
+    }
+    let mut with = vec![];
+    loop {
+        let name = parse_identifier(tokens, "No common table name specified.")?;
+        let columns = match tokens.front().map(String::as_str) {
+            Some("(") => {
+                tokens.pop_front();
+                Some(parse_column_list(tokens)?)
+            }
+            _ => None,
+        };
+        if tokens.pop_front().as_deref() != Some("as") {
+            return Err("Expected AS.".to_string());
+        }
+        if tokens.pop_front().as_deref() != Some("(") {
+            return Err(format!("Expected '(' before the query of {}.", name));
+        }
+        if tokens.pop_front().as_deref() != Some("select") {
+            return Err("Expected SELECT.".to_string());
+        }
+        let query = parse_query(tokens)?;
+        if tokens.pop_front().as_deref() != Some(")") {
+            return Err(format!("Expected ')' after the query of {}.", name));
+        }
+        with.push(CommonTable {
+            name,
+            columns,
+            query,
+        });
+        if tokens.front().map(String::as_str) != Some(",") {
+            break;
+        }
+        tokens.pop_front();
+    }
+    if tokens.pop_front().as_deref() != Some("select") {
+        return Err("Expected SELECT.".to_string());
+    }
+    let mut select = parse_query(tokens)?;
+    select.with = with;
+    select.recursive = recursive;
+    Ok(select)
+}
+
+/// Parses `<columns> FROM <source> [WHERE <condition>]`.
+fn parse_select_core(tokens: &mut VecDeque<String>) -> Result<Select, String> {
+    let columns = if tokens.front().map(String::as_str) == Some("*") {
+        tokens.pop_front();
+        None
+    } else {
+        let mut columns = vec![parse_expr(tokens)?];
+        while tokens.front().map(String::as_str) == Some(",") {
+            tokens.pop_front();
+            columns.push(parse_expr(tokens)?);
+        }
+        Some(columns)
+    };
+    if tokens.pop_front().as_deref() != Some("from") {
+        return Err("Expected FROM.".to_string());
+    }
+    let source = parse_identifier(tokens, "No table specified.")?;
+    let joins = parse_joins(tokens)?;
+    let condition = parse_condition(tokens)?;
+    Ok(Select {
+        columns,
+        source,
+        joins,
+        condition,
+        order_by: vec![],
+        limit: None,
+        compound: vec![],
+        with: vec![],
+        recursive: false,
+    })
+}
+
+/// Parses `[INNER] JOIN <source> ON <condition> ...` if present.
+fn parse_joins(tokens: &mut VecDeque<String>) -> Result<Vec<Join>, String> {
+    let mut joins = vec![];
+    loop {
+        match tokens.front().map(String::as_str) {
+            Some("inner") => {
+                tokens.pop_front();
+                if tokens.pop_front().as_deref() != Some("join") {
+                    return Err("Expected JOIN after INNER.".to_string());
+                }
+            }
+            Some("join") => {
+                tokens.pop_front();
+            }
+            _ => return Ok(joins),
+        }
+        let source = parse_identifier(tokens, "No table specified to join.")?;
+        if tokens.pop_front().as_deref() != Some("on") {
+            return Err(format!("Expected ON after JOIN {}.", source));
+        }
+        let condition = parse_comparison(tokens)?;
+        joins.push(Join { source, condition });
+    }
+}
+
+/// Parses `LIMIT <count>` if present.
+fn parse_limit(tokens: &mut VecDeque<String>) -> Result<Option<u64>, String> {
+    if tokens.front().map(String::as_str) != Some("limit") {
+        return Ok(None);
+    }
+    tokens.pop_front();
+    let count = tokens.pop_front().ok_or("Expected a count after LIMIT.")?;
+    count
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("Invalid LIMIT: {}", count))
+}
+
+/// Parses `ORDER BY <expression> [ASC|DESC], ...` if present.
+fn parse_order_by(tokens: &mut VecDeque<String>) -> Result<Vec<(Expr, bool)>, String> {
+    if tokens.front().map(String::as_str) != Some("order") {
+        return Ok(vec![]);
+    }
+    tokens.pop_front();
+    if tokens.pop_front().as_deref() != Some("by") {
+        return Err("Expected BY after ORDER.".to_string());
+    }
+    let mut order_by = vec![];
+    loop {
+        let expr = parse_expr(tokens)?;
+        let descending = match tokens.front().map(String::as_str) {
+            Some("asc") => {
+                tokens.pop_front();
+                false
+            }
+            Some("desc") => {
+                tokens.pop_front();
+                true
+            }
+            _ => false,
+        };
+        order_by.push((expr, descending));
+        if tokens.front().map(String::as_str) != Some(",") {
+            return Ok(order_by);
+        }
+        tokens.pop_front();
+    }
+}
+
+fn parse_create(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    match tokens.pop_front().as_deref() {
+        Some("table") => parse_create_table(tokens),
+        Some("index") => parse_create_index(tokens),
+        Some("view") => parse_create_view(tokens),
+        Some("trigger") => parse_create_trigger(tokens),
+        _ => Err("Invalid CREATE command. Expected TABLE, INDEX, VIEW or TRIGGER.".to_string()),
+    }
+}
+
+fn parse_create_table(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    let mut columns = vec![];
+    let mut flag_vec = vec![];
+    let if_not_exists = parse_if_exists(tokens, true)?;
+    let table_name = parse_identifier(tokens, "No table name specified.")?;
+    if tokens.pop_front().as_deref() != Some("(") {
+        return Err("Expected '('.".to_string());
+    }
+    let mut keys = vec![];
+    loop {
+        if let Some(key) = parse_table_key(tokens)? {
+            keys.push(key);
+            match tokens.pop_front().as_deref() {
+                Some(",") => continue,
+                Some(")") => break,
+                _ => return Err("Expected ',' or ')'.".to_string()),
+            }
+        }
+        let column_name = parse_identifier(tokens, "No column name specified.")?;
+        let (data_type, max_length) = parse_data_type(tokens)?;
+        let mut flags = (false, false, false);
+        let mut nullable = None;
+        let mut default = None;
+        let mut done = false;
+        while let Some(next) = tokens.pop_front().as_deref() {
+            let not_null = match next {
+                "primary_key" => {
+                    flags.0 = true;
+                    continue;
+                }
+                "primary" => {
+                    expect_keyword(tokens, "key")?;
+                    flags.0 = true;
+                    continue;
+                }
+                "not_null" => true,
+                "not" => {
+                    expect_keyword(tokens, "null")?;
+                    true
+                }
+                "null" => false,
+                "unique" => {
+                    flags.2 = true;
+                    continue;
+                }
+                "default" => {
+                    let literal = tokens.pop_front().ok_or("No default value specified.")?;
+                    if ["current_date", "current_timestamp"].contains(&literal.as_str()) {
+                        return Err(format!(
+                            "Default {} of column {} is not supported, defaults must be literals.",
+                            literal.to_uppercase(),
+                            column_name
+                        ));
+                    }
+                    default = Some(parse_default(&literal, &column_name, &data_type)?);
+                    continue;
+                }
+                "references" => {
+                    parse_references(tokens)?;
+                    continue;
+                }
+                "," => break,
+                ")" => {
+                    done = true;
+                    break;
+                }
+                _ => return Err("Expected ',' or ')' or flag.".to_string()),
+            };
+            if nullable == Some(not_null) {
+                return Err(format!(
+                    "Column {} is declared both NULL and NOT NULL.",
+                    column_name
+                ));
+            }
+            nullable = Some(!not_null);
+            flags.1 = not_null;
+        }
+        flag_vec.push(flags);
+        let col = Column::new(&column_name, data_type, !flags.1, flags.2)
+            .with_default(default)
+            .with_max_length(max_length);
+        columns.push(col);
+        if done {
+            break;
+        }
+    }
+    if !tokens.is_empty() {
+        return Err("Unexpected tokens.".to_string());
+    }
+    Ok(Statement::CreateTable {
+        name: table_name,
+        columns,
+        flags: flag_vec,
+        keys,
+        if_not_exists,
+    })
+}
+
+/// Parses a table constraint `PRIMARY KEY (<column>)` or `UNIQUE (<column>)`, returning whether
+/// it declares a primary key and the column it applies to, or [`None`] if the next entry is a
+/// column definition.
+fn parse_table_key(tokens: &mut VecDeque<String>) -> Result<Option<(bool, String)>, String> {
+    let primary = match (
+        tokens.front().map(String::as_str),
+        tokens.get(1).map(String::as_str),
+    ) {
+        (Some("primary"), Some("key")) => {
+            tokens.drain(..2);
+            true
+        }
+        (Some("primary_key"), Some("(")) => {
+            tokens.pop_front();
+            true
+        }
+        (Some("unique"), Some("(")) => {
+            tokens.pop_front();
+            false
+        }
+        _ => return Ok(None),
+    };
+    expect_keyword(tokens, "(")?;
+    let column_name = parse_identifier(tokens, "No key column specified.")?;
+    if tokens.front().map(String::as_str) == Some(",") {
+        return Err("Keys over several columns are not supported.".to_string());
+    }
+    expect_keyword(tokens, ")")?;
+    Ok(Some((primary, column_name)))
+}
+
+/// Parses a column type, returning it with the length `n` of `VARCHAR(n)` or `CHAR(n)`. Both are
+/// stored as `TEXT`, and `CHAR` without a length holds a single character. The database checks
+/// that `n` does not exceed the longest text a record can hold.
+fn parse_data_type(tokens: &mut VecDeque<String>) -> Result<(DataType, Option<usize>), String> {
+    let name = tokens.pop_front().ok_or("No data type specified.")?;
+    let mut max_length = (name == "char").then_some(1);
+    if ["varchar", "char"].contains(&name.as_str())
+        && tokens.front().map(String::as_str) == Some("(")
+    {
+        tokens.pop_front();
+        let length = tokens.pop_front().ok_or("No VARCHAR length specified.")?;
+        let length = length.parse::<usize>().map_err(|_| {
+            format!(
+                "VARCHAR length must be between 1 and {}, got {}.",
+                MAX_TEXT_LENGTH, length
+            )
+        })?;
+        max_length = Some(length);
+        expect_keyword(tokens, ")")?;
+    }
+    if name == "double" && tokens.front().map(String::as_str) == Some("precision") {
+        tokens.pop_front();
+    }
+    let data_type = name
+        .parse()
+        .map_err(|_| format!("Invalid data type: {}", name))?;
+    Ok((data_type, max_length))
+}
+
+fn parse_default(literal: &str, column: &str, data_type: &DataType) -> Result<DataType, String> {
+    if parameter_index(literal).is_some() {
+        return Err("Defaults cannot be parameters.".to_string());
+    }
+    parse_literal(literal, data_type)
+        .map_err(|e| format!("Invalid default {} for column {}: {}", literal, column, e))
+}
+
+/// Parses `REFERENCES <table> [(<column>)]` and its `ON DELETE` and `ON UPDATE` actions. Foreign
+/// keys are accepted so that schemas written for other databases load, but they are not enforced.
+fn parse_references(tokens: &mut VecDeque<String>) -> Result<(), String> {
+    tokens.pop_front().ok_or("No referenced table specified.")?;
+    if tokens.front().map(String::as_str) == Some("(") {
+        tokens.pop_front();
+        tokens
+            .pop_front()
+            .ok_or("No referenced column specified.")?;
+        expect_keyword(tokens, ")")?;
+    }
+    while tokens.front().map(String::as_str) == Some("on") {
+        tokens.pop_front();
+        match tokens.pop_front().as_deref() {
+            Some("delete" | "update") => {}
+            _ => return Err("Expected DELETE or UPDATE after ON.".to_string()),
+        }
+        match tokens.pop_front().as_deref() {
+            Some("cascade" | "restrict") => {}
+            Some("set") => match tokens.pop_front().as_deref() {
+                Some("null" | "default") => {}
+                _ => return Err("Expected NULL or DEFAULT after SET.".to_string()),
+            },
+            Some("no") => expect_keyword(tokens, "action")?,
+            _ => {
+                return Err(
+                    "Expected CASCADE, RESTRICT, SET NULL, SET DEFAULT or NO ACTION.".to_string(),
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses an optional `IF EXISTS`, or `IF NOT EXISTS` if `not` is set.
+fn parse_if_exists(tokens: &mut VecDeque<String>, not: bool) -> Result<bool, String> {
+    if tokens.front().map(String::as_str) != Some("if") {
+        return Ok(false);
+    }
+    tokens.pop_front();
+    if not {
+        expect_keyword(tokens, "not")?;
+    }
+    expect_keyword(tokens, "exists")?;
+    Ok(true)
+}
+
+fn expect_keyword(tokens: &mut VecDeque<String>, keyword: &str) -> Result<(), String> {
+    match tokens.pop_front() {
+        Some(token) if token == keyword => Ok(()),
+        Some(token) => Err(format!(
+            "Expected {}, found {}.",
+            keyword.to_uppercase(),
+            token
+        )),
+        None => Err(format!("Expected {}.", keyword.to_uppercase())),
+    }
+}
+
+fn parse_create_index(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    let if_not_exists = parse_if_exists(tokens, true)?;
+    let table = parse_identifier(tokens, "No table name specified.")?;
+    let parenthesized = tokens.front().map(String::as_str) == Some("(");
+    if parenthesized {
+        tokens.pop_front();
+    }
+    let column = parse_identifier(tokens, "No column name specified.")?;
+    if parenthesized {
+        tokens.pop_front().ok_or("Expected ')'")?;
+    }
+    Ok(Statement::CreateIndex {
+        table,
+        column,
+        if_not_exists,
+    })
+}
+
+fn parse_create_view(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    let if_not_exists = parse_if_exists(tokens, true)?;
+    let name = parse_identifier(tokens, "No view name specified.")?;
+    if tokens.pop_front().as_deref() != Some("as") {
+        return Err("Expected AS.".to_string());
+    }
+    if tokens.pop_front().as_deref() != Some("select") {
+        return Err("Expected SELECT.".to_string());
+    }
+    let definition = tokens.iter().cloned().collect::<Vec<_>>().join(" ");
+    let query = parse_query(tokens)?;
+    let mut statement = Statement::Select(query.clone());
+    if statement
+        .values_mut()
+        .iter()
+        .any(|value| matches!(value, Value::Param(_)))
+    {
+        return Err("Views cannot contain parameters.".to_string());
+    }
+    Ok(Statement::CreateView {
+        name,
+        definition,
+        query,
+        if_not_exists,
+    })
+}
+
+/// Parses `[IF NOT EXISTS] <name> BEFORE|AFTER INSERT|UPDATE|DELETE ON <table> FOR EACH ROW
+/// <statement>` after `CREATE TRIGGER`. The statement is an INSERT, UPDATE or DELETE that reads
+/// the row the trigger fires for as `old.<column>` and `new.<column>`.
+fn parse_create_trigger(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    let if_not_exists = parse_if_exists(tokens, true)?;
+    let name = parse_identifier(tokens, "No trigger name specified.")?;
+    let timing = tokens
+        .pop_front()
+        .and_then(|t| TriggerTiming::from_keyword(&t))
+        .ok_or("Expected BEFORE or AFTER.")?;
+    let event = tokens
+        .pop_front()
+        .and_then(|t| TriggerEvent::from_keyword(&t))
+        .ok_or("Expected INSERT, UPDATE or DELETE.")?;
+    expect_keyword(tokens, "on")?;
+    let table = parse_identifier(tokens, "No table specified.")?;
+    expect_keyword(tokens, "for")?;
+    expect_keyword(tokens, "each")?;
+    expect_keyword(tokens, "row")?;
+    let body = tokens.iter().cloned().collect::<Vec<_>>().join(" ");
+    let mut statement = parse_statement(tokens)?;
+    if !matches!(
+        statement,
+        Statement::Insert(_) | Statement::Update(_) | Statement::Delete(_)
+    ) {
+        return Err("A trigger runs an INSERT, UPDATE or DELETE statement.".to_string());
+    }
+    if statement
+        .values_mut()
+        .iter()
+        .any(|value| matches!(value, Value::Param(_)))
+    {
+        return Err("Triggers cannot contain parameters.".to_string());
+    }
+    Ok(Statement::CreateTrigger {
+        name,
+        timing,
+        event,
+        table,
+        body,
+        if_not_exists,
+    })
+}
+
+fn parse_insert(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    if tokens.pop_front().ok_or("Expected INTO")? != "into" {
+        return Err("Expected INTO.".to_string());
+    }
+    let table = parse_identifier(tokens, "No table specified.")?;
+    let columns = match tokens
+        .pop_front()
+        .ok_or("Expected columns or VALUES.")?
+        .as_str()
+    {
+        "(" => {
+            let columns = parse_column_list(tokens)?;
+            if tokens.pop_front().as_deref() != Some("values") {
+                return Err("Expected VALUES.".to_string());
+            }
+            Some(columns)
+        }
+        "values" => None,
+        _ => return Err("Expected columns or VALUES.".to_string()),
+    };
+    let values = parse_values(tokens)?;
+    let on_conflict = parse_on_conflict(tokens)?;
+    let returning = parse_returning(tokens)?;
+    Ok(Statement::Insert(Insert {
+        table,
+        columns,
+        values,
+        on_conflict,
+        returning,
+    }))
+}
+
+/// Parses `RETURNING *` or `RETURNING <expression>, ...` if present.
+fn parse_returning(tokens: &mut VecDeque<String>) -> Result<Option<Returning>, String> {
+    if tokens.front().map(String::as_str) != Some("returning") {
+        return Ok(None);
+    }
+    tokens.pop_front();
+    if tokens.front().map(String::as_str) == Some("*") {
+        tokens.pop_front();
+        return Ok(Some(Returning::All));
+    }
+    if tokens.is_empty() {
+        return Err("No columns specified after RETURNING.".to_string());
+    }
+    let mut exprs = vec![parse_expr(tokens)?];
+    while tokens.front().map(String::as_str) == Some(",") {
+        tokens.pop_front();
+        exprs.push(parse_expr(tokens)?);
+    }
+    Ok(Some(Returning::Columns(exprs)))
+}
+
+/// Parses `ON CONFLICT [(<column>)] DO NOTHING | DO UPDATE SET <column> = <expression>` if present.
+fn parse_on_conflict(tokens: &mut VecDeque<String>) -> Result<Option<OnConflict>, String> {
+    if tokens.front().map(String::as_str) != Some("on") {
+        return Ok(None);
+    }
+    tokens.pop_front();
+    expect_keyword(tokens, "conflict")?;
+    let target = match tokens.front().map(String::as_str) {
+        Some("(") => {
+            tokens.pop_front();
+            let column = parse_identifier(tokens, "No conflict column specified.")?;
+            expect_keyword(tokens, ")")?;
+            Some(column)
+        }
+        _ => None,
+    };
+    expect_keyword(tokens, "do")?;
+    let action = match tokens.pop_front().as_deref() {
+        Some("nothing") => ConflictAction::Nothing,
+        Some("update") => {
+            if target.is_none() {
+                return Err("ON CONFLICT DO UPDATE requires a conflict column.".to_string());
+            }
+            expect_keyword(tokens, "set")?;
+            let column = parse_identifier(tokens, "No column specified.")?;
+            expect_keyword(tokens, "=")?;
+            if tokens.is_empty() {
+                return Err("No value specified.".to_string());
+            }
+            let value = parse_expr(tokens)?;
+            ConflictAction::Update { column, value }
+        }
+        _ => return Err("Expected NOTHING or UPDATE after DO.".to_string()),
+    };
+    Ok(Some(OnConflict { target, action }))
+}
+
+fn parse_update(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    let table = parse_identifier(tokens, "No table specified.")?;
+    if tokens.pop_front().as_deref() != Some("set") {
+        return Err("Expected SET.".to_string());
+    }
+    let column = parse_identifier(tokens, "No column specified.")?;
+    if tokens.pop_front().as_deref() != Some("=") {
+        return Err("Expected =.".to_string());
+    }
+    if tokens.is_empty() {
+        return Err("No value specified.".to_string());
+    }
+    let value = parse_expr(tokens)?;
+    let condition = parse_condition(tokens)?;
+    let returning = parse_returning(tokens)?;
+    Ok(Statement::Update(Update {
+        table,
+        column,
+        value,
+        condition,
+        returning,
+    }))
+}
+
+fn parse_delete(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    if tokens.pop_front().as_deref() != Some("from") {
+        return Err("Expected FROM.".to_string());
+    }
+    let table = parse_identifier(tokens, "No table specified.")?;
+    let condition = parse_condition(tokens)?;
+    let returning = parse_returning(tokens)?;
+    Ok(Statement::Delete(Delete {
+        table,
+        condition,
+        returning,
+    }))
+}
+
+fn parse_drop(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    match tokens.pop_front().as_deref() {
+        Some("table") => {
+            let if_exists = parse_if_exists(tokens, false)?;
+            let name = parse_identifier(tokens, "No table specified.")?;
+            Ok(Statement::DropTable { name, if_exists })
+        }
+        Some("view") => {
+            let if_exists = parse_if_exists(tokens, false)?;
+            let name = parse_identifier(tokens, "No view specified.")?;
+            Ok(Statement::DropView { name, if_exists })
+        }
+        Some("trigger") => {
+            let if_exists = parse_if_exists(tokens, false)?;
+            let name = parse_identifier(tokens, "No trigger specified.")?;
+            Ok(Statement::DropTrigger { name, if_exists })
+        }
+        _ => Err("Expected TABLE, VIEW or TRIGGER.".to_string()),
+    }
+}
+
+/// Parses `TRUNCATE [TABLE] t [RESTART IDENTITY | CONTINUE IDENTITY]`. Row IDs restart unless
+/// `CONTINUE IDENTITY` is given.
+fn parse_truncate(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    if tokens.front().map(String::as_str) == Some("table") {
+        tokens.pop_front();
+    }
+    let table = parse_identifier(tokens, "No table specified.")?;
+    let continue_identity = match tokens.pop_front().as_deref() {
+        None => false,
+        Some(option @ ("restart" | "continue"))
+            if tokens.pop_front().as_deref() == Some("identity") =>
+        {
+            option == "continue"
+        }
+        Some(_) => return Err("Expected RESTART IDENTITY or CONTINUE IDENTITY.".to_string()),
+    };
+    Ok(Statement::Truncate {
+        table,
+        continue_identity,
+    })
+}
+
+fn parse_help(tokens: &mut VecDeque<String>) -> Result<Statement, String> {
+    if tokens.len() > 1 {
+        return Err("Invalid HELP command. Expected no arguments.".to_string());
+    }
+    Ok(Statement::Help)
+}
+
+/// Parses `column, ...)`, the opening parenthesis already consumed.
+fn parse_column_list(tokens: &mut VecDeque<String>) -> Result<Vec<String>, String> {
+    let mut columns = vec![];
+    while let Some(t) = tokens.pop_front() {
+        match t.as_str() {
+            ")" => return Ok(columns),
+            "," => continue,
+            _ => columns.push(identifier(t)?),
+        }
+    }
+    Err("Expected ')'.".to_string())
+}
+
+/// Parses `(<value>, ...)`. Tokens between commas are joined by spaces, so `DATETIME` values can
+/// be written without quotes.
+fn parse_values(tokens: &mut VecDeque<String>) -> Result<Vec<Value>, String> {
+    if tokens.is_empty() {
+        return Err("No values specified.".to_string());
+    }
+    if let Some(t) = tokens.pop_front() {
+        if t != "(" {
+            return Err(format!("Expected '('. Found: {}", t));
+        }
+    }
+    let mut values = vec![];
+    let mut current = String::new();
+    while let Some(t) = tokens.pop_front() {
+        if t == "," || t == ")" {
+            values.push(parse_value(&current));
+            current.clear();
+            if t == ")" {
+                break;
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&t);
+    }
+    Ok(values)
+}
+
+fn parse_value(token: &str) -> Value {
+    match parameter_index(token) {
+        Some(index) => Value::Param(index),
+        None => Value::Literal(token.to_string()),
+    }
+}
+
+fn parse_condition(tokens: &mut VecDeque<String>) -> Result<Option<Condition>, String> {
+    if tokens.front().map(String::as_str) != Some("where") {
+        return Ok(None);
+    }
+    tokens.pop_front();
+    if tokens.is_empty() {
+        return Err("No column specified.".to_string());
+    }
+    parse_comparison(tokens).map(Some)
+}
+
+/// Parses `<expression> <operator> <expression>`.
+fn parse_comparison(tokens: &mut VecDeque<String>) -> Result<Condition, String> {
+    let left = parse_expr(tokens)?;
+    let operator = tokens.pop_front().ok_or("No operator specified.")?;
+    if !["=", "<>", "<", "<=", ">", ">="].contains(&operator.as_str()) {
+        return Err(format!("Invalid operator: {}", operator));
+    }
+    if tokens.is_empty() {
+        return Err("No value specified.".to_string());
+    }
+    let right = parse_expr(tokens)?;
+    Ok(Condition {
+        left,
+        operator,
+        right,
+    })
+}
+
+/// Parses `<concatenation> [+|- INTERVAL '<interval>' ...]`.
+fn parse_expr(tokens: &mut VecDeque<String>) -> Result<Expr, String> {
+    let mut expr = parse_concat(tokens)?;
+    while let Some(operator) = tokens.front().filter(|t| *t == "+" || *t == "-").cloned() {
+        tokens.pop_front();
+        if tokens.pop_front().as_deref() != Some("interval") {
+            return Err(format!("Expected INTERVAL after {}.", operator));
+        }
+        expr = Expr::Interval {
+            value: Box::new(expr),
+            operator,
+            interval: parse_interval(tokens)?,
+        };
+    }
+    Ok(expr)
+}
+
+/// Parses the interval after `INTERVAL`: `'3 days'`, `'3' day` or `3 day`.
+fn parse_interval(tokens: &mut VecDeque<String>) -> Result<String, String> {
+    let token = tokens.pop_front().ok_or("Expected an interval.")?;
+    let mut interval = unquote(&token).unwrap_or(token);
+    let units = ["second", "minute", "hour", "day", "week", "month", "year"];
+    if let Some(unit) = tokens
+        .front()
+        .filter(|t| units.contains(&t.trim_end_matches('s')))
+    {
+        interval = format!("{} {}", interval, unit);
+        tokens.pop_front();
+    }
+    Ok(interval)
+}
+
+/// Parses `<term> [|| <term> ...]`.
+fn parse_concat(tokens: &mut VecDeque<String>) -> Result<Expr, String> {
+    let first = parse_term(tokens)?;
+    if tokens.front().map(String::as_str) != Some("||") {
+        return Ok(first);
+    }
+    let mut args = vec![first];
+    while tokens.front().map(String::as_str) == Some("||") {
+        tokens.pop_front();
+        args.push(parse_term(tokens)?);
+    }
+    Ok(Expr::Function {
+        name: "concat".to_string(),
+        args,
+    })
+}
+
+/// Parses a value, a column, a function call, a CASE expression or a parenthesized expression.
+fn parse_term(tokens: &mut VecDeque<String>) -> Result<Expr, String> {
+    let token = tokens.pop_front().ok_or("Expected an expression.")?;
+    if token == "(" {
+        let expr = parse_expr(tokens)?;
+        if tokens.pop_front().as_deref() != Some(")") {
+            return Err("Expected ')'.".to_string());
+        }
+        return Ok(expr);
+    }
+    if [")", ",", "||"].contains(&token.as_str()) {
+        return Err(format!("Expected an expression. Found: {}", token));
+    }
+    let is_call = tokens.front().map(String::as_str) == Some("(");
+    if !is_call && ["current_date", "current_timestamp"].contains(&token.as_str()) {
+        return Ok(Expr::Function {
+            name: token,
+            args: vec![],
+        });
+    }
+    if is_call && token == "extract" {
+        return parse_extract(tokens);
+    }
+    if is_call && token == "cast" {
+        return parse_cast(tokens);
+    }
+    if token == "case" {
+        return parse_case(tokens);
+    }
+    if is_call && token.chars().all(is_identifier_char) {
+        tokens.pop_front();
+        let mut args = vec![];
+        let count_all = token == "count"
+            && tokens.front().map(String::as_str) == Some("*")
+            && tokens.get(1).map(String::as_str) == Some(")");
+        if count_all {
+            tokens.pop_front();
+        }
+        if tokens.front().map(String::as_str) == Some(")") {
+            tokens.pop_front();
+        } else {
+            loop {
+                args.push(parse_expr(tokens)?);
+                match tokens.pop_front().as_deref() {
+                    Some(",") => continue,
+                    Some(")") => break,
+                    _ => return Err(format!("Expected ',' or ')' in call to {}.", token)),
+                }
+            }
+        }
+        if tokens.front().map(String::as_str) == Some("over") {
+            tokens.pop_front();
+            return parse_over(token, args, tokens);
+        }
+        if count_all {
+            return Err("COUNT(*) requires an OVER clause.".to_string());
+        }
+        return Ok(Expr::Function { name: token, args });
+    }
+    let is_literal = token.starts_with('\'')
+        || token == "null"
+        || (token.parse::<f64>().is_ok() && token.chars().any(|c| c.is_ascii_digit()))
+        || parameter_index(&token).is_some();
+    if is_literal {
+        Ok(Expr::Value(parse_value(&token)))
+    } else {
+        Ok(Expr::Column(identifier(token)?))
+    }
+}
+
+/// Parses `([PARTITION BY <expression>, ...] [ORDER BY ...] [ROWS ...])` after `OVER`.
+fn parse_over(
+    function: String,
+    args: Vec<Expr>,
+    tokens: &mut VecDeque<String>,
+) -> Result<Expr, String> {
+    if tokens.pop_front().as_deref() != Some("(") {
+        return Err("Expected '(' after OVER.".to_string());
+    }
+    let mut partition_by = vec![];
+    if tokens.front().map(String::as_str) == Some("partition") {
+        tokens.pop_front();
+        if tokens.pop_front().as_deref() != Some("by") {
+            return Err("Expected BY after PARTITION.".to_string());
+        }
+        partition_by.push(parse_expr(tokens)?);
+        while tokens.front().map(String::as_str) == Some(",") {
+            tokens.pop_front();
+            partition_by.push(parse_expr(tokens)?);
+        }
+    }
+    let order_by = parse_order_by(tokens)?;
+    let frame = match tokens.front().map(String::as_str) {
+        Some("rows") => {
+            tokens.pop_front();
+            Some(parse_frame(tokens)?)
+        }
+        _ => None,
+    };
+    if tokens.pop_front().as_deref() != Some(")") {
+        return Err("Expected ')' after the window of OVER.".to_string());
+    }
+    Ok(Expr::Window {
+        function,
+        args,
+        partition_by,
+        order_by,
+        frame,
+    })
+}
+
+/// Parses `BETWEEN <bound> AND <bound>` or `<bound>` after `ROWS`. A single bound is the start
+/// of a frame ending at the current row.
+fn parse_frame(tokens: &mut VecDeque<String>) -> Result<(FrameBound, FrameBound), String> {
+    if tokens.front().map(String::as_str) != Some("between") {
+        return Ok((parse_frame_bound(tokens)?, FrameBound::CurrentRow));
+    }
+    tokens.pop_front();
+    let start = parse_frame_bound(tokens)?;
+    if tokens.pop_front().as_deref() != Some("and") {
+        return Err("Expected AND in ROWS BETWEEN.".to_string());
+    }
+    let end = parse_frame_bound(tokens)?;
+    let position = |bound: FrameBound| match bound {
+        FrameBound::UnboundedPreceding => i64::MIN,
+        FrameBound::Preceding(n) => -(n as i64),
+        FrameBound::CurrentRow => 0,
+        FrameBound::Following(n) => n as i64,
+        FrameBound::UnboundedFollowing => i64::MAX,
+    };
+    if start == FrameBound::UnboundedFollowing
+        || end == FrameBound::UnboundedPreceding
+        || position(start) > position(end)
+    {
+        return Err(format!(
+            "Invalid frame: rows between {} and {}.",
+            start, end
+        ));
+    }
+    Ok((start, end))
+}
+
+fn parse_frame_bound(tokens: &mut VecDeque<String>) -> Result<FrameBound, String> {
+    let token = tokens.pop_front().ok_or("Expected a frame bound.")?;
+    let direction = tokens.pop_front();
+    let bound = match (token.as_str(), direction.as_deref()) {
+        ("unbounded", Some("preceding")) => FrameBound::UnboundedPreceding,
+        ("unbounded", Some("following")) => FrameBound::UnboundedFollowing,
+        ("current", Some("row")) => FrameBound::CurrentRow,
+        (n, Some("preceding")) if n.parse::<usize>().is_ok() => {
+            FrameBound::Preceding(n.parse().unwrap())
+        }
+        (n, Some("following")) if n.parse::<usize>().is_ok() => {
+            FrameBound::Following(n.parse().unwrap())
+        }
+        (token, direction) => {
+            return Err(format!(
+                "Invalid frame bound: {} {}",
+                token,
+                direction.unwrap_or_default()
+            ))
+        }
+    };
+    Ok(bound)
+}
+
+/// Parses the rest of a `CASE` expression, up to and including `END`.
+fn parse_case(tokens: &mut VecDeque<String>) -> Result<Expr, String> {
+    let operand = match tokens.front().map(String::as_str) {
+        Some("when") => None,
+        _ => Some(parse_expr(tokens)?),
+    };
+    let mut branches = vec![];
+    while tokens.front().map(String::as_str) == Some("when") {
+        tokens.pop_front();
+        let condition = match &operand {
+            Some(operand) => Condition {
+                left: operand.clone(),
+                operator: "=".to_string(),
+                right: parse_expr(tokens)?,
+            },
+            None => parse_comparison(tokens)?,
+        };
+        if tokens.pop_front().as_deref() != Some("then") {
+            return Err("Expected THEN in CASE.".to_string());
+        }
+        branches.push((condition, parse_expr(tokens)?));
+    }
+    if branches.is_empty() {
+        return Err("Expected WHEN in CASE.".to_string());
+    }
+    let otherwise = match tokens.front().map(String::as_str) {
+        Some("else") => {
+            tokens.pop_front();
+            Some(Box::new(parse_expr(tokens)?))
+        }
+        _ => None,
+    };
+    if tokens.pop_front().as_deref() != Some("end") {
+        return Err("Expected END in CASE.".to_string());
+    }
+    Ok(Expr::Case {
+        branches,
+        otherwise,
+    })
+}
+
+/// Parses `(<field> FROM <expression>)` after `EXTRACT` into a call with the field as a text
+/// literal.
+fn parse_extract(tokens: &mut VecDeque<String>) -> Result<Expr, String> {
+    tokens.pop_front();
+    let field = tokens.pop_front().ok_or("Expected a field to extract.")?;
+    let field = unquote(&field).unwrap_or(field).to_lowercase();
+    if tokens.pop_front().as_deref() != Some("from") {
+        return Err("Expected FROM in EXTRACT.".to_string());
+    }
+    let value = parse_expr(tokens)?;
+    if tokens.pop_front().as_deref() != Some(")") {
+        return Err("Expected ')'.".to_string());
+    }
+    Ok(Expr::Function {
+        name: "extract".to_string(),
+        args: vec![Expr::Value(Value::Literal(format!("'{}'", field))), value],
+    })
+}
+
+/// Parses `(<expression> AS <type>)` after `CAST`.
+fn parse_cast(tokens: &mut VecDeque<String>) -> Result<Expr, String> {
+    tokens.pop_front();
+    let value = parse_expr(tokens)?;
+    expect_keyword(tokens, "as")?;
+    let (data_type, _) = parse_data_type(tokens)?;
+    expect_keyword(tokens, ")")?;
+    Ok(Expr::Cast {
+        value: Box::new(value),
+        data_type,
+    })
+}
+
+/// Reads the name of a table, column, view or trigger, failing with `missing` if there is none.
+fn parse_identifier(tokens: &mut VecDeque<String>, missing: &str) -> Result<String, String> {
+    identifier(tokens.pop_front().ok_or(missing)?)
+}
+
+/// Returns the name `token` stands for. Quoted identifiers keep their case and may contain any
+/// character, including spaces, or be keywords.
+fn identifier(token: String) -> Result<String, String> {
+    match unquote_identifier(&token) {
+        Some(name) if name.is_empty() => Err("Quoted identifiers cannot be empty.".to_string()),
+        Some(name) => Ok(name),
+        None => Ok(token),
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    #[test]
+    fn test_parse_select() {
+        let statement = parse(&mut tokenize("SELECT id, name FROM test WHERE id >= ?;").unwrap());
+        assert_eq!(
+            statement.unwrap(),
+            Statement::Select(Select {
+                columns: Some(vec![
+                    Expr::Column("id".to_string()),
+                    Expr::Column("name".to_string())
+                ]),
+                source: "test".to_string(),
+                joins: vec![],
+                condition: Some(Condition {
+                    left: Expr::Column("id".to_string()),
+                    operator: ">=".to_string(),
+                    right: Expr::Value(Value::Param(1)),
+                }),
+                order_by: vec![],
+                limit: None,
+                compound: vec![],
+                with: vec![],
+                recursive: false,
+            })
+        );
+        let statement = parse(
+            &mut tokenize(
+                "WITH RECURSIVE t (n) AS (SELECT id FROM test UNION ALL SELECT n FROM t WHERE n < 3), \
+                 u AS (SELECT * FROM t) SELECT * FROM u;",
+            )
+            .unwrap(),
+        );
+        let Ok(Statement::Select(select)) = statement else {
+            panic!("Expected a SELECT, got {:?}", statement);
+        };
+        assert!(select.recursive);
+        assert_eq!(select.source, "u");
+        assert_eq!(select.with[0].columns, Some(vec!["n".to_string()]));
+        assert_eq!(select.with[0].query.compound[0].2.source, "t");
+        assert_eq!(select.with[1].query.source, "t");
+        assert!(
+            parse(&mut tokenize("WITH t AS SELECT * FROM test SELECT * FROM t;").unwrap()).is_err()
+        );
+        assert!(
+            parse(&mut tokenize("WITH t AS (SELECT * FROM test) DELETE FROM t;").unwrap()).is_err()
+        );
+        let select = parse_select(
+            &mut tokenize("* FROM test ORDER BY CASE id WHEN 1 THEN 'a' ELSE name END DESC, id")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            select.order_by[0].0.to_string(),
+            "case when id = 1 then 'a' else name end"
+        );
+        assert!(select.order_by[0].1);
+        assert_eq!(select.order_by[1], (Expr::Column("id".to_string()), false));
+        assert!(parse(&mut tokenize("SELECT * FROM test ORDER id;").unwrap()).is_err());
+        let select = parse_select(
+            &mut tokenize(
+                "a.id, b.name FROM a JOIN b ON a.id = b.a_id INNER JOIN c ON c.id = a.id \
+                 WHERE b.name <> 'x' ORDER BY a.id LIMIT 10",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let sources = select
+            .joins
+            .iter()
+            .map(|j| j.source.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(sources, ["b", "c"]);
+        assert_eq!(select.joins[0].condition.to_string(), "a.id = b.a_id");
+        assert_eq!(select.limit, Some(10));
+        assert!(parse(&mut tokenize("SELECT * FROM a JOIN b;").unwrap()).is_err());
+        assert!(parse(&mut tokenize("SELECT * FROM a INNER b ON x = y;").unwrap()).is_err());
+        assert!(parse(&mut tokenize("SELECT * FROM test LIMIT -1;").unwrap()).is_err());
+        assert!(
+            parse(&mut tokenize("SELECT CASE WHEN id THEN 1 END FROM test;").unwrap()).is_err()
+        );
+        assert!(
+            parse(&mut tokenize("SELECT CASE WHEN id = 1 THEN 1 FROM test;").unwrap()).is_err()
+        );
+        assert!(parse(&mut tokenize("SELECT * FROM test WHERE id ! 1;").unwrap()).is_err());
+        assert!(parse(&mut tokenize("SELECT * FROM test id = 1;").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_expr() {
+        let mut tokens = tokenize("upper(trim(name)) || '!' || 1, substr(name, 2) FROM t").unwrap();
+        let expr = parse_expr(&mut tokens).unwrap();
+        assert_eq!(expr.to_string(), "concat(upper(trim(name)), '!', 1)");
+        assert_eq!(tokens.pop_front().as_deref(), Some(","));
+        let expr = parse_expr(&mut tokens).unwrap();
+        assert_eq!(
+            expr,
+            Expr::Function {
+                name: "substr".to_string(),
+                args: vec![
+                    Expr::Column("name".to_string()),
+                    Expr::Value(Value::Literal("2".to_string()))
+                ]
+            }
+        );
+        assert!(parse_expr(&mut tokenize("upper(name").unwrap()).is_err());
+        assert!(parse_expr(&mut tokenize("name ||").unwrap()).is_err());
+
+        let mut tokens =
+            tokenize("EXTRACT(YEAR FROM d + INTERVAL '1' MONTH - INTERVAL 2 days)").unwrap();
+        assert_eq!(
+            parse_expr(&mut tokens).unwrap().to_string(),
+            "extract('year', d + interval '1 month' - interval '2 days')"
+        );
+        let expr = parse_expr(&mut tokenize("current_date").unwrap()).unwrap();
+        assert_eq!(expr.to_string(), "current_date()");
+        assert!(parse_expr(&mut tokenize("d + 1").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_insert() {
+        let mut statement = parse(
+            &mut tokenize(
+                "INSERT INTO test (id, name, born) VALUES (?, 'a, b', 2021-01-01 12:00:00);",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            statement.values_mut(),
+            [
+                &mut Value::Param(1),
+                &mut Value::Literal("'a, b'".to_string()),
+                &mut Value::Literal("2021-01-01 12:00:00".to_string()),
+            ]
+        );
+        assert!(parse(&mut tokenize("INSERT test VALUES (1);").unwrap()).is_err());
+        let statement = parse(
+            &mut tokenize(
+                "INSERT INTO t VALUES (1, 'a') ON CONFLICT (id) DO UPDATE SET n = excluded.n;",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let Statement::Insert(insert) = statement else {
+            panic!("Expected an INSERT");
+        };
+        assert_eq!(insert.values.len(), 2);
+        assert_eq!(
+            insert.on_conflict,
+            Some(OnConflict {
+                target: Some("id".to_string()),
+                action: ConflictAction::Update {
+                    column: "n".to_string(),
+                    value: Expr::Column("excluded.n".to_string()),
+                },
+            })
+        );
+        let statement =
+            parse(&mut tokenize("INSERT INTO t VALUES (1) ON CONFLICT DO NOTHING;").unwrap());
+        assert_eq!(
+            statement.unwrap(),
+            Statement::Insert(Insert {
+                table: "t".to_string(),
+                columns: None,
+                values: vec![Value::Literal("1".to_string())],
+                on_conflict: Some(OnConflict {
+                    target: None,
+                    action: ConflictAction::Nothing,
+                }),
+                returning: None,
+            })
+        );
+        assert!(parse(
+            &mut tokenize("INSERT INTO t VALUES (1) ON CONFLICT DO UPDATE SET a = 1;").unwrap()
+        )
+        .is_err());
+        assert!(parse(&mut tokenize("INSERT INTO t VALUES (1) extra;").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_create_trigger() {
+        let statement = parse(
+            &mut tokenize(
+                "CREATE TRIGGER IF NOT EXISTS audit AFTER UPDATE ON t FOR EACH ROW \
+                 INSERT INTO log VALUES (OLD.id, 'Changed');",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            statement,
+            Statement::CreateTrigger {
+                name: "audit".to_string(),
+                timing: TriggerTiming::After,
+                event: TriggerEvent::Update,
+                table: "t".to_string(),
+                body: "insert into log values ( old.id , 'Changed' )".to_string(),
+                if_not_exists: true,
+            }
+        );
+        for sql in [
+            "CREATE TRIGGER a DURING UPDATE ON t FOR EACH ROW DELETE FROM t;",
+            "CREATE TRIGGER a AFTER SELECT ON t FOR EACH ROW DELETE FROM t;",
+            "CREATE TRIGGER a AFTER UPDATE ON t DELETE FROM t;",
+            "CREATE TRIGGER a AFTER UPDATE ON t FOR EACH ROW SELECT * FROM t;",
+            "CREATE TRIGGER a AFTER UPDATE ON t FOR EACH ROW DELETE FROM t WHERE id = ?;",
+        ] {
+            assert!(parse(&mut tokenize(sql).unwrap()).is_err(), "{}", sql);
+        }
+        assert_eq!(
+            parse(&mut tokenize("DROP TRIGGER IF EXISTS audit;").unwrap()).unwrap(),
+            Statement::DropTrigger {
+                name: "audit".to_string(),
+                if_exists: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table() {
+        let statement = parse(
+            &mut tokenize(
+                "CREATE TABLE t (id INTEGER PRIMARY KEY NOT NULL, name VARCHAR(10) DEFAULT 'x', \
+                 owner INT REFERENCES users (id), at TIMESTAMP NULL);",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let Statement::CreateTable {
+            columns,
+            flags,
+            if_not_exists,
+            ..
+        } = statement
+        else {
+            panic!("Expected CREATE TABLE, got {:?}", statement);
+        };
+        assert!(!if_not_exists);
+        assert_eq!(
+            flags,
+            [
+                (true, true, false),
+                (false, false, false),
+                (false, false, false),
+                (false, false, false)
+            ]
+        );
+        assert_eq!(columns[0].data_type, DataType::Int(0));
+        assert_eq!(columns[1].default, Some(DataType::Text("'x'".to_string())));
+        assert_eq!(columns[3].data_type, DataType::DateTime(0));
+        assert!(columns[3].nullable);
+        assert_eq!(columns[1].max_length, Some(10));
+
+        let statement = parse(
+            &mut tokenize(
+                "CREATE TABLE p (id INTEGER NOT NULL, name TEXT, score DOUBLE PRECISION, \
+                 PRIMARY KEY (id), UNIQUE (name));",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let Statement::CreateTable {
+            columns,
+            flags,
+            keys,
+            ..
+        } = statement
+        else {
+            panic!("Expected CREATE TABLE, got {:?}", statement);
+        };
+        assert_eq!(
+            flags,
+            [
+                (false, true, false),
+                (false, false, false),
+                (false, false, false)
+            ]
+        );
+        assert_eq!(
+            keys,
+            [(true, "id".to_string()), (false, "name".to_string())]
+        );
+        assert_eq!(columns[2].data_type, DataType::Double(0.0));
+
+        let statement = parse(
+            &mut tokenize(
+                "CREATE TABLE o (id INT REFERENCES p (id) ON DELETE CASCADE ON UPDATE NO ACTION, \
+                 code CHAR(2), flag CHAR, done BOOLEAN REFERENCES q ON DELETE SET NULL);",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let Statement::CreateTable { columns, .. } = statement else {
+            panic!("Expected CREATE TABLE, got {:?}", statement);
+        };
+        assert_eq!(columns[1].data_type, DataType::Text(String::new()));
+        assert_eq!(columns[1].max_length, Some(2));
+        assert_eq!(columns[2].max_length, Some(1));
+        assert_eq!(columns[3].data_type, DataType::TinyInt(0));
+
+        for invalid in [
+            "CREATE TABLE t (id INT, PRIMARY KEY (id, name));",
+            "CREATE TABLE t (id INT NOT NULL NULL);",
+            "CREATE TABLE t (id INT DEFAULT 'a');",
+            "CREATE TABLE t (name VARCHAR(abc));",
+            "CREATE TABLE t (at DATE DEFAULT CURRENT_DATE);",
+            "CREATE TABLE t (id INT REFERENCES p (id) ON DELETE EXPLODE);",
+            "CREATE TABLE t (id INT PRIMARY);",
+            "CREATE TABLE IF EXISTS t (id INT);",
+            "DROP TABLE IF NOT EXISTS t;",
+        ] {
+            assert!(
+                parse(&mut tokenize(invalid).unwrap()).is_err(),
+                "{}",
+                invalid
+            );
+        }
+        assert_eq!(
+            parse(&mut tokenize("DROP VIEW IF EXISTS v;").unwrap()).unwrap(),
+            Statement::DropView {
+                name: "v".to_string(),
+                if_exists: true
+            }
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt::Display;
+
+use crate::constants::DataType;
+
+/// The steps a statement takes to produce or modify records, as shown by `EXPLAIN`. Steps
+/// reading a table or filtering records carry the estimated number of records they return in
+/// `rows`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Plan {
+    /// Reads every record of `table`.
+    FullScan {
+        table: String,
+        pages: u64,
+        rows: u64,
+    },
+    /// Reads the records with `column = value` through the index on `column`.
+    IndexLookup {
+        table: String,
+        column: String,
+        value: DataType,
+        pages: u64,
+        rows: u64,
+    },
+    /// Reads the records with `column <operator> value` through the index on `column`.
+    IndexRange {
+        table: String,
+        column: String,
+        operator: String,
+        value: DataType,
+        pages: u64,
+        rows: u64,
+    },
+    /// Reads the records with `column` equal to the value of the column `key` of each record of
+    /// the left side of a join, through the index on `column`. `pages` and `rows` are those of a
+    /// single lookup.
+    KeyLookup {
+        table: String,
+        column: String,
+        key: String,
+        pages: u64,
+        rows: u64,
+    },
+    /// Reads the records matching `condition`, if any, in the order of the values of `column`
+    /// through its index.
+    IndexOrder {
+        table: String,
+        column: String,
+        condition: Option<String>,
+        descending: bool,
+        pages: u64,
+        rows: u64,
+    },
+    /// Reads the records of the previous step of the recursive common table `name`.
+    WorkTable { name: String },
+    /// Computes the records of the common table expression `name`.
+    CommonTable { name: String, input: Box<Plan> },
+    /// Runs the stored query of the view `name`.
+    View { name: String, input: Box<Plan> },
+    /// Keeps the records of `input` matching `condition`.
+    Filter {
+        condition: String,
+        rows: u64,
+        input: Box<Plan>,
+    },
+    /// Pairs each record of `left` with each record of `right`, keeping the pairs matching
+    /// `condition`. `right` is read again for each left record, so its pages are counted once
+    /// for each record `left` is estimated to return.
+    Join {
+        condition: String,
+        left: Box<Plan>,
+        right: Box<Plan>,
+    },
+    /// Computes the aggregate `functions` over every record of `input`, giving a single record.
+    Aggregate {
+        functions: Vec<String>,
+        input: Box<Plan>,
+    },
+    /// Computes the window `functions` over the records of `input`.
+    Window {
+        functions: Vec<String>,
+        input: Box<Plan>,
+    },
+    /// Sorts the records of `input` by `keys`.
+    Sort { keys: Vec<String>, input: Box<Plan> },
+    /// Combines the records of `left` and `right` with a set operation such as `UNION ALL`.
+    SetOperation {
+        operator: String,
+        left: Box<Plan>,
+        right: Box<Plan>,
+    },
+    /// Keeps only `columns` of the records of `input`.
+    Project {
+        columns: Vec<String>,
+        input: Box<Plan>,
+    },
+    /// Keeps the first `count` records of `input`.
+    Limit { count: u64, input: Box<Plan> },
+    /// Sets `column` on every record of `input`.
+    Update {
+        table: String,
+        column: String,
+        input: Box<Plan>,
+    },
+    /// Deletes every record of `input`.
+    Delete { table: String, input: Box<Plan> },
+}
+
+impl Plan {
+    /// Returns the estimated number of pages read by this step and all steps below it.
+    pub fn pages(&self) -> u64 {
+        match self {
+            Plan::FullScan { pages, .. }
+            | Plan::IndexLookup { pages, .. }
+            | Plan::IndexRange { pages, .. }
+            | Plan::KeyLookup { pages, .. }
+            | Plan::IndexOrder { pages, .. } => *pages,
+            Plan::Join { left, right, .. } => left
+                .pages()
+                .saturating_add(left.rows().saturating_mul(right.pages())),
+            _ => self.inputs().into_iter().map(Plan::pages).sum(),
+        }
+    }
+
+    /// Returns the estimated number of records returned by this step. A join is estimated to
+    /// return every pair of records, and the records of a work table, which are only known while
+    /// the query runs, to be a single one.
+    pub fn rows(&self) -> u64 {
+        match self {
+            Plan::FullScan { rows, .. }
+            | Plan::IndexLookup { rows, .. }
+            | Plan::IndexRange { rows, .. }
+            | Plan::KeyLookup { rows, .. }
+            | Plan::IndexOrder { rows, .. }
+            | Plan::Filter { rows, .. } => *rows,
+            Plan::WorkTable { .. } | Plan::Aggregate { .. } => 1,
+            Plan::Join { left, right, .. } => left.rows().saturating_mul(right.rows()),
+            Plan::SetOperation { left, right, .. } => left.rows().saturating_add(right.rows()),
+            Plan::Limit { count, input } => input.rows().min(*count),
+            Plan::CommonTable { input, .. }
+            | Plan::View { input, .. }
+            | Plan::Window { input, .. }
+            | Plan::Sort { input, .. }
+            | Plan::Project { input, .. }
+            | Plan::Update { input, .. }
+            | Plan::Delete { input, .. } => input.rows(),
+        }
+    }
+
+    fn inputs(&self) -> Vec<&Plan> {
+        match self {
+            Plan::FullScan { .. }
+            | Plan::IndexLookup { .. }
+            | Plan::IndexRange { .. }
+            | Plan::KeyLookup { .. }
+            | Plan::IndexOrder { .. }
+            | Plan::WorkTable { .. } => vec![],
+            Plan::CommonTable { input, .. }
+            | Plan::View { input, .. }
+            | Plan::Filter { input, .. }
+            | Plan::Aggregate { input, .. }
+            | Plan::Window { input, .. }
+            | Plan::Sort { input, .. }
+            | Plan::Project { input, .. }
+            | Plan::Limit { input, .. }
+            | Plan::Update { input, .. }
+            | Plan::Delete { input, .. } => vec![input],
+            Plan::SetOperation { left, right, .. } | Plan::Join { left, right, .. } => {
+                vec![left, right]
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Plan::FullScan { table, pages, .. } => {
+                format!("Full scan on {} (est. pages: {})", table, pages)
+            }
+            Plan::IndexLookup {
+                table,
+                column,
+                value,
+                pages,
+                ..
+            } => format!(
+                "Index lookup on {}.{} ({} = {}) (est. pages: {})",
+                table, column, column, value, pages
+            ),
+            Plan::IndexRange {
+                table,
+                column,
+                operator,
+                value,
+                pages,
+                ..
+            } => format!(
+                "Index range scan on {}.{} ({} {} {}) (est. pages: {})",
+                table, column, column, operator, value, pages
+            ),
+            Plan::KeyLookup {
+                table,
+                column,
+                key,
+                pages,
+                ..
+            } => format!(
+                "Index lookup on {}.{} ({} = {}) (est. pages: {})",
+                table, column, column, key, pages
+            ),
+            Plan::IndexOrder {
+                table,
+                column,
+                condition,
+                descending,
+                pages,
+                ..
+            } => format!(
+                "Index scan on {}.{} in {} order{} (est. pages: {})",
+                table,
+                column,
+                if *descending {
+                    "descending"
+                } else {
+                    "ascending"
+                },
+                match condition {
+                    Some(condition) => format!(" ({})", condition),
+                    None => String::new(),
+                },
+                pages
+            ),
+            Plan::WorkTable { name } => format!("Work table {}", name),
+            Plan::CommonTable { name, .. } => format!("Common table {}", name),
+            Plan::View { name, .. } => format!("View {}", name),
+            Plan::Filter { condition, .. } => format!("Filter ({})", condition),
+            Plan::Join { condition, .. } => format!("Nested loop join ({})", condition),
+            Plan::Aggregate { functions, .. } => format!("Aggregate ({})", functions.join(", ")),
+            Plan::Window { functions, .. } => format!("Window ({})", functions.join(", ")),
+            Plan::Sort { keys, .. } => format!("Sort ({})", keys.join(", ")),
+            Plan::SetOperation { operator, .. } => operator.clone(),
+            Plan::Project { columns, .. } => format!("Project ({})", columns.join(", ")),
+            Plan::Limit { count, .. } => format!("Limit ({})", count),
+            Plan::Update { table, column, .. } => format!("Update {} set {}", table, column),
+            Plan::Delete { table, .. } => format!("Delete from {}", table),
+        }
+    }
+
+    /// Writes the steps below this one, each on its own line below its parent.
+    fn fmt_inputs(&self, f: &mut std::fmt::Formatter<'_>, prefix: &str) -> std::fmt::Result {
+        let inputs = self.inputs();
+        for (i, plan) in inputs.iter().enumerate() {
+            let last = i + 1 == inputs.len();
+            let branch = if last { "└── " } else { "├── " };
+            writeln!(f, "{}{}{}", prefix, branch, plan.describe())?;
+            let indent = if last { "    " } else { "│   " };
+            plan.fmt_inputs(f, &format!("{}{}", prefix, indent))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.describe())?;
+        self.fmt_inputs(f, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_plan() {
+        let plan = Plan::Project {
+            columns: vec!["id".to_string(), "name".to_string()],
+            input: Box::new(Plan::Filter {
+                condition: "name = 'bob'".to_string(),
+                rows: 4,
+                input: Box::new(Plan::FullScan {
+                    table: "test".to_string(),
+                    pages: 3,
+                    rows: 40,
+                }),
+            }),
+        };
+        assert_eq!(3, plan.pages());
+        assert_eq!(4, plan.rows());
+        assert_eq!(
+            "Project (id, name)\n\
+             └── Filter (name = 'bob')\n    \
+             └── Full scan on test (est. pages: 3)\n",
+            plan.to_string()
+        );
+
+        let scan = |table: &str| Plan::FullScan {
+            table: table.to_string(),
+            pages: 2,
+            rows: 20,
+        };
+        let plan = Plan::Sort {
+            keys: vec!["id".to_string()],
+            input: Box::new(Plan::SetOperation {
+                operator: "UNION".to_string(),
+                left: Box::new(Plan::Project {
+                    columns: vec!["id".to_string()],
+                    input: Box::new(scan("a")),
+                }),
+                right: Box::new(scan("b")),
+            }),
+        };
+        assert_eq!(4, plan.pages());
+        assert_eq!(40, plan.rows());
+
+        assert_eq!(
+            "Sort (id)\n\
+             └── UNION\n    \
+             ├── Project (id)\n    \
+             │   └── Full scan on a (est. pages: 2)\n    \
+             └── Full scan on b (est. pages: 2)\n",
+            plan.to_string()
+        );
+
+        // The right side of a join is read once for each record of the left side.
+        let plan = Plan::Join {
+            condition: "a.id = b.id".to_string(),
+            left: Box::new(scan("a")),
+            right: Box::new(scan("b")),
+        };
+        assert_eq!(2 + 20 * 2, plan.pages());
+        assert_eq!(400, plan.rows());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    constants::DataType,
+    database::Database,
+    error::{DbError, Result},
+    parser::{Statement, Value},
+};
+
+/// A statement parsed once by [`Database::prepare`] and run with the values bound to its
+/// placeholders.
+pub struct PreparedStatement {
+    statement: Statement,
+    /// The value bound to each placeholder, `$1` first.
+    params: Vec<Option<DataType>>,
+}
+
+impl PreparedStatement {
+    pub(crate) fn new(mut statement: Statement) -> Self {
+        let param_count = statement
+            .values_mut()
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::Param(index) => Some(*index),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        Self {
+            statement,
+            params: vec![None; param_count],
+        }
+    }
+
+    /// Returns the number of placeholders of the statement.
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Binds `value` to the placeholder `index`, counting from 1. The value stays bound for
+    /// later executions until it is bound again.
+    pub fn bind(&mut self, index: usize, value: DataType) -> Result<()> {
+        if index == 0 || index > self.params.len() {
+            return Err(DbError::Parse(format!(
+                "Parameter index {} out of range. The statement has {} parameters.",
+                index,
+                self.params.len()
+            )));
+        }
+        self.params[index - 1] = Some(value);
+        Ok(())
+    }
+
+    /// Runs the statement with the bound values on `database`.
+    pub fn execute(&self, database: &mut Database) -> Result<String> {
+        let mut statement = self.statement.clone();
+        for value in statement.values_mut() {
+            if let Value::Param(index) = *value {
+                let bound = self.params[index - 1]
+                    .clone()
+                    .ok_or_else(|| DbError::Parse(format!("Parameter ${} is not bound.", index)))?;
+                *value = Value::Bound(bound);
+            }
+        }
+        database.execute(&statement)
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use crate::constants::DataType;
+use crate::expression::{compare, matches_operator};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Record {
@@ -27,17 +28,10 @@ impl Record {
         }
     }
 
+    /// Returns whether the value of `column` compares to `value` as `operator` says. Numbers of
+    /// different types compare by value, and comparisons with `NULL` never match.
     pub fn compare_column(&self, column: usize, value: &DataType, operator: &str) -> bool {
-        let column_value = &self.values[column];
-        match operator {
-            "=" => column_value == value,
-            "<>" => column_value != value,
-            "<" => column_value < value,
-            "<=" => column_value <= value,
-            ">" => column_value > value,
-            ">=" => column_value >= value,
-            _ => unreachable!("Invalid operator"),
-        }
+        matches_operator(compare(&self.values[column], value), operator)
     }
 
     pub fn column_widths(&self) -> Vec<usize> {
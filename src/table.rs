@@ -1,13 +1,28 @@
-use std::cmp::min;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
-use crate::{constants::DataType, index_file::IndexFile, record::Record, table_file::TableFile};
+use crate::{
+    constants::DataType,
+    database_file::{index_file_path, table_file_path, DatabaseFile},
+    diagnostic::column_not_found,
+    error::{DbError, Result},
+    expression::{as_f64, coerce, display_literal, index_order, text_content},
+    index_file::{IndexFile, IndexStatistics},
+    record::Record,
+    table_file::{Cursor, TableFile},
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
     pub nullable: bool,
     pub unique: bool,
+    /// The value stored when an INSERT leaves the column out.
+    pub default: Option<DataType>,
+    /// The most characters a `VARCHAR(n)` column holds, or [`None`] if its text is only limited
+    /// by what a record can hold.
+    pub max_length: Option<usize>,
 }
 
 impl Column {
@@ -17,16 +32,82 @@ impl Column {
             data_type,
             nullable,
             unique,
+            default: None,
+            max_length: None,
         }
     }
+
+    /// Returns the column with `default` as its [`Column::default`].
+    pub fn with_default(mut self, default: Option<DataType>) -> Column {
+        self.default = default;
+        self
+    }
+
+    /// Returns the column with `max_length` as its [`Column::max_length`].
+    pub fn with_max_length(mut self, max_length: Option<usize>) -> Column {
+        self.max_length = max_length;
+        self
+    }
 }
 
+/// A value equals a column if the column can store it: `NULL` if the column is nullable, or a
+/// value that [`coerce`] converts to the column's type.
 impl PartialEq<DataType> for Column {
     fn eq(&self, other: &DataType) -> bool {
-        let u8_data_type: u8 = min((&self.data_type).into(), 0x0C);
-        let u8_other: u8 = min(other.into(), 0x0C);
-        u8_data_type == u8_other || (self.nullable && other == &DataType::Null)
+        match other {
+            DataType::Null => self.nullable,
+            value => coerce(value.clone(), &self.data_type).is_some(),
+        }
+    }
+}
+
+/// Returns the position of the column `name` in `columns`. The columns of a join are named
+/// `<source>.<column>`, and can also be found by `<column>` alone if only one source has it.
+pub fn column_index(columns: &[Column], name: &str) -> Option<usize> {
+    if let Some(index) = columns.iter().position(|c| c.name == name) {
+        return Some(index);
     }
+    let mut qualified = columns.iter().enumerate().filter(|(_, c)| {
+        c.name
+            .rsplit_once('.')
+            .is_some_and(|(_, column)| column == name)
+    });
+    match (qualified.next(), qualified.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+/// Estimates the share of the values of an index that compare to `value` with `operator`. A
+/// range of numbers is assumed to cover the values between the lowest and highest one evenly;
+/// any other range is assumed to hold a third of them.
+fn selectivity(statistics: &IndexStatistics, operator: &str, value: &DataType) -> f64 {
+    let equal = 1.0 / statistics.distinct.max(1.0);
+    let min = statistics.min.as_ref().and_then(as_f64);
+    let max = statistics.max.as_ref().and_then(as_f64);
+    let below = match (min, max, as_f64(value)) {
+        (Some(min), Some(max), Some(value)) if max > min => {
+            Some(((value - min) / (max - min)).clamp(0.0, 1.0)).filter(|b| !b.is_nan())
+        }
+        _ => None,
+    };
+    match operator {
+        "=" => equal,
+        "<>" => 1.0 - equal,
+        "<" | "<=" => below.unwrap_or(1.0 / 3.0),
+        _ => below.map_or(1.0 / 3.0, |below| 1.0 - below),
+    }
+}
+
+/// How [`Table::search`] finds the records matching a condition.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AccessPath {
+    /// Every record of the table file is read and compared.
+    FullScan,
+    /// The row IDs of a single value are read from the index on `column`.
+    IndexLookup { column: String },
+    /// The row IDs of a range of values are collected from the index on `column`.
+    IndexRange { column: String },
 }
 
 #[derive(Debug)]
@@ -35,18 +116,35 @@ pub struct Table {
     pub columns: Vec<Column>,
     pub dir: String,
     table_file: TableFile,
+    /// The row ID new records are numbered after even if no stored record has it, kept when
+    /// the table is truncated with `CONTINUE IDENTITY`.
+    row_id_floor: u32,
 }
 
 impl Table {
     /// Creates a new [`Table`].
-    pub fn new(name: &str, columns: Vec<Column>, dir: &str) -> Table {
-        let table_file = TableFile::new(name, dir);
-        Table {
+    pub fn new(name: &str, columns: Vec<Column>, dir: &str) -> Result<Table> {
+        let table_file = TableFile::new(name, dir)?;
+        Ok(Table {
             name: name.to_string(),
             columns,
             dir: dir.to_string(),
             table_file,
-        }
+            row_id_floor: 0,
+        })
+    }
+
+    /// Returns the table with `row_id_floor` as the row ID new records are numbered after at
+    /// the least.
+    pub fn with_row_id_floor(mut self, row_id_floor: u32) -> Table {
+        self.row_id_floor = row_id_floor;
+        self
+    }
+
+    /// Returns the row ID new records are numbered after: that of the last stored record, or
+    /// the kept row ID if it is larger.
+    pub fn last_row_id(&mut self) -> Result<u32> {
+        Ok(self.table_file.get_last_row_id()?.max(self.row_id_floor))
     }
 
     pub fn search(
@@ -54,66 +152,329 @@ impl Table {
         column_name: Option<&str>,
         value: DataType,
         operator: &str,
-    ) -> Result<Vec<Record>, String> {
-        if let Some(column_name) = column_name {
-            if let Some(mut index_file) = self.get_index_file(column_name) {
-                let record_ids = index_file.search(&value, operator);
-                Ok(record_ids
-                    .iter()
-                    .filter_map(|id| self.table_file.get_record(*id))
-                    .collect())
-            } else {
-                let column_index = self
-                    .columns
-                    .iter()
-                    .position(|c| c.name == column_name)
-                    .map(|i| i as u32);
-                if column_index.is_none() {
-                    return Err(format!("Column {} not found", column_name));
+    ) -> Result<Vec<Record>> {
+        // Comparisons with NULL match nothing, whether answered by an index or a full scan.
+        if column_name.is_some() && value == DataType::Null {
+            return Ok(vec![]);
+        }
+        match self.access_path(column_name, operator, &value)? {
+            AccessPath::IndexLookup { column } | AccessPath::IndexRange { column } => {
+                let mut index_file = self.open_index_file(&column)?;
+                let record_ids = index_file.search(&value, operator)?;
+                let mut records = vec![];
+                for id in record_ids {
+                    if let Some(record) = self.table_file.get_record(id)? {
+                        records.push(record);
+                    }
                 }
-                Ok(self.table_file.search(column_index, value, operator))
+                Ok(records)
             }
-        } else {
-            Ok(self.table_file.search(None, value, operator))
+            AccessPath::FullScan => {
+                let column_index = match column_name {
+                    Some(column_name) => Some(self.column_name_to_index(column_name)? as u32),
+                    None => None,
+                };
+                self.table_file.search(column_index, value, operator)
+            }
+        }
+    }
+
+    /// Returns how [`Table::search`] finds the records where `column_name` compares to `value`
+    /// with `operator`: through the column's index if it has one and reading through it is
+    /// estimated to touch fewer pages than a full scan, otherwise by a full scan.
+    pub fn access_path(
+        &mut self,
+        column_name: Option<&str>,
+        operator: &str,
+        value: &DataType,
+    ) -> Result<AccessPath> {
+        let column = match column_name {
+            Some(column) if self.get_index_file(column)?.is_some() => column.to_string(),
+            _ => return Ok(AccessPath::FullScan),
+        };
+        let index_path = match operator {
+            "=" => AccessPath::IndexLookup { column },
+            _ => AccessPath::IndexRange { column },
+        };
+        let comparison = Some((operator, value));
+        let index_pages = self.estimate_pages(&index_path, comparison, None)?;
+        let scan_pages = self.estimate_pages(&AccessPath::FullScan, comparison, None)?;
+        Ok(match index_pages < scan_pages {
+            true => index_path,
+            false => AccessPath::FullScan,
+        })
+    }
+
+    /// Estimates the number of table and index file pages read through `access_path` to find
+    /// the records matching `comparison`, the operator and value compared with the column of the
+    /// access path, or every record if [`None`]. With a `limit`, reading stops after that many
+    /// records.
+    ///
+    /// Reading through an index touches the index pages down to the first matching value, the
+    /// share of the others holding matching values, and the table pages down to the first record
+    /// read, then a leaf page for each further record, though never more than every table page.
+    /// Looking up a value of a UNIQUE column reads a single path down each file.
+    ///
+    /// The number of records is taken to be the last row ID, which still counts deleted records,
+    /// so after deletes reading through an index is estimated to touch more pages than it does.
+    pub fn estimate_pages(
+        &mut self,
+        access_path: &AccessPath,
+        comparison: Option<(&str, &DataType)>,
+        limit: Option<u64>,
+    ) -> Result<u64> {
+        let table_pages = self.table_file.num_pages()?;
+        let column = match access_path {
+            AccessPath::FullScan => return Ok(table_pages),
+            AccessPath::IndexLookup { column } | AccessPath::IndexRange { column } => column,
+        };
+        let mut index_file = self.open_index_file(column)?;
+        let unique = self.columns.iter().any(|c| c.name == *column && c.unique);
+        if let (Some(("=", _)), true) = (comparison, unique) {
+            // A UNIQUE column holds the value at most once, so the lookup reads one path down
+            // the index and one down the table. This saves sampling the index for the UNIQUE
+            // check of every INSERT.
+            return Ok(index_file.depth()? + self.table_file.depth()?);
         }
+        let statistics = index_file.statistics()?;
+        // Row IDs are given in order, so the last one bounds the number of records without
+        // reading them.
+        let rows = self.table_file.get_last_row_id()? as f64;
+        let matching = match comparison {
+            Some((operator, value)) => rows * selectivity(&statistics, operator, value),
+            None => rows,
+        };
+        let read = match limit {
+            Some(limit) => matching.min(limit as f64),
+            None => matching,
+        };
+        let share = if rows > 0.0 { read / rows } else { 0.0 };
+        let index_pages =
+            statistics.depth as f64 + (statistics.pages - statistics.depth) as f64 * share;
+        let record_pages = match read > 0.0 {
+            true => (self.table_file.depth()? as f64 - 1.0 + read).min(table_pages as f64),
+            false => 0.0,
+        };
+        Ok((index_pages + record_pages).ceil() as u64)
+    }
+
+    /// Estimates the number of records where `column_name` compares to `value` with `operator`,
+    /// or of every record if `column_name` is [`None`]. A column without an index has no
+    /// statistics, so a comparison on it is taken to match a third of the records.
+    pub fn estimate_rows(
+        &mut self,
+        column_name: Option<&str>,
+        operator: &str,
+        value: &DataType,
+    ) -> Result<u64> {
+        let rows = self.table_file.get_last_row_id()? as f64;
+        let Some(column) = column_name else {
+            return Ok(rows as u64);
+        };
+        let unique = self.columns.iter().any(|c| c.name == column && c.unique);
+        let matching = match self.get_index_file(column)? {
+            // Comparisons with NULL match nothing.
+            _ if *value == DataType::Null => 0.0,
+            _ if unique && operator == "=" => rows.min(1.0),
+            Some(mut index_file) => rows * selectivity(&index_file.statistics()?, operator, value),
+            None => rows / 3.0,
+        };
+        Ok(matching.ceil() as u64)
+    }
+
+    /// Estimates the pages read and the records found by looking up a single value, other than
+    /// `NULL`, of the indexed `column`. The share of the records holding a value is estimated
+    /// from the number of distinct values alone, so it does not matter which value it is.
+    pub fn estimate_lookup(&mut self, column: &str) -> Result<(u64, u64)> {
+        let any_value = DataType::Int(0);
+        let comparison = Some(("=", &any_value));
+        let access_path = AccessPath::IndexLookup {
+            column: column.to_string(),
+        };
+        let pages = self.estimate_pages(&access_path, comparison, None)?;
+        let rows = self.estimate_rows(Some(column), "=", &any_value)?;
+        Ok((pages, rows))
     }
 
-    pub fn get_index_file(&self, column_name: &str) -> Option<IndexFile> {
-        self.columns.iter().position(|c| c.name == column_name)?;
-        let index_file_path = format!("{}/{}.{}.ndx", self.dir, self.name, column_name);
-        let path = std::path::Path::new(&index_file_path);
-        if path.exists() {
-            Some(IndexFile::new(&self.name, column_name, &self.dir))
+    pub fn get_index_file(&self, column_name: &str) -> Result<Option<IndexFile>> {
+        if !self.columns.iter().any(|c| c.name == column_name) {
+            return Ok(None);
+        }
+        let path = index_file_path(&self.dir, &self.name, column_name);
+        if std::path::Path::new(&path).exists() {
+            Ok(Some(IndexFile::new(&self.name, column_name, &self.dir)?))
         } else {
-            None
+            Ok(None)
+        }
+    }
+
+    /// Returns the index file of `column_name`, which an [`AccessPath`] has found to exist.
+    pub fn open_index_file(&self, column_name: &str) -> Result<IndexFile> {
+        self.get_index_file(column_name)?.ok_or_else(|| {
+            DbError::Catalog(format!("Index for column {} does not exist", column_name))
+        })
+    }
+
+    /// Opens the files of the table again, for reading them while they change through `self`.
+    pub fn reopen(&self) -> Result<Table> {
+        Ok(Table::new(&self.name, self.columns.clone(), &self.dir)?
+            .with_row_id_floor(self.row_id_floor))
+    }
+
+    /// Returns the record with `row_id`, or [`None`] if it has been deleted.
+    pub fn get_record(&mut self, row_id: u32) -> Result<Option<Record>> {
+        self.table_file.get_record(row_id)
+    }
+
+    /// Returns a cursor over the records in row ID order. See [`TableFile::cursor`].
+    pub fn scan(&self) -> Result<Cursor> {
+        self.table_file.cursor()
+    }
+
+    /// Inserts a record with `values`, returning it with the row ID it was given.
+    pub fn insert(&mut self, values: Vec<DataType>) -> Result<Record> {
+        let values = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| self.check_value(i, value))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some((i, _)) = self.find_conflict(&values)? {
+            return Err(DbError::Constraint(format!(
+                "Value {} already exists in column {}",
+                values[i], self.columns[i].name
+            )));
+        }
+        let next_row_id = self.last_row_id()? + 1;
+        let record = Record::new(values, next_row_id);
+        let mut indexed = vec![];
+        let result = self
+            .insert_into_indexes(&record, &mut indexed)
+            .and_then(|_| self.table_file.append_record(record.clone()));
+        if let Err(e) = result {
+            // Remove the entries already written, so the indexes only hold stored records.
+            for (mut index_file, i) in indexed {
+                index_file.remove_item_from_cell(record.row_id, &record.values[i])?;
+            }
+            return Err(e);
+        }
+        Ok(record)
+    }
+
+    /// Adds `record` to the index of each indexed column, pushing each index file written to
+    /// `indexed` with the position of its column.
+    fn insert_into_indexes(
+        &self,
+        record: &Record,
+        indexed: &mut Vec<(IndexFile, usize)>,
+    ) -> Result<()> {
+        for (i, column) in self.columns.iter().enumerate() {
+            if let Some(mut index_file) = self.get_index_file(&column.name)? {
+                index_file.insert_item_into_cell(record.row_id, &record.values[i])?;
+                indexed.push((index_file, i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `value` converted to the type of the column at `column_index`. Fails if it cannot
+    /// be stored in the column because it is NULL, does not convert to the column's type or is
+    /// longer than the column's [`Column::max_length`].
+    fn check_value(&self, column_index: usize, value: DataType) -> Result<DataType> {
+        let column = &self.columns[column_index];
+        if value == DataType::Null && !column.nullable {
+            return Err(DbError::Constraint(format!(
+                "Column {} cannot be NULL",
+                column.name
+            )));
+        }
+        let value = coerce(value.clone(), &column.data_type).ok_or_else(|| {
+            DbError::Type(format!(
+                "Invalid data type for column {}. Expected {}, got {} {}",
+                column.name,
+                column.data_type.type_name(),
+                value.type_name(),
+                value
+            ))
+        })?;
+        match (&value, column.max_length) {
+            (DataType::Text(text), Some(max_length))
+                if text_content(text).chars().count() > max_length =>
+            {
+                Err(DbError::Constraint(format!(
+                    "Value {} is longer than {} characters for column {}",
+                    display_literal(&value),
+                    max_length,
+                    column.name
+                )))
+            }
+            _ => Ok(value),
         }
     }
 
-    pub fn insert(&mut self, values: Vec<DataType>) -> Result<(), String> {
-        let next_row_id = self.table_file.get_last_row_id() + 1;
+    /// Returns the first UNIQUE column for which a record already holds the value `values` has,
+    /// and that record.
+    pub fn find_conflict(&mut self, values: &[DataType]) -> Result<Option<(usize, Record)>> {
         for (i, column) in self.columns.clone().iter().enumerate() {
-            if *column != values[i] {
-                return Err(format!(
-                    "Invalid data type for column {}. Expected {:?}, got {:?}",
-                    column.name, column.data_type, values[i]
-                ));
+            if !column.unique {
+                continue;
             }
-            if column.unique {
-                let records = self.search(Some(&column.name), values[i].clone(), "=")?;
-                if !records.is_empty() {
-                    return Err(format!(
-                        "Value {} already exists in column {}",
-                        values[i], column.name
-                    ));
-                }
+            let records = self.search(Some(&column.name), values[i].clone(), "=")?;
+            if let Some(record) = records.into_iter().next() {
+                return Ok(Some((i, record)));
             }
-            if let Some(index_file) = &mut self.get_index_file(&column.name) {
-                index_file.insert_item_into_cell(next_row_id, &values[i]);
+        }
+        Ok(None)
+    }
+
+    /// Sets the column at `column_index` of `record` to `value` as [`Table::update_record`]
+    /// does, but fails instead if `value` cannot be stored in the column or another record of
+    /// a UNIQUE column already holds it.
+    pub fn update_checked(
+        &mut self,
+        record: &Record,
+        column_index: usize,
+        value: DataType,
+    ) -> Result<Record> {
+        let mut values =
+            self.check_update(std::slice::from_ref(record), column_index, vec![value])?;
+        self.update_record(record, column_index, values.remove(0))
+    }
+
+    /// Returns `values` converted to the type of the column at `column_index`, to set in
+    /// `records` in order. Fails if any of them cannot be stored in the column, or if a UNIQUE
+    /// column would hold a value twice once every record is updated. Nothing is written, so a
+    /// statement that fails here leaves the table unchanged.
+    pub fn check_update(
+        &mut self,
+        records: &[Record],
+        column_index: usize,
+        values: Vec<DataType>,
+    ) -> Result<Vec<DataType>> {
+        let values = values
+            .into_iter()
+            .map(|value| self.check_value(column_index, value))
+            .collect::<Result<Vec<_>>>()?;
+        let column = self.columns[column_index].clone();
+        if !column.unique {
+            return Ok(values);
+        }
+        let updated = records.iter().map(|r| r.row_id).collect::<HashSet<_>>();
+        for (i, value) in values.iter().enumerate() {
+            let taken = values[..i]
+                .iter()
+                .any(|v| *v != DataType::Null && index_order(v, value) == Ordering::Equal)
+                || self
+                    .search(Some(&column.name), value.clone(), "=")?
+                    .iter()
+                    .any(|r| !updated.contains(&r.row_id));
+            if taken {
+                return Err(DbError::Constraint(format!(
+                    "Value {} already exists in column {}",
+                    value, column.name
+                )));
             }
         }
-        let record = Record::new(values, next_row_id);
-        self.table_file.append_record(record);
-        Ok(())
+        Ok(values)
     }
 
     /// Deletes records from the table.
@@ -123,23 +484,75 @@ impl Table {
     ///     * `value` - The value to search for.
     ///     * `operator` - The operator to use for the search.
     /// Returns:
-    ///     * [`Result<usize, String>`] - The number of records deleted. Err if the column is not found.
+    ///     * [`Result<Vec<Record>>`] - The records deleted. Err if the column is not found.
     pub fn delete(
         &mut self,
         column_name: Option<&str>,
         value: &DataType,
         operator: &str,
-    ) -> Result<usize, String> {
+    ) -> Result<Vec<Record>> {
         let records = self.search(column_name, value.clone(), operator)?;
         for record in &records {
-            self.table_file.delete_record(record.row_id);
-            for (i, column) in self.columns.iter().enumerate() {
-                if let Some(mut index_file) = self.get_index_file(&column.name) {
-                    index_file.remove_item_from_cell(record.row_id, &record.values[i]);
+            self.delete_record(record)?;
+        }
+        Ok(records)
+    }
+
+    /// Deletes `record`, as returned by [`Table::search`], from the table and its indexes.
+    pub fn delete_record(&mut self, record: &Record) -> Result<()> {
+        let mut removed = vec![];
+        let result = self
+            .remove_from_indexes(record, &mut removed)
+            .and_then(|_| self.table_file.delete_record(record.row_id));
+        if let Err(e) = result {
+            // Put back the entries already removed, so the indexes hold every stored record.
+            for (mut index_file, i) in removed {
+                index_file.insert_item_into_cell(record.row_id, &record.values[i])?;
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Removes `record` from the index of each indexed column, pushing each index file written
+    /// to `removed` with the position of its column.
+    fn remove_from_indexes(
+        &self,
+        record: &Record,
+        removed: &mut Vec<(IndexFile, usize)>,
+    ) -> Result<()> {
+        for (i, column) in self.columns.iter().enumerate() {
+            if let Some(mut index_file) = self.get_index_file(&column.name)? {
+                index_file.remove_item_from_cell(record.row_id, &record.values[i])?;
+                removed.push((index_file, i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `records` back into the table and its indexes after [`Table::delete_record`]
+    /// deleted them, keeping their row IDs. See [`TableFile::restore_records`].
+    pub fn restore_records(&mut self, records: Vec<Record>) -> Result<()> {
+        self.table_file.restore_records(records.clone())?;
+        for (i, column) in self.columns.iter().enumerate() {
+            if let Some(mut index_file) = self.get_index_file(&column.name)? {
+                for record in &records {
+                    index_file.insert_item_into_cell(record.row_id, &record.values[i])?;
                 }
             }
         }
-        Ok(records.len())
+        Ok(())
+    }
+
+    /// Removes every record from the table and its indexes. Row IDs start again at 1.
+    pub fn truncate(&mut self) -> Result<()> {
+        self.table_file.truncate()?;
+        for column in &self.columns {
+            if let Some(mut index_file) = self.get_index_file(&column.name)? {
+                index_file.truncate()?;
+            }
+        }
+        Ok(())
     }
 
     /// Updates records in the table.
@@ -152,7 +565,8 @@ impl Table {
     ///    * `update_value` - The value to update.
     ///
     /// Returns:
-    ///   * [`Result<usize, String>`] - The number of records updated. Err if the column is not found.
+    ///   * [`Result<Vec<Record>>`] - The records updated, with their new values. Err if the column
+    ///     is not found.
     pub fn update(
         &mut self,
         search_column: Option<&str>,
@@ -160,97 +574,125 @@ impl Table {
         search_operator: &str,
         update_column: &str,
         update_value: DataType,
-    ) -> Result<usize, String> {
+    ) -> Result<Vec<Record>> {
         let records = self.search(search_column, search_value, search_operator)?;
-        let len = records.len();
-        for record in records {
-            let column_index = self.column_name_to_index(update_column)? as u32;
-            self.table_file
-                .update_record(record.row_id, column_index, update_value.clone());
-            if let Some(mut index_file) = self.get_index_file(update_column) {
-                index_file.update_record(
-                    record.row_id,
-                    &record.values[column_index as usize],
-                    &update_value,
-                );
-            }
+        let column_index = self.column_name_to_index(update_column)?;
+        let values =
+            self.check_update(&records, column_index, vec![update_value; records.len()])?;
+        records
+            .iter()
+            .zip(values)
+            .map(|(record, value)| self.update_record(record, column_index, value))
+            .collect()
+    }
+
+    /// Sets the column at `column_index` of `record`, as returned by [`Table::search`], to
+    /// `value` in the table and the column's index. Returns the record with its new values.
+    pub fn update_record(
+        &mut self,
+        record: &Record,
+        column_index: usize,
+        value: DataType,
+    ) -> Result<Record> {
+        if let Some(mut index_file) = self.get_index_file(&self.columns[column_index].name)? {
+            index_file.update_record(record.row_id, &record.values[column_index], &value)?;
         }
-        Ok(len)
+        self.table_file
+            .update_record(record.row_id, column_index as u32, value.clone())?;
+        let mut values = record.values.clone();
+        values[column_index] = value;
+        Ok(Record::new(values, record.row_id))
     }
 
-    pub fn column_name_to_index(&self, column_name: &str) -> Result<usize, String> {
+    pub fn column_name_to_index(&self, column_name: &str) -> Result<usize> {
         self.columns
             .iter()
             .position(|c| c.name == column_name)
-            .ok_or_else(|| format!("Column {} not found", column_name))
+            .ok_or_else(|| column_not_found(column_name, &self.columns))
     }
 
-    pub fn create_index(&mut self, column_name: &str) -> Result<IndexFile, String> {
-        if self.get_index_file(column_name).is_some() {
-            return Err(format!("Index for column {} already exists", column_name));
+    pub fn create_index(&mut self, column_name: &str) -> Result<IndexFile> {
+        if self.get_index_file(column_name)?.is_some() {
+            return Err(DbError::Catalog(format!(
+                "Index for column {} already exists",
+                column_name
+            )));
         }
-        let column = self
-            .columns
-            .iter()
-            .position(|c| c.name == column_name)
-            .ok_or(format!("Column {} not found", column_name))?;
-        let mut index_file = IndexFile::new(&self.name, column_name, &self.dir);
+        let column = self.column_name_to_index(column_name)?;
+        let mut index_file = IndexFile::new(&self.name, column_name, &self.dir)?;
         let values = self.search(None, DataType::Null, "=")?;
-        index_file.initialize_index(values, column);
+        index_file.initialize_index(values, column)?;
         Ok(index_file)
     }
 
-    pub fn drop_index(&self, column_name: &str) -> Result<(), String> {
-        let index_file_path = format!("{}/{}.{}.ndx", self.dir, self.name, column_name);
-        if self.get_index_file(column_name).is_some() {
-            std::fs::remove_file(index_file_path).expect("Failed removing index file");
+    pub fn drop_index(&self, column_name: &str) -> Result<()> {
+        if self.get_index_file(column_name)?.is_some() {
+            std::fs::remove_file(index_file_path(&self.dir, &self.name, column_name))?;
             Ok(())
         } else {
-            Err(format!("Index for column {} does not exist", column_name))
+            Err(DbError::Catalog(format!(
+                "Index for column {} does not exist",
+                column_name
+            )))
+        }
+    }
+
+    /// Removes the table file and the index file of every indexed column.
+    pub fn drop_files(self) -> Result<()> {
+        for column in &self.columns {
+            if self.get_index_file(&column.name)?.is_some() {
+                self.drop_index(&column.name)?;
+            }
         }
+        let path = table_file_path(&self.dir, &self.name);
+        drop(self);
+        std::fs::remove_file(path)?;
+        Ok(())
     }
 
-    pub fn has_index(&self) -> Option<&str> {
+    pub fn has_index(&self) -> Result<Option<&str>> {
         for column in &self.columns {
-            if self.get_index_file(&column.name).is_some() {
-                return Some(&column.name);
+            if self.get_index_file(&column.name)?.is_some() {
+                return Ok(Some(&column.name));
             }
         }
-        None
+        Ok(None)
     }
 
-    pub fn len(&mut self) -> usize {
-        self.search(None, DataType::Null, "=").unwrap().len()
+    pub fn len(&mut self) -> Result<usize> {
+        Ok(self.search(None, DataType::Null, "=")?.len())
     }
 
-    pub fn is_empty(&mut self) -> bool {
-        self.len() == 0
+    pub fn is_empty(&mut self) -> Result<bool> {
+        Ok(self.len()? == 0)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::constants::DataType;
+    use crate::database_file::DatabaseFile;
     use crate::utils::{setup_records, setup_table, setup_table_no_records, teardown};
 
     #[test]
     fn test_new_table() {
         let mut table = setup_table("test_new_table", "data/testdata.txt");
-        assert_eq!(table.len(), 10);
+        assert_eq!(table.len().unwrap(), 10);
         teardown("test_new_table");
     }
 
     #[test]
     fn test_long_table() {
         let mut table = setup_table("test_long_table", "data/longdata.txt");
-        assert_eq!(table.len(), 1000);
+        assert_eq!(table.len().unwrap(), 1000);
         teardown("test_long_table");
     }
 
     #[test]
     fn test_empty_table() {
         let mut table = setup_table_no_records("test_empty_table");
-        assert_eq!(table.len(), 0);
-        assert!(table.is_empty());
+        assert_eq!(table.len().unwrap(), 0);
+        assert!(table.is_empty().unwrap());
         teardown("test_empty_table");
     }
 
@@ -261,10 +703,61 @@ mod tests {
         table
             .insert(records[0].values.clone())
             .expect("Error inserting record");
-        assert_eq!(table.len(), 1);
+        assert_eq!(table.len().unwrap(), 1);
+        // Values of another numeric type are stored as the column's type if they fit.
+        let mut values = records[1].values.clone();
+        values[2] = DataType::SmallInt(12);
+        let record = table.insert(values.clone()).unwrap();
+        assert_eq!(record.values[2], DataType::Int(12));
+        let records = table
+            .search(Some("age"), DataType::BigInt(12), "=")
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        values[2] = DataType::Double(1.5);
+        assert_eq!(
+            table.insert(values).unwrap_err().to_string(),
+            "Invalid data type for column age. Expected INT, got DOUBLE 1.5"
+        );
         teardown("test_insert");
     }
 
+    #[test]
+    fn test_insert_undoes_index_writes() {
+        let mut table = setup_table_no_records("test_insert_undoes_index_writes");
+        table.create_index("name").unwrap();
+        table.create_index("age").unwrap();
+        let values = |i: usize| {
+            vec![
+                DataType::Text(format!("name {}", i)),
+                DataType::Text("address".to_string()),
+                DataType::Int(30),
+                DataType::Text("email".to_string()),
+            ]
+        };
+        // The index on age runs out of room for the row IDs of 30 after the index on name has
+        // been written.
+        let failed = (0..100)
+            .find(|i| table.insert(values(*i)).is_err())
+            .expect("Insert should fail");
+        let name = |i: usize| DataType::Text(format!("name {}", i));
+        let records = table.search(Some("name"), name(failed - 1), "=").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].values, values(failed - 1));
+        // The next record takes the row ID the failed insert was given, which the index on name
+        // must not lead to.
+        table.drop_index("age").unwrap();
+        let record = table.insert(values(failed + 1)).unwrap();
+        assert!(table
+            .search(Some("name"), name(failed), "=")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            table.search(Some("name"), name(failed + 1), "=").unwrap(),
+            [record]
+        );
+        teardown("test_insert_undoes_index_writes");
+    }
+
     #[test]
     fn test_search() {
         let mut table = setup_table("test_search", "data/testdata.txt");
@@ -276,4 +769,27 @@ mod tests {
         assert_eq!(records[0].values, real_records[0].values);
         teardown("test_search");
     }
+
+    #[test]
+    fn test_truncate() {
+        let mut table = setup_table("test_truncate", "data/longdata.txt");
+        table.create_index("age").unwrap();
+        table.truncate().unwrap();
+        assert!(table.is_empty().unwrap());
+        assert_eq!(table.table_file.num_pages().unwrap(), 1);
+        assert_eq!(
+            table
+                .get_index_file("age")
+                .unwrap()
+                .unwrap()
+                .num_pages()
+                .unwrap(),
+            1
+        );
+        let records = setup_records("data/testdata.txt");
+        table.insert(records[0].values.clone()).unwrap();
+        let records = table.search(None, DataType::Null, "=").unwrap();
+        assert_eq!(records[0].row_id, 1);
+        teardown("test_truncate");
+    }
 }
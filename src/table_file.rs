@@ -2,11 +2,13 @@ use std::cmp::max;
 use std::fmt::{Debug, Write as _};
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
 
 use owo_colors::OwoColorize;
 
 use crate::constants::{DataType, PageType, PAGE_SIZE};
-use crate::database_file::DatabaseFile;
+use crate::database_file::{table_file_path, DatabaseFile};
+use crate::error::{DbError, Result};
 use crate::read_write_types::ReadWriteTypes;
 use crate::record::Record;
 use crate::utils::rainbow;
@@ -16,16 +18,11 @@ pub struct TableFile {
 }
 
 impl DatabaseFile for TableFile {
-    fn len(&self) -> u64 {
-        self.file
-            .metadata()
-            .expect("error getting file metadata")
-            .len()
+    fn len(&self) -> Result<u64> {
+        Ok(self.file.metadata()?.len())
     }
-    fn set_len(&self, length: u64) {
-        self.file
-            .set_len(length)
-            .expect("Error setting file length");
+    fn set_len(&self, length: u64) -> Result<()> {
+        Ok(self.file.set_len(length)?)
     }
 }
 
@@ -53,277 +50,335 @@ impl Seek for TableFile {
 }
 
 impl TableFile {
-    pub fn new(table_name: &str, dir: &str) -> Self {
-        let path = format!("{}/{}.tbl", dir, table_name);
+    pub fn new(table_name: &str, dir: &str) -> Result<Self> {
+        TableFile::open(table_file_path(dir, table_name))
+    }
+
+    /// Opens the table file at `path` as it is named on disk, creating it with an empty root
+    /// page if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
             .truncate(false)
-            .open(path)
-            .expect("Error opening table file");
+            .open(path)?;
         let mut table_file = TableFile { file };
-        if table_file.len() == 0 {
-            table_file.create_page(0xFFFFFFFF, PageType::TableLeaf);
+        if table_file.len()? == 0 {
+            table_file.create_page(0xFFFFFFFF, PageType::TableLeaf)?;
         }
-        table_file
+        Ok(table_file)
     }
 
-    fn get_min_row_id(&mut self, page: u32) -> u32 {
-        if self.get_num_cells(page) == 0 {
-            panic!("Page {} has no cells", page);
-        }
-        let page_type = self.get_page_type(page);
-        let offset = self.get_cell_offset(page, 0);
-        self.seek_to_page_offset(page, offset);
+    /// Removes every record, leaving the file with a single empty root page.
+    pub fn truncate(&mut self) -> Result<()> {
+        self.set_len(0)?;
+        self.create_page(0xFFFFFFFF, PageType::TableLeaf)?;
+        Ok(())
+    }
+
+    /// Skips the cell fields in front of the row ID of a cell on `page`, which has `page_type`.
+    fn skip_to_row_id(&mut self, page: u32, page_type: PageType) -> Result<()> {
         match page_type {
-            PageType::TableLeaf => self.skip_bytes(2),
-            PageType::TableInterior => self.skip_bytes(4),
-            _ => unreachable!("Page {} is not a table page", page),
+            PageType::TableLeaf => self.skip_bytes(2)?,
+            PageType::TableInterior => self.skip_bytes(4)?,
+            page_type => {
+                return Err(DbError::Corruption(format!(
+                    "Page {} is not a table page but {:?}",
+                    page, page_type
+                )))
+            }
         };
+        Ok(())
+    }
+
+    fn get_min_row_id(&mut self, page: u32) -> Result<u32> {
+        if self.get_num_cells(page)? == 0 {
+            return Err(DbError::Corruption(format!("Page {} has no cells", page)));
+        }
+        let page_type = self.get_page_type(page)?;
+        let offset = self.get_cell_offset(page, 0)?;
+        self.seek_to_page_offset(page, offset)?;
+        self.skip_to_row_id(page, page_type)?;
         self.read_u32()
     }
 
-    pub fn get_last_row_id(&mut self) -> u32 {
-        let page = self.get_last_leaf_page();
-        if self.get_num_cells(page) == 0 {
-            return 0;
+    pub fn get_last_row_id(&mut self) -> Result<u32> {
+        let page = self.get_last_leaf_page()?;
+        if self.get_num_cells(page)? == 0 {
+            // Deleting every record of the last leaf page leaves it empty, so the last record
+            // left, if any, is on a page to its left.
+            let last = self.cursor()?.reverse()?.next().transpose()?;
+            return Ok(last.map_or(0, |record| record.row_id));
         }
-        let page_type = self.get_page_type(page);
-        let offset = self.get_content_start(page);
-        self.seek_to_page_offset(page, offset);
-        match page_type {
-            PageType::TableLeaf => self.skip_bytes(2),
-            PageType::TableInterior => self.skip_bytes(4),
-            _ => unreachable!("Page {} is not a table page", page),
-        };
+        let page_type = self.get_page_type(page)?;
+        let offset = self.get_content_start(page)?;
+        self.seek_to_page_offset(page, offset)?;
+        self.skip_to_row_id(page, page_type)?;
         self.read_u32()
     }
 
-    fn split_page(&mut self, page: u32, split_row_id: u32) -> u32 {
-        let page_type = self.get_page_type(page);
-        let mut parent_page = self.get_parent_page(page);
+    fn split_page(&mut self, page: u32, split_row_id: u32) -> Result<u32> {
+        let page_type = self.get_page_type(page)?;
+        let mut parent_page = self.get_parent_page(page)?;
         if parent_page == 0xFFFFFFFF {
-            parent_page = self.create_page(0xFFFFFFFF, PageType::TableInterior);
+            parent_page = self.create_page(0xFFFFFFFF, PageType::TableInterior)?;
             // 0x0A is the offset of the Parent Page pointer
-            self.seek_to_page_offset(page, 0x0A);
-            self.write_u32(parent_page);
-            let min_row_id = self.get_min_row_id(page);
-            self.write_page_pointer(parent_page, page, min_row_id);
+            self.seek_to_page_offset(page, 0x0A)?;
+            self.write_u32(parent_page)?;
+            let min_row_id = self.get_min_row_id(page)?;
+            self.write_page_pointer(parent_page, page, min_row_id)?;
         }
-        let new_page = self.create_page(parent_page, page_type);
-        self.write_page_pointer(parent_page, new_page, split_row_id);
+        let new_page = self.create_page(parent_page, page_type)?;
+        self.write_page_pointer(parent_page, new_page, split_row_id)?;
         if page_type == PageType::TableLeaf {
             // 0x06 is the offset of the right most child pointer
-            self.seek_to_page_offset(page, 0x06);
-            self.write_u32(new_page);
+            self.seek_to_page_offset(page, 0x06)?;
+            self.write_u32(new_page)?;
         }
-        new_page
+        Ok(new_page)
     }
 
-    fn write_page_pointer(&mut self, page: u32, pointer: u32, row_id: u32) {
+    fn write_page_pointer(&mut self, page: u32, pointer: u32, row_id: u32) -> Result<()> {
         let cell_size = 8;
         let mut page = page;
-        if self.should_split(page, cell_size) {
-            page = self.split_page(page, row_id);
-            self.seek_to_page_offset(pointer, 0x0A);
-            self.write_u32(page); // Update parent page pointer
-        }
-        let content_start = self.set_content_start(page, cell_size);
-        let num_cells = self.increment_num_cells(page);
-        self.seek_to_page_offset(page, 0x06);
-        self.write_u32(pointer);
-        self.seek_to_page_offset(page, 0x0E + num_cells * 2);
-        self.write_u32(content_start as u32);
-        self.seek_to_page_offset(page, content_start);
-        self.write_u32(pointer);
-        self.write_u32(row_id);
-    }
-
-    fn write_record(&mut self, record: Record, page: u32) {
+        if self.should_split(page, cell_size)? {
+            page = self.split_page(page, row_id)?;
+            self.seek_to_page_offset(pointer, 0x0A)?;
+            self.write_u32(page)?; // Update parent page pointer
+        }
+        let content_start = self.set_content_start(page, cell_size)?;
+        let num_cells = self.increment_num_cells(page)?;
+        self.seek_to_page_offset(page, 0x06)?;
+        self.write_u32(pointer)?;
+        self.seek_to_page_offset(page, 0x0E + num_cells * 2)?;
+        self.write_u32(content_start as u32)?;
+        self.seek_to_page_offset(page, content_start)?;
+        self.write_u32(pointer)?;
+        self.write_u32(row_id)?;
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: Record, page: u32) -> Result<()> {
         let mut page = page;
         let cell_size = record.record_size + 6;
-        if self.should_split(page, cell_size as i32) {
-            page = self.split_page(page, record.row_id);
+        if self.should_split(page, cell_size as i32)? {
+            page = self.split_page(page, record.row_id)?;
         }
-        let content_start = self.set_content_start(page, cell_size as i32);
-        let num_cells = self.increment_num_cells(page);
+        let content_start = self.set_content_start(page, cell_size as i32)?;
+        let num_cells = self.increment_num_cells(page)?;
         // 0x0E is 2 bytes before the first cell pointer since num_cells is at least 1
-        self.seek_to_page_offset(page, 0x0E + num_cells * 2);
-        self.write_u32(content_start as u32);
-        self.seek_to_page_offset(page, content_start);
-        self.write_u16(record.record_size);
-        self.write_u32(record.row_id);
-        self.write_all(&record.header)
-            .expect("Error writing record header");
+        self.seek_to_page_offset(page, 0x0E + num_cells * 2)?;
+        self.write_u32(content_start as u32)?;
+        self.write_cell(page, content_start, record)
+    }
+
+    fn write_cell(&mut self, page: u32, offset: u16, record: Record) -> Result<()> {
+        self.seek_to_page_offset(page, offset)?;
+        self.write_u16(record.record_size)?;
+        self.write_u32(record.row_id)?;
+        self.write_all(&record.header)?;
         for value in record.values {
-            self.write_value(value);
+            self.write_value(value)?;
         }
+        Ok(())
     }
 
-    pub fn append_record(&mut self, record: Record) {
-        let page = self.get_last_leaf_page();
-        self.write_record(record, page);
+    pub fn append_record(&mut self, record: Record) -> Result<()> {
+        let page = self.get_last_leaf_page()?;
+        self.write_record(record, page)
     }
 
-    pub fn update_record(&mut self, row_id: u32, column_index: u32, value: DataType) {
-        let (mut page, mut index) = self.find_record(row_id).expect("Record not found");
-        let mut offset = self.get_cell_offset(page, index);
-        let mut record = self.read_record(page, offset);
-        if let DataType::Text(v) = &record.values[column_index as usize] {
-            let old_size = v.len() as u16;
-            let new_size = match value {
-                DataType::Text(ref v_new) => v_new.len(),
-                _ => unreachable!("Value is not text while column is text"),
-            } as u16;
-            if self.should_split(page, new_size as i32 - old_size as i32) {
-                self.split_page(page, row_id);
-                (page, index) = self.find_record(row_id).expect("Record not found");
-            }
-            self.shift_cells(page, index as i32 - 1, new_size as i32 - old_size as i32, 0);
-            offset = self.get_cell_offset(page, index);
+    /// Writes `records` back after their deletion, keeping their row IDs. Every record of the
+    /// table is written again in row ID order through [`TableFile::append_record`], which splits
+    /// pages as needed, so the records fit however full their pages have become.
+    pub fn restore_records(&mut self, records: Vec<Record>) -> Result<()> {
+        let mut all = self.cursor()?.collect::<Result<Vec<_>>>()?;
+        all.extend(records);
+        all.sort_by_key(|record| record.row_id);
+        self.truncate()?;
+        for record in all {
+            self.append_record(record)?;
         }
+        Ok(())
+    }
+
+    /// Returns the page and cell of `row_id`, which must exist.
+    fn find_existing_record(&mut self, row_id: u32) -> Result<(u32, u16)> {
+        self.find_record(row_id)?
+            .ok_or_else(|| DbError::Corruption(format!("Record {} not found", row_id)))
+    }
+
+    pub fn update_record(&mut self, row_id: u32, column_index: u32, value: DataType) -> Result<()> {
+        let (mut page, mut index) = self.find_existing_record(row_id)?;
+        let mut offset = self.get_cell_offset(page, index)?;
+        let mut record = self.read_record(page, offset)?;
+        let old_size = record.record_size;
         record.values[column_index as usize] = value;
         let new_record = Record::new(record.values, row_id);
-        // 0x06 is the offset of the right most child pointer
-        self.seek_to_page_offset(page, offset + 0x06);
-        self.write_all(&new_record.header)
-            .expect("Error writing record header");
+        // Text and null values change the size of the record
+        let size_change = new_record.record_size as i32 - old_size as i32;
+        if size_change != 0 {
+            if self.should_split(page, size_change)? {
+                self.split_page(page, row_id)?;
+                (page, index) = self.find_existing_record(row_id)?;
+            }
+            self.shift_cells(page, index as i32 - 1, size_change, 0)?;
+            offset = self.get_cell_offset(page, index)?;
+        }
+        self.seek_to_page_offset(page, offset)?;
+        self.write_u16(new_record.record_size)?;
+        // Skip the row id
+        self.skip_bytes(4)?;
+        self.write_all(&new_record.header)?;
         for value in new_record.values {
-            self.write_value(value);
+            self.write_value(value)?;
         }
+        Ok(())
     }
 
-    pub fn delete_record(&mut self, row_id: u32) {
-        let (page, index) = self.find_record(row_id).expect("Record not found");
-        let offset = self.get_cell_offset(page, index);
-        self.seek_to_page_offset(page, offset);
-        let payload_size = self.read_u16();
-        self.shift_cells(page, index as i32 - 1, -(payload_size as i32 + 6), -1);
-        let parent_page = self.get_parent_page(page);
-        if index == 0 && parent_page != 0xFFFFFFFF {
-            let min_row_id = self.get_min_row_id(page);
-            self.update_page_pointer(parent_page, index, min_row_id);
-        }
-        let num_cells = self.get_num_cells(page);
+    pub fn delete_record(&mut self, row_id: u32) -> Result<()> {
+        let (page, index) = self.find_existing_record(row_id)?;
+        let offset = self.get_cell_offset(page, index)?;
+        self.seek_to_page_offset(page, offset)?;
+        let payload_size = self.read_u16()?;
+        self.shift_cells(page, index as i32 - 1, -(payload_size as i32 + 6), -1)?;
+        let num_cells = self.get_num_cells(page)?;
         // 0x02 is the offset of the number of cells
-        self.seek_to_page_offset(page, 0x02);
-        self.write_u16(num_cells - 1);
-        let content_start = self.get_content_start(page);
+        self.seek_to_page_offset(page, 0x02)?;
+        self.write_u16(num_cells - 1)?;
+        let content_start = self.get_content_start(page)?;
         // 0x10 is the offset of the first cell pointer
         let cell_pointer_list_end = 0x10 + num_cells * 2;
-        self.seek_to_page_offset(page, cell_pointer_list_end);
+        self.seek_to_page_offset(page, cell_pointer_list_end)?;
         for _ in cell_pointer_list_end..content_start {
-            self.write_u8(0);
+            self.write_u8(0)?;
+        }
+        let parent_page = self.get_parent_page(page)?;
+        if index == 0 && num_cells > 1 && parent_page != 0xFFFFFFFF {
+            let min_row_id = self.get_min_row_id(page)?;
+            self.update_page_pointer(parent_page, page, min_row_id)?;
         }
+        Ok(())
     }
 
-    fn update_page_pointer(&mut self, page: u32, index: u16, new_row_id: u32) {
-        let offset = self.get_cell_offset(page, index);
-        self.seek_to_page_offset(page, offset + 0x04);
-        let old_row_id = self.read_u32();
-        self.seek_to_page_offset(page, offset + 0x04);
-        self.write_u32(new_row_id);
-        if index == 0 {
-            let parent_page = self.get_parent_page(page);
-            let parent_index = self.find_record_on_page(parent_page, old_row_id);
-            self.update_page_pointer(parent_page, parent_index, new_row_id);
+    /// Sets the row ID of the cell on the interior `page` pointing to `child`, and of the cells
+    /// above it while it is the first cell of its page.
+    fn update_page_pointer(&mut self, page: u32, child: u32, new_row_id: u32) -> Result<()> {
+        let index = self.get_children(page)?.iter().position(|c| *c == child);
+        let index = index.ok_or_else(|| {
+            DbError::Corruption(format!("Page {} is not a child of page {}", child, page))
+        })? as u16;
+        let offset = self.get_cell_offset(page, index)?;
+        self.seek_to_page_offset(page, offset + 0x04)?;
+        self.write_u32(new_row_id)?;
+        let parent_page = self.get_parent_page(page)?;
+        if index == 0 && parent_page != 0xFFFFFFFF {
+            self.update_page_pointer(parent_page, page, new_row_id)?;
         }
+        Ok(())
     }
 
-    fn get_last_leaf_page(&mut self) -> u32 {
-        if self.len() < PAGE_SIZE {
-            panic!("Table file is empty");
+    fn get_last_leaf_page(&mut self) -> Result<u32> {
+        if self.len()? < PAGE_SIZE {
+            return Err(DbError::Corruption("Table file is empty".to_string()));
         }
-        if self.len() == PAGE_SIZE {
-            return 0;
+        if self.len()? == PAGE_SIZE {
+            return Ok(0);
         }
-        let mut next_page = self.get_root_page();
+        let mut next_page = self.get_root_page()?;
         loop {
-            let page_type = self.get_page_type(next_page);
+            let page_type = self.get_page_type(next_page)?;
             if page_type == PageType::TableLeaf {
                 break;
             }
             // 0x06 is the offset of the right most child pointer
-            self.seek_to_page_offset(next_page, 0x06);
-            next_page = self.read_u32();
+            self.seek_to_page_offset(next_page, 0x06)?;
+            next_page = self.read_u32()?;
         }
-        next_page
+        Ok(next_page)
     }
 
-    pub fn get_record(&mut self, row_id: u32) -> Option<Record> {
-        let (page, index) = match self.find_record(row_id) {
+    pub fn get_record(&mut self, row_id: u32) -> Result<Option<Record>> {
+        let (page, index) = match self.find_record(row_id)? {
             Some((page, index)) => (page, index),
-            None => return None,
+            None => return Ok(None),
         };
-        let offset = self.get_cell_offset(page, index);
-        Some(self.read_record(page, offset))
+        let offset = self.get_cell_offset(page, index)?;
+        Ok(Some(self.read_record(page, offset)?))
     }
 
-    fn find_record_on_page(&mut self, page: u32, row_id: u32) -> u16 {
-        let num_cells = self.get_num_cells(page);
-        let mut current_cell = num_cells / 2;
+    /// Returns the cell of `row_id` on a leaf page. On an interior page, returns the cell pointing
+    /// to the child that holds `row_id`: the last cell whose row ID is not greater than `row_id`.
+    fn find_record_on_page(&mut self, page: u32, row_id: u32) -> Result<u16> {
         let mut low = 0;
-        let mut high = num_cells - 1;
-        let mut current_row_id = self.get_row_id(page, current_cell);
+        let mut high = self.get_num_cells(page)?;
         while low < high {
-            match current_row_id.cmp(&row_id) {
-                std::cmp::Ordering::Less => low = current_cell + 1,
-                std::cmp::Ordering::Greater => high = current_cell - 1,
-                std::cmp::Ordering::Equal => break,
+            let mid = (low + high) / 2;
+            if self.get_row_id(page, mid)? <= row_id {
+                low = mid + 1;
+            } else {
+                high = mid;
             }
-            current_cell = (low + high).div_ceil(2);
-            current_row_id = self.get_row_id(page, current_cell);
         }
-        current_cell
+        Ok(low.saturating_sub(1))
     }
 
-    fn find_record(&mut self, row_id: u32) -> Option<(u32, u16)> {
-        let mut current_page = self.get_root_page();
+    fn find_record(&mut self, row_id: u32) -> Result<Option<(u32, u16)>> {
+        let page = self.find_leaf_page(row_id)?;
+        let cell = self.find_record_on_page(page, row_id)?;
+        if self.get_row_id(page, cell)? == row_id {
+            Ok(Some((page, cell)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the leaf page that holds `row_id`, or would hold it.
+    fn find_leaf_page(&mut self, row_id: u32) -> Result<u32> {
+        let mut current_page = self.get_root_page()?;
         loop {
-            let page_type = self.get_page_type(current_page);
-            let current_cell = self.find_record_on_page(current_page, row_id);
-            let current_row_id = self.get_row_id(current_page, current_cell);
-            if page_type == PageType::TableLeaf {
-                if current_row_id == row_id {
-                    return Some((current_page, current_cell));
-                } else {
-                    return None;
+            match self.get_page_type(current_page)? {
+                PageType::TableLeaf => return Ok(current_page),
+                PageType::TableInterior => {
+                    let current_cell = self.find_record_on_page(current_page, row_id)?;
+                    let offset = self.get_cell_offset(current_page, current_cell)?;
+                    self.seek_to_page_offset(current_page, offset)?;
+                    current_page = self.read_u32()?;
+                }
+                _ => {
+                    return Err(DbError::Corruption(format!(
+                        "Page {} is not a table page",
+                        current_page
+                    )))
                 }
-            } else if page_type == PageType::TableInterior {
-                let offset = self.get_cell_offset(current_page, current_cell);
-                self.seek_to_page_offset(current_page, offset);
-                current_page = self.read_u32();
-            } else {
-                panic!("Page {} is not a table page", current_page);
             }
         }
     }
 
-    fn get_row_id(&mut self, page: u32, current_cell: u16) -> u32 {
-        let page_type = self.get_page_type(page);
-        let offset = self.get_cell_offset(page, current_cell);
-        self.seek_to_page_offset(page, offset);
+    fn get_row_id(&mut self, page: u32, current_cell: u16) -> Result<u32> {
+        let page_type = self.get_page_type(page)?;
+        let offset = self.get_cell_offset(page, current_cell)?;
+        self.seek_to_page_offset(page, offset)?;
         if page_type == PageType::TableLeaf {
-            self.skip_bytes(2);
+            self.skip_bytes(2)?;
         } else {
-            self.skip_bytes(4);
+            self.skip_bytes(4)?;
         }
         self.read_u32()
     }
 
-    fn read_record(&mut self, page: u32, offset: u16) -> Record {
-        self.seek_to_page_offset(page, offset + 2);
-        let row_id = self.read_u32();
-        let num_columns = self.read_u8();
+    fn read_record(&mut self, page: u32, offset: u16) -> Result<Record> {
+        self.seek_to_page_offset(page, offset + 2)?;
+        let row_id = self.read_u32()?;
+        let num_columns = self.read_u8()?;
         let mut columns: Vec<u8> = vec![0; num_columns as usize];
-        self.read_exact(&mut columns)
-            .expect("Error reading columns");
+        self.read_exact(&mut columns)?;
         let mut values: Vec<DataType> = vec![];
         for column in columns {
-            values.push(self.read_value(column));
+            values.push(self.read_value(column)?);
         }
-        Record::new(values, row_id)
+        Ok(Record::new(values, row_id))
     }
 
     pub fn search(
@@ -331,65 +386,123 @@ impl TableFile {
         column_id: Option<u32>,
         value: DataType,
         operator: &str,
-    ) -> Vec<Record> {
+    ) -> Result<Vec<Record>> {
         let mut records = vec![];
-        let mut current_page = self.get_root_page();
-        let mut page_type = self.get_page_type(current_page);
-        while page_type != PageType::TableLeaf {
-            let offset = self.get_cell_offset(current_page, 0);
-            self.seek_to_page_offset(current_page, offset);
-            current_page = self.read_u32();
-            page_type = self.get_page_type(current_page);
-        }
-
-        while current_page != 0xFFFFFFFF {
-            self.seek_to_page(current_page);
-            let num_cells = self.get_num_cells(current_page);
-            for i in 0..num_cells {
-                let offset = self.get_cell_offset(current_page, i);
-                let record = self.read_record(current_page, offset);
-                if let Some(column_index) = column_id {
-                    if record.compare_column(column_index as usize, &value, operator) {
-                        records.push(record);
-                    }
-                } else {
-                    records.push(record);
+        for record in self.cursor()? {
+            let record = record?;
+            let matches = match column_id {
+                Some(column_index) => {
+                    record.compare_column(column_index as usize, &value, operator)
                 }
+                None => true,
+            };
+            if matches {
+                records.push(record);
             }
-            self.seek_to_page_offset(current_page, 0x06);
-            current_page = self.read_u32();
         }
-        records
+        Ok(records)
+    }
+
+    /// Returns a cursor over the records in row ID order. It reads through a file handle of its
+    /// own, so the table can change while it is open, though the cursor may then miss records.
+    pub fn cursor(&self) -> Result<Cursor> {
+        let mut cursor = Cursor {
+            file: TableFile {
+                file: self.file.try_clone()?,
+            },
+            descending: false,
+            page: 0xFFFFFFFF,
+            records: vec![].into_iter(),
+        };
+        let page = cursor.file.first_leaf_page()?;
+        cursor.load(page)?;
+        Ok(cursor)
+    }
+
+    /// Returns the leftmost leaf page, which holds the records with the lowest row IDs.
+    fn first_leaf_page(&mut self) -> Result<u32> {
+        let mut current_page = self.get_root_page()?;
+        while self.get_page_type(current_page)? != PageType::TableLeaf {
+            let offset = self.get_cell_offset(current_page, 0)?;
+            self.seek_to_page_offset(current_page, offset)?;
+            current_page = self.read_u32()?;
+        }
+        Ok(current_page)
     }
 
-    fn read_page_pointer(&mut self, page: u32, index: u16) -> (u32, u32) {
-        let offset = self.get_cell_offset(page, index);
-        self.seek_to_page_offset(page, offset);
-        let pointer = self.read_u32();
-        let row_id = self.read_u32();
-        (pointer, row_id)
+    /// Returns the leaf page to the right of the leaf `page`, `0xFFFFFFFF` if it is the last one.
+    fn next_leaf_page(&mut self, page: u32) -> Result<u32> {
+        // 0x06 is the offset of the right sibling pointer
+        self.seek_to_page_offset(page, 0x06)?;
+        self.read_u32()
+    }
+
+    /// Returns the leaf page to the left of the leaf `page`, `0xFFFFFFFF` if it is the first one.
+    /// Leaf pages only point to their right sibling, so this goes up to the closest ancestor
+    /// with a child to the left, then down the rightmost children of that child.
+    fn previous_leaf_page(&mut self, page: u32) -> Result<u32> {
+        let mut child = page;
+        loop {
+            let parent = self.get_parent_page(child)?;
+            if parent == 0xFFFFFFFF {
+                return Ok(0xFFFFFFFF);
+            }
+            let children = self.get_children(parent)?;
+            let index = children.iter().position(|c| *c == child).ok_or_else(|| {
+                DbError::Corruption(format!("Page {} is not a child of page {}", child, parent))
+            })?;
+            if index > 0 {
+                let mut current_page = children[index - 1];
+                while self.get_page_type(current_page)? != PageType::TableLeaf {
+                    // 0x06 is the offset of the right most child pointer
+                    self.seek_to_page_offset(current_page, 0x06)?;
+                    current_page = self.read_u32()?;
+                }
+                return Ok(current_page);
+            }
+            child = parent;
+        }
     }
 
-    fn get_children(&mut self, page: u32) -> Vec<u32> {
+    /// Returns the records on the leaf `page` in row ID order.
+    fn read_leaf(&mut self, page: u32) -> Result<Vec<Record>> {
+        let num_cells = self.get_num_cells(page)?;
+        let mut records = Vec::with_capacity(num_cells as usize);
+        for i in 0..num_cells {
+            let offset = self.get_cell_offset(page, i)?;
+            records.push(self.read_record(page, offset)?);
+        }
+        Ok(records)
+    }
+
+    fn read_page_pointer(&mut self, page: u32, index: u16) -> Result<(u32, u32)> {
+        let offset = self.get_cell_offset(page, index)?;
+        self.seek_to_page_offset(page, offset)?;
+        let pointer = self.read_u32()?;
+        let row_id = self.read_u32()?;
+        Ok((pointer, row_id))
+    }
+
+    fn get_children(&mut self, page: u32) -> Result<Vec<u32>> {
         let mut children = vec![];
-        let num_cells = self.get_num_cells(page);
+        let num_cells = self.get_num_cells(page)?;
         for i in 0..num_cells {
-            let (pointer, _) = self.read_page_pointer(page, i);
+            let (pointer, _) = self.read_page_pointer(page, i)?;
             children.push(pointer);
         }
-        children
+        Ok(children)
     }
 
-    pub fn graph(&mut self) {
-        let root_page = self.get_root_page();
+    pub fn graph(&mut self) -> Result<()> {
+        let root_page = self.get_root_page()?;
         println!("0x{:08X}", root_page.green());
-        self.print_children(root_page, 1);
+        self.print_children(root_page, 1)
     }
 
-    fn print_children(&mut self, page: u32, depth: u32) {
-        let children = self.get_children(page);
+    fn print_children(&mut self, page: u32, depth: u32) -> Result<()> {
+        let children = self.get_children(page)?;
         for child in children {
-            if self.get_page_type(child) == PageType::TableLeaf {
+            if self.get_page_type(child)? == PageType::TableLeaf {
                 println!(
                     "{:|<indent$}0x{:08X}",
                     "",
@@ -403,27 +516,28 @@ impl TableFile {
                     child.green(),
                     indent = depth as usize
                 );
-                self.print_children(child, depth + 1);
+                self.print_children(child, depth + 1)?;
             }
         }
+        Ok(())
     }
 
-    pub fn print(&mut self) {
-        let num_pages = self.len() / PAGE_SIZE;
-        for i in 0..num_pages {
-            self.print_page(i as u32);
+    pub fn print(&mut self) -> Result<()> {
+        for i in 0..self.num_pages()? {
+            self.print_page(i as u32)?;
         }
+        Ok(())
     }
 
-    pub fn print_page(&mut self, page: u32) {
-        self.seek_to_page(page);
-        let page_type = self.read_u8();
-        let unused_space = self.read_u8();
-        let num_cells = self.read_u16();
-        let content_start = self.read_u16();
-        let next_page = self.read_u32();
-        let parent_page = self.read_u32();
-        let unused_2 = self.read_u16();
+    pub fn print_page(&mut self, page: u32) -> Result<()> {
+        self.seek_to_page(page)?;
+        let page_type = self.read_u8()?;
+        let unused_space = self.read_u8()?;
+        let num_cells = self.read_u16()?;
+        let content_start = self.read_u16()?;
+        let next_page = self.read_u32()?;
+        let parent_page = self.read_u32()?;
+        let unused_2 = self.read_u16()?;
 
         let page_start = page as u64 * PAGE_SIZE;
         println!(
@@ -457,56 +571,53 @@ impl TableFile {
         if num_cells > 0 {
             print!("{:08X}  ", 0x10 + page_start);
             for i in 0..num_cells {
-                let offset = self.read_u16();
+                let offset = self.read_u16()?;
                 offsets[i as usize] = offset;
                 print!("{} ", rainbow(&format!("{:04X}", offset), i as usize));
             }
             println!();
             if page_type == PageType::TableLeaf {
-                self.print_leaf_cells(page, offsets);
+                self.print_leaf_cells(page, offsets)?;
             } else {
-                self.print_inner_cells(page, offsets);
+                self.print_inner_cells(page, offsets)?;
             }
         }
+        Ok(())
     }
 
-    fn print_inner_cells(&mut self, page: u32, offsets: Vec<u16>) {
+    fn print_inner_cells(&mut self, page: u32, offsets: Vec<u16>) -> Result<()> {
         let page_start = page * PAGE_SIZE as u32;
         println!("{:8}  {:8} {:8}", "", "Page".blue(), "Row ID".yellow());
         for (i, &o) in offsets.iter().enumerate() {
-            self.seek_to_page_offset(page, o);
-            let page = self.read_u32();
-            let row_id = self.read_u32();
+            self.seek_to_page_offset(page, o)?;
+            let page = self.read_u32()?;
+            let row_id = self.read_u32()?;
             print!(
                 "{}  ",
                 rainbow(&format!("{:08X}", o as u32 + page_start), i)
             );
             println!("{:08X} {:08X}", page.blue(), row_id.yellow());
         }
+        Ok(())
     }
 
-    fn print_leaf_cells(&mut self, page: u32, offsets: Vec<u16>) {
+    fn print_leaf_cells(&mut self, page: u32, offsets: Vec<u16>) -> Result<()> {
         let page_start = page * PAGE_SIZE as u32;
         let mut records = vec![];
         for i in 0..offsets.len() {
             let o = offsets[i];
-            self.seek_to_page_offset(page, o);
-            let record_size = self.read_u16();
+            self.seek_to_page_offset(page, o)?;
+            let record_size = self.read_u16()?;
             if i > 0 && o + record_size + 6 > offsets[i - 1] {
                 records.push(None);
             } else {
-                let record = self.read_record(page, o);
+                let record = self.read_record(page, o)?;
                 records.push(Some(record));
             }
         }
-        let num_columns = records
-            .iter()
-            .find(|r| r.is_some())
-            .unwrap()
-            .as_ref()
-            .unwrap()
-            .values
-            .len();
+        let Some(num_columns) = records.iter().flatten().map(|r| r.values.len()).next() else {
+            return Ok(());
+        };
         let mut column_widths = vec![0; num_columns];
         for record in records.iter().flatten() {
             for (i, value) in record.values.iter().enumerate() {
@@ -529,9 +640,8 @@ impl TableFile {
                 print!("{}", "MALFORMED RECORD:".on_red());
                 let num_malfomed_bytes = offsets[j - 1] - o;
                 let mut malformed_bytes = vec![0; num_malfomed_bytes as usize];
-                self.seek_to_page_offset(page, o);
-                self.read_exact(&mut malformed_bytes)
-                    .expect("Error reading malformed bytes");
+                self.seek_to_page_offset(page, o)?;
+                self.read_exact(&mut malformed_bytes)?;
                 let record_size = u16::from_le_bytes([malformed_bytes[0], malformed_bytes[1]]);
                 println!(
                     " should be {} bytes but is {} {}",
@@ -557,11 +667,10 @@ impl TableFile {
             }
             println!();
             if j > 0 && o + record.record_size + 6 < offsets[j - 1] {
-                self.seek_to_page_offset(page, o + record.record_size + 6);
+                self.seek_to_page_offset(page, o + record.record_size + 6)?;
                 let num_missing_bytes = offsets[j - 1] - o - record.record_size - 6;
                 let mut missing_bytes = vec![0; num_missing_bytes as usize];
-                self.read_exact(&mut missing_bytes)
-                    .expect("Error reading missing bytes");
+                self.read_exact(&mut missing_bytes)?;
                 let o2 = o as u32 + record.record_size as u32 + 6 + page_start;
                 println!(
                     "{:08X}  {}",
@@ -573,6 +682,84 @@ impl TableFile {
                 );
             }
         }
+        Ok(())
+    }
+}
+
+/// Reads the records of a table file in row ID order, one leaf page at a time, following the
+/// right sibling pointers of the leaf pages. See [`TableFile::cursor`].
+pub struct Cursor {
+    file: TableFile,
+    /// Whether the records are read in descending row ID order.
+    descending: bool,
+    /// The leaf page the records left to return come from, `0xFFFFFFFF` once past the last one.
+    page: u32,
+    records: std::vec::IntoIter<Record>,
+}
+
+impl Cursor {
+    /// Turns the cursor around to read the records in descending row ID order, starting from
+    /// the last one.
+    pub fn reverse(mut self) -> Result<Cursor> {
+        self.descending = true;
+        self.seek(u32::MAX)?;
+        Ok(self)
+    }
+
+    /// Moves the cursor to the first record whose row ID is not less than `row_id`, or not
+    /// greater than it when reading in descending order.
+    pub fn seek(&mut self, row_id: u32) -> Result<()> {
+        let page = self.file.find_leaf_page(row_id)?;
+        self.load(page)?;
+        let descending = self.descending;
+        let records = std::mem::take(&mut self.records)
+            .filter(|record| {
+                if descending {
+                    record.row_id <= row_id
+                } else {
+                    record.row_id >= row_id
+                }
+            })
+            .collect::<Vec<_>>();
+        self.records = records.into_iter();
+        Ok(())
+    }
+
+    fn load(&mut self, page: u32) -> Result<()> {
+        self.page = page;
+        let mut records = match page {
+            0xFFFFFFFF => vec![],
+            _ => self.file.read_leaf(page)?,
+        };
+        if self.descending {
+            records.reverse();
+        }
+        self.records = records.into_iter();
+        Ok(())
+    }
+}
+
+impl Iterator for Cursor {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.records.next() {
+                return Some(Ok(record));
+            }
+            if self.page == 0xFFFFFFFF {
+                return None;
+            }
+            let next_page = if self.descending {
+                self.file.previous_leaf_page(self.page)
+            } else {
+                self.file.next_leaf_page(self.page)
+            };
+            if let Err(e) = next_page.and_then(|page| self.load(page)) {
+                self.page = 0xFFFFFFFF;
+                return Some(Err(e));
+            }
+        }
     }
 }
 
@@ -589,7 +776,7 @@ mod tests {
     use std::fs::remove_file;
     use std::{fs::File, io::BufRead};
 
-    use crate::{constants::DataType, record::Record};
+    use crate::{constants::DataType, expression::compare, record::Record};
 
     use super::TableFile;
 
@@ -602,7 +789,7 @@ mod tests {
         if std::path::Path::new(&file_name).exists() {
             remove_file(file_name).expect("Error removing test table file");
         }
-        let table_file = TableFile::new(test_name, "data");
+        let table_file = TableFile::new(test_name, "data").unwrap();
         let columns = [
             DataType::Text("".to_string()),
             DataType::Text("".to_string()),
@@ -640,9 +827,9 @@ mod tests {
     fn test_append_record() {
         let (mut table_file, records) = setup("test_append_record");
         for record in records {
-            table_file.append_record(record);
+            table_file.append_record(record).unwrap();
         }
-        assert_eq!(2048, table_file.len());
+        assert_eq!(2048, table_file.len().unwrap());
         tear_down("test_append_record");
     }
 
@@ -650,32 +837,32 @@ mod tests {
     fn test_get_min_row_id() {
         let (mut table_file, records) = setup("test_get_min_row_id");
         for record in records {
-            table_file.append_record(record);
+            table_file.append_record(record).unwrap();
         }
-        assert_eq!(0, table_file.get_min_row_id(0));
-        assert_eq!(0, table_file.get_min_row_id(1));
-        assert_eq!(4, table_file.get_min_row_id(2));
-        assert_eq!(8, table_file.get_min_row_id(3));
+        assert_eq!(0, table_file.get_min_row_id(0).unwrap());
+        assert_eq!(0, table_file.get_min_row_id(1).unwrap());
+        assert_eq!(4, table_file.get_min_row_id(2).unwrap());
+        assert_eq!(8, table_file.get_min_row_id(3).unwrap());
         tear_down("test_get_min_row_id");
     }
 
     #[test]
     fn test_write_page_pointer() {
         let (mut table_file, _) = setup("test_write_page_pointer");
-        table_file.create_page(0, PageType::TableInterior);
-        table_file.write_page_pointer(1, 0, 4);
-        assert_eq!(4, table_file.get_min_row_id(1));
+        table_file.create_page(0, PageType::TableInterior).unwrap();
+        table_file.write_page_pointer(1, 0, 4).unwrap();
+        assert_eq!(4, table_file.get_min_row_id(1).unwrap());
         tear_down("test_write_page_pointer");
     }
 
     #[test]
     fn test_write_record() {
         let (mut table_file, records) = setup("test_write_record");
-        table_file.write_record(records[0].clone(), 0);
+        table_file.write_record(records[0].clone(), 0).unwrap();
         let cell_size = records[0].record_size + 6;
-        assert_eq!(512, table_file.len());
+        assert_eq!(512, table_file.len().unwrap());
         let (page_type, num_cells, content_start, right_most_child, parent_page) =
-            table_file.get_page_info(0);
+            table_file.get_page_info(0).unwrap();
         assert_eq!(PageType::TableLeaf, page_type);
         assert_eq!(1, num_cells);
         assert_eq!(512 - cell_size, content_start);
@@ -688,9 +875,9 @@ mod tests {
     fn test_get_last_leaf_page() {
         let (mut table_file, records) = setup("test_get_last_leaf_page");
         for record in records {
-            table_file.append_record(record);
+            table_file.append_record(record).unwrap();
         }
-        assert_eq!(3, table_file.get_last_leaf_page());
+        assert_eq!(3, table_file.get_last_leaf_page().unwrap());
         tear_down("test_get_last_leaf_page");
     }
 
@@ -698,9 +885,12 @@ mod tests {
     fn test_get_record() {
         let (mut table_file, records) = setup("test_get_record");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        let record = table_file.get_record(1).expect("Error getting record");
+        let record = table_file
+            .get_record(1)
+            .unwrap()
+            .expect("Error getting record");
         assert_eq!(records[1], record);
         tear_down("test_get_record");
     }
@@ -709,9 +899,9 @@ mod tests {
     fn test_find_record_on_page() {
         let (mut table_file, records) = setup("test_find_record_on_page");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        assert_eq!(1, table_file.find_record_on_page(0, 1));
+        assert_eq!(1, table_file.find_record_on_page(0, 1).unwrap());
         tear_down("test_find_record_on_page");
     }
 
@@ -719,11 +909,14 @@ mod tests {
     fn test_shift_cells() {
         let (mut table_file, records) = setup("test_shift_cells");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        let original_offset = table_file.get_cell_offset(3, 1);
-        table_file.shift_cells(3, 0, 50, 0);
-        assert_eq!(original_offset - 50, table_file.get_cell_offset(3, 1));
+        let original_offset = table_file.get_cell_offset(3, 1).unwrap();
+        table_file.shift_cells(3, 0, 50, 0).unwrap();
+        assert_eq!(
+            original_offset - 50,
+            table_file.get_cell_offset(3, 1).unwrap()
+        );
         tear_down("test_shift_cells");
     }
 
@@ -731,13 +924,33 @@ mod tests {
     fn test_update_record() {
         let (mut table_file, records) = setup("test_update_record");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
         let new_text =
             DataType::Text("TEST UPDATE RECORD ABC DEF GHI JKL MNO PQR STU VWX YZ".to_string());
-        table_file.update_record(9, 1, new_text.clone());
-        let updated_record = table_file.get_record(9).expect("Error getting record");
+        table_file.update_record(9, 1, new_text.clone()).unwrap();
+        let updated_record = table_file
+            .get_record(9)
+            .unwrap()
+            .expect("Error getting record");
         assert_eq!(new_text, updated_record.values[1]);
+        table_file
+            .update_record(5, 1, DataType::Text("a".to_string()))
+            .unwrap();
+        table_file.update_record(6, 1, DataType::Null).unwrap();
+        assert_eq!(
+            DataType::Text("a".to_string()),
+            table_file.get_record(5).unwrap().unwrap().values[1]
+        );
+        assert_eq!(
+            DataType::Null,
+            table_file.get_record(6).unwrap().unwrap().values[1]
+        );
+        // The records after the resized ones must still be readable
+        table_file.delete_record(5).unwrap();
+        let all = table_file.search(None, DataType::Null, "=").unwrap();
+        assert_eq!(all.len(), records.len() - 1);
+        assert_eq!(all.last().unwrap().values[1], new_text);
         tear_down("test_update_record");
     }
 
@@ -745,28 +958,98 @@ mod tests {
     fn test_delete_record() {
         let (mut table_file, records) = setup("test_delete_record");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        table_file.delete_record(2);
-        let should_not_exist = table_file.get_record(2);
+        table_file.delete_record(2).unwrap();
+        let should_not_exist = table_file.get_record(2).unwrap();
         assert!(should_not_exist.is_none());
-        let serch_result = table_file.search(Some(2), DataType::Int(9800), "=");
+        let serch_result = table_file
+            .search(Some(2), DataType::Int(9800), "=")
+            .unwrap();
         assert_eq!(0, serch_result.len());
         tear_down("test_delete_record");
     }
 
+    #[test]
+    fn test_cursor() {
+        let (mut table_file, records) = setup("test_cursor");
+        for record in &records {
+            table_file.append_record(record.clone()).unwrap();
+        }
+        let row_ids = |cursor: super::Cursor| {
+            cursor
+                .map(|record| record.unwrap().row_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            row_ids(table_file.cursor().unwrap()),
+            (0..10).collect::<Vec<_>>()
+        );
+        let reverse = table_file.cursor().unwrap().reverse().unwrap();
+        assert_eq!(row_ids(reverse), (0..10).rev().collect::<Vec<_>>());
+
+        let mut cursor = table_file.cursor().unwrap();
+        cursor.seek(5).unwrap();
+        assert_eq!(row_ids(cursor), [5, 6, 7, 8, 9]);
+        let mut cursor = table_file.cursor().unwrap().reverse().unwrap();
+        cursor.seek(5).unwrap();
+        assert_eq!(row_ids(cursor), [5, 4, 3, 2, 1, 0]);
+        let mut cursor = table_file.cursor().unwrap();
+        cursor.seek(10).unwrap();
+        assert_eq!(row_ids(cursor), []);
+
+        // Deleting the first record of a page updates the row ID pointing to it, and emptying
+        // the last leaf page leaves the last row ID on the page before it.
+        table_file.delete_record(4).unwrap();
+        let mut cursor = table_file.cursor().unwrap().reverse().unwrap();
+        cursor.seek(4).unwrap();
+        assert_eq!(row_ids(cursor), [3, 2, 1, 0]);
+        table_file.delete_record(8).unwrap();
+        table_file.delete_record(9).unwrap();
+        assert_eq!(7, table_file.get_last_row_id().unwrap());
+        let reverse = table_file.cursor().unwrap().reverse().unwrap();
+        assert_eq!(row_ids(reverse), [7, 6, 5, 3, 2, 1, 0]);
+        tear_down("test_cursor");
+    }
+
+    #[test]
+    fn test_restore_records() {
+        let (mut table_file, records) = setup("test_restore_records");
+        for record in &records {
+            table_file.append_record(record.clone()).unwrap();
+        }
+        for row_id in [4, 6, 9] {
+            table_file.delete_record(row_id).unwrap();
+        }
+        let deleted = [9, 6, 4].map(|row_id| records[row_id as usize].clone());
+        table_file.restore_records(deleted.to_vec()).unwrap();
+        let restored = table_file
+            .cursor()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(restored, records);
+        for record in &records {
+            assert_eq!(
+                table_file.get_record(record.row_id).unwrap().as_ref(),
+                Some(record)
+            );
+        }
+        tear_down("test_restore_records");
+    }
+
     #[test]
     fn test_search() {
         let (mut table_file, records) = setup("test_search");
         let search_value = DataType::Int(8000);
         let real_results = records
             .iter()
-            .filter(|r| r.values[2] >= search_value)
+            .filter(|r| compare(&r.values[2], &search_value).is_some_and(|o| o.is_ge()))
             .collect::<Vec<&Record>>();
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        let search_results = table_file.search(Some(2), search_value, ">=");
+        let search_results = table_file.search(Some(2), search_value, ">=").unwrap();
         assert_eq!(real_results.len(), search_results.len());
         for (real_result, search_result) in real_results.iter().zip(search_results.iter()) {
             assert_eq!(*real_result, search_result);
@@ -778,11 +1061,11 @@ mod tests {
     fn test_split_page() {
         let (mut table_file, records) = setup("test_split_page");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        table_file.split_page(3, 9);
+        table_file.split_page(3, 9).unwrap();
         let (page_type, num_cells, content_start, right_most_child, parent_page) =
-            table_file.get_page_info(4);
+            table_file.get_page_info(4).unwrap();
         assert_eq!(PageType::TableLeaf, page_type);
         assert_eq!(0, num_cells);
         assert_eq!(0x200, content_start);
@@ -795,11 +1078,13 @@ mod tests {
     fn test_long_file() {
         let (mut table_file, records) = setup_table("test_long_file", "data/longdata.txt");
         for record in &records {
-            table_file.append_record(record.clone());
+            table_file.append_record(record.clone()).unwrap();
         }
-        assert_eq!(table_file.get_last_leaf_page(), 0x9D);
-        assert_eq!(table_file.get_root_page(), 0x33);
-        let search_results = table_file.search(Some(2), DataType::Int(800), "<>");
+        assert_eq!(table_file.get_last_leaf_page().unwrap(), 0x9D);
+        assert_eq!(table_file.get_root_page().unwrap(), 0x33);
+        let search_results = table_file
+            .search(Some(2), DataType::Int(800), "<>")
+            .unwrap();
         assert_eq!(1000, search_results.len());
         tear_down("test_long_file");
     }
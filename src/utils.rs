@@ -60,7 +60,7 @@ pub fn setup_table(test_name: &str, test_file_path: &str) -> crate::table::Table
     if std::path::Path::new(&table_file_path).exists() {
         teardown(test_name);
     }
-    let mut table = Table::new(test_name, columns, test_dir);
+    let mut table = Table::new(test_name, columns, test_dir).expect("Error creating table");
     let records = setup_records(test_file_path);
     for record in records {
         table.insert(record.values).expect("Error inserting record");
@@ -75,7 +75,7 @@ pub fn setup_table_no_records(test_name: &str) -> crate::table::Table {
         std::fs::create_dir_all(test_dir).expect("Error creating test directory");
     }
     let columns = setup_columns();
-    Table::new(test_name, columns, test_dir)
+    Table::new(test_name, columns, test_dir).expect("Error creating table")
 }
 
 pub fn teardown(test_name: &str) {
@@ -0,0 +1,562 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use crate::{
+    constants::DataType,
+    error::{DbError, Result},
+    expression::{check_arity, coerce, compare, compare_sort_keys, unify, Expression},
+    parser::{Expr, FrameBound},
+    record::Record,
+    table::Column,
+};
+
+/// The functions computed over a window of records.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WindowFunction {
+    RowNumber,
+    Rank,
+    DenseRank,
+    Lag,
+    Lead,
+    Sum,
+    Count,
+    Avg,
+    Min,
+    Max,
+}
+
+impl WindowFunction {
+    pub fn from_name(name: &str) -> Option<WindowFunction> {
+        let function = match name {
+            "row_number" => WindowFunction::RowNumber,
+            "rank" => WindowFunction::Rank,
+            "dense_rank" => WindowFunction::DenseRank,
+            "lag" => WindowFunction::Lag,
+            "lead" => WindowFunction::Lead,
+            "sum" => WindowFunction::Sum,
+            "count" => WindowFunction::Count,
+            "avg" => WindowFunction::Avg,
+            "min" => WindowFunction::Min,
+            "max" => WindowFunction::Max,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowFunction::RowNumber => "ROW_NUMBER",
+            WindowFunction::Rank => "RANK",
+            WindowFunction::DenseRank => "DENSE_RANK",
+            WindowFunction::Lag => "LAG",
+            WindowFunction::Lead => "LEAD",
+            WindowFunction::Sum => "SUM",
+            WindowFunction::Count => "COUNT",
+            WindowFunction::Avg => "AVG",
+            WindowFunction::Min => "MIN",
+            WindowFunction::Max => "MAX",
+        }
+    }
+
+    /// Returns the smallest and largest number of arguments.
+    fn arity(self) -> (usize, usize) {
+        match self {
+            WindowFunction::RowNumber | WindowFunction::Rank | WindowFunction::DenseRank => (0, 0),
+            WindowFunction::Lag | WindowFunction::Lead => (1, 3),
+            WindowFunction::Count => (0, 1),
+            WindowFunction::Sum
+            | WindowFunction::Avg
+            | WindowFunction::Min
+            | WindowFunction::Max => (1, 1),
+        }
+    }
+
+    /// Checks the types of the arguments and returns the type of the result.
+    fn return_type(self, args: &[Expression]) -> Result<DataType> {
+        let arg_type = || args[0].data_type();
+        match self {
+            WindowFunction::RowNumber
+            | WindowFunction::Rank
+            | WindowFunction::DenseRank
+            | WindowFunction::Count => Ok(DataType::BigInt(0)),
+            WindowFunction::Lag | WindowFunction::Lead => {
+                if let Some(offset) = args.get(1) {
+                    match offset {
+                        Expression::Literal(value) if as_offset(value).is_some() => {}
+                        _ => {
+                            return Err(DbError::Type(format!(
+                                "{} expects a whole number as offset, got {}.",
+                                self.name(),
+                                offset
+                            )))
+                        }
+                    }
+                }
+                match args.get(2) {
+                    Some(default) => unify(&arg_type(), &default.data_type()).ok_or_else(|| {
+                        DbError::Type(format!(
+                            "{} default {} does not match type {}.",
+                            self.name(),
+                            default,
+                            arg_type().type_name()
+                        ))
+                    }),
+                    None => Ok(arg_type()),
+                }
+            }
+            WindowFunction::Sum | WindowFunction::Avg => match arg_type() {
+                DataType::Float(_) | DataType::Double(_) => Ok(DataType::Double(0.0)),
+                DataType::Null
+                | DataType::TinyInt(_)
+                | DataType::SmallInt(_)
+                | DataType::Int(_)
+                | DataType::BigInt(_) => match self {
+                    WindowFunction::Sum => Ok(DataType::BigInt(0)),
+                    _ => Ok(DataType::Double(0.0)),
+                },
+                data_type => Err(DbError::Type(format!(
+                    "{} expects a number, got {}.",
+                    self.name(),
+                    data_type.type_name()
+                ))),
+            },
+            WindowFunction::Min | WindowFunction::Max => Ok(arg_type()),
+        }
+    }
+}
+
+/// A window function resolved against the columns of the records it is computed over.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Window {
+    function: WindowFunction,
+    args: Vec<Expression>,
+    partition_by: Vec<Expression>,
+    order_by: Vec<(Expression, bool)>,
+    frame: Option<(FrameBound, FrameBound)>,
+    data_type: DataType,
+    /// The window as written.
+    text: String,
+}
+
+impl Window {
+    /// Resolves an [`Expr::Window`] against `columns`.
+    pub fn resolve(expr: &Expr, columns: &[Column]) -> Result<Window> {
+        let Expr::Window {
+            function: name,
+            args,
+            partition_by,
+            order_by,
+            frame,
+        } = expr
+        else {
+            unreachable!("Not a window function");
+        };
+        let function = WindowFunction::from_name(name).ok_or_else(|| {
+            DbError::Parse(format!("Unknown window function {}.", name.to_uppercase()))
+        })?;
+        check_arity(function.name(), function.arity(), args.len())?;
+        let mut resolved: Vec<Expression> = vec![];
+        for arg in args {
+            // The default of LAG and LEAD takes the type of the first argument.
+            let hint = match resolved.len() {
+                2 => resolved.first().map(Expression::data_type),
+                _ => None,
+            };
+            resolved.push(Expression::resolve(arg, columns, hint.as_ref())?);
+        }
+        let data_type = function.return_type(&resolved)?;
+        Ok(Window {
+            function,
+            args: resolved,
+            partition_by: partition_by
+                .iter()
+                .map(|expr| Expression::resolve(expr, columns, None))
+                .collect::<Result<_, _>>()?,
+            order_by: order_by
+                .iter()
+                .map(|(expr, descending)| {
+                    Ok((Expression::resolve(expr, columns, None)?, *descending))
+                })
+                .collect::<Result<_>>()?,
+            frame: *frame,
+            data_type,
+            text: expr.to_string(),
+        })
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.data_type.clone()
+    }
+
+    /// Computes the window function for each record, returning the results in the order of
+    /// `records`.
+    pub fn evaluate(&self, records: &[Record]) -> Result<Vec<DataType>> {
+        let evaluate = |expressions: &[&Expression], record: &Record| {
+            expressions
+                .iter()
+                .map(|expression| expression.evaluate(&record.values))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let partition_by: Vec<&Expression> = self.partition_by.iter().collect();
+        let order_by: Vec<&Expression> = self.order_by.iter().map(|(e, _)| e).collect();
+        let args: Vec<&Expression> = self.args.iter().collect();
+        // Each record with its position and the values of its partition, order and arguments.
+        let mut rows = vec![];
+        for (index, record) in records.iter().enumerate() {
+            let partition = evaluate(&partition_by, record)?;
+            let order = evaluate(&order_by, record)?;
+            let args = evaluate(&args, record)?;
+            rows.push((index, partition, order, args));
+        }
+        let descending: Vec<bool> = self.order_by.iter().map(|(_, d)| *d).collect();
+        let ascending = vec![false; self.partition_by.len()];
+        rows.sort_by(|a, b| {
+            compare_sort_keys(&a.1, &b.1, &ascending)
+                .then_with(|| compare_sort_keys(&a.2, &b.2, &descending))
+        });
+        let mut results = vec![DataType::Null; records.len()];
+        let mut start = 0;
+        while start < rows.len() {
+            let end = (start..rows.len())
+                .find(|&i| !same_keys(&rows[i].1, &rows[start].1))
+                .unwrap_or(rows.len());
+            let partition = &rows[start..end];
+            let orders: Vec<&[DataType]> = partition.iter().map(|row| row.2.as_slice()).collect();
+            let args: Vec<&[DataType]> = partition.iter().map(|row| row.3.as_slice()).collect();
+            for (position, row) in partition.iter().enumerate() {
+                results[row.0] = self.compute(position, &orders, &args)?;
+            }
+            start = end;
+        }
+        Ok(results)
+    }
+
+    /// Computes the result for the record at `position` of a sorted partition.
+    fn compute(
+        &self,
+        position: usize,
+        orders: &[&[DataType]],
+        args: &[&[DataType]],
+    ) -> Result<DataType> {
+        // Records with equal ORDER BY values are peers and share a rank.
+        let first_peer = (0..=position)
+            .rev()
+            .take_while(|&i| same_keys(orders[i], orders[position]))
+            .last()
+            .unwrap_or(position);
+        let result = match self.function {
+            WindowFunction::RowNumber => DataType::BigInt(position as i64 + 1),
+            WindowFunction::Rank => DataType::BigInt(first_peer as i64 + 1),
+            WindowFunction::DenseRank => {
+                let groups = (1..=first_peer)
+                    .filter(|&i| !same_keys(orders[i], orders[i - 1]))
+                    .count();
+                DataType::BigInt(groups as i64 + 1)
+            }
+            WindowFunction::Lag | WindowFunction::Lead => {
+                let offset = args[position]
+                    .get(1)
+                    .map_or(Some(1), as_offset)
+                    .expect("Checked by return_type");
+                let target = match self.function {
+                    WindowFunction::Lag => position.checked_sub(offset),
+                    _ => position.checked_add(offset).filter(|&i| i < args.len()),
+                };
+                match target {
+                    Some(target) => args[target][0].clone(),
+                    None => args[position].get(2).cloned().unwrap_or(DataType::Null),
+                }
+            }
+            _ => {
+                let (start, end) = self.frame(position, orders);
+                let values = args[start..end].iter().map(|args| args.first());
+                self.aggregate(values, end - start)?
+            }
+        };
+        coerce(result.clone(), &self.data_type).ok_or_else(|| {
+            DbError::Type(format!(
+                "Result {} of {} does not fit {}.",
+                result,
+                self.function.name(),
+                self.data_type.type_name()
+            ))
+        })
+    }
+
+    /// Returns the range of positions in the partition the aggregate at `position` is computed
+    /// over. Without a frame it is the whole partition, or with ORDER BY every record up to the
+    /// last peer of the current one.
+    fn frame(&self, position: usize, orders: &[&[DataType]]) -> (usize, usize) {
+        let len = orders.len();
+        let Some((start, end)) = self.frame else {
+            if self.order_by.is_empty() {
+                return (0, len);
+            }
+            let end = (position..len)
+                .find(|&i| !same_keys(orders[i], orders[position]))
+                .unwrap_or(len);
+            return (0, end);
+        };
+        let bound = |bound: FrameBound| match bound {
+            FrameBound::UnboundedPreceding => 0,
+            FrameBound::Preceding(n) => position.saturating_sub(n),
+            FrameBound::CurrentRow => position,
+            FrameBound::Following(n) => position.saturating_add(n),
+            FrameBound::UnboundedFollowing => len,
+        };
+        let start = bound(start).min(len);
+        let end = match end {
+            FrameBound::UnboundedFollowing => len,
+            end => bound(end).saturating_add(1).min(len),
+        };
+        (start, end.max(start))
+    }
+
+    /// Aggregates the values of the records of a frame, skipping `NULL`. `values` has [`None`]
+    /// for `COUNT(*)`.
+    fn aggregate<'a>(
+        &self,
+        values: impl Iterator<Item = Option<&'a DataType>>,
+        count: usize,
+    ) -> Result<DataType> {
+        let values: Vec<&DataType> = match self.args.is_empty() {
+            true => return Ok(DataType::BigInt(count as i64)),
+            false => values
+                .flatten()
+                .filter(|value| **value != DataType::Null)
+                .collect(),
+        };
+        if self.function == WindowFunction::Count {
+            return Ok(DataType::BigInt(values.len() as i64));
+        }
+        if values.is_empty() {
+            return Ok(DataType::Null);
+        }
+        let result = match self.function {
+            WindowFunction::Min | WindowFunction::Max => {
+                let wanted = match self.function {
+                    WindowFunction::Min => Ordering::Less,
+                    _ => Ordering::Greater,
+                };
+                values
+                    .into_iter()
+                    .reduce(|best, value| match compare(value, best) {
+                        Some(ordering) if ordering == wanted => value,
+                        _ => best,
+                    })
+                    .expect("Not empty")
+                    .clone()
+            }
+            WindowFunction::Sum if self.data_type == DataType::BigInt(0) => {
+                let mut sum: i64 = 0;
+                for value in values {
+                    let value = coerce(value.clone(), &DataType::BigInt(0));
+                    sum = match value {
+                        Some(DataType::BigInt(value)) => sum.checked_add(value),
+                        _ => None,
+                    }
+                    .ok_or_else(|| DbError::Type("SUM is out of range.".to_string()))?;
+                }
+                DataType::BigInt(sum)
+            }
+            _ => {
+                let count = values.len() as f64;
+                let sum: f64 = values
+                    .into_iter()
+                    .filter_map(
+                        |value| match coerce(value.clone(), &DataType::Double(0.0)) {
+                            Some(DataType::Double(value)) => Some(value),
+                            _ => None,
+                        },
+                    )
+                    .sum();
+                match self.function {
+                    WindowFunction::Avg => DataType::Double(sum / count),
+                    _ => DataType::Double(sum),
+                }
+            }
+        };
+        Ok(result)
+    }
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// A MIN or MAX written without an OVER clause, computed over every selected record at once.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Aggregate {
+    function: WindowFunction,
+    arg: Expression,
+    /// The aggregate as written.
+    text: String,
+}
+
+impl Aggregate {
+    /// Resolves `expr` against `columns` if it is a call of MIN or MAX.
+    pub fn resolve(expr: &Expr, columns: &[Column]) -> Result<Option<Aggregate>> {
+        let Expr::Function { name, args } = expr else {
+            return Ok(None);
+        };
+        let function = match WindowFunction::from_name(name) {
+            Some(function @ (WindowFunction::Min | WindowFunction::Max)) => function,
+            _ => return Ok(None),
+        };
+        check_arity(function.name(), function.arity(), args.len())?;
+        Ok(Some(Aggregate {
+            function,
+            arg: Expression::resolve(&args[0], columns, None)?,
+            text: expr.to_string(),
+        }))
+    }
+
+    /// Returns the expression the aggregate is computed over.
+    pub fn arg(&self) -> &Expression {
+        &self.arg
+    }
+
+    /// Returns whether the result is the last value in ascending order, that is, whether it is
+    /// a MAX.
+    pub fn descending(&self) -> bool {
+        self.function == WindowFunction::Max
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.arg.data_type()
+    }
+
+    /// Returns the result so far, `result`, updated with the value of `arg` for `record`. NULL
+    /// values are skipped, so the result is NULL until a record has another.
+    pub fn accumulate(&self, result: DataType, record: &Record) -> Result<DataType> {
+        let value = self.arg.evaluate(&record.values)?;
+        let wanted = match self.descending() {
+            true => Ordering::Greater,
+            false => Ordering::Less,
+        };
+        Ok(match compare(&value, &result) {
+            _ if value == DataType::Null => result,
+            _ if result == DataType::Null => value,
+            Some(ordering) if ordering == wanted => value,
+            _ => result,
+        })
+    }
+}
+
+impl Display for Aggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Appends the result of each window function to the values of every record.
+pub fn evaluate_windows(records: Vec<Record>, windows: &[Window]) -> Result<Vec<Record>> {
+    if windows.is_empty() {
+        return Ok(records);
+    }
+    let results = windows
+        .iter()
+        .map(|window| window.evaluate(&records))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let mut values = record.values;
+            values.extend(results.iter().map(|result| result[i].clone()));
+            Record::new(values, record.row_id)
+        })
+        .collect())
+}
+
+/// Returns whether two lists of keys are equal, with `NULL` equal to `NULL`.
+fn same_keys(a: &[DataType], b: &[DataType]) -> bool {
+    compare_sort_keys(a, b, &vec![false; a.len()]) == Ordering::Equal
+}
+
+fn as_offset(value: &DataType) -> Option<usize> {
+    match coerce(value.clone(), &DataType::BigInt(0)) {
+        Some(DataType::BigInt(offset)) => offset.try_into().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::tokenize, parser};
+
+    #[test]
+    fn test_window_functions() {
+        let columns = vec![
+            Column::new("team", DataType::Text(String::new()), true, false),
+            Column::new("score", DataType::Int(0), true, false),
+        ];
+        let records: Vec<Record> = [("a", 3), ("b", 1), ("a", 5), ("a", 3), ("b", 2)]
+            .iter()
+            .enumerate()
+            .map(|(i, (team, score))| {
+                let values = vec![DataType::Text(team.to_string()), DataType::Int(*score)];
+                Record::new(values, i as u32 + 1)
+            })
+            .collect();
+        let eval = |window: &str| {
+            let select =
+                parser::parse_select(&mut tokenize(&format!("{} FROM t", window)).unwrap())
+                    .unwrap();
+            let window = Window::resolve(&select.columns.unwrap()[0], &columns)
+                .map_err(|e| e.to_string())?;
+            let results = window.evaluate(&records).map_err(|e| e.to_string())?;
+            Ok::<_, String>(
+                results
+                    .iter()
+                    .map(DataType::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+        };
+        let over = "over (partition by team order by score)";
+        assert_eq!(
+            eval(&format!("row_number() {}", over)),
+            Ok("1 1 3 2 2".into())
+        );
+        assert_eq!(eval(&format!("rank() {}", over)), Ok("1 1 3 1 2".into()));
+        assert_eq!(
+            eval("dense_rank() over (order by score desc)"),
+            Ok("2 4 1 2 3".into())
+        );
+        assert_eq!(
+            eval(&format!("lag(score) {}", over)),
+            Ok("NULL NULL 3 3 1".into())
+        );
+        assert_eq!(
+            eval(&format!("lead(score, 1, 0) {}", over)),
+            Ok("3 2 0 5 0".into())
+        );
+        // Peers are summed together without a frame, and one at a time with ROWS.
+        assert_eq!(
+            eval(&format!("sum(score) {}", over)),
+            Ok("6 1 11 6 3".into())
+        );
+        assert_eq!(
+            eval("sum(score) over (partition by team order by score rows unbounded preceding)"),
+            Ok("3 1 11 6 3".into())
+        );
+        assert_eq!(
+            eval("avg(score) over (order by score rows between 1 preceding and 1 following)"),
+            Ok("2.6666666666666665 1.5 4 3.6666666666666665 2".into())
+        );
+        assert_eq!(
+            eval("count(*) over (partition by team)"),
+            Ok("3 2 3 3 2".into())
+        );
+        assert_eq!(
+            eval("max(score) over (partition by team)"),
+            Ok("5 2 5 5 2".into())
+        );
+        assert!(eval("sum(team) over ()").is_err());
+        assert!(eval("lag(score, team) over ()").is_err());
+        assert!(eval("rank(score) over ()").is_err());
+    }
+}